@@ -0,0 +1,245 @@
+use syn::{Attribute, ExprPath, LitStr};
+
+/// Naming conventions supported by `#[json(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_name(name: &LitStr) -> syn::Result<Self> {
+        Ok(match name.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "unknown rename rule, expected one of \"lowercase\", \"UPPERCASE\", \
+                     \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \
+                     \"kebab-case\" or \"SCREAMING-KEBAB-CASE\"",
+                ))
+            }
+        })
+    }
+
+    /// Apply the rule to a Rust identifier, which can either be a `snake_case` field or a
+    /// `PascalCase` variant.
+    pub(crate) fn apply(self, name: &str) -> String {
+        // Split the identifier into lowercase words on underscores and uppercase letters.
+        let mut words: Vec<String> = Vec::new();
+        let mut word = String::new();
+        for character in name.chars() {
+            if (character == '_' || character.is_uppercase()) && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            if character != '_' {
+                word.extend(character.to_lowercase());
+            }
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+
+        let capitalize = |word: &String| {
+            let mut characters = word.chars();
+            characters
+                .next()
+                .map(|first| first.to_uppercase().chain(characters).collect::<String>())
+                .unwrap_or_default()
+        };
+
+        match self {
+            RenameRule::Lower => words.concat(),
+            RenameRule::Upper => words.concat().to_uppercase(),
+            RenameRule::Pascal => words.iter().map(capitalize).collect(),
+            RenameRule::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| {
+                    if index == 0 {
+                        word.clone()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            RenameRule::Snake => words.join("_"),
+            RenameRule::ScreamingSnake => words.join("_").to_uppercase(),
+            RenameRule::Kebab => words.join("-"),
+            RenameRule::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+}
+
+/// How the variant of an enum is encoded.
+#[derive(Default)]
+pub(crate) enum Tagging {
+    /// `{"Variant": content}`, or `"Variant"` for unit variants.
+    #[default]
+    External,
+    /// `{"tag": "Variant", ...fields}`.
+    Internal { tag: String },
+    /// `{"tag": "Variant", "content": content}`.
+    Adjacent { tag: String, content: String },
+    /// Only the content, picking the first variant that matches when converting from JSON.
+    Untagged,
+}
+
+/// Attributes placed on the struct or enum itself.
+#[derive(Default)]
+pub(crate) struct ContainerAttributes {
+    /// Naming convention for the fields of a struct, or the variants of an enum.
+    pub(crate) rename_all: Option<RenameRule>,
+    /// Reject objects with members that don't correspond to any field.
+    pub(crate) deny_unknown_fields: bool,
+    /// Representation of enum variants.
+    pub(crate) tagging: Tagging,
+}
+
+impl ContainerAttributes {
+    pub(crate) fn parse(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut container = ContainerAttributes::default();
+        let mut tag = None;
+        let mut content = None;
+        let mut untagged = None;
+
+        for attribute in json_attributes(attributes) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    container.rename_all = Some(RenameRule::from_name(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("deny_unknown_fields") {
+                    container.deny_unknown_fields = true;
+                } else if meta.path.is_ident("tag") {
+                    tag = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("content") {
+                    content = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("untagged") {
+                    untagged = Some(meta.path.clone());
+                } else {
+                    return Err(meta.error("unknown container attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        container.tagging = match (tag, content, untagged) {
+            (None, None, None) => Tagging::External,
+            (Some(tag), None, None) => Tagging::Internal { tag: tag.value() },
+            (Some(tag), Some(content), None) => Tagging::Adjacent {
+                tag: tag.value(),
+                content: content.value(),
+            },
+            (None, None, Some(_)) => Tagging::Untagged,
+            (None, Some(content), _) => {
+                return Err(syn::Error::new_spanned(
+                    content,
+                    "`content` requires a `tag` to be set as well",
+                ))
+            }
+            (Some(tag), _, Some(_)) => {
+                return Err(syn::Error::new_spanned(
+                    tag,
+                    "`untagged` can't be combined with `tag`",
+                ))
+            }
+        };
+
+        Ok(container)
+    }
+}
+
+/// Attributes placed on an enum variant.
+#[derive(Default)]
+pub(crate) struct VariantAttributes {
+    /// Name used for the variant instead of its identifier.
+    pub(crate) rename: Option<String>,
+    /// Naming convention for the fields of a struct variant.
+    pub(crate) rename_all: Option<RenameRule>,
+}
+
+impl VariantAttributes {
+    pub(crate) fn parse(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut variant = VariantAttributes::default();
+
+        for attribute in json_attributes(attributes) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    variant.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("rename_all") {
+                    variant.rename_all = Some(RenameRule::from_name(&meta.value()?.parse()?)?);
+                } else {
+                    return Err(meta.error("unknown variant attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(variant)
+    }
+}
+
+/// How to fill in a field which is missing from its object.
+pub(crate) enum FieldDefault {
+    /// Use [`Default::default`].
+    Trait,
+    /// Call the given function.
+    Function(ExprPath),
+}
+
+/// Attributes placed on a struct field.
+#[derive(Default)]
+pub(crate) struct FieldAttributes {
+    /// Name used for the member instead of the field identifier.
+    pub(crate) rename: Option<String>,
+    /// Value used when the member is missing.
+    pub(crate) default: Option<FieldDefault>,
+    /// Build the field from the members of the surrounding object that don't belong to any
+    /// other field, and spread its own members into that object when converting back.
+    pub(crate) flatten: bool,
+}
+
+impl FieldAttributes {
+    pub(crate) fn parse(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut field = FieldAttributes::default();
+
+        for attribute in json_attributes(attributes) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    field.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    field.default = Some(if meta.input.peek(syn::Token![=]) {
+                        FieldDefault::Function(meta.value()?.parse::<LitStr>()?.parse()?)
+                    } else {
+                        FieldDefault::Trait
+                    });
+                } else if meta.path.is_ident("flatten") {
+                    field.flatten = true;
+                } else {
+                    return Err(meta.error("unknown field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(field)
+    }
+}
+
+fn json_attributes(attributes: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("json"))
+}
@@ -0,0 +1,383 @@
+use crate::attr::{ContainerAttributes, FieldDefault, RenameRule, Tagging, VariantAttributes};
+use crate::{add_trait_bounds, parse_fields, type_mismatch};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataEnum, DeriveInput, Fields};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let container = ContainerAttributes::parse(&input.attrs)?;
+    let generics = add_trait_bounds(
+        input.generics.clone(),
+        &quote!(::json_parser::convert::FromJson),
+    );
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(_) if !matches!(container.tagging, Tagging::External) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`tag`, `content` and `untagged` can only be used on enums",
+            ))
+        }
+        Data::Struct(data) => fields_from_value(
+            &quote!(Self),
+            &data.fields,
+            container.rename_all,
+            container.deny_unknown_fields,
+            None,
+            &name.to_string(),
+        )?,
+        Data::Enum(data) => enum_from_value(data, &container, &name.to_string())?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "FromJson cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::json_parser::convert::FromJson for #name #type_generics #where_clause {
+            #[allow(clippy::redundant_closure_call)]
+            fn from_json(
+                value: &::json_parser::value::Value,
+            ) -> ::std::result::Result<Self, ::json_parser::error::JsonError> {
+                #body
+            }
+        }
+    })
+}
+
+/// Statements building `constructor` out of the `&Value` bound to `value`, evaluating to a
+/// `Result<Self, JsonError>`.
+///
+/// `tag` is the key of an internal enum tag, which can appear in the object besides the fields.
+fn fields_from_value(
+    constructor: &TokenStream,
+    fields: &Fields,
+    rename_all: Option<RenameRule>,
+    deny_unknown_fields: bool,
+    tag: Option<&str>,
+    expected: &str,
+) -> syn::Result<TokenStream> {
+    let mismatch = type_mismatch(expected);
+    let parsed = parse_fields(fields, rename_all)?;
+
+    Ok(match fields {
+        Fields::Named(_) => {
+            let flattened = parsed.iter().any(|field| field.attributes.flatten);
+            if flattened && deny_unknown_fields {
+                return Err(syn::Error::new_spanned(
+                    fields,
+                    "flattened fields can't be combined with `deny_unknown_fields`",
+                ));
+            }
+
+            let members = parsed.iter().map(|field| {
+                let member = &field.member;
+                let ty = field.ty;
+                let key = &field.key;
+
+                if field.attributes.flatten {
+                    return quote! {
+                        #member: ::json_parser::convert::FromJson::from_json(&rest)?
+                    };
+                }
+
+                let missing = match &field.attributes.default {
+                    Some(FieldDefault::Trait) => quote!(::std::default::Default::default()),
+                    Some(FieldDefault::Function(function)) => quote!(#function()),
+                    None => quote! {
+                        <#ty as ::json_parser::convert::FromJson>::from_missing().ok_or_else(
+                            || ::json_parser::error::JsonError::MissingField {
+                                pointer: ::std::string::String::new(),
+                                field: ::std::string::ToString::to_string(#key),
+                            },
+                        )?
+                    },
+                };
+
+                quote! {
+                    #member: match object.get(#key) {
+                        ::std::option::Option::Some(value) => {
+                            ::json_parser::convert::FromJson::from_json(value)
+                                .map_err(|error| error.nested(#key))?
+                        }
+                        ::std::option::Option::None => #missing,
+                    }
+                }
+            });
+
+            let keys = parsed
+                .iter()
+                .filter(|field| !field.attributes.flatten)
+                .map(|field| field.key.as_str())
+                .chain(tag);
+            let known = quote!(let known: &[&str] = &[#(#keys),*];);
+
+            let unknown_fields_check = deny_unknown_fields.then(|| {
+                quote! {
+                    #known
+                    for key in object.keys() {
+                        if !known.contains(&key) {
+                            return ::std::result::Result::Err(
+                                ::json_parser::error::JsonError::UnknownField {
+                                    pointer: ::std::string::String::new(),
+                                    field: key.to_string(),
+                                },
+                            );
+                        }
+                    }
+                }
+            });
+
+            // Collect the members which don't belong to any other field for flattened ones.
+            let rest = flattened.then(|| {
+                quote! {
+                    #known
+                    let rest = ::json_parser::value::Value::Object(
+                        object
+                            .iter()
+                            .filter(|(key, _)| !known.contains(key))
+                            .map(|(key, value)| (key.to_string(), value.clone()))
+                            .collect(),
+                    );
+                }
+            });
+
+            quote! {
+                let ::json_parser::value::Value::Object(object) = value else {
+                    return ::std::result::Result::Err(#mismatch);
+                };
+                #unknown_fields_check
+                #rest
+
+                ::std::result::Result::Ok(#constructor { #(#members,)* })
+            }
+        }
+        // A newtype is represented by its inner value directly.
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+            ::json_parser::convert::FromJson::from_json(value).map(#constructor)
+        },
+        Fields::Unnamed(fields) => {
+            let length = fields.unnamed.len();
+            let elements = parsed.iter().enumerate().map(|(index, field)| {
+                let token = &field.key;
+                quote! {
+                    ::json_parser::convert::FromJson::from_json(&array[#index])
+                        .map_err(|error| error.nested(#token))?
+                }
+            });
+
+            quote! {
+                let ::json_parser::value::Value::Array(array) = value else {
+                    return ::std::result::Result::Err(#mismatch);
+                };
+                if array.len() != #length {
+                    return ::std::result::Result::Err(#mismatch);
+                }
+
+                ::std::result::Result::Ok(#constructor(#(#elements,)*))
+            }
+        }
+        Fields::Unit => quote! {
+            match value {
+                ::json_parser::value::Value::Null => ::std::result::Result::Ok(#constructor),
+                _ => ::std::result::Result::Err(#mismatch),
+            }
+        },
+    })
+}
+
+/// Statements building an enum out of the `&Value` bound to `value`.
+fn enum_from_value(
+    data: &DataEnum,
+    container: &ContainerAttributes,
+    expected: &str,
+) -> syn::Result<TokenStream> {
+    let mismatch = type_mismatch(expected);
+    let internal_tag = match &container.tagging {
+        Tagging::Internal { tag } => Some(tag.as_str()),
+        _ => None,
+    };
+
+    // Tags and identifiers of unit variants.
+    let mut unit_keys = Vec::new();
+    let mut unit_idents = Vec::new();
+    // Match arms from the tag of every variant to a closure building it from its content.
+    let mut tagged_variants = Vec::new();
+    // Closures building every variant from its content.
+    let mut builders = Vec::new();
+
+    for variant in &data.variants {
+        let attributes = VariantAttributes::parse(&variant.attrs)?;
+        let ident = &variant.ident;
+        let key = attributes.rename.unwrap_or_else(|| {
+            container
+                .rename_all
+                .map_or(ident.to_string(), |rule| rule.apply(&ident.to_string()))
+        });
+
+        let body = match (&variant.fields, internal_tag) {
+            // Internally tagged newtypes are built from the surrounding object without the tag.
+            (Fields::Unnamed(fields), Some(tag)) if fields.unnamed.len() == 1 => quote! {
+                let ::json_parser::value::Value::Object(object) = value else {
+                    return ::std::result::Result::Err(#mismatch);
+                };
+                let content = ::json_parser::value::Value::Object(
+                    object
+                        .iter()
+                        .filter(|(key, _)| *key != #tag)
+                        .map(|(key, value)| (key.to_string(), value.clone()))
+                        .collect(),
+                );
+
+                ::json_parser::convert::FromJson::from_json(&content).map(Self::#ident)
+            },
+            (Fields::Unnamed(_), Some(_)) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "tuple variants can't be internally tagged",
+                ))
+            }
+            // Internally tagged unit variants only consist of the tag.
+            (Fields::Unit, Some(_)) => quote!(::std::result::Result::Ok(Self::#ident)),
+            (fields, tag) => fields_from_value(
+                &quote!(Self::#ident),
+                fields,
+                attributes.rename_all,
+                container.deny_unknown_fields,
+                tag,
+                &key,
+            )?,
+        };
+
+        // Wrap the body in a closure so that its early returns only leave the variant, and
+        // its errors can be nested under the tag.
+        let builder = quote! {
+            (|| -> ::std::result::Result<Self, ::json_parser::error::JsonError> {
+                #body
+            })()
+        };
+
+        if let Fields::Unit = variant.fields {
+            unit_keys.push(key.clone());
+            unit_idents.push(ident);
+        }
+        tagged_variants.push(quote!(#key => #builder));
+        builders.push(builder);
+    }
+
+    let unknown_variant = quote! {
+        ::json_parser::error::JsonError::UnknownVariant {
+            pointer: ::std::string::String::new(),
+            variant: ::std::string::ToString::to_string(tag),
+        }
+    };
+
+    // Statements reading the tag out of the object bound to `object`.
+    let read_tag = |tag: &str| {
+        quote! {
+            let tag = match object.get(#tag) {
+                ::std::option::Option::Some(::json_parser::value::Value::String(tag)) => tag,
+                ::std::option::Option::Some(value) => {
+                    return ::std::result::Result::Err(
+                        ::json_parser::error::JsonError::TypeMismatch {
+                            pointer: ::std::string::String::new(),
+                            expected: ::std::string::ToString::to_string("String"),
+                            found: value.type_name(),
+                        }
+                        .nested(#tag),
+                    );
+                }
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(
+                        ::json_parser::error::JsonError::MissingField {
+                            pointer: ::std::string::String::new(),
+                            field: ::std::string::ToString::to_string(#tag),
+                        },
+                    );
+                }
+            };
+        }
+    };
+
+    Ok(match &container.tagging {
+        Tagging::External => quote! {
+            match value {
+                ::json_parser::value::Value::String(tag) => match tag.as_str() {
+                    #(#unit_keys => ::std::result::Result::Ok(Self::#unit_idents),)*
+                    _ => ::std::result::Result::Err(#unknown_variant),
+                },
+                ::json_parser::value::Value::Object(object) if object.len() == 1 => {
+                    let ::std::option::Option::Some((tag, value)) = object.iter().next() else {
+                        return ::std::result::Result::Err(#mismatch);
+                    };
+
+                    let result = match tag {
+                        #(#tagged_variants,)*
+                        _ => return ::std::result::Result::Err(#unknown_variant),
+                    };
+
+                    result.map_err(|error| error.nested(tag))
+                }
+                _ => ::std::result::Result::Err(#mismatch),
+            }
+        },
+        Tagging::Internal { tag } => {
+            let read_tag = read_tag(tag);
+            quote! {
+                let ::json_parser::value::Value::Object(object) = value else {
+                    return ::std::result::Result::Err(#mismatch);
+                };
+                #read_tag
+
+                match tag.as_str() {
+                    #(#tagged_variants,)*
+                    _ => ::std::result::Result::Err(#unknown_variant.nested(#tag)),
+                }
+            }
+        }
+        Tagging::Adjacent { tag, content } => {
+            let read_tag = read_tag(tag);
+            quote! {
+                let ::json_parser::value::Value::Object(object) = value else {
+                    return ::std::result::Result::Err(#mismatch);
+                };
+                #read_tag
+
+                // Unit variants don't need any content.
+                match tag.as_str() {
+                    #(#unit_keys => return ::std::result::Result::Ok(Self::#unit_idents),)*
+                    _ => {}
+                }
+
+                let ::std::option::Option::Some(value) = object.get(#content) else {
+                    return ::std::result::Result::Err(
+                        ::json_parser::error::JsonError::MissingField {
+                            pointer: ::std::string::String::new(),
+                            field: ::std::string::ToString::to_string(#content),
+                        },
+                    );
+                };
+
+                let result = match tag.as_str() {
+                    #(#tagged_variants,)*
+                    _ => return ::std::result::Result::Err(#unknown_variant.nested(#tag)),
+                };
+
+                result.map_err(|error| error.nested(#content))
+            }
+        }
+        Tagging::Untagged => quote! {
+            #(
+                if let ::std::result::Result::Ok(result) = #builders {
+                    return ::std::result::Result::Ok(result);
+                }
+            )*
+
+            ::std::result::Result::Err(#mismatch)
+        },
+    })
+}
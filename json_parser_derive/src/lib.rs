@@ -0,0 +1,212 @@
+//! Derive macros for the `FromJson` and `ToJson` traits of `json_parser`.
+//!
+//! These are re-exported from `json_parser::convert` when its `derive` feature is enabled, and
+//! generate field-by-field conversions between structs or enums and `json_parser::value::Value`.
+//!
+//! Structs with named fields map to objects, tuple structs map to arrays, newtype structs map
+//! to their inner value, and unit structs map to `null`. Enums are externally tagged by default:
+//! unit variants map to their name as a string, and other variants map to an object with a
+//! single member whose key is the variant name.
+//!
+//! # Attributes
+//!
+//! Conversions can be customized with `#[json(...)]` attributes:
+//!
+//! - `#[json(rename_all = "...")]` on a struct renames all of its fields, and on an enum all of
+//!   its variants, following one of the conventions `"lowercase"`, `"UPPERCASE"`,
+//!   `"PascalCase"`, `"camelCase"`, `"snake_case"`, `"SCREAMING_SNAKE_CASE"`, `"kebab-case"`
+//!   or `"SCREAMING-KEBAB-CASE"`. On an enum variant it renames the fields of that variant.
+//! - `#[json(deny_unknown_fields)]` on a struct or enum rejects objects with members that
+//!   don't correspond to any field.
+//! - `#[json(rename = "...")]` on a field or variant uses the given name instead.
+//! - `#[json(default)]` on a field uses [`Default::default`] when the member is missing, and
+//!   `#[json(default = "path::to::function")]` calls the given function instead.
+//! - `#[json(flatten)]` on a field builds it from all members of the surrounding object which
+//!   don't belong to another field, such as a `HashMap<String, Value>` catch-all or a nested
+//!   struct sharing the same object. When converting back, the members of the field are spread
+//!   into the surrounding object, so the field has to convert into an object or `null`.
+//!   Flattened fields can't be combined with `deny_unknown_fields`.
+//! - `#[json(tag = "type")]` on an enum makes it internally tagged, so that variants are
+//!   represented as `{"type": "Variant", ...}` with the members of struct variants, or of the
+//!   object held by newtype variants, next to the tag. Tuple variants can't be internally
+//!   tagged.
+//! - `#[json(tag = "t", content = "c")]` on an enum makes it adjacently tagged, so that
+//!   variants are represented as `{"t": "Variant", "c": content}`.
+//! - `#[json(untagged)]` on an enum represents variants by their content alone, and picks the
+//!   first variant which can be built from a value when converting from JSON.
+//!
+//! # Examples
+//!
+//! ```
+//! use json_parser::convert::{FromJson, ToJson};
+//! use json_parser::parser::JsonParser;
+//! use json_parser::value::Value;
+//! use std::collections::HashMap;
+//!
+//! #[derive(Debug, PartialEq, FromJson, ToJson)]
+//! struct User {
+//!     name: String,
+//!     age: Option<u8>,
+//!     role: Role,
+//! }
+//!
+//! #[derive(Debug, PartialEq, FromJson, ToJson)]
+//! enum Role {
+//!     Admin,
+//!     Member { team: String },
+//! }
+//!
+//! let value = JsonParser::parse_from_bytes(br#"{"name":"Ada","role":{"Member":{"team":"core"}}}"#)
+//!     .unwrap();
+//! let user = User::from_json(&value).unwrap();
+//!
+//! assert_eq!(user.role, Role::Member { team: "core".to_string() });
+//! assert_eq!(User::from_json(&user.to_json()).unwrap(), user);
+//!
+//! let value = JsonParser::parse_from_bytes(br#"{"name":"Ada","role":{"Member":{}}}"#).unwrap();
+//! let error = User::from_json(&value).unwrap_err();
+//!
+//! assert_eq!(error.to_string(), "missing field `team` at /role/Member");
+//!
+//! #[derive(Debug, PartialEq, FromJson, ToJson)]
+//! #[json(rename_all = "camelCase", deny_unknown_fields)]
+//! struct Settings {
+//!     max_connections: u32,
+//!     #[json(default)]
+//!     read_only: bool,
+//!     #[json(rename = "TTL")]
+//!     ttl: u64,
+//! }
+//!
+//! let value = JsonParser::parse_from_bytes(br#"{"maxConnections":8,"TTL":60}"#).unwrap();
+//! let settings = Settings::from_json(&value).unwrap();
+//!
+//! assert_eq!(settings, Settings { max_connections: 8, read_only: false, ttl: 60 });
+//!
+//! let value = JsonParser::parse_from_bytes(br#"{"maxConnections":8,"TTL":60,"ttl":1}"#).unwrap();
+//! assert!(Settings::from_json(&value).is_err());
+//!
+//! #[derive(Debug, PartialEq, FromJson, ToJson)]
+//! struct Event {
+//!     kind: String,
+//!     #[json(flatten)]
+//!     extra: HashMap<String, Value>,
+//! }
+//!
+//! let value = JsonParser::parse_from_bytes(br#"{"kind":"click","x":1,"y":2}"#).unwrap();
+//! let event = Event::from_json(&value).unwrap();
+//!
+//! assert_eq!(event.extra.len(), 2);
+//! assert_eq!(event.to_json(), value);
+//!
+//! #[derive(Debug, PartialEq, FromJson, ToJson)]
+//! #[json(tag = "type", rename_all = "lowercase")]
+//! enum Shape {
+//!     Circle { radius: f64 },
+//!     Square { side: f64 },
+//! }
+//!
+//! let value = JsonParser::parse_from_bytes(br#"{"type":"circle","radius":1.5}"#).unwrap();
+//! let shape = Shape::from_json(&value).unwrap();
+//!
+//! assert_eq!(shape, Shape::Circle { radius: 1.5 });
+//! assert_eq!(shape.to_json(), value);
+//! ```
+
+use attr::{FieldAttributes, RenameRule};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, DeriveInput, Fields, GenericParam, Generics, Member, Type,
+};
+
+mod attr;
+mod from_json;
+mod to_json;
+
+/// Derive `json_parser::convert::FromJson` for a struct or enum.
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    from_json::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `json_parser::convert::ToJson` for a struct or enum.
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    to_json::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Require every type parameter of `generics` to implement `bound`.
+fn add_trait_bounds(mut generics: Generics, bound: &TokenStream2) -> Generics {
+    for parameter in &mut generics.params {
+        if let GenericParam::Type(parameter) = parameter {
+            parameter.bounds.push(parse_quote!(#bound));
+        }
+    }
+
+    generics
+}
+
+/// Expression building the error for a value that doesn't have the shape of `expected`.
+///
+/// The generated code expects the offending value to be bound to `value`.
+fn type_mismatch(expected: &str) -> TokenStream2 {
+    quote! {
+        ::json_parser::error::JsonError::TypeMismatch {
+            pointer: ::std::string::String::new(),
+            expected: ::std::string::ToString::to_string(#expected),
+            found: value.type_name(),
+        }
+    }
+}
+
+/// A field of a struct or enum variant, along with its parsed attributes.
+struct Field<'a> {
+    /// How the field is accessed, either by name or by position.
+    member: Member,
+    ty: &'a Type,
+    /// Key of the field inside its object, after applying renames.
+    key: String,
+    attributes: FieldAttributes,
+}
+
+/// Parse the attributes of every field, naming them according to `rename_all`.
+fn parse_fields(fields: &Fields, rename_all: Option<RenameRule>) -> syn::Result<Vec<Field<'_>>> {
+    fields
+        .members()
+        .zip(fields)
+        .map(|(member, field)| {
+            let attributes = FieldAttributes::parse(&field.attrs)?;
+            if attributes.flatten && field.ident.is_none() {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "only named fields can be flattened",
+                ));
+            }
+            let key = match (&attributes.rename, &member) {
+                (Some(rename), _) => rename.clone(),
+                (None, Member::Named(ident)) => {
+                    let name = ident.to_string();
+                    rename_all.map_or(name.clone(), |rule| rule.apply(&name))
+                }
+                (None, Member::Unnamed(index)) => index.index.to_string(),
+            };
+
+            Ok(Field {
+                member,
+                ty: &field.ty,
+                key,
+                attributes,
+            })
+        })
+        .collect()
+}
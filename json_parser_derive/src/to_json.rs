@@ -0,0 +1,202 @@
+use crate::attr::{ContainerAttributes, RenameRule, Tagging, VariantAttributes};
+use crate::{add_trait_bounds, parse_fields};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DataEnum, DeriveInput, Fields, Member};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let container = ContainerAttributes::parse(&input.attrs)?;
+    let generics = add_trait_bounds(
+        input.generics.clone(),
+        &quote!(::json_parser::convert::ToJson),
+    );
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(_) if !matches!(container.tagging, Tagging::External) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`tag`, `content` and `untagged` can only be used on enums",
+            ))
+        }
+        Data::Struct(data) => {
+            // Access the fields of `self` directly.
+            let accessors = data
+                .fields
+                .members()
+                .map(|member| quote!(&self.#member))
+                .collect::<Vec<_>>();
+
+            fields_to_value(&data.fields, container.rename_all, &accessors)?
+        }
+        Data::Enum(data) => enum_to_value(data, &container)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "ToJson cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::json_parser::convert::ToJson for #name #type_generics #where_clause {
+            fn to_json(&self) -> ::json_parser::value::Value {
+                #body
+            }
+        }
+    })
+}
+
+/// Expression converting `fields` into a `Value`, where `accessors` are expressions evaluating
+/// to a reference to each field.
+fn fields_to_value(
+    fields: &Fields,
+    rename_all: Option<RenameRule>,
+    accessors: &[TokenStream],
+) -> syn::Result<TokenStream> {
+    let parsed = parse_fields(fields, rename_all)?;
+
+    Ok(match fields {
+        Fields::Named(_) => {
+            let members = parsed.iter().zip(accessors).map(|(field, accessor)| {
+                let key = &field.key;
+
+                if field.attributes.flatten {
+                    return quote! {
+                        if let ::json_parser::value::Value::Object(members) =
+                            ::json_parser::convert::ToJson::to_json(#accessor)
+                        {
+                            object.extend(members);
+                        }
+                    };
+                }
+
+                quote! {
+                    object.insert(
+                        ::std::string::ToString::to_string(#key),
+                        ::json_parser::convert::ToJson::to_json(#accessor),
+                    );
+                }
+            });
+
+            quote! {{
+                let mut object = ::json_parser::map::Map::new();
+                #(#members)*
+                ::json_parser::value::Value::Object(object)
+            }}
+        }
+        // A newtype is represented by its inner value directly.
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let accessor = &accessors[0];
+            quote!(::json_parser::convert::ToJson::to_json(#accessor))
+        }
+        Fields::Unnamed(_) => quote! {
+            ::json_parser::value::Value::Array(::std::vec![
+                #(::json_parser::convert::ToJson::to_json(#accessors),)*
+            ])
+        },
+        Fields::Unit => quote!(::json_parser::value::Value::Null),
+    })
+}
+
+/// Expression converting an enum into a `Value`.
+fn enum_to_value(data: &DataEnum, container: &ContainerAttributes) -> syn::Result<TokenStream> {
+    let mut arms = Vec::new();
+
+    for variant in &data.variants {
+        let attributes = VariantAttributes::parse(&variant.attrs)?;
+        let ident = &variant.ident;
+        let key = attributes.rename.unwrap_or_else(|| {
+            container
+                .rename_all
+                .map_or(ident.to_string(), |rule| rule.apply(&ident.to_string()))
+        });
+
+        // Bind every field of the variant to a local, which are then used as accessors.
+        let bindings = variant
+            .fields
+            .members()
+            .map(|member| match member {
+                Member::Named(ident) => ident,
+                Member::Unnamed(index) => format_ident!("field{}", index.index),
+            })
+            .collect::<Vec<_>>();
+        let accessors = bindings
+            .iter()
+            .map(|binding| quote!(#binding))
+            .collect::<Vec<_>>();
+
+        let pattern = match &variant.fields {
+            Fields::Named(_) => quote!(Self::#ident { #(#bindings),* }),
+            Fields::Unnamed(_) => quote!(Self::#ident(#(#bindings),*)),
+            Fields::Unit => quote!(Self::#ident),
+        };
+
+        let name = quote!(::json_parser::value::Value::from(#key));
+        let content = match &variant.fields {
+            Fields::Unit => None,
+            fields => Some(fields_to_value(fields, attributes.rename_all, &accessors)?),
+        };
+
+        let body = match (&container.tagging, content) {
+            (Tagging::External, None) => name,
+            (Tagging::External, Some(content)) => quote! {{
+                let mut tagged = ::json_parser::map::Map::new();
+                tagged.insert(::std::string::ToString::to_string(#key), #content);
+                ::json_parser::value::Value::Object(tagged)
+            }},
+            (Tagging::Internal { .. }, Some(_)) if matches!(variant.fields, Fields::Unnamed(ref fields) if fields.unnamed.len() != 1) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "tuple variants can't be internally tagged",
+                ))
+            }
+            // The tag comes first, followed by the members of the content.
+            (Tagging::Internal { tag }, content) => {
+                let spread = content.map(|content| {
+                    quote! {
+                        if let ::json_parser::value::Value::Object(members) = #content {
+                            tagged.extend(members);
+                        }
+                    }
+                });
+                quote! {{
+                    let mut tagged = ::json_parser::map::Map::new();
+                    tagged.insert(::std::string::ToString::to_string(#tag), #name);
+                    #spread
+                    ::json_parser::value::Value::Object(tagged)
+                }}
+            }
+            (
+                Tagging::Adjacent {
+                    tag,
+                    content: content_key,
+                },
+                content,
+            ) => {
+                let content = content.map(|content| {
+                    quote! {
+                        tagged.insert(::std::string::ToString::to_string(#content_key), #content);
+                    }
+                });
+                quote! {{
+                    let mut tagged = ::json_parser::map::Map::new();
+                    tagged.insert(::std::string::ToString::to_string(#tag), #name);
+                    #content
+                    ::json_parser::value::Value::Object(tagged)
+                }}
+            }
+            (Tagging::Untagged, None) => quote!(::json_parser::value::Value::Null),
+            (Tagging::Untagged, Some(content)) => content,
+        };
+
+        arms.push(quote!(#pattern => #body));
+    }
+
+    Ok(quote! {
+        match self {
+            #(#arms,)*
+        }
+    })
+}
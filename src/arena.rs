@@ -0,0 +1,116 @@
+//! Documents whose strings, arrays and objects all live in one arena, freed at once when the
+//! arena is dropped or reset.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::tape::TapeValue;
+use crate::value::{Number, Value};
+pub use ::bumpalo::Bump;
+
+/// A value allocated in a [`Bump`] arena by [`JsonParser::parse_in_arena`].
+///
+/// Members of objects are kept in input order, including those with duplicate keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaValue<'b> {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(&'b str),
+    Array(&'b [ArenaValue<'b>]),
+    Object(&'b [(&'b str, ArenaValue<'b>)]),
+}
+
+impl JsonParser {
+    /// Parse JSON from bytes into values allocated in `arena`, which avoids an allocation per
+    /// string, array and object, and keeps the document together in memory.
+    ///
+    /// The input is parsed like with [`parse_tape`](JsonParser::parse_tape), then copied into
+    /// the arena, so it can be dropped right after.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::arena::{ArenaValue, Bump};
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut arena = Bump::new();
+    /// for line in [br#"{"level": "info"}"#, br#"{"level": "warn"}"#] {
+    ///     let value = JsonParser::parse_in_arena(line, &arena).unwrap();
+    ///     assert!(matches!(value.get("level"), Some(ArenaValue::String(_))));
+    ///
+    ///     // Free the whole document before parsing the next one.
+    ///     arena.reset();
+    /// }
+    /// ```
+    pub fn parse_in_arena<'b>(input: &[u8], arena: &'b Bump) -> Result<ArenaValue<'b>, JsonError> {
+        let tape = Self::parse_tape(input)?;
+
+        Ok(copy_to_arena(tape.root(), arena))
+    }
+}
+
+fn copy_to_arena<'b>(value: TapeValue, arena: &'b Bump) -> ArenaValue<'b> {
+    if let Some(elements) = value.elements() {
+        let elements =
+            arena.alloc_slice_fill_iter(elements.map(|element| copy_to_arena(element, arena)));
+        return ArenaValue::Array(elements);
+    }
+    if let Some(members) = value.members() {
+        let members = arena.alloc_slice_fill_iter(
+            members.map(|(key, value)| (&*arena.alloc_str(key), copy_to_arena(value, arena))),
+        );
+        return ArenaValue::Object(members);
+    }
+
+    if let Some(string) = value.as_str() {
+        ArenaValue::String(arena.alloc_str(string))
+    } else if let Some(number) = value.as_number() {
+        ArenaValue::Number(number)
+    } else if let Some(boolean) = value.as_bool() {
+        ArenaValue::Boolean(boolean)
+    } else {
+        ArenaValue::Null
+    }
+}
+
+impl<'b> ArenaValue<'b> {
+    /// The value of the member with the given key, if this is an object which has one. Of
+    /// duplicate keys, the last one counts.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&'b ArenaValue<'b>> {
+        match self {
+            ArenaValue::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(member, _)| *member == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Copy the value out of the arena.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match *self {
+            ArenaValue::Null => Value::Null,
+            ArenaValue::Boolean(boolean) => Value::Boolean(boolean),
+            ArenaValue::Number(number) => Value::Number(number),
+            ArenaValue::String(string) => Value::from(string),
+            ArenaValue::Array(elements) => {
+                Value::Array(elements.iter().map(ArenaValue::to_value).collect())
+            }
+            ArenaValue::Object(members) => {
+                let mut map = Map::new();
+                for (key, value) in members {
+                    map.insert((*key).to_string(), value.to_value());
+                }
+                Value::Object(map)
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+
+/// Start of every encoded value, identifying the format and its version so that caches written
+/// by other versions are rejected rather than misread.
+const MAGIC: &[u8; 4] = b"JPB\x01";
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const INTEGER: u8 = 3;
+const FLOAT: u8 = 4;
+const STRING: u8 = 5;
+const ARRAY: u8 = 6;
+const OBJECT: u8 = 7;
+
+impl Value {
+    /// Encode the value in this crate's own binary format, to cache parsed documents on disk.
+    ///
+    /// Every value is a tag byte followed by its contents, with strings, arrays and objects
+    /// prefixed by their length, so [`from_binary`] reads them back without tokenizing
+    /// anything. The format may change between versions of this crate, which [`from_binary`]
+    /// detects.
+    ///
+    /// [`from_binary`]: Value::from_binary
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"id":7,"tags":["a","b"],"ratio":0.5}"#)
+    ///     .unwrap();
+    /// let bytes = value.to_binary();
+    ///
+    /// assert_eq!(Value::from_binary(&bytes).unwrap(), value);
+    /// assert!(Value::from_binary(&bytes[..bytes.len() - 1]).is_err());
+    /// ```
+    #[must_use]
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        encode(self, &mut bytes);
+
+        bytes
+    }
+
+    /// Decode a value encoded with [`to_binary`](Value::to_binary).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the bytes weren't written by this version of
+    /// [`to_binary`](Value::to_binary), or are truncated or corrupted.
+    pub fn from_binary(bytes: &[u8]) -> Result<Value, JsonError> {
+        let Some(rest) = bytes.strip_prefix(MAGIC) else {
+            return Err(format_error("missing header or unsupported version"));
+        };
+
+        let mut decoder = Decoder { bytes: rest };
+        let value = decoder.value()?;
+        if !decoder.bytes.is_empty() {
+            return Err(format_error("trailing bytes after the value"));
+        }
+
+        Ok(value)
+    }
+}
+
+fn format_error(message: &str) -> JsonError {
+    JsonError::Format {
+        format: "binary",
+        message: message.to_string(),
+    }
+}
+
+fn encode(value: &Value, bytes: &mut Vec<u8>) {
+    match value {
+        Value::Null => bytes.push(NULL),
+        Value::Boolean(false) => bytes.push(FALSE),
+        Value::Boolean(true) => bytes.push(TRUE),
+        Value::Number(Number::I64(integer)) => {
+            bytes.push(INTEGER);
+            bytes.extend_from_slice(&integer.to_le_bytes());
+        }
+        Value::Number(Number::F64(float)) => {
+            bytes.push(FLOAT);
+            bytes.extend_from_slice(&float.to_le_bytes());
+        }
+        Value::String(string) => {
+            bytes.push(STRING);
+            encode_str(string, bytes);
+        }
+        Value::Array(elements) => {
+            bytes.push(ARRAY);
+            encode_length(elements.len(), bytes);
+            for element in elements {
+                encode(element, bytes);
+            }
+        }
+        Value::Object(members) => {
+            bytes.push(OBJECT);
+            encode_length(members.len(), bytes);
+            for (key, value) in members {
+                encode_str(key, bytes);
+                encode(value, bytes);
+            }
+        }
+    }
+}
+
+fn encode_str(string: &str, bytes: &mut Vec<u8>) {
+    encode_length(string.len(), bytes);
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+/// Write a length as a LEB128 variable-length integer, seven bits per byte.
+fn encode_length(mut length: usize, bytes: &mut Vec<u8>) {
+    while length >= 0x80 {
+        // Truncation keeps the low seven bits, with the continuation bit set.
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.push(length as u8 | 0x80);
+        length >>= 7;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    bytes.push(length as u8);
+}
+
+struct Decoder<'a> {
+    /// Bytes not decoded yet.
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], JsonError> {
+        if self.bytes.len() < length {
+            return Err(format_error("unexpected end of input"));
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], JsonError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+
+        Ok(array)
+    }
+
+    fn length(&mut self) -> Result<usize, JsonError> {
+        let mut length = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let [byte] = self.take_array()?;
+            length |= usize::from(byte & 0x7F)
+                .checked_shl(shift)
+                .ok_or_else(|| format_error("length out of range"))?;
+            if byte & 0x80 == 0 {
+                return Ok(length);
+            }
+        }
+
+        Err(format_error("length out of range"))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        let length = self.length()?;
+        let bytes = self.take(length)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| format_error("invalid UTF-8 in string"))
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        let [tag] = self.take_array()?;
+
+        Ok(match tag {
+            NULL => Value::Null,
+            FALSE => Value::Boolean(false),
+            TRUE => Value::Boolean(true),
+            INTEGER => Value::Number(Number::I64(i64::from_le_bytes(self.take_array()?))),
+            FLOAT => Value::Number(Number::F64(f64::from_le_bytes(self.take_array()?))),
+            STRING => Value::from(self.string()?),
+            ARRAY => {
+                let length = self.length()?;
+                // Every element takes at least a byte, which bounds what corrupted lengths
+                // can make us allocate.
+                let mut elements = Vec::with_capacity(length.min(self.bytes.len()));
+                for _ in 0..length {
+                    elements.push(self.value()?);
+                }
+                Value::Array(elements)
+            }
+            OBJECT => {
+                let length = self.length()?;
+                let mut members = Map::with_capacity(length.min(self.bytes.len() / 2));
+                for _ in 0..length {
+                    let key = self.string()?;
+                    members.insert(key, self.value()?);
+                }
+                Value::Object(members)
+            }
+            _ => return Err(format_error(&format!("unknown tag {tag}"))),
+        })
+    }
+}
@@ -0,0 +1,88 @@
+//! Values borrowing their strings from the input they're parsed from.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::value::{Number, Value};
+use std::borrow::Cow;
+
+/// A value parsed by [`JsonParser::parse_borrowed`], whose strings and keys borrow from the
+/// input unless they had escape sequences to decode.
+///
+/// Members of objects are kept in input order, including those with duplicate keys.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedValue<'a>>),
+    Object(Vec<(Cow<'a, str>, BorrowedValue<'a>)>),
+}
+
+impl JsonParser {
+    /// Parse JSON from bytes without copying the strings out of the input, which saves most
+    /// allocations for documents made mostly of strings.
+    ///
+    /// The input is parsed like with [`parse_tape`](JsonParser::parse_tape), so a leading byte
+    /// order mark is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::borrowed::BorrowedValue;
+    /// use json_parser::parser::JsonParser;
+    /// use std::borrow::Cow;
+    ///
+    /// let input = br#"{"plain": "text", "escaped": "line\nbreak"}"#;
+    /// let value = JsonParser::parse_borrowed(input).unwrap();
+    ///
+    /// assert!(matches!(value.get("plain"), Some(BorrowedValue::String(Cow::Borrowed("text")))));
+    /// assert!(matches!(value.get("escaped"), Some(BorrowedValue::String(Cow::Owned(_)))));
+    /// assert_eq!(value.to_value(), JsonParser::parse_from_bytes(input).unwrap());
+    /// ```
+    pub fn parse_borrowed(input: &[u8]) -> Result<BorrowedValue<'_>, JsonError> {
+        Ok(Self::parse_tape(input)?.into_borrowed())
+    }
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// The value of the member with the given key, if this is an object which has one. Of
+    /// duplicate keys, the last one counts.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+        match self {
+            BorrowedValue::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(member, _)| member == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Copy the value into a [`Value`], which doesn't borrow from the input.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self {
+            BorrowedValue::Null => Value::Null,
+            BorrowedValue::Boolean(boolean) => Value::Boolean(*boolean),
+            BorrowedValue::Number(number) => Value::Number(*number),
+            BorrowedValue::String(string) => Value::from(&**string),
+            BorrowedValue::Array(elements) => {
+                Value::Array(elements.iter().map(BorrowedValue::to_value).collect())
+            }
+            BorrowedValue::Object(members) => {
+                let mut map = Map::with_capacity(members.len());
+                for (key, value) in members {
+                    map.insert(key.to_string(), value.to_value());
+                }
+                Value::Object(map)
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+use ::bson::{Bson, Document};
+
+/// How BSON ObjectIds are represented by [`Value::from_bson`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectIdFormat {
+    /// The Extended JSON form `{"$oid": "<hex>"}`, which [`Value::to_bson`] turns back into an
+    /// ObjectId.
+    #[default]
+    Extended,
+    /// The 24 character hexadecimal string, which stays a string when converted back.
+    Hex,
+}
+
+/// How BSON datetimes are represented by [`Value::from_bson`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateTimeFormat {
+    /// The relaxed Extended JSON form `{"$date": "<RFC 3339>"}`, which [`Value::to_bson`]
+    /// turns back into a datetime.
+    #[default]
+    Extended,
+    /// An RFC 3339 string such as `"2024-01-31T12:00:00Z"`, falling back to the Extended JSON
+    /// form for dates that can't be written this way.
+    Rfc3339,
+    /// The number of milliseconds since the Unix epoch.
+    Millis,
+}
+
+/// Options controlling how BSON specific types are mapped by [`Value::from_bson`].
+///
+/// Types without a JSON equivalent other than ObjectIds and datetimes, such as binary data or
+/// regular expressions, are always represented in relaxed Extended JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BsonOptions {
+    /// Representation of ObjectIds.
+    pub object_ids: ObjectIdFormat,
+    /// Representation of datetimes.
+    pub dates: DateTimeFormat,
+}
+
+impl Value {
+    /// Convert a BSON value into a value.
+    ///
+    /// Doubles become floats, 32 and 64-bit integers become integers, and documents become
+    /// objects. ObjectIds and datetimes are mapped according to `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bson::oid::ObjectId;
+    /// use json_parser::bson::{BsonOptions, ObjectIdFormat};
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let id = ObjectId::parse_str("65f1a2b3c4d5e6f708192a3b").unwrap();
+    /// let document = bson::doc! { "_id": id, "name": "Ada", "visits": 3 };
+    ///
+    /// let value = Value::from_bson(document.clone().into(), &BsonOptions::default());
+    /// let expected = JsonParser::parse_from_bytes(
+    ///     br#"{"_id":{"$oid":"65f1a2b3c4d5e6f708192a3b"},"name":"Ada","visits":3}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(value, expected);
+    /// assert_eq!(value.to_bson_document().unwrap(), document);
+    ///
+    /// let options = BsonOptions {
+    ///     object_ids: ObjectIdFormat::Hex,
+    ///     ..BsonOptions::default()
+    /// };
+    /// let value = Value::from_bson(document.into(), &options);
+    /// let expected = JsonParser::parse_from_bytes(
+    ///     br#"{"_id":"65f1a2b3c4d5e6f708192a3b","name":"Ada","visits":3}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(value, expected);
+    /// ```
+    pub fn from_bson(bson: Bson, options: &BsonOptions) -> Value {
+        match bson {
+            Bson::Null | Bson::Undefined => Value::Null,
+            Bson::Boolean(boolean) => Value::Boolean(boolean),
+            Bson::Int32(integer) => Value::Number(Number::I64(integer.into())),
+            Bson::Int64(integer) => Value::Number(Number::I64(integer)),
+            Bson::Double(float) => Value::Number(Number::F64(float)),
+            Bson::String(string) => Value::from(string),
+            Bson::Array(array) => Value::Array(
+                array
+                    .into_iter()
+                    .map(|bson| Value::from_bson(bson, options))
+                    .collect(),
+            ),
+            Bson::Document(document) => Value::Object(
+                document
+                    .into_iter()
+                    .map(|(key, bson)| (key, Value::from_bson(bson, options)))
+                    .collect::<Map>(),
+            ),
+            Bson::ObjectId(id) if options.object_ids == ObjectIdFormat::Hex => {
+                Value::from(id.to_hex())
+            }
+            Bson::DateTime(date) if options.dates == DateTimeFormat::Millis => {
+                Value::Number(Number::I64(date.timestamp_millis()))
+            }
+            Bson::DateTime(date) if options.dates == DateTimeFormat::Rfc3339 => {
+                match date.try_to_rfc3339_string() {
+                    Ok(string) => Value::from(string),
+                    Err(_) => from_extended_json(Bson::DateTime(date)),
+                }
+            }
+            bson => from_extended_json(bson),
+        }
+    }
+
+    /// Convert the value into BSON.
+    ///
+    /// Integers that fit in 32 bits become `Int32`, other integers `Int64`, and floats
+    /// `Double`. Objects in Extended JSON form, such as `{"$oid": "..."}` or
+    /// `{"$date": "..."}`, are turned back into the BSON type they describe.
+    pub fn to_bson(&self) -> Bson {
+        match self {
+            Value::Null => Bson::Null,
+            Value::Boolean(boolean) => Bson::Boolean(*boolean),
+            Value::Number(Number::I64(integer)) => match i32::try_from(*integer) {
+                Ok(integer) => Bson::Int32(integer),
+                Err(_) => Bson::Int64(*integer),
+            },
+            Value::Number(Number::F64(float)) => Bson::Double(*float),
+            Value::String(string) => Bson::String(string.to_string()),
+            Value::Array(array) => Bson::Array(array.iter().map(Value::to_bson).collect()),
+            Value::Object(object) => {
+                to_extended_json(self).unwrap_or_else(|| Bson::Document(to_document(object)))
+            }
+        }
+    }
+
+    /// Convert an object into a BSON document, as done by [`to_bson`](Value::to_bson).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] if the value isn't an object.
+    pub fn to_bson_document(&self) -> Result<Document, JsonError> {
+        match self {
+            Value::Object(object) => Ok(to_document(object)),
+            value => Err(JsonError::TypeMismatch {
+                pointer: String::new(),
+                expected: "object".to_string(),
+                found: value.type_name(),
+            }),
+        }
+    }
+}
+
+fn to_document(object: &Map) -> Document {
+    object
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_bson()))
+        .collect()
+}
+
+/// Represent a BSON value in relaxed Extended JSON.
+fn from_extended_json(bson: Bson) -> Value {
+    Value::try_from(bson.into_relaxed_extjson())
+        .expect("Extended JSON only holds numbers representable as values")
+}
+
+/// Parse an object in Extended JSON form, or return `None` if it's a regular object.
+fn to_extended_json(value: &Value) -> Option<Bson> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    if object.is_empty() || !object.keys().all(|key| key.starts_with('$')) {
+        return None;
+    }
+
+    let json = serde_json::Value::try_from(value.clone()).ok()?;
+    match Bson::try_from(json) {
+        // Operators such as `$set` are parsed as plain documents.
+        Ok(Bson::Document(_)) | Err(_) => None,
+        Ok(bson) => Some(bson),
+    }
+}
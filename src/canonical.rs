@@ -0,0 +1,90 @@
+use crate::error::JsonError;
+use crate::ser::{format_float_ecmascript, write_string};
+use crate::value::{Number, Value};
+
+impl Value {
+    /// Serialize the value following the JSON Canonicalization Scheme of
+    /// [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785), so that equal values always produce
+    /// the same bytes and can be hashed or signed.
+    ///
+    /// The output has no whitespace, object members are sorted by the UTF-16 code units of
+    /// their keys, strings only escape what JSON requires, and numbers are written the way
+    /// ECMAScript formats floats, so `1.0` becomes `1` and `1e21` becomes `1e+21`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::UnrepresentableNumber`] for NaN and infinite floats, and for
+    /// integers which can't be represented exactly as a float, since the scheme treats every
+    /// number as an IEEE 754 double.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let value = JsonParser::parse_from_bytes(r#"{"b":[1.0,2.5e3],"a":"€"}"#.as_bytes()).unwrap();
+    ///
+    /// assert_eq!(value.to_canonical_string().unwrap(), r#"{"a":"€","b":[1,2500]}"#);
+    /// ```
+    pub fn to_canonical_string(&self) -> Result<String, JsonError> {
+        let mut output = String::new();
+        write_canonical(&mut output, self)?;
+
+        Ok(output)
+    }
+}
+
+fn write_canonical(output: &mut String, value: &Value) -> Result<(), JsonError> {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Boolean(boolean) => output.push_str(if *boolean { "true" } else { "false" }),
+        Value::Number(number) => {
+            let float = match *number {
+                // Integers past 2^53 only survive if they happen to be representable.
+                Number::I64(integer) if integer as f64 as i128 == i128::from(integer) => {
+                    integer as f64
+                }
+                Number::F64(float) if float.is_finite() => float,
+                _ => {
+                    return Err(JsonError::UnrepresentableNumber {
+                        pointer: String::new(),
+                        number: match number {
+                            Number::I64(integer) => integer.to_string(),
+                            Number::F64(float) => float.to_string(),
+                        },
+                    })
+                }
+            };
+            output.push_str(&format_float_ecmascript(float));
+        }
+        Value::String(string) => write_string(output, string),
+        Value::Array(array) => {
+            output.push('[');
+            for (index, element) in array.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical(output, element)
+                    .map_err(|error| error.nested(&index.to_string()))?;
+            }
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut members = object.iter().collect::<Vec<_>>();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            output.push('{');
+            for (index, (key, member)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_string(output, key);
+                output.push(':');
+                write_canonical(output, member).map_err(|error| error.nested(key))?;
+            }
+            output.push('}');
+        }
+    }
+
+    Ok(())
+}
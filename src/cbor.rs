@@ -0,0 +1,55 @@
+use crate::error::JsonError;
+use crate::value::Value;
+
+impl Value {
+    /// Encode the value as [CBOR](https://www.rfc-editor.org/rfc/rfc8949), a compact binary
+    /// format with the same data model as JSON.
+    ///
+    /// Integers are encoded as CBOR integers and floats as CBOR floats, so that [`from_cbor`]
+    /// gives back an identical value.
+    ///
+    /// [`from_cbor`]: Value::from_cbor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    ///
+    /// let value = Value::Array(vec![Value::from(7), Value::from(0.5)]);
+    /// let bytes = value.to_cbor();
+    ///
+    /// // An array of two items: the integer 7, and 0.5 as a half-precision float.
+    /// assert_eq!(bytes, [0x82, 0x07, 0xF9, 0x38, 0x00]);
+    /// assert_eq!(Value::from_cbor(&bytes).unwrap(), value);
+    /// ```
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes).expect("writing to a Vec can't fail");
+
+        bytes
+    }
+
+    /// Decode a value from CBOR.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the bytes aren't valid CBOR, or hold items that have no
+    /// JSON equivalent such as byte strings or non-string map keys.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Value, JsonError> {
+        ciborium::from_reader(bytes).map_err(|error| {
+            let message = match error {
+                ciborium::de::Error::Io(error) => error.to_string(),
+                ciborium::de::Error::Syntax(offset) => format!("syntax error at byte {offset}"),
+                ciborium::de::Error::Semantic(_, message) => message,
+                ciborium::de::Error::RecursionLimitExceeded => {
+                    "recursion limit exceeded".to_string()
+                }
+            };
+
+            JsonError::Format {
+                format: "CBOR",
+                message,
+            }
+        })
+    }
+}
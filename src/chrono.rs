@@ -0,0 +1,50 @@
+use crate::datetime::{FromDate, FromDateTime};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+
+/// Split nanoseconds since the Unix epoch into the whole seconds and the nanoseconds after
+/// them that chrono expects.
+fn split_nanos(nanos: i128) -> Option<(i64, u32)> {
+    let seconds = i64::try_from(nanos.div_euclid(1_000_000_000)).ok()?;
+    let nanos = u32::try_from(nanos.rem_euclid(1_000_000_000)).expect("less than a second");
+
+    Some((seconds, nanos))
+}
+
+impl FromDateTime for DateTime<FixedOffset> {
+    fn from_rfc3339(text: &str) -> Option<Self> {
+        DateTime::parse_from_rfc3339(text).ok()
+    }
+
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        DateTime::<Utc>::from_unix_nanos(nanos).map(|instant| instant.fixed_offset())
+    }
+}
+
+impl FromDateTime for DateTime<Utc> {
+    fn from_rfc3339(text: &str) -> Option<Self> {
+        DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|instant| instant.with_timezone(&Utc))
+    }
+
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let (seconds, nanos) = split_nanos(nanos)?;
+        DateTime::from_timestamp(seconds, nanos)
+    }
+}
+
+impl FromDate for NaiveDate {
+    fn from_ymd(year: i32, month: u8, day: u8) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year, month.into(), day.into())
+    }
+
+    fn from_rfc3339(text: &str) -> Option<Self> {
+        DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|instant| instant.date_naive())
+    }
+
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        DateTime::<Utc>::from_unix_nanos(nanos).map(|instant| instant.date_naive())
+    }
+}
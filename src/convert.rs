@@ -0,0 +1,400 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{short_type_name, Number, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+/// A type which can be built from a JSON [`Value`].
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::convert::{FromJson, ToJson};
+/// use json_parser::error::JsonError;
+/// use json_parser::map::Map;
+/// use json_parser::parser::JsonParser;
+/// use json_parser::value::Value;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// impl FromJson for Point {
+///     fn from_json(value: &Value) -> Result<Self, JsonError> {
+///         let object = Map::from_json(value)?;
+///         Ok(Point {
+///             x: i32::from_json(&object["x"]).map_err(|error| error.nested("x"))?,
+///             y: i32::from_json(&object["y"]).map_err(|error| error.nested("y"))?,
+///         })
+///     }
+/// }
+///
+/// impl ToJson for Point {
+///     fn to_json(&self) -> Value {
+///         let mut object = Map::new();
+///         object.insert("x".to_string(), self.x.to_json());
+///         object.insert("y".to_string(), self.y.to_json());
+///         Value::Object(object)
+///     }
+/// }
+///
+/// let value = JsonParser::parse_from_bytes(br#"{"x":1,"y":2}"#).unwrap();
+/// let point = Point::from_json(&value).unwrap();
+///
+/// assert_eq!(point, Point { x: 1, y: 2 });
+/// assert_eq!(point.to_json(), value);
+///
+/// // Integers are only built from numbers they hold exactly.
+/// assert_eq!(u8::from_json(&Value::from(2.0)).unwrap(), 2);
+/// assert!(u8::from_json(&Value::from(256)).is_err());
+/// assert!(i64::from_json(&Value::from(2f64.powi(63))).is_err());
+/// ```
+pub trait FromJson: Sized {
+    /// Build `Self` from a JSON value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] pointing at the first value that doesn't have the
+    /// expected type.
+    fn from_json(value: &Value) -> Result<Self, JsonError>;
+
+    /// Value to use when a struct field of this type is missing from its object.
+    ///
+    /// Returns `None` by default, which makes a missing field an error. [`Option`] overrides
+    /// this so that missing fields become `None`.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// A type which can be converted into a JSON [`Value`].
+pub trait ToJson {
+    /// Convert `self` into a JSON value.
+    fn to_json(&self) -> Value;
+}
+
+#[cfg(feature = "derive")]
+pub use json_parser_derive::{FromJson, ToJson};
+
+/// Build the error returned when `value` can't be converted to `T`.
+fn mismatch<T>(value: &Value) -> JsonError {
+    JsonError::TypeMismatch {
+        pointer: String::new(),
+        expected: short_type_name::<T>(),
+        found: value.type_name(),
+    }
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        match value {
+            Value::Boolean(boolean) => Ok(*boolean),
+            _ => Err(mismatch::<Self>(value)),
+        }
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Value {
+        Value::Boolean(*self)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        boolean.to_json()
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        match value {
+            Value::String(string) => Ok(string.to_string()),
+            _ => Err(mismatch::<Self>(value)),
+        }
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::from(self.as_str())
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::from(self)
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        if let Value::String(string) = value {
+            let mut characters = string.chars();
+            if let (Some(character), None) = (characters.next(), characters.next()) {
+                return Ok(character);
+            }
+        }
+
+        Err(mismatch::<Self>(value))
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> Value {
+        Value::from(self.to_string())
+    }
+}
+
+/// Implement the conversion traits for integer types, which only accept numbers without a
+/// fractional part that fit in the target type.
+macro_rules! impl_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl FromJson for $integer {
+                #[allow(clippy::cast_possible_truncation)]
+                fn from_json(value: &Value) -> Result<Self, JsonError> {
+                    let integer = match value {
+                        Value::Number(Number::I64(integer)) => Some(*integer),
+                        // Casting saturates, so only floats in the range of `i64` are cast,
+                        // which converts them exactly.
+                        Value::Number(Number::F64(float))
+                            if float.fract() == 0.0
+                                && *float >= -(2f64.powi(63))
+                                && *float < 2f64.powi(63) =>
+                        {
+                            Some(*float as i64)
+                        }
+                        _ => None,
+                    };
+
+                    integer
+                        .and_then(|integer| <$integer>::try_from(integer).ok())
+                        .ok_or_else(|| mismatch::<Self>(value))
+                }
+            }
+
+            impl ToJson for $integer {
+                #[allow(clippy::cast_precision_loss)]
+                fn to_json(&self) -> Value {
+                    // Integers which don't fit in an `i64` fall back to a float.
+                    match i64::try_from(*self) {
+                        Ok(integer) => Value::Number(Number::I64(integer)),
+                        Err(_) => Value::Number(Number::F64(*self as f64)),
+                    }
+                }
+            }
+
+            impl From<$integer> for Value {
+                fn from(integer: $integer) -> Self {
+                    integer.to_json()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromJson for f64 {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        f64::try_from(value).map_err(|()| mismatch::<Self>(value))
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> Value {
+        Value::Number(Number::F64(*self))
+    }
+}
+
+impl From<f64> for Value {
+    fn from(float: f64) -> Self {
+        float.to_json()
+    }
+}
+
+impl FromJson for f32 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        f64::try_from(value)
+            .map(|float| float as f32)
+            .map_err(|()| mismatch::<Self>(value))
+    }
+}
+
+impl ToJson for f32 {
+    fn to_json(&self) -> Value {
+        Value::Number(Number::F64(f64::from(*self)))
+    }
+}
+
+impl From<f32> for Value {
+    fn from(float: f32) -> Self {
+        float.to_json()
+    }
+}
+
+impl FromJson for () {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        match value {
+            Value::Null => Ok(()),
+            _ => Err(mismatch::<Self>(value)),
+        }
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> Value {
+        Value::Null
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        match self {
+            Some(value) => value.to_json(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        let Value::Array(array) = value else {
+            return Err(mismatch::<Self>(value));
+        };
+
+        array
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                T::from_json(value).map_err(|error| error.nested(&index.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl FromJson for Map {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        match value {
+            Value::Object(object) => Ok(object.clone()),
+            _ => Err(mismatch::<Self>(value)),
+        }
+    }
+}
+
+impl ToJson for Map {
+    fn to_json(&self) -> Value {
+        Value::Object(self.clone())
+    }
+}
+
+/// Convert every member of an object into `T`, collecting them into any map type.
+fn members_from_json<T, M>(value: &Value) -> Result<M, JsonError>
+where
+    T: FromJson,
+    M: FromIterator<(String, T)>,
+{
+    let Value::Object(object) = value else {
+        return Err(mismatch::<M>(value));
+    };
+
+    object
+        .iter()
+        .map(|(key, value)| {
+            T::from_json(value)
+                .map(|value| (key.to_string(), value))
+                .map_err(|error| error.nested(key))
+        })
+        .collect()
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        members_from_json(value)
+    }
+}
+
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> Value {
+        Value::Object(
+            self.iter()
+                .map(|(key, value)| (key.clone(), value.to_json()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        members_from_json(value)
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> Value {
+        Value::Object(
+            self.iter()
+                .map(|(key, value)| (key.clone(), value.to_json()))
+                .collect(),
+        )
+    }
+}
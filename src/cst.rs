@@ -0,0 +1,651 @@
+//! A lossless parse mode, which keeps the exact text of a document along with its structure.
+//!
+//! Unlike [`JsonParser`](crate::parser::JsonParser), which only keeps the values of a
+//! document, a [`Document`] remembers where every value, key and comment is located in the
+//! original text. Whitespace, comments and the order of members are all preserved, so that
+//! tools can inspect a file and write it back exactly as it was written.
+//!
+//! Besides standard JSON, documents may contain `// line` and `/* block */` comments wherever
+//! whitespace is allowed.
+
+use crate::convert::ToJson;
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::pointer::{parse_index, parse_pointer};
+use crate::ser::write_string;
+use crate::token::MAX_NESTING;
+use crate::value::{Number, Value};
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// A JSON document parsed without losing any of its text.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::cst::Document;
+///
+/// let text = "{\n  // Where to listen.\n  \"port\": 8080,\n  \"hosts\": [\"a\", \"b\"]\n}\n";
+/// let document = Document::parse(text).unwrap();
+///
+/// assert_eq!(document.to_string(), text);
+/// assert_eq!(document.comments().collect::<Vec<_>>(), ["// Where to listen."]);
+///
+/// let port = document.get("/port").unwrap();
+/// assert_eq!(&document.source()[port.span()], "8080");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    source: String,
+    root: Node,
+    comments: Vec<Range<usize>>,
+}
+
+/// A value inside a [`Document`], along with the location of its text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    span: Range<usize>,
+    kind: NodeKind,
+}
+
+/// The kind of value a [`Node`] holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Null,
+    Boolean(bool),
+    /// A number, whose exact text can be found through the span of its node.
+    Number,
+    /// A string, with its escape sequences decoded.
+    String(String),
+    Array(Vec<Node>),
+    /// Members in the order in which they appear in the document, including duplicate keys.
+    Object(Vec<Member>),
+}
+
+/// A member of an object inside a [`Document`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    key: String,
+    key_span: Range<usize>,
+    value: Node,
+}
+
+impl Document {
+    /// Parse a document, keeping all of its text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the text isn't a single valid JSON value, possibly
+    /// surrounded and interspersed with whitespace and comments, or has arrays and objects
+    /// nested more than [`MAX_NESTING`] levels deep. A leading byte order mark is allowed, and
+    /// kept in the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::cst::Document;
+    ///
+    /// let document = Document::parse("\u{FEFF}[1, 2]").unwrap();
+    /// assert_eq!(document.source(), "\u{FEFF}[1, 2]");
+    ///
+    /// assert!(Document::parse(&"[".repeat(100_000)).is_err());
+    /// ```
+    pub fn parse(source: &str) -> Result<Document, JsonError> {
+        let mut parser = Parser {
+            source,
+            // A leading byte order mark is kept in the text, but isn't part of the value.
+            position: if source.starts_with('\u{FEFF}') {
+                '\u{FEFF}'.len_utf8()
+            } else {
+                0
+            },
+            depth: 0,
+            comments: Vec::new(),
+        };
+
+        parser.skip_trivia()?;
+        let root = parser.parse_value()?;
+        parser.skip_trivia()?;
+        if parser.position < source.len() {
+            return Err(parser.error("end of input"));
+        }
+
+        Ok(Document {
+            source: source.to_string(),
+            root,
+            comments: parser.comments,
+        })
+    }
+
+    /// The text of the document, exactly as it was parsed.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The root value of the document.
+    #[must_use]
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// The text of every comment in the document, including their `//` or `/* */` delimiters.
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.comments.iter().map(|span| &self.source[span.clone()])
+    }
+
+    /// Where every comment is in the text, including their delimiters.
+    pub(crate) fn comment_spans(&self) -> &[Range<usize>] {
+        &self.comments
+    }
+
+    /// Find the node referenced by a JSON Pointer, such as `/servers/0/port`.
+    ///
+    /// When an object has duplicate keys, the last one is used, like when converting the
+    /// document into a [`Value`].
+    #[must_use]
+    pub fn get(&self, pointer: &str) -> Option<&Node> {
+        self.root.find(&parse_pointer(pointer)?)
+    }
+
+    /// Set the value referenced by a JSON Pointer, leaving the rest of the text untouched.
+    ///
+    /// An existing value is replaced in place. A missing member is added at the end of its
+    /// object, and an index one past the end of an array, or `-`, appends to it. New members
+    /// copy the whitespace used around the first member of the object, so that they line up
+    /// with their siblings. The new value itself is written without any whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::InvalidPath`] if the pointer is malformed or its parent doesn't
+    /// exist, and [`JsonError::PathConflict`] if its parent is neither an object nor an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::cst::Document;
+    ///
+    /// let mut document = Document::parse(
+    ///     "{\n  // Listening address.\n  \"server\": { \"port\": 80 },\n  \"debug\": false\n}",
+    /// )
+    /// .unwrap();
+    /// document.set("/server/port", 8080).unwrap();
+    /// document.set("/workers", 4).unwrap();
+    ///
+    /// assert_eq!(
+    ///     document.to_string(),
+    ///     "{\n  // Listening address.\n  \"server\": { \"port\": 8080 },\n  \"debug\": false,\n  \"workers\": 4\n}",
+    /// );
+    ///
+    /// let mut document = Document::parse(r#"{"a": 0, "b": [1, 2]}"#).unwrap();
+    /// document.set("/b/2", 3).unwrap();
+    /// document.set("/c", 4).unwrap();
+    ///
+    /// assert_eq!(document.to_string(), r#"{"a": 0, "b": [1, 2, 3], "c": 4}"#);
+    /// ```
+    pub fn set<T: ToJson>(&mut self, pointer: &str, value: T) -> Result<(), JsonError> {
+        let invalid = || JsonError::InvalidPath(pointer.to_string());
+        let value = value.to_json().to_string_compact();
+
+        let tokens = parse_pointer(pointer).ok_or_else(invalid)?;
+        let Some((last, parents)) = tokens.split_last() else {
+            let span = self.root.span();
+            return self.splice(span, &value);
+        };
+        let parent = self.root.find(parents).ok_or_else(invalid)?;
+
+        let (range, text) = match &parent.kind {
+            NodeKind::Object(members) => {
+                match members.iter().rev().find(|member| &member.key == last) {
+                    Some(member) => (member.value.span(), value),
+                    None => {
+                        let mut key = String::new();
+                        write_string(&mut key, last);
+                        self.new_member(parent, members, &key, &value)
+                    }
+                }
+            }
+            NodeKind::Array(elements) => {
+                let index = match last.as_str() {
+                    "-" => elements.len(),
+                    token => parse_index(token).ok_or_else(invalid)?,
+                };
+                match elements.get(index) {
+                    Some(element) => (element.span(), value),
+                    None if index == elements.len() => self.new_element(parent, elements, &value),
+                    None => return Err(invalid()),
+                }
+            }
+            _ => return Err(JsonError::PathConflict(pointer.to_string())),
+        };
+
+        self.splice(range, &text)
+    }
+
+    /// Position and text of a member appended to `object`.
+    fn new_member(
+        &self,
+        object: &Node,
+        members: &[Member],
+        key: &str,
+        value: &str,
+    ) -> (Range<usize>, String) {
+        let Some(first) = members.first() else {
+            // Insert right after the opening brace of an empty object.
+            let start = object.span.start + 1;
+            return (start..start, format!("{key}: {value}"));
+        };
+        let last = members.last().unwrap_or(first);
+
+        // Follow the whitespace after an existing comma, or else after the opening brace.
+        let indentation = self.indentation_before(members.get(1).unwrap_or(first).key_span.start);
+        let separator = &self.source[first.key_span.end..first.value.span.start];
+        let end = last.value.span.end;
+
+        (end..end, format!(",{indentation}{key}{separator}{value}"))
+    }
+
+    /// Position and text of an element appended to `array`.
+    fn new_element(&self, array: &Node, elements: &[Node], value: &str) -> (Range<usize>, String) {
+        match (elements.first(), elements.last()) {
+            (Some(first), Some(last)) => {
+                // Follow the whitespace after an existing comma, or else after the opening bracket.
+                let indentation =
+                    self.indentation_before(elements.get(1).unwrap_or(first).span.start);
+                let end = last.span.end;
+                (end..end, format!(",{indentation}{value}"))
+            }
+            _ => {
+                let start = array.span.start + 1;
+                (start..start, value.to_string())
+            }
+        }
+    }
+
+    /// The whitespace directly preceding the byte at `position`.
+    fn indentation_before(&self, position: usize) -> &str {
+        let before = &self.source[..position];
+        let trimmed = before.trim_end_matches(|character: char| character.is_ascii_whitespace());
+
+        &before[trimmed.len()..]
+    }
+
+    /// Replace a range of the source and parse it again, so that spans stay accurate.
+    fn splice(&mut self, range: Range<usize>, text: &str) -> Result<(), JsonError> {
+        let mut source = self.source.clone();
+        source.replace_range(range, text);
+        *self = Document::parse(&source)?;
+
+        Ok(())
+    }
+
+    /// Convert the document into a value, dropping its formatting and comments.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        self.root.to_value(&self.source)
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Node {
+    /// Byte range of the value in the source of its document.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &NodeKind {
+        &self.kind
+    }
+
+    /// Find the descendant referenced by the unescaped tokens of a JSON Pointer.
+    fn find(&self, tokens: &[String]) -> Option<&Node> {
+        let mut node = self;
+        for token in tokens {
+            node = match &node.kind {
+                NodeKind::Object(members) => {
+                    &members
+                        .iter()
+                        .rev()
+                        .find(|member| &member.key == token)?
+                        .value
+                }
+                NodeKind::Array(elements) => elements.get(parse_index(token)?)?,
+                _ => return None,
+            };
+        }
+
+        Some(node)
+    }
+
+    fn to_value(&self, source: &str) -> Value {
+        match &self.kind {
+            NodeKind::Null => Value::Null,
+            NodeKind::Boolean(boolean) => Value::Boolean(*boolean),
+            NodeKind::Number => Value::Number(parse_number(&source[self.span()])),
+            NodeKind::String(string) => Value::from(string.as_str()),
+            NodeKind::Array(elements) => Value::Array(
+                elements
+                    .iter()
+                    .map(|element| element.to_value(source))
+                    .collect(),
+            ),
+            NodeKind::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|member| (member.key.clone(), member.value.to_value(source)))
+                    .collect::<Map>(),
+            ),
+        }
+    }
+}
+
+impl Member {
+    /// The key of the member, with its escape sequences decoded.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Byte range of the key in the source of its document, including its quotes.
+    #[must_use]
+    pub fn key_span(&self) -> Range<usize> {
+        self.key_span.clone()
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &Node {
+        &self.value
+    }
+}
+
+/// Convert the text of a valid JSON number, keeping integers as integers when they fit.
+pub(crate) fn parse_number(text: &str) -> Number {
+    if !text.contains(['.', 'e', 'E']) {
+        if let Ok(integer) = text.parse() {
+            return Number::I64(integer);
+        }
+    }
+
+    Number::F64(text.parse().unwrap_or_default())
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    /// Byte offset of the next character to read.
+    position: usize,
+    /// Number of arrays and objects the next character is in.
+    depth: usize,
+    comments: Vec<Range<usize>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, expected: &str) -> JsonError {
+        match self.source[self.position..].chars().next() {
+            Some(found) => JsonError::Syntax(format!(
+                "expected {expected} at byte {}, found `{found}`",
+                self.position
+            )),
+            None => JsonError::Syntax(format!("expected {expected}, found end of input")),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    /// Skip whitespace and comments, recording where the comments are.
+    fn skip_trivia(&mut self) -> Result<(), JsonError> {
+        loop {
+            let rest = self.rest();
+            if let Some(character) = rest.chars().next().filter(|c| c.is_ascii_whitespace()) {
+                self.position += character.len_utf8();
+            } else if rest.starts_with("//") {
+                let length = rest.find('\n').unwrap_or(rest.len());
+                self.comments.push(self.position..self.position + length);
+                self.position += length;
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let Some(end) = comment.find("*/") else {
+                    return Err(self.error("`*/` closing the comment"));
+                };
+                let length = end + 4;
+                self.comments.push(self.position..self.position + length);
+                self.position += length;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consume `expected` if it's the next character, after any trivia.
+    fn eat(&mut self, expected: u8) -> Result<bool, JsonError> {
+        self.skip_trivia()?;
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn parse_value(&mut self) -> Result<Node, JsonError> {
+        let start = self.position;
+        let kind = match self.peek() {
+            Some(open @ (b'{' | b'[')) => {
+                // Nodes are built recursively, so deep nesting would overflow the stack.
+                if self.depth == MAX_NESTING {
+                    return Err(JsonError::Syntax(format!(
+                        "arrays and objects are nested more than {MAX_NESTING} levels deep at byte {start}"
+                    )));
+                }
+                self.depth += 1;
+                let kind = match open {
+                    b'{' => self.parse_object()?,
+                    _ => self.parse_array()?,
+                };
+                self.depth -= 1;
+                kind
+            }
+            Some(b'"') => NodeKind::String(self.parse_string()?),
+            Some(b'-' | b'0'..=b'9') => self.parse_number()?,
+            _ => self.parse_literal()?,
+        };
+
+        Ok(Node {
+            span: start..self.position,
+            kind,
+        })
+    }
+
+    fn parse_literal(&mut self) -> Result<NodeKind, JsonError> {
+        for (literal, kind) in [
+            ("null", NodeKind::Null),
+            ("true", NodeKind::Boolean(true)),
+            ("false", NodeKind::Boolean(false)),
+        ] {
+            if self.rest().starts_with(literal) {
+                self.position += literal.len();
+                return Ok(kind);
+            }
+        }
+
+        Err(self.error("a value"))
+    }
+
+    fn parse_number(&mut self) -> Result<NodeKind, JsonError> {
+        let digits = |parser: &mut Self| {
+            let length = parser.rest().bytes().take_while(u8::is_ascii_digit).count();
+            parser.position += length;
+            length
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        // The integer part can't have leading zeros.
+        if self.peek() == Some(b'0') {
+            self.position += 1;
+        } else if digits(self) == 0 {
+            return Err(self.error("a digit"));
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if digits(self) == 0 {
+                return Err(self.error("a digit"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.error("a digit"));
+            }
+        }
+
+        Ok(NodeKind::Number)
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        // Skip the opening quote.
+        self.position += 1;
+        let mut string = String::new();
+
+        loop {
+            let Some(character) = self.rest().chars().next() else {
+                return Err(self.error("`\"` closing the string"));
+            };
+            self.position += character.len_utf8();
+
+            match character {
+                '"' => return Ok(string),
+                '\\' => string.push(self.parse_escape()?),
+                control if control < ' ' => {
+                    self.position -= 1;
+                    return Err(self.error("an escaped control character"));
+                }
+                character => string.push(character),
+            }
+        }
+    }
+
+    /// Decode the escape sequence following a `\`.
+    fn parse_escape(&mut self) -> Result<char, JsonError> {
+        let escape = self.peek();
+        self.position += 1;
+
+        Ok(match escape {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{08}',
+            Some(b'f') => '\u{0C}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let high = self.parse_hex()?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    char::from_u32(high.into()).unwrap_or(char::REPLACEMENT_CHARACTER)
+                } else if self.rest().starts_with("\\u") {
+                    // A high surrogate must be followed by a low one to form a character.
+                    self.position += 2;
+                    let low = self.parse_hex()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("a low surrogate"));
+                    }
+                    let code =
+                        0x10000 + ((u32::from(high) - 0xD800) << 10) + (u32::from(low) - 0xDC00);
+                    char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                } else {
+                    char::REPLACEMENT_CHARACTER
+                }
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error("an escape sequence"));
+            }
+        })
+    }
+
+    fn parse_hex(&mut self) -> Result<u16, JsonError> {
+        let code = self
+            .rest()
+            .get(..4)
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| self.error("four hexadecimal digits"))?;
+        self.position += 4;
+
+        Ok(code)
+    }
+
+    fn parse_array(&mut self) -> Result<NodeKind, JsonError> {
+        // Skip the opening bracket.
+        self.position += 1;
+        let mut elements = Vec::new();
+
+        if self.eat(b']')? {
+            return Ok(NodeKind::Array(elements));
+        }
+        loop {
+            self.skip_trivia()?;
+            elements.push(self.parse_value()?);
+
+            if self.eat(b']')? {
+                return Ok(NodeKind::Array(elements));
+            }
+            if !self.eat(b',')? {
+                return Err(self.error("`,` or `]`"));
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<NodeKind, JsonError> {
+        // Skip the opening brace.
+        self.position += 1;
+        let mut members = Vec::new();
+
+        if self.eat(b'}')? {
+            return Ok(NodeKind::Object(members));
+        }
+        loop {
+            self.skip_trivia()?;
+            if self.peek() != Some(b'"') {
+                return Err(self.error("a string key"));
+            }
+            let start = self.position;
+            let key = self.parse_string()?;
+            let key_span = start..self.position;
+
+            if !self.eat(b':')? {
+                return Err(self.error("`:`"));
+            }
+            self.skip_trivia()?;
+            let value = self.parse_value()?;
+            members.push(Member {
+                key,
+                key_span,
+                value,
+            });
+
+            if self.eat(b'}')? {
+                return Ok(NodeKind::Object(members));
+            }
+            if !self.eat(b',')? {
+                return Err(self.error("`,` or `}`"));
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::Value;
+use std::collections::HashSet;
+
+impl Value {
+    /// Encode an array of objects as CSV, with one row per object after a header row.
+    ///
+    /// Nested objects and arrays are flattened into columns named by their path, as done by
+    /// [`Value::flatten`], so `{"user": {"id": 1}}` gives a `user.id` column. Columns appear in
+    /// the order their keys are first seen. Strings are written as is, other values as JSON,
+    /// and `null` as well as missing members as empty fields. Fields are quoted when needed,
+    /// as described in RFC 4180, and rows end with `\n`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the value isn't an array of objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let value = JsonParser::parse_from_bytes(
+    ///     br#"[{"id": 1, "user": {"name": "Ada, Countess"}}, {"id": 2, "active": true}]"#,
+    /// )
+    /// .unwrap();
+    /// let csv = value.to_csv().unwrap();
+    ///
+    /// assert_eq!(csv, "id,user.name,active\n1,\"Ada, Countess\",\n2,,true\n");
+    ///
+    /// // Fields are read back as strings.
+    /// let records = Value::from_csv(&csv).unwrap();
+    /// let expected = JsonParser::parse_from_bytes(
+    ///     br#"[{"id": "1", "user": {"name": "Ada, Countess"}, "active": ""},
+    ///          {"id": "2", "user": {"name": ""}, "active": "true"}]"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(records, expected);
+    /// ```
+    pub fn to_csv(&self) -> Result<String, JsonError> {
+        let Value::Array(records) = self else {
+            return Err(csv_error("expected an array of objects"));
+        };
+
+        let mut rows = Vec::with_capacity(records.len());
+        let mut columns = Vec::new();
+        let mut seen = HashSet::new();
+        for record in records {
+            if !matches!(record, Value::Object(_)) {
+                return Err(csv_error("expected an array of objects"));
+            }
+            let row = record.flatten();
+            for column in row.keys() {
+                if seen.insert(column.to_string()) {
+                    columns.push(column.to_string());
+                }
+            }
+            rows.push(row);
+        }
+
+        let mut output = String::new();
+        write_row(&mut output, &columns);
+        for row in &rows {
+            write_row(
+                &mut output,
+                columns.iter().map(|column| match row.get(column) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(string)) => string.to_string(),
+                    Some(value) => value.to_string_compact(),
+                }),
+            );
+        }
+
+        Ok(output)
+    }
+
+    /// Decode CSV into an array with an object per record, keyed by the fields of the first
+    /// record.
+    ///
+    /// Every field is kept as a string. Headers are paths, as produced by
+    /// [`to_csv`](Value::to_csv), so a `user.id` column gives an `id` member inside a `user`
+    /// object. Fields may be quoted as described in RFC 4180, and records may end with `\n`
+    /// or `\r\n`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if a quoted field isn't closed or a record doesn't have as
+    /// many fields as the header, and the errors of [`Value::unflatten`] for conflicting
+    /// headers such as `a` and `a.b`.
+    pub fn from_csv(input: &str) -> Result<Value, JsonError> {
+        let mut records = parse_records(input)?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(Value::Array(Vec::new()));
+        };
+
+        records
+            .enumerate()
+            .map(|(position, fields)| {
+                if fields.len() != header.len() {
+                    return Err(csv_error(&format!(
+                        "record {} has {} fields, but the header has {}",
+                        position + 2,
+                        fields.len(),
+                        header.len()
+                    )));
+                }
+                let mut flat = Map::with_capacity(fields.len());
+                for (column, field) in header.iter().zip(fields) {
+                    flat.insert(column.clone(), Value::from(field));
+                }
+                Value::unflatten(&flat)
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::Array)
+    }
+}
+
+fn csv_error(message: &str) -> JsonError {
+    JsonError::Format {
+        format: "CSV",
+        message: message.to_string(),
+    }
+}
+
+fn write_row<I>(output: &mut String, fields: I)
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    for (position, field) in fields.into_iter().enumerate() {
+        if position > 0 {
+            output.push(',');
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\n', '\r']) {
+            output.push('"');
+            output.push_str(&field.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(field);
+        }
+    }
+    output.push('\n');
+}
+
+/// Split CSV text into records and unquoted fields.
+fn parse_records(input: &str) -> Result<Vec<Vec<String>>, JsonError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut characters = input.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if field.is_empty() => loop {
+                match characters.next() {
+                    Some('"') if characters.peek() == Some(&'"') => {
+                        characters.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(character) => field.push(character),
+                    None => return Err(csv_error("unterminated quoted field")),
+                }
+            },
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if characters.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            character => field.push(character),
+        }
+    }
+    // The last record may lack its line break.
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,186 @@
+//! Reading timestamps and dates out of values, as the types of the `chrono` and `time` crates
+//! with the features of the same names.
+//!
+//! Strings are read as RFC 3339 date-times such as `"2024-02-29T12:30:00+01:00"`, or full
+//! dates such as `"2024-02-29"`, and numbers as times since the Unix epoch, in seconds unless
+//! another [`EpochUnit`] is given.
+
+use crate::value::{Number, Value};
+
+/// Unit of the numbers read as times since the Unix epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EpochUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl EpochUnit {
+    fn nanoseconds(self) -> i128 {
+        match self {
+            EpochUnit::Seconds => 1_000_000_000,
+            EpochUnit::Milliseconds => 1_000_000,
+            EpochUnit::Microseconds => 1_000,
+            EpochUnit::Nanoseconds => 1,
+        }
+    }
+}
+
+/// A date and time which [`Value::as_datetime`] can read.
+pub trait FromDateTime: Sized {
+    /// Parse an RFC 3339 date-time.
+    fn from_rfc3339(text: &str) -> Option<Self>;
+
+    /// The instant a number of nanoseconds after the Unix epoch.
+    fn from_unix_nanos(nanos: i128) -> Option<Self>;
+}
+
+/// A calendar date which [`Value::as_date`] can read.
+pub trait FromDate: Sized {
+    /// The date of a year, month from 1 to 12, and day of the month.
+    fn from_ymd(year: i32, month: u8, day: u8) -> Option<Self>;
+
+    /// The date, in its own offset, of an RFC 3339 date-time.
+    fn from_rfc3339(text: &str) -> Option<Self>;
+
+    /// The date in UTC of the instant a number of nanoseconds after the Unix epoch.
+    fn from_unix_nanos(nanos: i128) -> Option<Self>;
+}
+
+impl Value {
+    /// Read an RFC 3339 date-time string, or a number of seconds since the Unix epoch, as a
+    /// date and time such as `chrono::DateTime<Utc>` or `time::OffsetDateTime`.
+    ///
+    /// Returns `None` for other values, strings which aren't valid date-times, and instants
+    /// the type can't represent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "chrono")]
+    /// # {
+    /// use chrono::{DateTime, FixedOffset, Utc};
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let value = Value::from("2024-02-29T12:30:00+01:00");
+    /// let local: DateTime<FixedOffset> = value.as_datetime().unwrap();
+    /// let utc: DateTime<Utc> = value.as_datetime().unwrap();
+    ///
+    /// assert_eq!(local.to_rfc3339(), "2024-02-29T12:30:00+01:00");
+    /// assert_eq!(utc.to_rfc3339(), "2024-02-29T11:30:00+00:00");
+    /// assert_eq!(Value::Number(Number::I64(1_709_206_200)).as_datetime::<DateTime<Utc>>(), Some(utc));
+    /// assert_eq!(Value::from("yesterday").as_datetime::<DateTime<Utc>>(), None);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn as_datetime<T: FromDateTime>(&self) -> Option<T> {
+        self.as_datetime_with(EpochUnit::Seconds)
+    }
+
+    /// Read a date and time like [`as_datetime`](Value::as_datetime), with numbers counting
+    /// `unit`s since the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "time")]
+    /// # {
+    /// use json_parser::datetime::EpochUnit;
+    /// use json_parser::value::{Number, Value};
+    /// use time::OffsetDateTime;
+    ///
+    /// let value = Value::Number(Number::I64(1_709_206_200_500));
+    /// let instant: OffsetDateTime = value.as_datetime_with(EpochUnit::Milliseconds).unwrap();
+    ///
+    /// assert_eq!(instant.unix_timestamp(), 1_709_206_200);
+    /// assert_eq!(instant.millisecond(), 500);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn as_datetime_with<T: FromDateTime>(&self, unit: EpochUnit) -> Option<T> {
+        match self {
+            Value::String(text) => T::from_rfc3339(text),
+            Value::Number(number) => T::from_unix_nanos(unix_nanos(*number, unit)?),
+            _ => None,
+        }
+    }
+
+    /// Read a full date string such as `"2024-02-29"`, the date part of an RFC 3339
+    /// date-time string, or the date in UTC of a number of seconds since the Unix epoch, as a
+    /// date such as `chrono::NaiveDate` or `time::Date`.
+    ///
+    /// Returns `None` for other values, strings which aren't valid dates or date-times, and
+    /// dates the type can't represent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "time")]
+    /// # {
+    /// use json_parser::value::{Number, Value};
+    /// use time::{Date, Month};
+    ///
+    /// let leap_day = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+    ///
+    /// assert_eq!(Value::from("2024-02-29").as_date(), Some(leap_day));
+    /// assert_eq!(Value::from("2024-02-29T23:30:00-05:00").as_date(), Some(leap_day));
+    /// assert_eq!(Value::Number(Number::I64(1_709_164_800)).as_date(), Some(leap_day));
+    /// assert_eq!(Value::from("2023-02-29").as_date::<Date>(), None);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn as_date<T: FromDate>(&self) -> Option<T> {
+        self.as_date_with(EpochUnit::Seconds)
+    }
+
+    /// Read a date like [`as_date`](Value::as_date), with numbers counting `unit`s since the
+    /// Unix epoch.
+    #[must_use]
+    pub fn as_date_with<T: FromDate>(&self, unit: EpochUnit) -> Option<T> {
+        match self {
+            Value::String(text) => match parse_full_date(text) {
+                Some((year, month, day)) => T::from_ymd(year, month, day),
+                None => T::from_rfc3339(text),
+            },
+            Value::Number(number) => T::from_unix_nanos(unix_nanos(*number, unit)?),
+            _ => None,
+        }
+    }
+}
+
+/// The number of nanoseconds since the Unix epoch of a number of `unit`s, rounding floats
+/// to the nearest nanosecond.
+fn unix_nanos(number: Number, unit: EpochUnit) -> Option<i128> {
+    match number {
+        Number::I64(integer) => i128::from(integer).checked_mul(unit.nanoseconds()),
+        Number::F64(float) => {
+            #[allow(clippy::cast_precision_loss)]
+            let nanos = (float * unit.nanoseconds() as f64).round();
+            // Beyond this range, no date type can represent the instant anyway.
+            #[allow(clippy::cast_possible_truncation)]
+            (nanos.abs() < 1e36).then_some(nanos as i128)
+        }
+    }
+}
+
+/// Split an RFC 3339 full date, which is exactly `YYYY-MM-DD`, into its year, month and day.
+fn parse_full_date(text: &str) -> Option<(i32, u8, u8)> {
+    let bytes = text.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        bytes[range.clone()]
+            .iter()
+            .all(u8::is_ascii_digit)
+            .then(|| &text[range])
+    };
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+
+    Some((
+        digits(0..4)?.parse().ok()?,
+        digits(5..7)?.parse().ok()?,
+        digits(8..10)?.parse().ok()?,
+    ))
+}
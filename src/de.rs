@@ -0,0 +1,337 @@
+use crate::error::JsonError;
+use crate::token::{JsonTokenizer, Token};
+use crate::value::Number;
+use serde::de::value::StrDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::io::{BufReader, Cursor};
+use std::iter::Peekable;
+use std::slice::Iter;
+
+/// Deserialize an instance of `T` from a string of JSON.
+///
+/// The value is built directly from the tokens of the input, without going through an
+/// intermediate [`Value`](crate::value::Value).
+///
+/// # Errors
+///
+/// Returns [`JsonError::Syntax`] if the tokens don't form valid JSON, and [`JsonError::Custom`]
+/// if the JSON doesn't match what `T` expects.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let point: Point = json_parser::from_str(r#"{"x":1,"y":2}"#).unwrap();
+///
+/// assert_eq!((point.x, point.y), (1, 2));
+/// ```
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, JsonError> {
+    from_slice(input.as_bytes())
+}
+
+/// Deserialize an instance of `T` from bytes of JSON.
+///
+/// # Errors
+///
+/// See [`from_str`].
+pub fn from_slice<T: DeserializeOwned>(input: &[u8]) -> Result<T, JsonError> {
+    let mut json_tokenizer = JsonTokenizer::<BufReader<Cursor<&[u8]>>>::from_bytes(input);
+    let tokens = json_tokenizer.tokenize_json()?;
+
+    let mut deserializer = Deserializer {
+        iterator: tokens.iter().peekable(),
+    };
+    let value = T::deserialize(&mut deserializer)?;
+
+    // Nothing but the value itself should be present in the input.
+    match deserializer.iterator.next() {
+        None => Ok(value),
+        Some(token) => Err(unexpected(token, "end of input")),
+    }
+}
+
+/// A serde deserializer over the tokens produced by [`JsonTokenizer`].
+struct Deserializer<'a> {
+    iterator: Peekable<Iter<'a, Token>>,
+}
+
+/// Build the error for a token that doesn't fit the structure of the document.
+fn unexpected(token: &Token, expected: &str) -> JsonError {
+    JsonError::Syntax(format!("expected {expected}, found {token:?}"))
+}
+
+impl<'a> Deserializer<'a> {
+    /// Consume the next token, failing at the end of the input.
+    fn next(&mut self) -> Result<&'a Token, JsonError> {
+        self.iterator
+            .next()
+            .ok_or_else(|| JsonError::Syntax("unexpected end of input".to_string()))
+    }
+
+    /// Peek at the next token, failing at the end of the input.
+    fn peek(&mut self) -> Result<&'a Token, JsonError> {
+        self.iterator
+            .peek()
+            .copied()
+            .ok_or_else(|| JsonError::Syntax("unexpected end of input".to_string()))
+    }
+
+    /// Consume the next token, which must be equal to `expected`.
+    fn expect(&mut self, expected: &Token, description: &str) -> Result<(), JsonError> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(unexpected(token, description)),
+        }
+    }
+
+    /// Consume a string, which is made up of a string token surrounded by quotes.
+    fn string(&mut self) -> Result<&'a str, JsonError> {
+        self.expect(&Token::Quotes, "a string")?;
+        let Token::String(string) = self.next()? else {
+            return Err(JsonError::Syntax("malformed string".to_string()));
+        };
+        self.expect(&Token::Quotes, "a closing quote")?;
+
+        Ok(string)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'_> {
+    type Error = JsonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        match self.peek()? {
+            Token::Quotes => visitor.visit_str(self.string()?),
+            Token::Number(Number::I64(integer)) => {
+                let _ = self.next();
+                visitor.visit_i64(*integer)
+            }
+            Token::Number(Number::F64(float)) => {
+                let _ = self.next();
+                visitor.visit_f64(*float)
+            }
+            Token::Boolean(boolean) => {
+                let _ = self.next();
+                visitor.visit_bool(*boolean)
+            }
+            Token::Null => {
+                let _ = self.next();
+                visitor.visit_unit()
+            }
+            Token::ArrayOpen => {
+                let _ = self.next();
+                let mut elements = Elements::new(self, Token::ArrayClose);
+                let value = visitor.visit_seq(&mut elements)?;
+                elements.end()?;
+
+                Ok(value)
+            }
+            Token::CurlyOpen => {
+                let _ = self.next();
+                let mut members = Elements::new(self, Token::CurlyClose);
+                let value = visitor.visit_map(&mut members)?;
+                members.end()?;
+
+                Ok(value)
+            }
+            token => Err(unexpected(token, "a value")),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        if let Token::Null = self.peek()? {
+            let _ = self.next();
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        match self.peek()? {
+            // Unit variants are represented by their name alone.
+            Token::Quotes => visitor.visit_enum(self.string()?.into_deserializer()),
+            // Other variants are represented by an object with the name as its only key.
+            Token::CurlyOpen => {
+                let _ = self.next();
+                let value = visitor.visit_enum(Variant { deserializer: self })?;
+                self.expect(&Token::CurlyClose, "`}` after an enum variant")?;
+
+                Ok(value)
+            }
+            token => Err(unexpected(token, "an enum variant")),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Access to the elements of an array or the members of an object.
+struct Elements<'a, 'b> {
+    deserializer: &'a mut Deserializer<'b>,
+    /// Token closing the array or object.
+    close: Token,
+    /// Whether no element has been read yet, in which case no comma is expected.
+    first: bool,
+    /// Whether the closing token has been consumed.
+    closed: bool,
+}
+
+impl<'a, 'b> Elements<'a, 'b> {
+    fn new(deserializer: &'a mut Deserializer<'b>, close: Token) -> Self {
+        Elements {
+            deserializer,
+            close,
+            first: true,
+            closed: false,
+        }
+    }
+
+    /// Consume a separating comma unless this is the first element, and report whether the
+    /// container is closed instead.
+    fn next_element(&mut self) -> Result<bool, JsonError> {
+        if self.closed {
+            return Ok(false);
+        }
+        if *self.deserializer.peek()? == self.close {
+            let _ = self.deserializer.next();
+            self.closed = true;
+            return Ok(false);
+        }
+
+        if !std::mem::take(&mut self.first) {
+            self.deserializer
+                .expect(&Token::Comma, "`,` between elements")?;
+        }
+
+        Ok(true)
+    }
+
+    /// Consume the closing token, which the visitor may have stopped short of if it expected a
+    /// fixed number of elements.
+    fn end(self) -> Result<(), JsonError> {
+        if self.closed {
+            return Ok(());
+        }
+
+        let description = if self.close == Token::ArrayClose {
+            "`]`"
+        } else {
+            "`}`"
+        };
+        self.deserializer.expect(&self.close, description)
+    }
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, '_> {
+    type Error = JsonError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, JsonError> {
+        if !self.next_element()? {
+            return Ok(None);
+        }
+
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_, '_> {
+    type Error = JsonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, JsonError> {
+        if !self.next_element()? {
+            return Ok(None);
+        }
+
+        let key: StrDeserializer<JsonError> = self.deserializer.string()?.into_deserializer();
+        let key = seed.deserialize(key)?;
+        self.deserializer
+            .expect(&Token::Colon, "`:` after an object key")?;
+
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, JsonError> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+}
+
+/// Access to the single member of an object representing an enum variant.
+struct Variant<'a, 'b> {
+    deserializer: &'a mut Deserializer<'b>,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'_, '_> {
+    type Error = JsonError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), JsonError> {
+        let name: StrDeserializer<JsonError> = self.deserializer.string()?.into_deserializer();
+        let name = seed.deserialize(name)?;
+        self.deserializer
+            .expect(&Token::Colon, "`:` after an enum variant")?;
+
+        Ok((name, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Variant<'_, '_> {
+    type Error = JsonError;
+
+    fn unit_variant(self) -> Result<(), JsonError> {
+        self.deserializer.expect(&Token::Null, "`null`")
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, JsonError> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        de::Deserializer::deserialize_seq(&mut *self.deserializer, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        de::Deserializer::deserialize_map(&mut *self.deserializer, visitor)
+    }
+}
@@ -0,0 +1,237 @@
+//! Reports of the differences between two values, meant to be read by people rather than
+//! applied like a JSON Patch.
+
+use crate::pointer::push_token;
+use crate::value::Value;
+use std::fmt::{self, Display};
+
+/// Options for [`Value::diff_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Whether arrays are compared as collections of elements rather than sequences, so that
+    /// reordering elements isn't a change. Elements are then matched only with equal ones, so
+    /// a changed element is reported as removed and added.
+    pub ignore_array_order: bool,
+    /// JSON Pointers of values whose differences are ignored, along with everything inside
+    /// them, where a `*` token stands for any member or element, such as `/items/*/id`.
+    pub ignore: Vec<String>,
+}
+
+/// One difference listed by [`Value::diff_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A value only the target has.
+    Added { pointer: String, value: Value },
+    /// A value only the source has.
+    Removed { pointer: String, value: Value },
+    /// A value which isn't equal in both, and isn't an object or array in both either.
+    Changed {
+        pointer: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+impl Change {
+    /// JSON Pointer of the value which changed.
+    #[must_use]
+    pub fn pointer(&self) -> &str {
+        match self {
+            Change::Added { pointer, .. }
+            | Change::Removed { pointer, .. }
+            | Change::Changed { pointer, .. } => pointer,
+        }
+    }
+}
+
+impl Display for Change {
+    /// Describe the change on one line, such as `added /a/b = 1`, `removed /c` or
+    /// `changed /d: "x" -> "y"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { pointer, value } => {
+                write!(f, "added {pointer} = {}", value.to_string_compact())
+            }
+            Change::Removed { pointer, .. } => write!(f, "removed {pointer}"),
+            Change::Changed { pointer, from, to } => write!(
+                f,
+                "changed {pointer}: {} -> {}",
+                from.to_string_compact(),
+                to.to_string_compact()
+            ),
+        }
+    }
+}
+
+impl Value {
+    /// List the differences between this value and `target`, which is empty if they're equal
+    /// apart from what `options` ignore.
+    ///
+    /// Members are compared by key and, unless [`DiffOptions::ignore_array_order`] is set,
+    /// elements by position. Objects and arrays are compared recursively, and other values
+    /// which differ are reported as changed. Changes are listed in the order of the source,
+    /// followed by what only the target has.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::diff::DiffOptions;
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let source = JsonParser::parse_from_bytes(
+    ///     br#"{"a": {"c": true}, "d": "x", "tags": ["x", "y"], "id": 1}"#,
+    /// )
+    /// .unwrap();
+    /// let target = JsonParser::parse_from_bytes(
+    ///     br#"{"a": {"b": 1}, "d": "y", "tags": ["y", "x"], "id": 2}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let report = |options| -> Vec<String> {
+    ///     let changes = source.diff_report(&target, &options);
+    ///     changes.iter().map(ToString::to_string).collect()
+    /// };
+    /// assert_eq!(
+    ///     report(DiffOptions::default()),
+    ///     [
+    ///         "removed /a/c",
+    ///         "added /a/b = 1",
+    ///         r#"changed /d: "x" -> "y""#,
+    ///         r#"changed /tags/0: "x" -> "y""#,
+    ///         r#"changed /tags/1: "y" -> "x""#,
+    ///         "changed /id: 1 -> 2",
+    ///     ],
+    /// );
+    /// assert_eq!(
+    ///     report(DiffOptions {
+    ///         ignore_array_order: true,
+    ///         ignore: vec!["/id".to_string(), "/a/*".to_string()],
+    ///     }),
+    ///     [r#"changed /d: "x" -> "y""#],
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diff_report(&self, target: &Value, options: &DiffOptions) -> Vec<Change> {
+        let mut changes = Vec::new();
+        Differ {
+            options,
+            changes: &mut changes,
+        }
+        .diff(self, target, &mut String::new());
+
+        changes
+    }
+}
+
+/// Walks two values together, collecting the changes between them.
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    changes: &'a mut Vec<Change>,
+}
+
+impl Differ<'_> {
+    /// Add the changes between `source` and `target`, both located at `pointer`.
+    fn diff(&mut self, source: &Value, target: &Value, pointer: &mut String) {
+        if source == target || self.ignored(pointer) {
+            return;
+        }
+
+        let length = pointer.len();
+        match (source, target) {
+            (Value::Object(source), Value::Object(target)) => {
+                for (key, old) in source {
+                    push_token(pointer, key);
+                    match target.get(key) {
+                        Some(new) => self.diff(old, new, pointer),
+                        None => self.removed(pointer, old),
+                    }
+                    pointer.truncate(length);
+                }
+                for (key, new) in target {
+                    if !source.contains_key(key) {
+                        push_token(pointer, key);
+                        self.added(pointer, new);
+                        pointer.truncate(length);
+                    }
+                }
+            }
+            (Value::Array(source), Value::Array(target)) if self.options.ignore_array_order => {
+                // Match every element with the first equal one not matched yet.
+                let mut matched = vec![false; target.len()];
+                for (index, old) in source.iter().enumerate() {
+                    let found =
+                        (0..target.len()).find(|&other| !matched[other] && target[other] == *old);
+                    match found {
+                        Some(other) => matched[other] = true,
+                        None => {
+                            push_token(pointer, &index.to_string());
+                            self.removed(pointer, old);
+                            pointer.truncate(length);
+                        }
+                    }
+                }
+                for (index, new) in target.iter().enumerate() {
+                    if !matched[index] {
+                        push_token(pointer, &index.to_string());
+                        self.added(pointer, new);
+                        pointer.truncate(length);
+                    }
+                }
+            }
+            (Value::Array(source), Value::Array(target)) => {
+                for (index, old) in source.iter().enumerate() {
+                    push_token(pointer, &index.to_string());
+                    match target.get(index) {
+                        Some(new) => self.diff(old, new, pointer),
+                        None => self.removed(pointer, old),
+                    }
+                    pointer.truncate(length);
+                }
+                for (index, new) in target.iter().enumerate().skip(source.len()) {
+                    push_token(pointer, &index.to_string());
+                    self.added(pointer, new);
+                    pointer.truncate(length);
+                }
+            }
+            _ => self.changes.push(Change::Changed {
+                pointer: pointer.clone(),
+                from: source.clone(),
+                to: target.clone(),
+            }),
+        }
+    }
+
+    fn added(&mut self, pointer: &str, value: &Value) {
+        if !self.ignored(pointer) {
+            self.changes.push(Change::Added {
+                pointer: pointer.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+
+    fn removed(&mut self, pointer: &str, value: &Value) {
+        if !self.ignored(pointer) {
+            self.changes.push(Change::Removed {
+                pointer: pointer.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+
+    /// Whether `pointer` matches one of the ignored pointers. Both are compared while still
+    /// escaped, since escaped tokens can't contain a `/`.
+    fn ignored(&self, pointer: &str) -> bool {
+        self.options.ignore.iter().any(|ignored| {
+            let mut ignored = ignored.split('/');
+            let mut tokens = pointer.split('/');
+            loop {
+                match (ignored.next(), tokens.next()) {
+                    (None, None) => return true,
+                    (Some(expected), Some(token)) if expected == "*" || expected == token => {}
+                    _ => return false,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use crate::error::JsonError;
+use crate::value::Value;
+use sha2::{Digest, Sha256};
+
+impl Value {
+    /// SHA-256 hash of the [canonical form](Value::to_canonical_string) of the value.
+    ///
+    /// Values which are equal up to the order of object members and the formatting of numbers
+    /// have the same digest, which stays the same across versions of this crate and platforms,
+    /// making it suitable for caches, ETags and change detection.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::UnrepresentableNumber`] if the value has no canonical form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let a = JsonParser::parse_from_bytes(br#"{"id":1,"tags":["x"]}"#).unwrap();
+    /// let b = JsonParser::parse_from_bytes(br#"{"tags":["x"],"id":1.0}"#).unwrap();
+    ///
+    /// assert_eq!(a.digest().unwrap(), b.digest().unwrap());
+    ///
+    /// let etag = a.digest().unwrap().iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+    /// assert_eq!(etag.len(), 64);
+    /// ```
+    pub fn digest(&self) -> Result<[u8; 32], JsonError> {
+        let canonical = self.to_canonical_string()?;
+
+        Ok(Sha256::digest(canonical.as_bytes()).into())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::pointer::push_token;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io;
+
+/// Errors that can occur while parsing or manipulating JSON values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonError {
+    /// A flattened key which does not describe a valid path, such as `a[x]` or `a..b`.
+    InvalidPath(String),
+    /// A flattened key which describes a structure incompatible with another key, such as
+    /// `a` and `a.b`, or `a[0]` and `a.b`.
+    PathConflict(String),
+    /// A value had a different type than the one it was being converted to.
+    TypeMismatch {
+        /// JSON Pointer of the offending value, relative to the value being converted.
+        pointer: String,
+        /// Name of the type the value was being converted to.
+        expected: String,
+        /// JSON type of the offending value, as returned by [`Value::type_name`].
+        ///
+        /// [`Value::type_name`]: crate::value::Value::type_name
+        found: &'static str,
+    },
+    /// An object was missing a member required to build a struct.
+    MissingField {
+        /// JSON Pointer of the object, relative to the value being converted.
+        pointer: String,
+        /// Name of the missing member.
+        field: String,
+    },
+    /// An object had a member which doesn't correspond to any field of the struct being built.
+    UnknownField {
+        /// JSON Pointer of the object, relative to the value being converted.
+        pointer: String,
+        /// Name of the unexpected member.
+        field: String,
+    },
+    /// A number which can't be represented exactly in the format it was being converted to.
+    UnrepresentableNumber {
+        /// JSON Pointer of the number, relative to the value being converted.
+        pointer: String,
+        /// The number, formatted as text.
+        number: String,
+    },
+    /// An enum was being built from a tag that doesn't match any of its variants.
+    UnknownVariant {
+        /// JSON Pointer of the tagged value, relative to the value being converted.
+        pointer: String,
+        /// The unrecognized tag.
+        variant: String,
+    },
+    /// The tokens of the input don't form a valid JSON document.
+    Syntax(String),
+    /// An error raised by a serde `Serialize` or `Deserialize` implementation.
+    Custom(String),
+    /// Input in another data format, such as CBOR, which couldn't be decoded into a value.
+    Format {
+        /// Name of the data format.
+        format: &'static str,
+        /// Description of the problem.
+        message: String,
+    },
+    /// An operation of a JSON Patch couldn't be applied.
+    Patch {
+        /// Position of the operation in the patch, starting at 0.
+        operation: usize,
+        /// Description of the problem.
+        message: String,
+    },
+    /// A `$ref`, or an included file, which couldn't be resolved.
+    Reference {
+        /// The reference or the path of the included file, as written in the document.
+        reference: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// A string referred to an environment variable which isn't set, and gave no default.
+    UndefinedVariable {
+        /// JSON Pointer of the string.
+        pointer: String,
+        /// Name of the variable.
+        name: String,
+    },
+    /// A jq expression applied to a value of the wrong type, such as indexing a number.
+    Evaluation(String),
+    /// Parsing was stopped by a [`CancellationToken`] before the end of the input.
+    ///
+    /// [`CancellationToken`]: crate::parser::CancellationToken
+    Cancelled,
+    /// The input couldn't be read.
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl JsonError {
+    /// Prefix the pointer of a conversion error with a reference token of its parent, so that
+    /// errors raised while converting nested values point at the right place in the outer one.
+    ///
+    /// Errors without a pointer are returned unchanged.
+    #[must_use]
+    pub fn nested(mut self, token: &str) -> Self {
+        if let JsonError::TypeMismatch { pointer, .. }
+        | JsonError::MissingField { pointer, .. }
+        | JsonError::UnknownField { pointer, .. }
+        | JsonError::UnrepresentableNumber { pointer, .. }
+        | JsonError::UnknownVariant { pointer, .. } = &mut self
+        {
+            let mut prefixed = String::new();
+            push_token(&mut prefixed, token);
+            prefixed.push_str(pointer);
+            *pointer = prefixed;
+        }
+
+        self
+    }
+}
+
+/// Format a JSON Pointer for error messages, where the empty pointer would be invisible.
+fn display_pointer(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "(root)"
+    } else {
+        pointer
+    }
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::InvalidPath(path) => write!(f, "invalid path: {path}"),
+            JsonError::PathConflict(path) => write!(f, "conflicting path: {path}"),
+            JsonError::TypeMismatch {
+                pointer,
+                expected,
+                found,
+            } => write!(
+                f,
+                "type mismatch at {}: expected {expected}, found {found}",
+                display_pointer(pointer)
+            ),
+            JsonError::MissingField { pointer, field } => {
+                write!(f, "missing field `{field}` at {}", display_pointer(pointer))
+            }
+            JsonError::UnknownField { pointer, field } => {
+                write!(f, "unknown field `{field}` at {}", display_pointer(pointer))
+            }
+            JsonError::UnrepresentableNumber { pointer, number } => write!(
+                f,
+                "number {number} at {} can't be represented exactly",
+                display_pointer(pointer)
+            ),
+            JsonError::UnknownVariant { pointer, variant } => write!(
+                f,
+                "unknown variant `{variant}` at {}",
+                display_pointer(pointer)
+            ),
+            JsonError::Syntax(message) => write!(f, "syntax error: {message}"),
+            JsonError::Custom(message) => f.write_str(message),
+            JsonError::Format { format, message } => write!(f, "invalid {format}: {message}"),
+            JsonError::Patch { operation, message } => {
+                write!(f, "patch operation {operation} failed: {message}")
+            }
+            JsonError::Reference { reference, message } => {
+                write!(f, "unresolvable reference `{reference}`: {message}")
+            }
+            JsonError::UndefinedVariable { pointer, name } => write!(
+                f,
+                "undefined variable `{name}` at {}",
+                display_pointer(pointer)
+            ),
+            JsonError::Evaluation(message) => write!(f, "evaluation failed: {message}"),
+            JsonError::Cancelled => f.write_str("parsing was cancelled"),
+            JsonError::Io { message, .. } => write!(f, "I/O error: {message}"),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<io::Error> for JsonError {
+    fn from(error: io::Error) -> Self {
+        JsonError::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,1097 @@
+//! Streaming parsing of JSON as a sequence of events, without building the whole value.
+
+use crate::cst::parse_number;
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::pointer::{parse_index, parse_pointer, push_token};
+use crate::recover::is_valid_number;
+use crate::ser::{to_writer, write_string_to};
+use crate::token::MAX_NESTING;
+use crate::value::{Number, Value};
+use std::io::{self, BufWriter, ErrorKind, Read, Write};
+
+/// A piece of a JSON document, in the order it appears in the input.
+///
+/// Objects and arrays are announced by a start event and closed by an end event, with their
+/// contents in between. Every member of an object is a [`Event::Key`] followed by the events of
+/// its value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// The key of the next member of the current object.
+    Key(String),
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(String),
+}
+
+/// What a [`Feeder`] can do with the input given so far.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Event(Event),
+    /// The input ends in the middle of a token or before the document is complete, and more
+    /// has to be fed before parsing can go on.
+    NeedMoreData,
+    /// The document is complete and the input is finished.
+    End,
+}
+
+/// What is allowed at the current point of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    /// A value, at the root, after `:` or after `,` in an array.
+    Value,
+    /// A value or `]`, right after `[`.
+    ValueOrEnd,
+    /// A key or `}`, right after `{`.
+    KeyOrEnd,
+    /// A key, after `,` in an object.
+    Key,
+    Colon,
+    /// `,` or the end of the current array or object.
+    CommaOrEnd,
+    /// Nothing but whitespace, after the root value.
+    Done,
+}
+
+/// A parser which is fed its input in chunks of any size, and turns it into events as soon as
+/// they're complete.
+pub(crate) struct EventParser {
+    /// Input fed but not consumed yet, from `position` on.
+    buffer: Vec<u8>,
+    position: usize,
+    /// Number of bytes consumed and dropped from the front of `buffer`, for error offsets.
+    dropped: usize,
+    /// Opening bracket of every array or object being parsed, innermost last.
+    stack: Vec<u8>,
+    expect: Expect,
+    /// Whether the end of the input has been reached, so no more will be fed.
+    finished: bool,
+    /// Whether the strings being parsed are thrown away, in which case they're only checked
+    /// and their events hold empty strings, so that nothing is allocated.
+    skipping: bool,
+    /// Start of the last key or scalar value parsed inside `buffer`, which ends at `position`.
+    token_start: usize,
+    /// Whether nothing has been parsed yet, so a leading byte order mark is still to be skipped.
+    at_start: bool,
+}
+
+impl EventParser {
+    pub(crate) fn new() -> Self {
+        EventParser {
+            buffer: Vec::new(),
+            position: 0,
+            dropped: 0,
+            stack: Vec::new(),
+            expect: Expect::Value,
+            finished: false,
+            skipping: false,
+            token_start: 0,
+            at_start: true,
+        }
+    }
+
+    /// Parse a whole document, which is entirely in `input`.
+    #[cfg(any(feature = "futures", feature = "rayon"))]
+    pub(crate) fn parse(input: &[u8]) -> Result<Value, JsonError> {
+        let mut parser = EventParser::new();
+        parser.feed(input);
+        parser.finish();
+
+        let mut builder = ValueBuilder::default();
+        let mut root = None;
+        while let Step::Event(event) = parser.next_event()? {
+            root = builder.push(event).or(root);
+        }
+
+        Ok(root.unwrap_or(Value::Null))
+    }
+
+    /// Add the next chunk of input.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.drain(..self.position);
+        self.dropped += self.position;
+        self.position = 0;
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Mark the end of the input, after which a truncated document is an error.
+    pub(crate) fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Parse the next event out of the input fed so far.
+    pub(crate) fn next_event(&mut self) -> Result<Step, JsonError> {
+        if self.at_start {
+            // Skip the byte order mark which Windows tools often write at the start of UTF-8
+            // text, once enough input has been fed to tell whether there's one.
+            const BOM: &[u8] = "\u{FEFF}".as_bytes();
+            let rest = &self.buffer[self.position..];
+            if rest.len() < BOM.len() && BOM.starts_with(rest) && !self.finished {
+                return Ok(Step::NeedMoreData);
+            }
+            if rest.starts_with(BOM) {
+                self.position += BOM.len();
+            }
+            self.at_start = false;
+        }
+
+        loop {
+            while self
+                .buffer
+                .get(self.position)
+                .is_some_and(|byte| matches!(byte, b' ' | b'\t' | b'\n' | b'\r'))
+            {
+                self.position += 1;
+            }
+
+            let Some(&byte) = self.buffer.get(self.position) else {
+                return match self.expect {
+                    Expect::Done if self.finished => Ok(Step::End),
+                    _ if self.finished => Err(JsonError::Syntax(format!(
+                        "unexpected end of input at byte {}",
+                        self.offset()
+                    ))),
+                    _ => Ok(Step::NeedMoreData),
+                };
+            };
+
+            match (self.expect, byte) {
+                (Expect::ValueOrEnd, b']') | (Expect::KeyOrEnd, b'}') => return Ok(self.close()),
+                (Expect::Value | Expect::ValueOrEnd, _) => return self.parse_value(byte),
+                (Expect::KeyOrEnd | Expect::Key, b'"') => {
+                    self.token_start = self.position;
+                    let Some(key) = self.parse_string()? else {
+                        return Ok(Step::NeedMoreData);
+                    };
+                    self.expect = Expect::Colon;
+                    return Ok(Step::Event(Event::Key(key)));
+                }
+                (Expect::Colon, b':') => {
+                    self.position += 1;
+                    self.expect = Expect::Value;
+                }
+                (Expect::CommaOrEnd, b',') => {
+                    self.position += 1;
+                    self.expect = match self.stack.last() {
+                        Some(b'{') => Expect::Key,
+                        _ => Expect::Value,
+                    };
+                }
+                (Expect::CommaOrEnd, b']') if self.stack.last() == Some(&b'[') => {
+                    return Ok(self.close())
+                }
+                (Expect::CommaOrEnd, b'}') if self.stack.last() == Some(&b'{') => {
+                    return Ok(self.close())
+                }
+                (expect, _) => {
+                    let expected = match expect {
+                        Expect::Value => "a value",
+                        Expect::ValueOrEnd => "a value or `]`",
+                        Expect::KeyOrEnd => "a string key or `}`",
+                        Expect::Key => "a string key",
+                        Expect::Colon => "`:`",
+                        Expect::CommaOrEnd if self.stack.last() == Some(&b'{') => "`,` or `}`",
+                        Expect::CommaOrEnd => "`,` or `]`",
+                        Expect::Done => "the end of input",
+                    };
+                    return Err(self.unexpected(expected));
+                }
+            }
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.dropped + self.position
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        JsonError::Syntax(format!(
+            "expected {expected} at byte {}, found `{}`",
+            self.offset(),
+            self.buffer[self.position].escape_ascii()
+        ))
+    }
+
+    /// Consume the bracket closing the innermost array or object.
+    fn close(&mut self) -> Step {
+        self.position += 1;
+        let event = match self.stack.pop() {
+            Some(b'{') => Event::EndObject,
+            _ => Event::EndArray,
+        };
+        self.end_value();
+
+        Step::Event(event)
+    }
+
+    fn end_value(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Done
+        } else {
+            Expect::CommaOrEnd
+        };
+    }
+
+    /// The text of the last key or scalar value parsed, as it appears in the input, which is
+    /// only kept until more input is fed.
+    pub(crate) fn token(&self) -> &[u8] {
+        &self.buffer[self.token_start..self.position]
+    }
+
+    fn parse_value(&mut self, byte: u8) -> Result<Step, JsonError> {
+        self.token_start = self.position;
+        let event = match byte {
+            b'{' | b'[' => {
+                self.position += 1;
+                self.stack.push(byte);
+                if byte == b'{' {
+                    self.expect = Expect::KeyOrEnd;
+                    return Ok(Step::Event(Event::StartObject));
+                }
+                self.expect = Expect::ValueOrEnd;
+                return Ok(Step::Event(Event::StartArray));
+            }
+            b'"' => match self.parse_string()? {
+                Some(string) => Event::String(string),
+                None => return Ok(Step::NeedMoreData),
+            },
+            b'-' | b'0'..=b'9' => match self.parse_number()? {
+                Some(number) => Event::Number(number),
+                None => return Ok(Step::NeedMoreData),
+            },
+            b't' | b'f' | b'n' => {
+                let (literal, event) = match byte {
+                    b't' => ("true", Event::Boolean(true)),
+                    b'f' => ("false", Event::Boolean(false)),
+                    _ => ("null", Event::Null),
+                };
+                let rest = &self.buffer[self.position..];
+                if rest.len() < literal.len() && literal.as_bytes().starts_with(rest) {
+                    if self.finished {
+                        return Err(JsonError::Syntax(format!(
+                            "unexpected end of input at byte {}",
+                            self.dropped + self.buffer.len()
+                        )));
+                    }
+                    return Ok(Step::NeedMoreData);
+                }
+                if !rest.starts_with(literal.as_bytes()) {
+                    return Err(self.unexpected("a value"));
+                }
+                self.position += literal.len();
+                event
+            }
+            _ => return Err(self.unexpected("a value")),
+        };
+        self.end_value();
+
+        Ok(Step::Event(event))
+    }
+
+    /// Parse the number starting at the current position, or return `None` if it may go on in
+    /// input not fed yet.
+    fn parse_number(&mut self) -> Result<Option<Number>, JsonError> {
+        let rest = &self.buffer[self.position..];
+        let length = rest
+            .iter()
+            .position(|byte| !matches!(byte, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+            .unwrap_or(rest.len());
+        if length == rest.len() && !self.finished {
+            return Ok(None);
+        }
+
+        // Only ASCII bytes were taken.
+        let text = std::str::from_utf8(&rest[..length]).unwrap_or_default();
+        if !is_valid_number(text) {
+            return Err(JsonError::Syntax(format!(
+                "invalid number `{text}` at byte {}",
+                self.offset()
+            )));
+        }
+        let number = parse_number(text);
+        self.position += length;
+
+        Ok(Some(number))
+    }
+
+    /// Parse the string starting at the current position, or return `None` if its closing
+    /// quote hasn't been fed yet.
+    fn parse_string(&mut self) -> Result<Option<String>, JsonError> {
+        let start = self.position + 1;
+        let mut end = start;
+        loop {
+            match self.buffer.get(end) {
+                None => {
+                    if self.finished {
+                        return Err(JsonError::Syntax(format!(
+                            "unterminated string at byte {}",
+                            self.offset()
+                        )));
+                    }
+                    return Ok(None);
+                }
+                Some(b'"') => break,
+                Some(b'\\') => end += 2,
+                Some(byte) if *byte < 0x20 => {
+                    return Err(JsonError::Syntax(format!(
+                        "unescaped control character in string at byte {}",
+                        self.dropped + end
+                    )));
+                }
+                Some(_) => end += 1,
+            }
+        }
+
+        let text = std::str::from_utf8(&self.buffer[start..end]).map_err(|error| {
+            JsonError::Syntax(format!(
+                "invalid UTF-8 at byte {}",
+                self.dropped + start + error.valid_up_to()
+            ))
+        })?;
+        let string = if self.skipping {
+            decode(text, |_| {}).map(|()| String::new())
+        } else {
+            unescape(text)
+        }
+        .map_err(|index| {
+            JsonError::Syntax(format!(
+                "invalid escape sequence at byte {}",
+                self.dropped + start + index
+            ))
+        })?;
+        self.position = end + 1;
+
+        Ok(Some(string))
+    }
+}
+
+/// Decode the escape sequences of the text between the quotes of a string, or return the
+/// index of the first invalid one. Lone surrogates are replaced with U+FFFD.
+pub(crate) fn unescape(text: &str) -> Result<String, usize> {
+    let mut string = String::with_capacity(text.len());
+    decode(text, |piece| string.push_str(piece))?;
+
+    Ok(string)
+}
+
+/// Decode the escape sequences of the text between the quotes of a string like [`unescape`],
+/// passing the decoded text to `push` piece by piece.
+pub(crate) fn decode(text: &str, mut push: impl FnMut(&str)) -> Result<(), usize> {
+    let mut rest = text;
+
+    while let Some(backslash) = rest.find('\\') {
+        push(&rest[..backslash]);
+        let index = text.len() - rest.len() + backslash;
+        let escape = &rest[backslash + 1..];
+
+        let (character, length) = match escape.as_bytes().first() {
+            Some(b'"') => ('"', 1),
+            Some(b'\\') => ('\\', 1),
+            Some(b'/') => ('/', 1),
+            Some(b'b') => ('\u{8}', 1),
+            Some(b'f') => ('\u{c}', 1),
+            Some(b'n') => ('\n', 1),
+            Some(b'r') => ('\r', 1),
+            Some(b't') => ('\t', 1),
+            Some(b'u') => {
+                let high = hex(&escape[1..]).ok_or(index)?;
+                let low = escape[5..]
+                    .strip_prefix("\\u")
+                    .and_then(hex)
+                    .filter(|low| (0xDC00..0xE000).contains(low));
+                match low {
+                    Some(low) if (0xD800..0xDC00).contains(&high) => {
+                        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        (char::from_u32(code).ok_or(index)?, 11)
+                    }
+                    _ => (char::from_u32(high).unwrap_or('\u{FFFD}'), 5),
+                }
+            }
+            _ => return Err(index),
+        };
+        push(character.encode_utf8(&mut [0; 4]));
+        rest = &escape[length..];
+    }
+    push(rest);
+
+    Ok(())
+}
+
+/// Parse the four hexadecimal digits at the start of `text`.
+fn hex(text: &str) -> Option<u32> {
+    let digits = text.get(..4)?;
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Builds values out of the events of a document.
+#[derive(Default)]
+pub(crate) struct ValueBuilder {
+    /// Arrays and objects being built, innermost last, with the key of the member being parsed
+    /// for objects.
+    stack: Vec<(Value, Option<String>)>,
+}
+
+impl ValueBuilder {
+    /// Add the next event, returning the root value once it's complete.
+    pub(crate) fn push(&mut self, event: Event) -> Option<Value> {
+        let value = match event {
+            Event::StartObject => {
+                self.stack.push((Value::Object(Map::new()), None));
+                return None;
+            }
+            Event::StartArray => {
+                self.stack.push((Value::Array(Vec::new()), None));
+                return None;
+            }
+            Event::Key(key) => {
+                if let Some((_, current)) = self.stack.last_mut() {
+                    *current = Some(key);
+                }
+                return None;
+            }
+            Event::EndObject | Event::EndArray => self.stack.pop()?.0,
+            Event::Null => Value::Null,
+            Event::Boolean(boolean) => Value::Boolean(boolean),
+            Event::Number(number) => Value::Number(number),
+            Event::String(string) => Value::from(string),
+        };
+
+        match self.stack.last_mut() {
+            None => Some(value),
+            Some((Value::Array(elements), _)) => {
+                elements.push(value);
+                None
+            }
+            Some((Value::Object(members), key)) => {
+                members.insert(key.take().unwrap_or_default(), value);
+                None
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+/// A push parser which is fed input in chunks of any size as they arrive, such as the
+/// bodies of network reads, and turns them into events as soon as they're complete.
+///
+/// Unlike [`EventReader`], the feeder never reads its input, so it can sit on top of any
+/// source, blocking or not. Events are taken with [`next_event`](Feeder::next_event) until it
+/// returns [`Step::NeedMoreData`], after which the next chunk is fed. Once the input is
+/// exhausted, [`finish`](Feeder::finish) marks its end, so that a truncated document is an
+/// error and a complete one gives [`Step::End`].
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::event::{Event, Feeder, Step};
+/// use json_parser::value::Number;
+///
+/// let chunks: [&[u8]; 3] = [br#"{"ids": [1"#, br#"2, 3], "ok": tr"#, b"ue}"];
+/// let mut feeder = Feeder::new();
+/// let mut events = Vec::new();
+///
+/// let mut chunks = chunks.into_iter();
+/// loop {
+///     match feeder.next_event().unwrap() {
+///         Step::Event(event) => events.push(event),
+///         Step::NeedMoreData => match chunks.next() {
+///             Some(chunk) => feeder.feed(chunk),
+///             None => feeder.finish(),
+///         },
+///         Step::End => break,
+///     }
+/// }
+///
+/// assert_eq!(events[3], Event::Number(Number::I64(12)));
+/// assert_eq!(events[7], Event::Boolean(true));
+/// assert_eq!(events.len(), 9);
+/// ```
+pub struct Feeder {
+    parser: EventParser,
+}
+
+impl Feeder {
+    #[must_use]
+    pub fn new() -> Self {
+        Feeder {
+            parser: EventParser::new(),
+        }
+    }
+
+    /// Add the next chunk of input. Tokens may be split between chunks anywhere.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.parser.feed(bytes);
+    }
+
+    /// Mark the end of the input, after which no more can be fed.
+    pub fn finish(&mut self) {
+        self.parser.finish();
+    }
+
+    /// Parse the next event out of the input fed so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid JSON, or if it's been
+    /// [finished](Feeder::finish) before the end of the document.
+    pub fn next_event(&mut self) -> Result<Step, JsonError> {
+        self.parser.next_event()
+    }
+}
+
+impl Default for Feeder {
+    fn default() -> Self {
+        Feeder::new()
+    }
+}
+
+/// A pull parser reading events from a reader as they're needed, so documents of any size can
+/// be processed in constant memory.
+///
+/// A leading byte order mark is skipped. The iteration stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::event::{Event, EventReader};
+///
+/// let input: &[u8] = br#"{"tags": ["a", "b"]}"#;
+/// let events: Result<Vec<_>, _> = EventReader::new(input).collect();
+///
+/// assert_eq!(
+///     events.unwrap(),
+///     [
+///         Event::StartObject,
+///         Event::Key("tags".to_string()),
+///         Event::StartArray,
+///         Event::String("a".to_string()),
+///         Event::String("b".to_string()),
+///         Event::EndArray,
+///         Event::EndObject,
+///     ]
+/// );
+/// ```
+pub struct EventReader<R> {
+    reader: R,
+    parser: EventParser,
+    chunk: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> EventReader<R> {
+    pub fn new(reader: R) -> Self {
+        EventReader {
+            reader,
+            parser: EventParser::new(),
+            chunk: vec![0; 8192],
+            done: false,
+        }
+    }
+
+    /// Consume the next value, along with everything nested in it, without producing its
+    /// events, to cheaply ignore large parts of a document such as the value of a key that
+    /// isn't needed.
+    ///
+    /// The skipped input is still checked, but its strings aren't decoded, so nothing is
+    /// allocated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the skipped input isn't valid, or if the next event
+    /// isn't the start of a value, such as at the end of an array, after which the iteration
+    /// stops like for any other error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::event::{Event, EventReader};
+    /// use json_parser::value::Number;
+    ///
+    /// let input: &[u8] = br#"{"history": [{"at": 1}, {"at": 2}], "id": 7}"#;
+    /// let mut reader = EventReader::new(input);
+    ///
+    /// assert_eq!(reader.next().unwrap().unwrap(), Event::StartObject);
+    /// assert_eq!(reader.next().unwrap().unwrap(), Event::Key("history".to_string()));
+    /// reader.skip_value().unwrap();
+    /// assert_eq!(reader.next().unwrap().unwrap(), Event::Key("id".to_string()));
+    /// assert_eq!(reader.next().unwrap().unwrap(), Event::Number(Number::I64(7)));
+    /// ```
+    pub fn skip_value(&mut self) -> Result<(), JsonError> {
+        let skipped = match self.skip_next() {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(JsonError::Syntax(
+                "expected a value to skip, found the end of an array".to_string(),
+            )),
+            Err(error) => Err(error),
+        };
+        if skipped.is_err() {
+            self.done = true;
+        }
+
+        skipped
+    }
+
+    /// Create a reader whose strings aren't decoded, for reading the text of each token with
+    /// [`token`](EventReader::token) rather than the value of its event.
+    pub(crate) fn raw(reader: R) -> Self {
+        let mut events = EventReader::new(reader);
+        events.parser.skipping = true;
+
+        events
+    }
+
+    /// The text of the key or scalar value of the last event, as it appears in the input, such
+    /// as `1.50` or `"caf\u00e9"`.
+    pub(crate) fn token(&self) -> &[u8] {
+        self.parser.token()
+    }
+
+    /// Skip the next value, or consume the end of the array holding it and return `false` if
+    /// there's none left.
+    fn skip_next(&mut self) -> Result<bool, JsonError> {
+        self.parser.skipping = true;
+        let skipped = self.skip_events();
+        self.parser.skipping = false;
+
+        skipped
+    }
+
+    fn skip_events(&mut self) -> Result<bool, JsonError> {
+        let mut depth = 0_usize;
+        loop {
+            match self.next_event()? {
+                Event::StartObject | Event::StartArray => depth += 1,
+                Event::EndObject | Event::EndArray if depth > 0 => depth -= 1,
+                Event::Key(_) if depth > 0 => {}
+                Event::EndArray => return Ok(false),
+                event @ (Event::EndObject | Event::Key(_)) => {
+                    return Err(JsonError::Syntax(format!(
+                        "expected a value to skip, found {event:?}"
+                    )))
+                }
+                Event::Null | Event::Boolean(_) | Event::Number(_) | Event::String(_) => {}
+            }
+            if depth == 0 {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Consume the rest of the array or object whose start was the last event, without
+    /// producing its events.
+    fn skip_rest(&mut self) -> Result<(), JsonError> {
+        self.parser.skipping = true;
+        let mut depth = 1_usize;
+        let skipped = loop {
+            match self.next_event() {
+                Ok(Event::StartObject | Event::StartArray) => depth += 1,
+                Ok(Event::EndObject | Event::EndArray) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break Ok(());
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => break Err(error),
+            }
+        };
+        self.parser.skipping = false;
+
+        skipped
+    }
+
+    /// The next event, where the end of the events is an error.
+    fn next_event(&mut self) -> Result<Event, JsonError> {
+        self.next()
+            .unwrap_or_else(|| Err(JsonError::Syntax("unexpected end of input".to_string())))
+    }
+}
+
+impl JsonParser {
+    /// Read the value referenced by a JSON Pointer, such as `/data/items/0/id`, out of a
+    /// stream, without building anything else.
+    ///
+    /// The input is read as events up to the end of the value, skipping everything outside
+    /// the path to it like [`EventReader::skip_value`], so only the value itself is kept in
+    /// memory, and the rest of the input isn't read. Of duplicate keys, the first one counts.
+    ///
+    /// Returns `None` if the document has no such value, or if `pointer` isn't a valid JSON
+    /// Pointer, like [`Value::pointer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input read isn't valid JSON, and [`JsonError::Io`]
+    /// if it can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let input: &[u8] = br#"{"meta": {"pages": 3}, "data": {"items": [{"id": 4}, {"id": 5}]}}"#;
+    ///
+    /// let id = JsonParser::extract(input, "/data/items/1/id").unwrap();
+    /// assert_eq!(id, Some(Value::Number(Number::I64(5))));
+    /// assert_eq!(JsonParser::extract(input, "/data/items/2").unwrap(), None);
+    ///
+    /// // The input after the value isn't read, so it doesn't have to be valid.
+    /// let truncated: &[u8] = br#"{"meta": {"pages": 3}, "data": [1, 2"#;
+    /// let meta = JsonParser::extract(truncated, "/meta").unwrap().unwrap();
+    /// assert_eq!(meta.pointer("/pages"), Some(&Value::Number(Number::I64(3))));
+    /// ```
+    pub fn extract<R: Read>(reader: R, pointer: &str) -> Result<Option<Value>, JsonError> {
+        let Some(tokens) = parse_pointer(pointer) else {
+            return Ok(None);
+        };
+        let mut events = EventReader::new(reader);
+
+        for token in &tokens {
+            match events.next_event()? {
+                Event::StartObject => loop {
+                    match events.next_event()? {
+                        Event::Key(key) if key == *token => break,
+                        Event::Key(_) => events.skip_value()?,
+                        _ => return Ok(None),
+                    }
+                },
+                Event::StartArray => {
+                    let Some(index) = parse_index(token) else {
+                        return Ok(None);
+                    };
+                    for _ in 0..index {
+                        if !events.skip_next()? {
+                            return Ok(None);
+                        }
+                    }
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        let mut event = events.next_event()?;
+        // The array ended before the element.
+        if event == Event::EndArray {
+            return Ok(None);
+        }
+        let mut builder = ValueBuilder::default();
+        loop {
+            if let Some(value) = builder.push(event) {
+                return Ok(Some(value));
+            }
+            event = events.next_event()?;
+        }
+    }
+
+    /// Check that a stream holds exactly one valid JSON document, without building anything.
+    ///
+    /// The input is read in chunks and checked like by [`EventReader`], except that strings
+    /// aren't decoded and no event is kept, so memory use doesn't grow with the size of the
+    /// input but only with its nesting depth. This is much cheaper than parsing the input and
+    /// dropping the value, to turn away invalid untrusted input.
+    ///
+    /// Like [`parse_from_bytes`](JsonParser::parse_from_bytes), a leading byte order mark is
+    /// skipped and arrays and objects may be nested up to [`MAX_NESTING`] levels deep. The
+    /// input is checked against the grammar of RFC 8259 though, which `parse_from_bytes` is
+    /// more lenient than: it also accepts numbers such as `01`, `1.` or `1+2`, control
+    /// characters in strings, and form feeds or NUL bytes after the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] for the first error in the input, and [`JsonError::Io`]
+    /// if it can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let valid: &[u8] = br#"{"items": [{"id": 1, "tags": ["a\u00e9"]}], "next": null}"#;
+    /// assert!(JsonParser::validate(valid).is_ok());
+    ///
+    /// for invalid in ["[1, 2", r#"{"a" 1}"#, "[1] [2]", r#"["\x"]"#, "01", ""] {
+    ///     assert!(JsonParser::validate(invalid.as_bytes()).is_err(), "{invalid}");
+    /// }
+    ///
+    /// // Both accept a byte order mark and nesting up to the limit, and nothing deeper.
+    /// let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+    /// for input in ["\u{FEFF}[1]".to_string(), nested(512), nested(513), nested(200_000)] {
+    ///     assert_eq!(
+    ///         JsonParser::validate(input.as_bytes()).is_ok(),
+    ///         JsonParser::parse_from_bytes(input.as_bytes()).is_ok(),
+    ///     );
+    /// }
+    ///
+    /// // Only parsing accepts what RFC 8259 doesn't allow.
+    /// for lenient in ["01", "1+2", "[1]\x0C"] {
+    ///     assert!(JsonParser::parse_from_bytes(lenient.as_bytes()).is_ok());
+    ///     assert!(JsonParser::validate(lenient.as_bytes()).is_err(), "{lenient}");
+    /// }
+    /// ```
+    pub fn validate<R: Read>(reader: R) -> Result<(), JsonError> {
+        let mut events = EventReader::raw(reader);
+        let mut depth = 0_usize;
+        while let Some(event) = events.next() {
+            match event? {
+                Event::StartObject | Event::StartArray => {
+                    depth += 1;
+                    if depth > MAX_NESTING {
+                        return Err(JsonError::Syntax(format!(
+                            "arrays and objects are nested more than {MAX_NESTING} levels deep at byte {}",
+                            events.parser.offset() - 1
+                        )));
+                    }
+                }
+                Event::EndObject | Event::EndArray => depth -= 1,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = Result<Event, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.parser.next_event() {
+                Ok(Step::Event(event)) => return Some(Ok(event)),
+                Ok(Step::End) => self.done = true,
+                Ok(Step::NeedMoreData) => match self.reader.read(&mut self.chunk) {
+                    Ok(0) => self.parser.finish(),
+                    Ok(length) => self.parser.feed(&self.chunk[..length]),
+                    Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                    Err(error) => {
+                        self.done = true;
+                        return Some(Err(error.into()));
+                    }
+                },
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Writes events back out as compact JSON text, adding the commas and colons between them.
+///
+/// Events are written as they come, without checking that they form a valid document.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::event::{Event, EventWriter};
+/// use json_parser::value::Number;
+///
+/// let mut writer = EventWriter::new(Vec::new());
+/// for event in [
+///     Event::StartObject,
+///     Event::Key("ids".to_string()),
+///     Event::StartArray,
+///     Event::Number(Number::I64(1)),
+///     Event::Number(Number::I64(2)),
+///     Event::EndArray,
+///     Event::Key("name".to_string()),
+///     Event::String("a\"b".to_string()),
+///     Event::EndObject,
+/// ] {
+///     writer.write(&event).unwrap();
+/// }
+///
+/// assert_eq!(writer.into_inner(), br#"{"ids":[1,2],"name":"a\"b"}"#);
+/// ```
+pub struct EventWriter<W> {
+    writer: W,
+    /// Whether each array or object being written is still empty, innermost last.
+    empty: Vec<bool>,
+    /// Whether the last event written was a key, so the next one is its value.
+    after_key: bool,
+}
+
+impl<W: Write> EventWriter<W> {
+    pub fn new(writer: W) -> Self {
+        EventWriter {
+            writer,
+            empty: Vec::new(),
+            after_key: false,
+        }
+    }
+
+    /// Write `event`, preceded by a comma if it starts another member or element.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised while writing to the underlying writer.
+    pub fn write(&mut self, event: &Event) -> io::Result<()> {
+        if let Event::EndObject | Event::EndArray = event {
+            self.empty.pop();
+            return self.writer.write_all(match event {
+                Event::EndObject => b"}",
+                _ => b"]",
+            });
+        }
+
+        if !std::mem::take(&mut self.after_key) {
+            if let Some(empty) = self.empty.last_mut() {
+                if !std::mem::take(empty) {
+                    self.writer.write_all(b",")?;
+                }
+            }
+        }
+        match event {
+            Event::StartObject | Event::StartArray => {
+                self.empty.push(true);
+                self.writer.write_all(match event {
+                    Event::StartObject => b"{",
+                    _ => b"[",
+                })
+            }
+            Event::Key(key) => {
+                self.after_key = true;
+                write_string_to(&mut self.writer, key)?;
+                self.writer.write_all(b":")
+            }
+            Event::Null => self.writer.write_all(b"null"),
+            Event::Boolean(true) => self.writer.write_all(b"true"),
+            Event::Boolean(false) => self.writer.write_all(b"false"),
+            Event::Number(number) => to_writer(&mut self.writer, &Value::Number(*number)),
+            Event::String(string) => write_string_to(&mut self.writer, string),
+            Event::EndObject | Event::EndArray => unreachable!("ends are written above"),
+        }
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// What [`rewrite`] does with an event once the callback has seen it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Write the event, as the callback left it.
+    Keep,
+    /// Leave out the event, along with the value it starts or whose key it is.
+    Skip,
+}
+
+/// Copy the JSON document read from `reader` to `writer` as compact JSON, passing every event
+/// to `edit` on the way, which can change it or skip it.
+///
+/// `edit` is called with the JSON Pointer of the event in the input, which for a key is the
+/// pointer of its member, and the event, which it can modify to rename a key or rewrite a
+/// value. Returning [`Action::Skip`] for a key leaves out the whole member, and for the
+/// start of an array or object, the whole array or object. Skipped values are still checked
+/// but aren't passed to `edit`. The ends of arrays and objects are always written, and the
+/// value of a member whose key is kept is replaced with `null` if it's skipped.
+///
+/// Only the events being edited are kept in memory, so arbitrarily large documents are
+/// rewritten in constant memory, apart from their nesting depth.
+///
+/// # Errors
+///
+/// Returns [`JsonError::Syntax`] if the input isn't valid JSON, and [`JsonError::Io`] if it
+/// can't be read or the output can't be written. Whatever was rewritten up to the error has
+/// been written by then.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::event::{rewrite, Action, Event};
+///
+/// let input = br#"{"user": {"name": "Ada", "email": "ada@example.com"}, "tags": ["a", "b"]}"#;
+/// let mut output = Vec::new();
+/// rewrite(input.as_slice(), &mut output, |pointer, event| match event {
+///     Event::Key(key) if key == "email" => Action::Skip,
+///     Event::Key(key) if key == "name" => {
+///         *key = "full_name".to_string();
+///         Action::Keep
+///     }
+///     Event::String(tag) if pointer.starts_with("/tags/") => {
+///         *tag = tag.to_uppercase();
+///         Action::Keep
+///     }
+///     _ => Action::Keep,
+/// })
+/// .unwrap();
+///
+/// assert_eq!(output, br#"{"user":{"full_name":"Ada"},"tags":["A","B"]}"#);
+/// ```
+pub fn rewrite<R, W, F>(reader: R, writer: W, mut edit: F) -> Result<(), JsonError>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(&str, &mut Event) -> Action,
+{
+    let mut events = EventReader::new(reader);
+    let mut output = EventWriter::new(BufWriter::new(writer));
+    let mut pointer = String::new();
+    // The length of the pointer of every array or object being read, innermost last, along
+    // with the index of the next element of arrays.
+    let mut open: Vec<(usize, Option<usize>)> = Vec::new();
+
+    while let Some(event) = events.next() {
+        let mut event = event?;
+        match &event {
+            Event::Key(key) => {
+                if let Some(&(length, _)) = open.last() {
+                    pointer.truncate(length);
+                }
+                push_token(&mut pointer, key);
+            }
+            Event::EndObject | Event::EndArray => {
+                if let Some((length, _)) = open.pop() {
+                    pointer.truncate(length);
+                }
+            }
+            _ => {
+                if let Some((length, Some(index))) = open.last_mut() {
+                    pointer.truncate(*length);
+                    push_token(&mut pointer, &index.to_string());
+                    *index += 1;
+                }
+            }
+        }
+
+        let action = edit(&pointer, &mut event);
+        match (action, &event) {
+            (Action::Keep, _) | (Action::Skip, Event::EndObject | Event::EndArray) => {
+                match event {
+                    Event::StartObject => open.push((pointer.len(), None)),
+                    Event::StartArray => open.push((pointer.len(), Some(0))),
+                    _ => {}
+                }
+                output.write(&event)?;
+            }
+            (Action::Skip, Event::Key(_)) => events.skip_value()?,
+            (Action::Skip, value) => {
+                if let Event::StartObject | Event::StartArray = value {
+                    events.skip_rest()?;
+                }
+                if output.after_key {
+                    output.write(&Event::Null)?;
+                }
+            }
+        }
+    }
+
+    output.writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,853 @@
+//! A small subset of the jq language, such as `.items[] | select(.active) | .name`, filtering
+//! and transforming values.
+//!
+//! An expression turns its input into a stream of zero or more outputs:
+//!
+//! - `.` outputs the input, and `..` the input followed by all its descendants.
+//! - `.name`, `."name"` and `.["name"]` output a member of an object, `.[0]` an element of an
+//!   array, counting from the end if negative, and `.[]` every element or member. They chain
+//!   as in `.items[0].name`, and output `null` when the input is `null` or lacks the member.
+//! - `a | b` applies `b` to every output of `a`, and `a, b` outputs those of `a` then `b`.
+//! - Numbers, strings in double quotes, `true`, `false` and `null` output themselves, `[a]`
+//!   collects the outputs of `a` into an array, and `{name: a, "b": .c, d}` builds objects,
+//!   `d` standing for `d: .d`.
+//! - `==`, `!=`, `<`, `<=`, `>` and `>=` compare values in jq's order, `and` and `or`
+//!   combine them where only `false` and `null` are false, and `a // b` outputs those of `a`
+//!   which are true, or those of `b` if there are none.
+//! - `a?` outputs nothing instead of failing.
+//! - The functions are `length`, `keys`, `type`, `not`, `empty`, `has(key)`, `map(f)` and
+//!   `select(f)`.
+
+use crate::error::JsonError;
+use crate::event::unescape;
+use crate::map::Map;
+use crate::value::{Number, Value};
+use std::cmp::Ordering;
+
+/// A compiled jq expression, to evaluate on values.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::expr::Expression;
+/// use json_parser::parser::JsonParser;
+/// use json_parser::value::Value;
+///
+/// let expression = Expression::parse(".items[] | select(.active) | .name").unwrap();
+/// let value = JsonParser::parse_from_bytes(
+///     br#"{"items": [{"name": "a", "active": true}, {"name": "b"}, {"name": "c", "active": 1}]}"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(expression.evaluate(&value).unwrap(), [Value::from("a"), Value::from("c")]);
+/// assert!(Expression::parse(".items[").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    root: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Identity,
+    Recurse,
+    Literal(Value),
+    /// The member or element of each output of the first expression at each output of the
+    /// second, both applied to the input.
+    Index(Box<Expr>, Box<Expr>),
+    /// Every element or member of each output.
+    Iterate(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Comparison, Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    Try(Box<Expr>),
+    Length,
+    Keys,
+    Type,
+    Not,
+    Empty,
+    Has(Box<Expr>),
+    Select(Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Expression {
+    /// Compile a jq expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the expression isn't valid, giving the byte offset of
+    /// the problem.
+    pub fn parse(expression: &str) -> Result<Expression, JsonError> {
+        let mut parser = Parser {
+            text: expression,
+            position: 0,
+        };
+        parser.skip_whitespace();
+        let root = parser.pipe()?;
+        if parser.position < expression.len() {
+            return Err(parser.unexpected("end of the expression"));
+        }
+
+        Ok(Expression { root })
+    }
+
+    /// Every output of the expression for `input`, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Evaluation`] if the expression applies to a value of the wrong
+    /// type, such as indexing a number or taking the keys of a string, outside of a `?`.
+    pub fn evaluate(&self, input: &Value) -> Result<Vec<Value>, JsonError> {
+        let mut outputs = Vec::new();
+        self.root.evaluate(input, &mut outputs)?;
+
+        Ok(outputs)
+    }
+}
+
+impl Value {
+    /// Every output of a jq expression applied to this value, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the expression isn't valid, and
+    /// [`JsonError::Evaluation`] if it applies to a value of the wrong type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let value = JsonParser::parse_from_bytes(
+    ///     br#"{"users": [{"name": "ada", "age": 36}, {"name": "alan", "age": 41}], "v": 2}"#,
+    /// )
+    /// .unwrap();
+    /// let outputs = |expression| value.evaluate(expression).unwrap();
+    ///
+    /// assert_eq!(outputs(".users | length"), [Value::Number(Number::I64(2))]);
+    /// assert_eq!(outputs("keys")[0].to_string_compact(), r#"["users","v"]"#);
+    /// assert_eq!(
+    ///     outputs(".users | map(.age > 40)")[0].to_string_compact(),
+    ///     "[false,true]"
+    /// );
+    /// assert_eq!(
+    ///     outputs(r#"{version: .v, first: .users[0].name, "missing": (.nothing // "none")}"#)[0]
+    ///         .to_string_compact(),
+    ///     r#"{"version":2,"first":"ada","missing":"none"}"#
+    /// );
+    /// assert_eq!(outputs(".users[-1].name, .v"), [Value::from("alan"), Value::Number(Number::I64(2))]);
+    /// assert!(value.evaluate(".v[0]").is_err());
+    /// assert_eq!(outputs(".v[0]?"), []);
+    /// ```
+    pub fn evaluate(&self, expression: &str) -> Result<Vec<Value>, JsonError> {
+        Expression::parse(expression)?.evaluate(self)
+    }
+}
+
+impl Expr {
+    /// Append the outputs of the expression for `input` to `outputs`.
+    fn evaluate(&self, input: &Value, outputs: &mut Vec<Value>) -> Result<(), JsonError> {
+        match self {
+            Expr::Identity => outputs.push(input.clone()),
+            Expr::Recurse => {
+                let mut stack = vec![input];
+                while let Some(value) = stack.pop() {
+                    outputs.push(value.clone());
+                    let start = stack.len();
+                    match value {
+                        Value::Array(elements) => stack.extend(elements),
+                        Value::Object(members) => stack.extend(members.values()),
+                        _ => {}
+                    }
+                    // Visit children in document order.
+                    stack[start..].reverse();
+                }
+            }
+            Expr::Literal(value) => outputs.push(value.clone()),
+            Expr::Index(target, index) => {
+                let indices = index.outputs(input)?;
+                target.for_each(input, |target| {
+                    for index in &indices {
+                        outputs.push(lookup(target, index)?);
+                    }
+                    Ok(())
+                })?;
+            }
+            Expr::Iterate(target) => target.for_each(input, |target| {
+                match target {
+                    Value::Array(elements) => outputs.extend(elements.iter().cloned()),
+                    Value::Object(members) => outputs.extend(members.values().cloned()),
+                    other => {
+                        return Err(JsonError::Evaluation(format!(
+                            "can't iterate over {}",
+                            other.type_name()
+                        )))
+                    }
+                }
+                Ok(())
+            })?,
+            Expr::Pipe(left, right) => {
+                left.for_each(input, |value| right.evaluate(value, outputs))?;
+            }
+            Expr::Comma(left, right) => {
+                left.evaluate(input, outputs)?;
+                right.evaluate(input, outputs)?;
+            }
+            Expr::Alternative(left, right) => {
+                let start = outputs.len();
+                let mut values = Vec::new();
+                // Errors of the left side count as no output.
+                let _ = left.evaluate(input, &mut values);
+                outputs.extend(values.into_iter().filter(truthy));
+                if outputs.len() == start {
+                    right.evaluate(input, outputs)?;
+                }
+            }
+            Expr::Or(left, right) | Expr::And(left, right) => {
+                let or = matches!(self, Expr::Or(..));
+                for value in left.outputs(input)? {
+                    if truthy(&value) == or {
+                        outputs.push(Value::Boolean(or));
+                        continue;
+                    }
+                    for value in right.outputs(input)? {
+                        outputs.push(Value::Boolean(truthy(&value)));
+                    }
+                }
+            }
+            Expr::Compare(left, comparison, right) => {
+                let rights = right.outputs(input)?;
+                for left in left.outputs(input)? {
+                    for right in &rights {
+                        let ordering = compare(&left, right);
+                        outputs.push(Value::Boolean(match comparison {
+                            Comparison::Equal => ordering.is_eq(),
+                            Comparison::NotEqual => ordering.is_ne(),
+                            Comparison::Less => ordering.is_lt(),
+                            Comparison::LessOrEqual => ordering.is_le(),
+                            Comparison::Greater => ordering.is_gt(),
+                            Comparison::GreaterOrEqual => ordering.is_ge(),
+                        }));
+                    }
+                }
+            }
+            Expr::Array(elements) => {
+                let elements = match elements {
+                    Some(elements) => elements.outputs(input)?,
+                    None => Vec::new(),
+                };
+                outputs.push(Value::Array(elements));
+            }
+            Expr::Object(entries) => {
+                // Every combination of the outputs of the keys and values makes an object.
+                let mut objects = vec![Map::new()];
+                for (key, value) in entries {
+                    let values = value.outputs(input)?;
+                    let mut combined = Vec::new();
+                    for key in key.outputs(input)? {
+                        let Value::String(key) = key else {
+                            return Err(JsonError::Evaluation(format!(
+                                "object keys must be strings, found {}",
+                                key.type_name()
+                            )));
+                        };
+                        for object in &objects {
+                            for value in &values {
+                                let mut object = object.clone();
+                                object.insert(key.to_string(), value.clone());
+                                combined.push(object);
+                            }
+                        }
+                    }
+                    objects = combined;
+                }
+                outputs.extend(objects.into_iter().map(Value::Object));
+            }
+            Expr::Try(inner) => {
+                let start = outputs.len();
+                if inner.evaluate(input, outputs).is_err() {
+                    outputs.truncate(start);
+                }
+            }
+            Expr::Length => outputs.push(length(input)?),
+            Expr::Keys => {
+                let keys = match input {
+                    Value::Object(members) => {
+                        let mut keys: Vec<_> = members.keys().collect();
+                        keys.sort_unstable();
+                        keys.into_iter().map(Value::from).collect()
+                    }
+                    Value::Array(elements) => (0..elements.len()).map(index_value).collect(),
+                    other => {
+                        return Err(JsonError::Evaluation(format!(
+                            "{} has no keys",
+                            other.type_name()
+                        )))
+                    }
+                };
+                outputs.push(Value::Array(keys));
+            }
+            Expr::Type => outputs.push(Value::from(input.type_name())),
+            Expr::Not => outputs.push(Value::Boolean(!truthy(input))),
+            Expr::Empty => {}
+            Expr::Has(key) => {
+                for key in key.outputs(input)? {
+                    let has = match (input, &key) {
+                        (Value::Object(members), Value::String(key)) => members.contains_key(key),
+                        (Value::Array(elements), Value::Number(number)) => integer(*number)
+                            .is_some_and(|index| {
+                                usize::try_from(index).is_ok_and(|index| index < elements.len())
+                            }),
+                        (input, key) => {
+                            return Err(JsonError::Evaluation(format!(
+                                "can't check whether {} has a {} key",
+                                input.type_name(),
+                                key.type_name()
+                            )))
+                        }
+                    };
+                    outputs.push(Value::Boolean(has));
+                }
+            }
+            Expr::Select(condition) => {
+                for value in condition.outputs(input)? {
+                    if truthy(&value) {
+                        outputs.push(input.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Call `f` with every output of the expression for `input`, without copying the input
+    /// when the expression is `.`, so that paths such as `.a.b` don't copy the whole input.
+    fn for_each(
+        &self,
+        input: &Value,
+        mut f: impl FnMut(&Value) -> Result<(), JsonError>,
+    ) -> Result<(), JsonError> {
+        if *self == Expr::Identity {
+            return f(input);
+        }
+        self.outputs(input)?.iter().try_for_each(f)
+    }
+
+    fn outputs(&self, input: &Value) -> Result<Vec<Value>, JsonError> {
+        let mut outputs = Vec::new();
+        self.evaluate(input, &mut outputs)?;
+
+        Ok(outputs)
+    }
+}
+
+/// Whether a value counts as true, which all do except `false` and `null`.
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Boolean(false))
+}
+
+fn index_value(index: usize) -> Value {
+    Value::Number(Number::I64(i64::try_from(index).unwrap_or(i64::MAX)))
+}
+
+/// The integer a number stands for as an index, rounding floats down.
+fn integer(number: Number) -> Option<i64> {
+    match number {
+        Number::I64(integer) => Some(integer),
+        #[allow(clippy::cast_possible_truncation)]
+        Number::F64(float) if float.is_finite() => Some(float.floor() as i64),
+        Number::F64(_) => None,
+    }
+}
+
+/// The member or element of `target` at `index`.
+fn lookup(target: &Value, index: &Value) -> Result<Value, JsonError> {
+    match (target, index) {
+        (Value::Object(members), Value::String(key)) => {
+            Ok(members.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(elements), Value::Number(number)) => {
+            let element = integer(*number).and_then(|index| {
+                let index = if index < 0 {
+                    i64::try_from(elements.len()).ok()? + index
+                } else {
+                    index
+                };
+                elements.get(usize::try_from(index).ok()?)
+            });
+            Ok(element.cloned().unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_) | Value::Number(_)) => Ok(Value::Null),
+        (target, index) => Err(JsonError::Evaluation(format!(
+            "can't index {} with {}",
+            target.type_name(),
+            index.type_name()
+        ))),
+    }
+}
+
+fn length(value: &Value) -> Result<Value, JsonError> {
+    let count = match value {
+        Value::Null => 0,
+        Value::String(string) => string.chars().count(),
+        Value::Array(elements) => elements.len(),
+        Value::Object(members) => members.len(),
+        Value::Number(Number::I64(integer)) => {
+            return Ok(match integer.checked_abs() {
+                Some(absolute) => Value::Number(Number::I64(absolute)),
+                None => Value::Number(Number::F64(-as_f64(Number::I64(*integer)))),
+            })
+        }
+        Value::Number(Number::F64(float)) => return Ok(Value::Number(Number::F64(float.abs()))),
+        Value::Boolean(_) => {
+            return Err(JsonError::Evaluation("boolean has no length".to_string()))
+        }
+    };
+
+    Ok(index_value(count))
+}
+
+/// Compare two values in jq's order: `null`, then `false`, `true`, numbers, strings, arrays
+/// compared element by element, and objects compared by their sorted keys then their values.
+fn compare(left: &Value, right: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Boolean(false) => 1,
+            Value::Boolean(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (left, right) {
+        (Value::Number(Number::I64(left)), Value::Number(Number::I64(right))) => left.cmp(right),
+        (Value::Number(left), Value::Number(right)) => as_f64(*left).total_cmp(&as_f64(*right)),
+        (Value::String(left), Value::String(right)) => left.cmp(right),
+        (Value::Array(left), Value::Array(right)) => left
+            .iter()
+            .zip(right)
+            .map(|(left, right)| compare(left, right))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| left.len().cmp(&right.len())),
+        (Value::Object(left), Value::Object(right)) => {
+            let mut left_keys: Vec<_> = left.keys().collect();
+            let mut right_keys: Vec<_> = right.keys().collect();
+            left_keys.sort_unstable();
+            right_keys.sort_unstable();
+            left_keys.cmp(&right_keys).then_with(|| {
+                left_keys
+                    .iter()
+                    .map(|key| {
+                        compare(
+                            left.get(key).expect("key is shared"),
+                            right.get(key).expect("key is shared"),
+                        )
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        (left, right) => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn as_f64(number: Number) -> f64 {
+    match number {
+        #[allow(clippy::cast_precision_loss)]
+        Number::I64(integer) => integer as f64,
+        Number::F64(float) => float,
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    /// Byte offset of the next character to read.
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.text[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume `keyword` if it's the next whole identifier.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let rest = &self.text[self.position..];
+        let follows = rest[keyword.len().min(rest.len())..].chars().next();
+        if rest.starts_with(keyword) && !follows.is_some_and(is_identifier) {
+            self.position += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume `expected` and the whitespace after it.
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            self.skip_whitespace();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{expected}`")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Format {
+            format: "jq",
+            message: format!("{message} at byte {}", self.position),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        match self.peek() {
+            Some(found) => self.error(&format!("expected {expected}, found `{found}`")),
+            None => self.error(&format!("expected {expected}, found the end")),
+        }
+    }
+
+    // Every rule parses the whitespace after what it matches.
+
+    fn pipe(&mut self) -> Result<Expr, JsonError> {
+        let mut expr = self.comma()?;
+        while self.eat("|") {
+            self.skip_whitespace();
+            expr = Expr::Pipe(Box::new(expr), Box::new(self.comma()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn comma(&mut self) -> Result<Expr, JsonError> {
+        let mut expr = self.alternative()?;
+        while self.eat(",") {
+            self.skip_whitespace();
+            expr = Expr::Comma(Box::new(expr), Box::new(self.alternative()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn alternative(&mut self) -> Result<Expr, JsonError> {
+        let expr = self.or()?;
+        if self.eat("//") {
+            self.skip_whitespace();
+            // The alternative operator groups to the right.
+            return Ok(Expr::Alternative(
+                Box::new(expr),
+                Box::new(self.alternative()?),
+            ));
+        }
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, JsonError> {
+        let mut expr = self.and()?;
+        while self.eat_keyword("or") {
+            self.skip_whitespace();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, JsonError> {
+        let mut expr = self.comparison()?;
+        while self.eat_keyword("and") {
+            self.skip_whitespace();
+            expr = Expr::And(Box::new(expr), Box::new(self.comparison()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, JsonError> {
+        let left = self.postfix()?;
+        let comparison = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ]
+        .into_iter()
+        .find(|(operator, _)| self.eat(operator));
+
+        let Some((_, comparison)) = comparison else {
+            return Ok(left);
+        };
+        self.skip_whitespace();
+        let right = self.postfix()?;
+
+        Ok(Expr::Compare(Box::new(left), comparison, Box::new(right)))
+    }
+
+    /// Parse a term followed by any number of `.name`, `[...]` and `?` suffixes.
+    fn postfix(&mut self) -> Result<Expr, JsonError> {
+        let mut expr = self.term()?;
+        loop {
+            let rest = &self.text[self.position..];
+            if rest.starts_with('.') && !rest.starts_with("..") {
+                self.position += 1;
+                if self.peek() != Some('[') {
+                    let name = self.name()?;
+                    expr = Expr::Index(Box::new(expr), Box::new(Expr::Literal(name)));
+                }
+            } else if self.eat("[") {
+                self.skip_whitespace();
+                if self.eat("]") {
+                    expr = Expr::Iterate(Box::new(expr));
+                } else {
+                    let index = self.pipe()?;
+                    if self.peek() != Some(']') {
+                        return Err(self.unexpected("`]`"));
+                    }
+                    self.position += 1;
+                    expr = Expr::Index(Box::new(expr), Box::new(index));
+                }
+            } else if self.eat("?") {
+                expr = Expr::Try(Box::new(expr));
+            } else {
+                self.skip_whitespace();
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Parse a term without its suffixes, nor the whitespace after it.
+    fn term(&mut self) -> Result<Expr, JsonError> {
+        if self.eat("..") {
+            return Ok(Expr::Recurse);
+        }
+        if self.eat(".") {
+            return Ok(match self.peek() {
+                Some('"') => Expr::Index(Box::new(Expr::Identity), Box::new(self.string()?)),
+                Some(character) if is_identifier_start(character) => {
+                    let name = self.name()?;
+                    Expr::Index(Box::new(Expr::Identity), Box::new(Expr::Literal(name)))
+                }
+                _ => Expr::Identity,
+            });
+        }
+
+        match self.peek() {
+            Some('"') => self.string(),
+            Some('-' | '0'..='9') => self.number(),
+            Some('(') => {
+                self.expect('(')?;
+                let expr = self.pipe()?;
+                self.closing(')')?;
+                Ok(expr)
+            }
+            Some('[') => {
+                self.expect('[')?;
+                let elements = if self.peek() == Some(']') {
+                    None
+                } else {
+                    Some(Box::new(self.pipe()?))
+                };
+                self.closing(']')?;
+                Ok(Expr::Array(elements))
+            }
+            Some('{') => self.object(),
+            Some(character) if is_identifier_start(character) => self.function(),
+            _ => Err(self.unexpected("an expression")),
+        }
+    }
+
+    /// Consume the `closing` character ending a term, without the whitespace after it.
+    fn closing(&mut self, closing: char) -> Result<(), JsonError> {
+        if self.peek() == Some(closing) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{closing}`")))
+        }
+    }
+
+    fn object(&mut self) -> Result<Expr, JsonError> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        while self.peek() != Some('}') {
+            if !entries.is_empty() {
+                self.expect(',')?;
+            }
+            let (key, shorthand) = match self.peek() {
+                Some('"') => (self.string()?, None),
+                Some('(') => {
+                    self.expect('(')?;
+                    let key = self.pipe()?;
+                    self.closing(')')?;
+                    (key, None)
+                }
+                Some(character) if is_identifier_start(character) => {
+                    let name = self.name()?;
+                    (Expr::Literal(name.clone()), Some(name))
+                }
+                _ => return Err(self.unexpected("an object key")),
+            };
+            self.skip_whitespace();
+
+            let value = if self.eat(":") {
+                self.skip_whitespace();
+                // Values stop at commas, which separate members.
+                self.alternative()?
+            } else if let Some(name) = shorthand {
+                Expr::Index(Box::new(Expr::Identity), Box::new(Expr::Literal(name)))
+            } else {
+                return Err(self.unexpected("`:`"));
+            };
+            entries.push((key, value));
+        }
+        self.closing('}')?;
+
+        Ok(Expr::Object(entries))
+    }
+
+    fn function(&mut self) -> Result<Expr, JsonError> {
+        let start = self.position;
+        let Value::String(name) = self.name()? else {
+            unreachable!("names are strings");
+        };
+        let argument = |parser: &mut Self| -> Result<Box<Expr>, JsonError> {
+            parser.skip_whitespace();
+            parser.expect('(')?;
+            let argument = parser.pipe()?;
+            parser.closing(')')?;
+            Ok(Box::new(argument))
+        };
+
+        Ok(match name.as_str() {
+            "length" => Expr::Length,
+            "keys" => Expr::Keys,
+            "type" => Expr::Type,
+            "not" => Expr::Not,
+            "empty" => Expr::Empty,
+            "true" => Expr::Literal(Value::Boolean(true)),
+            "false" => Expr::Literal(Value::Boolean(false)),
+            "null" => Expr::Literal(Value::Null),
+            "has" => Expr::Has(argument(self)?),
+            "select" => Expr::Select(argument(self)?),
+            // `map(f)` is `[.[] | f]`.
+            "map" => Expr::Array(Some(Box::new(Expr::Pipe(
+                Box::new(Expr::Iterate(Box::new(Expr::Identity))),
+                argument(self)?,
+            )))),
+            _ => {
+                self.position = start;
+                return Err(self.error(&format!("unknown function `{name}`")));
+            }
+        })
+    }
+
+    /// Parse an identifier or a string, as the name of a member.
+    fn name(&mut self) -> Result<Value, JsonError> {
+        if self.peek() == Some('"') {
+            let Expr::Literal(name) = self.string()? else {
+                unreachable!("strings are literals");
+            };
+            return Ok(name);
+        }
+        let start = self.position;
+        if !self.peek().is_some_and(is_identifier_start) {
+            return Err(self.unexpected("a name"));
+        }
+        while self.peek().is_some_and(is_identifier) {
+            self.position += 1;
+        }
+
+        Ok(Value::from(&self.text[start..self.position]))
+    }
+
+    /// Parse a string literal, with the escapes of JSON.
+    fn string(&mut self) -> Result<Expr, JsonError> {
+        let start = self.position + 1;
+        let mut escaped = false;
+        for (offset, character) in self.text[start..].char_indices() {
+            match character {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    let end = start + offset;
+                    let string = unescape(&self.text[start..end]).map_err(|invalid| {
+                        self.position = start + invalid;
+                        self.error("invalid escape sequence")
+                    })?;
+                    self.position = end + 1;
+                    return Ok(Expr::Literal(Value::from(string)));
+                }
+                _ => {}
+            }
+        }
+
+        Err(self.error("unterminated string"))
+    }
+
+    fn number(&mut self) -> Result<Expr, JsonError> {
+        let start = self.position;
+        self.eat("-");
+        while let Some(character @ ('+' | '-' | '.' | 'e' | 'E' | '0'..='9')) = self.peek() {
+            // Signs only belong to the number in its exponent.
+            if matches!(character, '+' | '-') && !self.text[..self.position].ends_with(['e', 'E']) {
+                break;
+            }
+            self.position += 1;
+        }
+        let text = &self.text[start..self.position];
+
+        if let Ok(integer) = text.parse() {
+            return Ok(Expr::Literal(Value::Number(Number::I64(integer))));
+        }
+        text.parse()
+            .ok()
+            .filter(|float: &f64| float.is_finite())
+            .map(|float| Expr::Literal(Value::Number(Number::F64(float))))
+            .ok_or_else(|| self.error(&format!("invalid number `{text}`")))
+    }
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character.is_ascii_alphabetic() || character == '_'
+}
+
+fn is_identifier(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_'
+}
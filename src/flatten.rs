@@ -0,0 +1,245 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// A single step of a flattened path.
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Intermediate tree used while unflattening, which keeps track of which slots have already
+/// been assigned so that conflicting paths can be reported.
+enum Node {
+    Empty,
+    Leaf(Value),
+    /// Members in insertion order, along with the position of each key.
+    Object(Vec<(String, Node)>, HashMap<String, usize>),
+    Array(Vec<Node>),
+}
+
+impl Value {
+    /// Flatten nested objects and arrays into a single map keyed by paths such as `a.b[0].c`.
+    ///
+    /// Keys appear in the same order as the leaves of the original document.
+    ///
+    /// Object keys are joined with `.` and array indices are written as `[n]`. The characters
+    /// `.`, `[`, `]` and `\` inside object keys are escaped with a `\`, and an empty key is
+    /// written as `[""]`, so that it can't be mistaken for the root. Empty objects and arrays are
+    /// kept as leaf values, and a scalar at the root is stored under the empty path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"a":{"b":[{"c":1}]}}"#).unwrap();
+    /// let flat = value.flatten();
+    ///
+    /// assert_eq!(flat.get("a.b[0].c"), Some(&Value::Number(Number::I64(1))));
+    /// assert_eq!(Value::unflatten(&flat).unwrap(), value);
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"": {"": 1}}"#).unwrap();
+    /// let flat = value.flatten();
+    ///
+    /// assert_eq!(flat.get(r#"[""][""]"#), Some(&Value::Number(Number::I64(1))));
+    /// assert_eq!(Value::unflatten(&flat).unwrap(), value);
+    /// ```
+    #[must_use]
+    pub fn flatten(&self) -> Map {
+        let mut output = Map::new();
+        flatten_into(self, &mut String::new(), &mut output);
+
+        output
+    }
+
+    /// Rebuild a nested value from a map produced by [`Value::flatten`].
+    ///
+    /// Array slots which are not covered by any key are filled with [`Value::Null`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::InvalidPath`] if a key cannot be parsed as a path, and
+    /// [`JsonError::PathConflict`] if two keys describe incompatible structures.
+    pub fn unflatten(map: &Map) -> Result<Value, JsonError> {
+        let mut root = Node::Empty;
+
+        for (path, value) in map {
+            let segments = parse_path(path)?;
+            insert(&mut root, &segments, value.clone())
+                .ok_or_else(|| JsonError::PathConflict(path.to_string()))?;
+        }
+
+        Ok(match root {
+            // An empty map is the flattened form of an empty object.
+            Node::Empty => Value::Object(Map::new()),
+            root => root.into_value(),
+        })
+    }
+}
+
+fn flatten_into(value: &Value, prefix: &mut String, output: &mut Map) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let length = prefix.len();
+                if key.is_empty() {
+                    prefix.push_str("[\"\"]");
+                } else {
+                    if !prefix.is_empty() {
+                        prefix.push('.');
+                    }
+                    escape_key(key, prefix);
+                }
+                flatten_into(value, prefix, output);
+                prefix.truncate(length);
+            }
+        }
+        Value::Array(array) if !array.is_empty() => {
+            for (index, value) in array.iter().enumerate() {
+                let length = prefix.len();
+                prefix.push_str(&format!("[{index}]"));
+                flatten_into(value, prefix, output);
+                prefix.truncate(length);
+            }
+        }
+        _ => {
+            output.insert(prefix.clone(), value.clone());
+        }
+    }
+}
+
+fn escape_key(key: &str, output: &mut String) {
+    for character in key.chars() {
+        if matches!(character, '.' | '[' | ']' | '\\') {
+            output.push('\\');
+        }
+        output.push(character);
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, JsonError> {
+    let invalid = || JsonError::InvalidPath(path.to_string());
+    let mut segments = Vec::new();
+    let mut iterator = path.chars().peekable();
+
+    // The empty path refers to the root value itself.
+    if path.is_empty() {
+        return Ok(segments);
+    }
+
+    // A path can start with either a key or an index, so only expect a key when the first
+    // character isn't the start of an index.
+    let mut expect_key = iterator.peek() != Some(&'[');
+
+    loop {
+        if expect_key {
+            let mut key = String::new();
+            while let Some(&character) = iterator.peek() {
+                match character {
+                    '.' | '[' => break,
+                    ']' => return Err(invalid()),
+                    '\\' => {
+                        let _ = iterator.next();
+                        key.push(iterator.next().ok_or_else(invalid)?);
+                    }
+                    other => {
+                        key.push(other);
+                        let _ = iterator.next();
+                    }
+                }
+            }
+            segments.push(Segment::Key(key));
+        }
+
+        match iterator.next() {
+            None => break,
+            Some('.') => expect_key = true,
+            Some('[') if iterator.peek() == Some(&'"') => {
+                // `[""]` is an empty key, which would otherwise be written as nothing at all.
+                for expected in ['"', '"', ']'] {
+                    if iterator.next() != Some(expected) {
+                        return Err(invalid());
+                    }
+                }
+                segments.push(Segment::Key(String::new()));
+                expect_key = false;
+            }
+            Some('[') => {
+                let mut digits = String::new();
+                loop {
+                    match iterator.next() {
+                        Some(']') => break,
+                        Some(digit @ '0'..='9') => digits.push(digit),
+                        _ => return Err(invalid()),
+                    }
+                }
+                segments.push(Segment::Index(digits.parse().map_err(|_| invalid())?));
+                expect_key = false;
+            }
+            Some(_) => return Err(invalid()),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Insert `value` at the position described by `segments`, returning `None` if the slot is
+/// already taken or has an incompatible type.
+fn insert(node: &mut Node, segments: &[Segment], value: Value) -> Option<()> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return match node {
+            Node::Empty => {
+                *node = Node::Leaf(value);
+                Some(())
+            }
+            _ => None,
+        };
+    };
+
+    match segment {
+        Segment::Key(key) => {
+            if let Node::Empty = node {
+                *node = Node::Object(Vec::new(), HashMap::new());
+            }
+            let Node::Object(members, index) = node else {
+                return None;
+            };
+            let position = *index.entry(key.clone()).or_insert_with(|| {
+                members.push((key.clone(), Node::Empty));
+                members.len() - 1
+            });
+            insert(&mut members[position].1, rest, value)
+        }
+        Segment::Index(index) => {
+            if let Node::Empty = node {
+                *node = Node::Array(Vec::new());
+            }
+            let Node::Array(array) = node else {
+                return None;
+            };
+            if array.len() <= *index {
+                array.resize_with(index + 1, || Node::Empty);
+            }
+            insert(&mut array[*index], rest, value)
+        }
+    }
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        match self {
+            Node::Empty => Value::Null,
+            Node::Leaf(value) => value,
+            Node::Object(members, _) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(key, node)| (key, node.into_value()))
+                    .collect(),
+            ),
+            Node::Array(array) => Value::Array(array.into_iter().map(Node::into_value).collect()),
+        }
+    }
+}
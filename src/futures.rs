@@ -0,0 +1,92 @@
+use crate::error::JsonError;
+use crate::event::{Event, EventParser, Step};
+use crate::parser::JsonParser;
+use crate::value::Value;
+use ::futures::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use ::futures::stream::{self, Stream, StreamExt};
+
+impl JsonParser {
+    /// Parse JSON from an asynchronous reader as a stream of [`Event`]s.
+    ///
+    /// Input is only read when the next event is polled, so a slow consumer holds back the
+    /// producer instead of having the document buffered in memory. The stream ends after the
+    /// first error.
+    ///
+    /// # Errors
+    ///
+    /// Yields [`JsonError::Io`] if reading fails, or [`JsonError::Syntax`] if the input isn't
+    /// valid JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::TryStreamExt;
+    /// use json_parser::event::Event;
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let body: &[u8] = br#"{"ids": [1, 2]}"#;
+    /// let events: Vec<Event> = block_on(JsonParser::event_stream(body).try_collect()).unwrap();
+    ///
+    /// assert_eq!(events.len(), 7);
+    /// assert_eq!(events[1], Event::Key("ids".to_string()));
+    /// ```
+    pub fn event_stream<R>(reader: R) -> impl Stream<Item = Result<Event, JsonError>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let state = (reader, EventParser::new(), vec![0; 8192]);
+        stream::try_unfold(state, |(mut reader, mut parser, mut chunk)| async move {
+            loop {
+                match parser.next_event()? {
+                    Step::Event(event) => return Ok(Some((event, (reader, parser, chunk)))),
+                    Step::End => return Ok(None),
+                    Step::NeedMoreData => match reader.read(&mut chunk).await? {
+                        0 => parser.finish(),
+                        length => parser.feed(&chunk[..length]),
+                    },
+                }
+            }
+        })
+    }
+
+    /// Parse newline-delimited JSON from an asynchronous reader as a stream of values, one per
+    /// non-blank line.
+    ///
+    /// Lines are read as the stream is polled. An invalid line yields an error without ending
+    /// the stream, so the remaining lines can still be processed.
+    ///
+    /// # Errors
+    ///
+    /// Yields [`JsonError::Io`] if reading fails or a line isn't valid UTF-8, or
+    /// [`JsonError::Syntax`] if a line isn't valid JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::StreamExt;
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let feed: &[u8] = b"{\"id\": 1}\n\nnot json\n{\"id\": 2}\n";
+    /// let values: Vec<_> = block_on(JsonParser::ndjson_stream(feed).collect());
+    ///
+    /// assert_eq!(values.len(), 3);
+    /// assert_eq!(values[0], JsonParser::parse_from_bytes(br#"{"id": 1}"#));
+    /// assert!(values[1].is_err());
+    /// ```
+    pub fn ndjson_stream<R>(reader: R) -> impl Stream<Item = Result<Value, JsonError>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        BufReader::new(reader)
+            .lines()
+            .filter_map(|line| async move {
+                match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    Ok(line) => Some(EventParser::parse(line.as_bytes())),
+                    Err(error) => Some(Err(error.into())),
+                }
+            })
+    }
+}
@@ -0,0 +1,213 @@
+//! Composition of documents split across files, where objects such as
+//! `{"$include": "database.json"}` stand for the contents of another file.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::pointer::push_token;
+use crate::reference::{join, normalize};
+use crate::value::Value;
+use std::fs::File;
+use std::path::Path;
+
+impl JsonParser {
+    /// Parse the file at `path`, replacing every object with a `key` member, such as
+    /// `{"$include": "database.json"}`, with the file it names, parsed the same way.
+    ///
+    /// Relative paths are relative to the directory of the file including them. See
+    /// [`Value::resolve_includes_with`] for how included files are combined.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Io`] for a file which can't be opened, the errors of parsing any
+    /// of the files, and those of [`Value::resolve_includes_with`].
+    pub fn parse_with_includes(path: impl AsRef<Path>, key: &str) -> Result<Value, JsonError> {
+        let location = normalize(&path.as_ref().to_string_lossy());
+        let root = load(&location)?;
+
+        Includes {
+            key,
+            loader: &mut load,
+            including: vec![location.clone()],
+        }
+        .resolve(&root, &location, &mut String::new())
+    }
+}
+
+impl Value {
+    /// Copy this value with every object with a `key` member, such as
+    /// `{"$include": "database.json"}`, replaced by the document `loader` loads from the
+    /// location it names, itself with its includes resolved.
+    ///
+    /// The member can also name several locations, such as
+    /// `{"$include": ["defaults.json", "local.json"]}`, whose documents are merged in order
+    /// as JSON Merge Patches. The other members of the object are then merged into the result
+    /// the same way, so they override what's included, and a member set to `null` removes it.
+    ///
+    /// `loader` is called with locations relative to this document. A relative location in a
+    /// loaded document is joined to the directory of that document, so `"tls.json"` in
+    /// `"server/main.json"` is loaded as `"server/tls.json"`, while locations starting with
+    /// `/` or containing `://` aren't joined to anything. The `.` and `..` segments of
+    /// locations are resolved, apart from those of URLs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] for an include which isn't a string or an array of
+    /// strings, [`JsonError::Reference`] for a document including itself, directly or not, and
+    /// any error `loader` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use std::collections::HashMap;
+    ///
+    /// let files = HashMap::from([
+    ///     ("config/database.json", r#"{"host": "localhost", "port": 5432, "pool": {"$include": "pool.json"}}"#),
+    ///     ("config/pool.json", r#"{"size": 4}"#),
+    ///     ("config/loop.json", r#"{"$include": "loop.json"}"#),
+    /// ]);
+    /// let load = |location: &str| JsonParser::parse_from_bytes(files[location].as_bytes());
+    ///
+    /// let config = JsonParser::parse_from_bytes(
+    ///     br#"{"database": {"$include": "config/database.json", "port": 5433, "host": null}}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     config.resolve_includes_with("$include", load).unwrap().to_string_compact(),
+    ///     r#"{"database":{"port":5433,"pool":{"size":4}}}"#
+    /// );
+    ///
+    /// let config = JsonParser::parse_from_bytes(br#"{"$include": "config/loop.json"}"#).unwrap();
+    /// assert!(config.resolve_includes_with("$include", load).is_err());
+    /// ```
+    pub fn resolve_includes_with<F>(&self, key: &str, mut loader: F) -> Result<Value, JsonError>
+    where
+        F: FnMut(&str) -> Result<Value, JsonError>,
+    {
+        Includes {
+            key,
+            loader: &mut loader,
+            including: Vec::new(),
+        }
+        .resolve(self, "", &mut String::new())
+    }
+}
+
+/// Parse the file at `location`.
+fn load(location: &str) -> Result<Value, JsonError> {
+    let file = File::open(location).map_err(|error| JsonError::Io {
+        kind: error.kind(),
+        message: format!("{location}: {error}"),
+    })?;
+
+    JsonParser::parse_from_file(file)
+}
+
+/// Loads the document at a location, for [`Value::resolve_includes_with`].
+type Loader<'a> = &'a mut dyn FnMut(&str) -> Result<Value, JsonError>;
+
+/// Copies values while splicing in the documents they include.
+struct Includes<'a> {
+    key: &'a str,
+    loader: Loader<'a>,
+    /// The locations of the documents being included, to detect cycles.
+    including: Vec<String>,
+}
+
+impl Includes<'_> {
+    /// Copy `value`, located at `pointer` inside the document loaded from `location`,
+    /// resolving its includes.
+    fn resolve(
+        &mut self,
+        value: &Value,
+        location: &str,
+        pointer: &mut String,
+    ) -> Result<Value, JsonError> {
+        let length = pointer.len();
+        match value {
+            Value::Object(members) => {
+                let mut resolved = Map::with_capacity(members.len());
+                for (key, member) in members {
+                    if key != self.key {
+                        push_token(pointer, key);
+                        resolved.insert(key.to_string(), self.resolve(member, location, pointer)?);
+                        pointer.truncate(length);
+                    }
+                }
+                let Some(include) = members.get(self.key) else {
+                    return Ok(Value::Object(resolved));
+                };
+
+                push_token(pointer, self.key);
+                let mut included = self.include(include, location, pointer)?;
+                pointer.truncate(length);
+                if !resolved.is_empty() {
+                    included.merge_patch(&Value::Object(resolved));
+                }
+
+                Ok(included)
+            }
+            Value::Array(elements) => {
+                let mut resolved = Vec::with_capacity(elements.len());
+                for (index, element) in elements.iter().enumerate() {
+                    push_token(pointer, &index.to_string());
+                    resolved.push(self.resolve(element, location, pointer)?);
+                    pointer.truncate(length);
+                }
+
+                Ok(Value::Array(resolved))
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// Load and merge the documents named by `include`, located at `pointer` inside the
+    /// document loaded from `location`.
+    fn include(
+        &mut self,
+        include: &Value,
+        location: &str,
+        pointer: &str,
+    ) -> Result<Value, JsonError> {
+        let paths = match include {
+            Value::Array(paths) => paths.as_slice(),
+            _ => std::slice::from_ref(include),
+        };
+
+        let mut merged = Value::Null;
+        for (index, path) in paths.iter().enumerate() {
+            let Value::String(path) = path else {
+                let mut pointer = pointer.to_string();
+                if matches!(include, Value::Array(_)) {
+                    push_token(&mut pointer, &index.to_string());
+                }
+                return Err(JsonError::TypeMismatch {
+                    pointer,
+                    expected: "string".to_string(),
+                    found: path.type_name(),
+                });
+            };
+
+            let target = join(location, path);
+            if self.including.contains(&target) {
+                return Err(JsonError::Reference {
+                    reference: path.to_string(),
+                    message: format!("`{target}` is already being included"),
+                });
+            }
+            let loaded = (self.loader)(&target)?;
+            self.including.push(target.clone());
+            let resolved = self.resolve(&loaded, &target, &mut String::new());
+            self.including.pop();
+
+            if index == 0 {
+                merged = resolved?;
+            } else {
+                merged.merge_patch(&resolved?);
+            }
+        }
+
+        Ok(merged)
+    }
+}
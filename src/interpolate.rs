@@ -0,0 +1,195 @@
+//! Substitution of environment variables in strings, such as `"${DATA_DIR:-/var/data}/db"`.
+
+use crate::error::JsonError;
+use crate::pointer::push_token;
+use crate::value::Value;
+
+impl Value {
+    /// Replace every `${NAME}` in the strings inside this value with the value of the
+    /// environment variable `NAME`, and every `${NAME:-default}` with `default` if the variable
+    /// isn't set or is empty.
+    ///
+    /// Names are made of ASCII letters, digits and underscores, and don't start with a digit.
+    /// Anything else between `${` and `}` is kept as is, and so is `${` without a closing `}`,
+    /// while `$${` stands for a literal `${`. Keys aren't changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::UndefinedVariable`] for the first `${NAME}` whose variable isn't
+    /// set, in which case nothing is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut config = JsonParser::parse_from_bytes(
+    ///     br#"{"data": "${JSON_PARSER_EXAMPLE_UNSET:-/var/data}/db", "price": "$${amount}"}"#,
+    /// )
+    /// .unwrap();
+    /// config.interpolate_env().unwrap();
+    /// assert_eq!(
+    ///     config.to_string_compact(),
+    ///     r#"{"data":"/var/data/db","price":"${amount}"}"#
+    /// );
+    ///
+    /// let mut config = JsonParser::parse_from_bytes(br#"{"key": ["${JSON_PARSER_EXAMPLE_UNSET}"]}"#)
+    ///     .unwrap();
+    /// let error = config.interpolate_env().unwrap_err();
+    /// assert_eq!(error.to_string(), "undefined variable `JSON_PARSER_EXAMPLE_UNSET` at /key/0");
+    /// ```
+    pub fn interpolate_env(&mut self) -> Result<(), JsonError> {
+        self.interpolate_with(|name| std::env::var(name).ok())
+    }
+
+    /// Replace variables like [`interpolate_env`](Value::interpolate_env), with the values
+    /// `lookup` returns instead of environment variables, where `None` means the variable isn't
+    /// set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::UndefinedVariable`] for the first `${NAME}` for which `lookup`
+    /// returns `None`, in which case nothing is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    ///
+    /// let mut url = Value::from("https://${HOST}:${PORT:-443}/");
+    /// url.interpolate_with(|name| (name == "HOST").then(|| "example.com".to_string()))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(url, Value::from("https://example.com:443/"));
+    /// ```
+    pub fn interpolate_with<F>(&mut self, mut lookup: F) -> Result<(), JsonError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        // Substitute every string first, so that nothing is changed if one of them fails.
+        let mut substituted = Vec::new();
+        collect(self, &mut String::new(), &mut lookup, &mut substituted)?;
+        replace(self, &mut substituted.into_iter());
+
+        Ok(())
+    }
+}
+
+/// Whether the string `text` may contain variables.
+fn has_variables(text: &str) -> bool {
+    text.contains("${")
+}
+
+/// Push the substituted text of every string with variables inside `value`, located at
+/// `pointer`, in document order.
+fn collect<F>(
+    value: &Value,
+    pointer: &mut String,
+    lookup: &mut F,
+    substituted: &mut Vec<String>,
+) -> Result<(), JsonError>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let length = pointer.len();
+    match value {
+        Value::String(text) if has_variables(text) => {
+            let text = substitute(text, lookup).map_err(|name| JsonError::UndefinedVariable {
+                pointer: pointer.clone(),
+                name,
+            })?;
+            substituted.push(text);
+        }
+        Value::Object(members) => {
+            for (key, member) in members {
+                push_token(pointer, key);
+                collect(member, pointer, lookup, substituted)?;
+                pointer.truncate(length);
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                push_token(pointer, &index.to_string());
+                collect(element, pointer, lookup, substituted)?;
+                pointer.truncate(length);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Replace every string with variables inside `value` with the next of `substituted`.
+fn replace(value: &mut Value, substituted: &mut impl Iterator<Item = String>) {
+    match value {
+        Value::String(text) if has_variables(text) => {
+            *value = Value::from(substituted.next().expect("every string was substituted"));
+        }
+        Value::Object(members) => {
+            for member in members.values_mut() {
+                replace(member, substituted);
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                replace(element, substituted);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Substitute the variables in `text`, or return the name of the first one which isn't set
+/// and has no default.
+fn substitute<F>(text: &str, lookup: &mut F) -> Result<String, String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = after;
+            continue;
+        }
+
+        output.push_str(&rest[..start]);
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let (name, default) = match after[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&after[..end], None),
+        };
+        if !is_name(name) {
+            output.push_str("${");
+            rest = after;
+            continue;
+        }
+
+        match (lookup(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => output.push_str(default),
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => return Err(name.to_string()),
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Whether `name` is a valid variable name.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
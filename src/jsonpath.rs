@@ -0,0 +1,761 @@
+//! JSONPath expressions, such as `$.items[?@.price > 10].name`, selecting values inside a
+//! document.
+//!
+//! The syntax is the one of RFC 9535, without function extensions:
+//!
+//! - `$` is the root of the document, and `@` the value being tested inside a filter.
+//! - `.name` and `['name']` select a member of an object, `.*` and `[*]` every element or
+//!   member.
+//! - `[1]` selects an element of an array, counting from the end if negative, and
+//!   `[start:end:step]` a slice of it.
+//! - `[a, b]` selects everything each of the selectors `a` and `b` does.
+//! - `..` applies the selector after it to a value and all its descendants, as in `$..name`.
+//! - `[?expression]` keeps the elements or members for which the expression is true.
+//!   Expressions compare values with `==`, `!=`, `<`, `<=`, `>` and `>=`, test that a path
+//!   selects something, and combine these with `&&`, `||`, `!` and parentheses.
+//!
+//! Such expressions can also be used on their own as a [`Filter`], to test whole values.
+
+use crate::error::JsonError;
+use crate::value::{Number, Value};
+
+/// A compiled JSONPath expression, to select values with.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::jsonpath::JsonPath;
+///
+/// let path = JsonPath::parse("$.store..price").unwrap();
+/// assert!(JsonPath::parse("$.store[").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    /// Whether the selectors apply to all descendants of the values, and not only to them.
+    descendants: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Or(Box<Expression>, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Compare(Operand, Comparison, Operand),
+    /// True if the query selects anything.
+    Exists(Query),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(Value),
+    /// A query which is expected to select at most one value.
+    Query(Query),
+}
+
+/// A path inside a filter, starting either from the root or from the value being tested.
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    relative: bool,
+    path: JsonPath,
+}
+
+impl JsonPath {
+    /// Compile a JSONPath expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the expression isn't valid, giving the byte offset of
+    /// the problem.
+    pub fn parse(expression: &str) -> Result<JsonPath, JsonError> {
+        let mut parser = Parser {
+            text: expression,
+            position: 0,
+        };
+        parser.skip_whitespace();
+        parser.expect('$')?;
+        let path = parser.path()?;
+        parser.skip_whitespace();
+        if parser.position < expression.len() {
+            return Err(parser.unexpected("end of the expression"));
+        }
+
+        Ok(path)
+    }
+
+    /// Every value of `root` the path selects, in document order.
+    #[must_use]
+    pub fn select<'v>(&self, root: &'v Value) -> Vec<&'v Value> {
+        self.select_from(root, root)
+    }
+
+    /// Apply the path to `start`, with `root` as the value `$` refers to inside filters.
+    fn select_from<'v>(&self, start: &'v Value, root: &'v Value) -> Vec<&'v Value> {
+        let mut nodes = vec![start];
+        for segment in &self.segments {
+            let mut selected = Vec::new();
+            for node in nodes {
+                if segment.descendants {
+                    let mut stack = vec![node];
+                    while let Some(node) = stack.pop() {
+                        segment.select(node, root, &mut selected);
+                        let start = stack.len();
+                        stack.extend(children(node));
+                        // Visit children in document order.
+                        stack[start..].reverse();
+                    }
+                } else {
+                    segment.select(node, root, &mut selected);
+                }
+            }
+            nodes = selected;
+        }
+
+        nodes
+    }
+}
+
+/// A compiled filter expression, such as `$.level == "error" && $.code >= 500`, testing whole
+/// values.
+///
+/// This is the expression of a `[?expression]` selector, with both `$` and `@` standing for
+/// the value being tested.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::jsonpath::Filter;
+/// use json_parser::parser::JsonParser;
+///
+/// let filter = Filter::parse(r#"$.level == "error" && @.code >= 500"#).unwrap();
+///
+/// let record = |text: &str| JsonParser::parse_from_bytes(text.as_bytes()).unwrap();
+/// assert!(filter.matches(&record(r#"{"level": "error", "code": 503}"#)));
+/// assert!(!filter.matches(&record(r#"{"level": "error", "code": 404}"#)));
+/// assert!(!filter.matches(&record(r#"{"level": "info"}"#)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expression: Expression,
+}
+
+impl Filter {
+    /// Compile a filter expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the expression isn't valid, giving the byte offset of
+    /// the problem.
+    pub fn parse(expression: &str) -> Result<Filter, JsonError> {
+        let mut parser = Parser {
+            text: expression,
+            position: 0,
+        };
+        parser.skip_whitespace();
+        let filter = Filter {
+            expression: parser.or()?,
+        };
+        if parser.position < expression.len() {
+            return Err(parser.unexpected("end of the expression"));
+        }
+
+        Ok(filter)
+    }
+
+    /// Whether the expression is true for `value`.
+    #[must_use]
+    pub fn matches(&self, value: &Value) -> bool {
+        self.expression.evaluate(value, value)
+    }
+}
+
+impl Value {
+    /// Every value a JSONPath expression selects inside this one, in document order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the expression isn't valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let value = JsonParser::parse_from_bytes(
+    ///     br#"{"items": [
+    ///         {"name": "pen", "price": 2},
+    ///         {"name": "book", "price": 12.5},
+    ///         {"name": "lamp", "price": 30, "sale": true}
+    ///     ]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let names = |expression| value.select(expression).unwrap();
+    /// assert_eq!(
+    ///     names("$.items[?@.price > 10].name"),
+    ///     [&Value::from("book"), &Value::from("lamp")]
+    /// );
+    /// assert_eq!(names("$.items[?(@.sale)].name"), [&Value::from("lamp")]);
+    /// assert_eq!(names("$..name").len(), 3);
+    /// assert_eq!(names("$.items[-1:].name"), [&Value::from("lamp")]);
+    /// assert_eq!(names("$.items[?@.name == 'pen' || !@.sale].price").len(), 2);
+    /// ```
+    pub fn select(&self, expression: &str) -> Result<Vec<&Value>, JsonError> {
+        Ok(JsonPath::parse(expression)?.select(self))
+    }
+}
+
+/// The elements or member values of a value, in order.
+fn children(value: &Value) -> Box<dyn DoubleEndedIterator<Item = &Value> + '_> {
+    match value {
+        Value::Array(elements) => Box::new(elements.iter()),
+        Value::Object(members) => Box::new(members.iter().map(|(_, value)| value)),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+impl Segment {
+    fn select<'v>(&self, node: &'v Value, root: &'v Value, selected: &mut Vec<&'v Value>) {
+        for selector in &self.selectors {
+            match selector {
+                Selector::Name(name) => {
+                    if let Value::Object(members) = node {
+                        selected.extend(members.get(name));
+                    }
+                }
+                Selector::Wildcard => selected.extend(children(node)),
+                Selector::Index(index) => {
+                    if let Value::Array(elements) = node {
+                        selected.extend(
+                            normalize(*index, elements.len())
+                                .and_then(|index| usize::try_from(index).ok())
+                                .and_then(|index| elements.get(index)),
+                        );
+                    }
+                }
+                Selector::Slice { start, end, step } => {
+                    if let Value::Array(elements) = node {
+                        slice(elements, *start, *end, *step, selected);
+                    }
+                }
+                Selector::Filter(expression) => {
+                    selected.extend(children(node).filter(|child| expression.evaluate(child, root)))
+                }
+            }
+        }
+    }
+}
+
+/// Turn an index counted from the end of an array, if negative, into one counted from its
+/// start.
+fn normalize(index: i64, length: usize) -> Option<i64> {
+    let length = i64::try_from(length).ok()?;
+    if index < 0 {
+        length.checked_add(index)
+    } else {
+        Some(index)
+    }
+}
+
+fn slice<'v>(
+    elements: &'v [Value],
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    selected: &mut Vec<&'v Value>,
+) {
+    let step = step.unwrap_or(1);
+    let Ok(length) = i64::try_from(elements.len()) else {
+        return;
+    };
+    let bound = |index: i64, low: i64, high: i64| {
+        normalize(index, elements.len())
+            .unwrap_or(low)
+            .clamp(low, high)
+    };
+    // Indices are within `0..length` whenever they're used.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let element = |index: i64| &elements[index as usize];
+
+    if step > 0 {
+        let mut index = bound(start.unwrap_or(0), 0, length);
+        let upper = bound(end.unwrap_or(length), 0, length);
+        while index < upper {
+            selected.push(element(index));
+            index = index.saturating_add(step);
+        }
+    } else if step < 0 {
+        let mut index = bound(start.unwrap_or(length - 1), -1, length - 1);
+        let lower = end.map_or(-1, |end| bound(end, -1, length - 1));
+        while lower < index {
+            selected.push(element(index));
+            index = index.saturating_add(step);
+        }
+    }
+}
+
+impl Expression {
+    fn evaluate(&self, current: &Value, root: &Value) -> bool {
+        match self {
+            Expression::Or(left, right) => {
+                left.evaluate(current, root) || right.evaluate(current, root)
+            }
+            Expression::And(left, right) => {
+                left.evaluate(current, root) && right.evaluate(current, root)
+            }
+            Expression::Not(expression) => !expression.evaluate(current, root),
+            Expression::Compare(left, comparison, right) => {
+                let left = left.evaluate(current, root);
+                let right = right.evaluate(current, root);
+                match comparison {
+                    Comparison::Equal => equal(left, right),
+                    Comparison::NotEqual => !equal(left, right),
+                    Comparison::Less => less(left, right),
+                    Comparison::LessOrEqual => less(left, right) || equal(left, right),
+                    Comparison::Greater => less(right, left),
+                    Comparison::GreaterOrEqual => less(right, left) || equal(left, right),
+                }
+            }
+            Expression::Exists(query) => !query.select(current, root).is_empty(),
+        }
+    }
+}
+
+impl Operand {
+    /// The value of the operand, or `None` if it's a query which doesn't select exactly one.
+    fn evaluate<'v>(&'v self, current: &'v Value, root: &'v Value) -> Option<&'v Value> {
+        match self {
+            Operand::Literal(value) => Some(value),
+            Operand::Query(query) => match query.select(current, root)[..] {
+                [value] => Some(value),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Query {
+    fn select<'v>(&self, current: &'v Value, root: &'v Value) -> Vec<&'v Value> {
+        let start = if self.relative { current } else { root };
+        self.path.select_from(start, root)
+    }
+}
+
+/// Whether two operands are equal, numbers being compared by value whatever their type, and
+/// two missing values being equal.
+fn equal(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => values_equal(left, right),
+        _ => false,
+    }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => as_f64(*left) == as_f64(*right),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| values_equal(left, right))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left.iter().all(|(key, left)| {
+                    right
+                        .get(key)
+                        .is_some_and(|right| values_equal(left, right))
+                })
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Whether `left` is less than `right`, which only holds for two numbers or two strings.
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(left)), Some(Value::Number(right))) => match (left, right) {
+            (Number::I64(left), Number::I64(right)) => left < right,
+            (left, right) => as_f64(*left) < as_f64(*right),
+        },
+        (Some(Value::String(left)), Some(Value::String(right))) => left < right,
+        _ => false,
+    }
+}
+
+fn as_f64(number: Number) -> f64 {
+    match number {
+        #[allow(clippy::cast_precision_loss)]
+        Number::I64(integer) => integer as f64,
+        Number::F64(float) => float,
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    /// Byte offset of the next character to read.
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.position += character.len_utf8();
+        Some(character)
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.text[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{expected}`")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Format {
+            format: "JSONPath",
+            message: format!("{message} at byte {}", self.position),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        match self.peek() {
+            Some(found) => self.error(&format!("expected {expected}, found `{found}`")),
+            None => self.error(&format!("expected {expected}, found the end")),
+        }
+    }
+
+    /// Parse the segments following `$` or `@`.
+    fn path(&mut self) -> Result<JsonPath, JsonError> {
+        let mut segments = Vec::new();
+        loop {
+            let start = self.position;
+            self.skip_whitespace();
+            let descendants = self.eat("..");
+            let selectors = if descendants {
+                if self.peek() == Some('[') {
+                    self.bracket()?
+                } else {
+                    vec![self.shorthand()?]
+                }
+            } else if self.eat(".") {
+                vec![self.shorthand()?]
+            } else if self.peek() == Some('[') {
+                self.bracket()?
+            } else {
+                // Whitespace is only allowed between segments.
+                self.position = start;
+                break;
+            };
+            segments.push(Segment {
+                descendants,
+                selectors,
+            });
+        }
+
+        Ok(JsonPath { segments })
+    }
+
+    /// Parse the selector after `.` or `..`, a wildcard or a member name.
+    fn shorthand(&mut self) -> Result<Selector, JsonError> {
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+
+        let start = self.position;
+        while let Some(character) = self.peek() {
+            let allowed = character == '_'
+                || character.is_ascii_alphabetic()
+                || !character.is_ascii()
+                || (self.position > start && character.is_ascii_digit());
+            if !allowed {
+                break;
+            }
+            self.position += character.len_utf8();
+        }
+        if self.position == start {
+            return Err(self.unexpected("a member name or `*`"));
+        }
+
+        Ok(Selector::Name(self.text[start..self.position].to_string()))
+    }
+
+    /// Parse a bracketed list of selectors.
+    fn bracket(&mut self) -> Result<Vec<Selector>, JsonError> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            if !self.eat(",") {
+                return Err(self.unexpected("`,` or `]`"));
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.position += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.position += 1;
+                self.skip_whitespace();
+                Ok(Selector::Filter(self.or()?))
+            }
+            _ => self.index_or_slice(),
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, JsonError> {
+        let start = self.optional_integer()?;
+        self.skip_whitespace();
+        if !self.eat(":") {
+            return match start {
+                Some(index) => Ok(Selector::Index(index)),
+                None => Err(self.unexpected("a selector")),
+            };
+        }
+
+        self.skip_whitespace();
+        let end = self.optional_integer()?;
+        self.skip_whitespace();
+        let step = if self.eat(":") {
+            self.skip_whitespace();
+            self.optional_integer()?
+        } else {
+            None
+        };
+
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, JsonError> {
+        let start = self.position;
+        self.eat("-");
+        while let Some('0'..='9') = self.peek() {
+            self.position += 1;
+        }
+        match &self.text[start..self.position] {
+            "" => Ok(None),
+            text => text
+                .parse()
+                .map(Some)
+                .map_err(|_| self.error(&format!("invalid integer `{text}`"))),
+        }
+    }
+
+    /// Parse a string literal quoted with `'` or `"`.
+    fn string(&mut self) -> Result<String, JsonError> {
+        let quote = self.bump();
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some(character) if Some(character) == quote => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some(character @ ('\'' | '"' | '\\' | '/')) => character,
+                        Some('b') => '\u{08}',
+                        Some('f') => '\u{0C}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    string.push(escaped);
+                }
+                Some(character) => string.push(character),
+            }
+        }
+    }
+
+    /// Parse the code point of a `\u` escape, after the `u`.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex()?;
+        let code_point = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            let low = self.hex()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code_point).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn hex(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected four hexadecimal digits"))?;
+        self.position += 4;
+
+        Ok(u32::from_str_radix(digits, 16).expect("digits are hexadecimal"))
+    }
+
+    fn or(&mut self) -> Result<Expression, JsonError> {
+        let mut expression = self.and()?;
+        while self.eat("||") {
+            self.skip_whitespace();
+            expression = Expression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<Expression, JsonError> {
+        let mut expression = self.basic()?;
+        while self.eat("&&") {
+            self.skip_whitespace();
+            expression = Expression::And(Box::new(expression), Box::new(self.basic()?));
+        }
+
+        Ok(expression)
+    }
+
+    /// Parse a negation, parenthesized expression, comparison or existence test, along with
+    /// the whitespace after it.
+    fn basic(&mut self) -> Result<Expression, JsonError> {
+        let rest = &self.text[self.position..];
+        if rest.starts_with('!') && !rest.starts_with("!=") {
+            self.position += 1;
+            self.skip_whitespace();
+            return Ok(Expression::Not(Box::new(self.basic()?)));
+        }
+        if self.eat("(") {
+            self.skip_whitespace();
+            let expression = self.or()?;
+            self.expect(')')?;
+            self.skip_whitespace();
+            return Ok(expression);
+        }
+
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let comparison = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ]
+        .into_iter()
+        .find(|(operator, _)| self.eat(operator));
+
+        let Some((_, comparison)) = comparison else {
+            return match left {
+                Operand::Query(query) => Ok(Expression::Exists(query)),
+                Operand::Literal(_) => Err(self.unexpected("a comparison")),
+            };
+        };
+        self.skip_whitespace();
+        let right = self.operand()?;
+        self.skip_whitespace();
+
+        Ok(Expression::Compare(left, comparison, right))
+    }
+
+    fn operand(&mut self) -> Result<Operand, JsonError> {
+        let literal = match self.peek() {
+            Some(relative @ ('@' | '$')) => {
+                self.position += 1;
+                return Ok(Operand::Query(Query {
+                    relative: relative == '@',
+                    path: self.path()?,
+                }));
+            }
+            Some('\'' | '"') => Value::from(self.string()?),
+            Some('-' | '0'..='9') => self.number()?,
+            _ if self.eat("true") => Value::Boolean(true),
+            _ if self.eat("false") => Value::Boolean(false),
+            _ if self.eat("null") => Value::Null,
+            _ => return Err(self.unexpected("a value or a path")),
+        };
+
+        Ok(Operand::Literal(literal))
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let start = self.position;
+        while let Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9') = self.peek() {
+            self.position += 1;
+        }
+        let text = &self.text[start..self.position];
+
+        if let Ok(integer) = text.parse() {
+            return Ok(Value::Number(Number::I64(integer)));
+        }
+        text.parse()
+            .ok()
+            .filter(|float: &f64| float.is_finite())
+            .map(|float| Value::Number(Number::F64(float)))
+            .ok_or_else(|| self.error(&format!("invalid number `{text}`")))
+    }
+}
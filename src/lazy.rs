@@ -0,0 +1,65 @@
+//! Lazy parsing, which checks a document and records where its values are, but only decodes
+//! the values which are read.
+//!
+//! Parsing lays the document out on a [`Tape`] like [`parse_tape`](JsonParser::parse_tape)
+//! does, checking it against the grammar, but records numbers as offsets into the input and
+//! leaves escape sequences as they are. Numbers are converted when they're read, strings with
+//! escape sequences are decoded the first time they're read, and arrays and objects are only
+//! built into a [`Value`](crate::value::Value) on request, so reading a few fields of a big
+//! document costs little more than finding them.
+
+use crate::error::JsonError;
+use crate::parser::JsonParser;
+use crate::tape::{self, Tape, TapeValue};
+
+/// A document parsed by [`JsonParser::parse_lazy`], whose values are decoded from the input
+/// as they're read.
+pub type LazyDocument<'a> = Tape<'a>;
+
+/// A value of a [`LazyDocument`], decoded from the input as needed.
+pub type LazyValue<'d> = TapeValue<'d>;
+
+impl JsonParser {
+    /// Parse JSON from bytes into a [`LazyDocument`], which only decodes the values that are
+    /// read.
+    ///
+    /// The whole input is checked, so reading values never fails. A leading byte order mark is
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON, or has arrays
+    /// and objects nested more than [`MAX_NESTING`](crate::token::MAX_NESTING) levels deep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::map::Map;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let input = br#"{"id": 7, "name": "caf\u00e9", "items": [1.5, {"deep": [true, null]}]}"#;
+    /// let document = JsonParser::parse_lazy(input).unwrap();
+    /// let root = document.root();
+    ///
+    /// // Only the values read are decoded.
+    /// assert_eq!(root.get("id").and_then(|id| id.as_number()), Some(Number::I64(7)));
+    /// assert_eq!(root.get("name").and_then(|name| name.as_str()), Some("café"));
+    ///
+    /// let items = root.get("items").unwrap();
+    /// assert_eq!(items.len(), Some(2));
+    /// let deep = Value::Array(vec![Value::Boolean(true), Value::Null]);
+    /// assert_eq!(
+    ///     items.at(1).unwrap().to_value(),
+    ///     Value::Object(Map::from_iter([("deep".to_string(), deep)]))
+    /// );
+    ///
+    /// assert_eq!(document.to_value(), JsonParser::parse_from_bytes(input).unwrap());
+    ///
+    /// let deep = "[".repeat(100_000);
+    /// assert!(JsonParser::parse_lazy(deep.as_bytes()).is_err());
+    /// ```
+    pub fn parse_lazy(input: &[u8]) -> Result<LazyDocument<'_>, JsonError> {
+        tape::parse(input, true)
+    }
+}
@@ -0,0 +1,220 @@
+//! Lenses, composable paths to nested values built in code rather than parsed from strings,
+//! such as `key("servers").index(0).key("port")`.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::pointer::push_token;
+use crate::value::Value;
+use std::fmt::{self, Display};
+
+/// A path to a value nested inside another, to get, set or modify it with.
+///
+/// Lenses are built with [`key`] and [`index`] and extended with the methods of the same
+/// names, or joined with [`then`](Lens::then). They can be stored and applied to any number of
+/// values.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::lens::key;
+/// use json_parser::parser::JsonParser;
+/// use json_parser::value::{Number, Value};
+///
+/// let mut value = JsonParser::parse_from_bytes(br#"{"servers": [{"port": 80}]}"#).unwrap();
+/// let servers = key("servers");
+/// let port = servers.clone().index(0).key("port");
+///
+/// assert_eq!(port.get(&value), Some(&Value::Number(Number::I64(80))));
+/// assert_eq!(port.to_string(), "/servers/0/port");
+///
+/// port.modify(&mut value, |port| *port = Value::Number(Number::I64(8080)));
+/// servers.index(1).key("host").set(&mut value, Value::from("b")).unwrap();
+/// assert_eq!(
+///     value.to_string_compact(),
+///     r#"{"servers":[{"port":8080},{"host":"b"}]}"#
+/// );
+///
+/// assert!(key("servers").key("port").set(&mut value, Value::Null).is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Lens {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// A lens focusing on the member `key` of an object.
+#[must_use]
+pub fn key(key: impl Into<String>) -> Lens {
+    Lens::new().key(key)
+}
+
+/// A lens focusing on the element at `index` of an array.
+#[must_use]
+pub fn index(index: usize) -> Lens {
+    Lens::new().index(index)
+}
+
+impl Lens {
+    /// The lens focusing on the whole value.
+    #[must_use]
+    pub fn new() -> Lens {
+        Lens::default()
+    }
+
+    /// Focus on the member `key` of the object this lens focuses on.
+    #[must_use]
+    pub fn key(mut self, key: impl Into<String>) -> Lens {
+        self.steps.push(Step::Key(key.into()));
+        self
+    }
+
+    /// Focus on the element at `index` of the array this lens focuses on.
+    #[must_use]
+    pub fn index(mut self, index: usize) -> Lens {
+        self.steps.push(Step::Index(index));
+        self
+    }
+
+    /// Focus on what `other` focuses on, inside what this lens focuses on.
+    #[must_use]
+    pub fn then(mut self, other: &Lens) -> Lens {
+        self.steps.extend(other.steps.iter().cloned());
+        self
+    }
+
+    /// The value this lens focuses on inside `value`, if it exists.
+    #[must_use]
+    pub fn get<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.steps
+            .iter()
+            .try_fold(value, |value, step| match (value, step) {
+                (Value::Object(members), Step::Key(key)) => members.get(key),
+                (Value::Array(elements), Step::Index(index)) => elements.get(*index),
+                _ => None,
+            })
+    }
+
+    /// The value this lens focuses on inside `value`, if it exists, to modify it.
+    pub fn get_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.steps
+            .iter()
+            .try_fold(value, |value, step| match (value, step) {
+                (Value::Object(members), Step::Key(key)) => members.get_mut(key),
+                (Value::Array(elements), Step::Index(index)) => elements.get_mut(*index),
+                _ => None,
+            })
+    }
+
+    /// Call `f` on the value this lens focuses on inside `value`, returning whether it exists.
+    pub fn modify(&self, value: &mut Value, f: impl FnOnce(&mut Value)) -> bool {
+        match self.get_mut(value) {
+            Some(focus) => {
+                f(focus);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the value this lens focuses on inside `value` to `new`, returning the value it
+    /// replaces, if any.
+    ///
+    /// Missing members are added along the way, and `null` is replaced with the object or
+    /// array the next step needs. An element can be added at the end of an array, but not
+    /// further.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::PathConflict`] with the pointer of the first value which isn't the
+    /// object or array a step needs, or [`JsonError::InvalidPath`] with the pointer of an
+    /// element beyond the end of an array. Nothing is changed in either case.
+    pub fn set(&self, value: &mut Value, new: Value) -> Result<Option<Value>, JsonError> {
+        self.check(value)?;
+        let existed = self.get(value).is_some();
+
+        let mut focus = value;
+        for step in &self.steps {
+            focus = match step {
+                Step::Key(key) => {
+                    if *focus == Value::Null {
+                        *focus = Value::Object(Map::new());
+                    }
+                    let Value::Object(members) = focus else {
+                        unreachable!("the path was checked");
+                    };
+                    if !members.contains_key(key) {
+                        members.insert(key.clone(), Value::Null);
+                    }
+                    members.get_mut(key).expect("the member was just added")
+                }
+                Step::Index(index) => {
+                    if *focus == Value::Null {
+                        *focus = Value::Array(Vec::new());
+                    }
+                    let Value::Array(elements) = focus else {
+                        unreachable!("the path was checked");
+                    };
+                    if *index == elements.len() {
+                        elements.push(Value::Null);
+                    }
+                    &mut elements[*index]
+                }
+            };
+        }
+
+        let old = std::mem::replace(focus, new);
+
+        Ok(existed.then_some(old))
+    }
+
+    /// Check that [`set`](Lens::set) can follow the steps inside `value`.
+    fn check(&self, value: &Value) -> Result<(), JsonError> {
+        let mut pointer = String::new();
+        let mut focus = Some(value);
+        for step in &self.steps {
+            let next = match (focus, step) {
+                (None | Some(Value::Null), Step::Key(_)) => None,
+                (None | Some(Value::Null), Step::Index(0)) => None,
+                (Some(Value::Object(members)), Step::Key(key)) => members.get(key),
+                (Some(Value::Array(elements)), Step::Index(index)) if *index <= elements.len() => {
+                    elements.get(*index)
+                }
+                (None | Some(Value::Null | Value::Array(_)), Step::Index(index)) => {
+                    push_token(&mut pointer, &index.to_string());
+                    return Err(JsonError::InvalidPath(pointer));
+                }
+                (Some(_), _) => return Err(JsonError::PathConflict(pointer)),
+            };
+            step.push(&mut pointer);
+            focus = next;
+        }
+
+        Ok(())
+    }
+}
+
+impl Step {
+    fn push(&self, pointer: &mut String) {
+        match self {
+            Step::Key(key) => push_token(pointer, key),
+            Step::Index(index) => push_token(pointer, &index.to_string()),
+        }
+    }
+}
+
+impl Display for Lens {
+    /// Write the JSON Pointer of the value the lens focuses on, such as `/servers/0/port`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pointer = String::new();
+        for step in &self.steps {
+            step.push(&mut pointer);
+        }
+
+        f.write_str(&pointer)
+    }
+}
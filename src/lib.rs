@@ -1,4 +1,88 @@
+#[cfg(feature = "arena")]
+pub mod arena;
+mod binary;
+pub mod borrowed;
+#[cfg(feature = "bson")]
+pub mod bson;
+mod canonical;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "chrono")]
+mod chrono;
+pub mod convert;
+pub mod cst;
+mod csv;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod datetime;
+#[cfg(feature = "serde")]
+mod de;
+pub mod diff;
+#[cfg(feature = "digest")]
+mod digest;
+pub mod error;
+pub mod event;
+pub mod expr;
+mod flatten;
+#[cfg(feature = "futures")]
+mod futures;
+mod include;
+mod interpolate;
+pub mod jsonpath;
+pub mod lazy;
+pub mod lens;
+pub mod map;
+pub mod merge;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod net;
 pub mod parser;
+pub mod patch;
+mod pointer;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+pub mod prune;
+#[cfg(feature = "pyo3")]
+mod pyo3;
+mod query;
+#[cfg(feature = "quickcheck")]
+pub mod quickcheck;
+#[cfg(feature = "rayon")]
+pub mod rayon;
 pub mod reader;
+pub mod recover;
+mod redact;
+mod reference;
+pub mod schema;
+pub mod ser;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "serde_json")]
+mod serde_json_impls;
+pub mod shared;
+mod sort;
+pub mod span;
+pub mod stats;
+pub mod tape;
+mod template;
+#[cfg(feature = "time")]
+mod time;
 pub mod token;
+#[cfg(feature = "tokio")]
+mod tokio;
+#[cfg(feature = "toml")]
+mod toml;
+mod transform;
+#[cfg(feature = "uuid")]
+mod uuid;
 pub mod value;
+pub mod visitor;
+pub mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+#[cfg(feature = "serde")]
+pub use de::{from_slice, from_str};
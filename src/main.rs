@@ -1,9 +1,1253 @@
+use json_parser::convert::{FromJson, ToJson};
+use json_parser::diff::DiffOptions;
+use json_parser::expr::Expression;
+use json_parser::jsonpath::{Filter, JsonPath};
+use json_parser::map::Map;
+use json_parser::merge::merge3;
 use json_parser::parser::JsonParser;
-use std::fs::File;
+use json_parser::patch::PatchOperation;
+use json_parser::schema;
+use json_parser::ser::{self, Layout, LineEnding, WriterOptions};
+use json_parser::value::Value;
+use std::env;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, ErrorKind, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::{self, ExitCode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
-fn main() {
-    let file = File::open("test.json").unwrap();
-    let parser = JsonParser::parse_from_file(file).unwrap();
+const USAGE: &str = "\
+Usage: json-parser <command> [arguments]
 
-    dbg!(parser);
+Files are read from standard input if they are `-` or left out, and results are written to
+standard output. Commands taking several files also take glob patterns such as
+`configs/**/*.json`, and summarize how many files failed.
+
+Options:
+  --color WHEN       Color the JSON printed to standard output: `auto` (the default) when it's
+                     a terminal and `NO_COLOR` isn't set, `always` or `never`
+  --output FORMAT    Print errors to standard error as `text` (the default), or as `json`, one
+                     object per line with the `path`, `line`, `column`, `code` and `message`
+                     of the error, and the counts of `files` and `failed` ones at the end
+  --watch            Run `validate`, `fmt` or `query` again whenever one of its files changes,
+                     until interrupted
+
+Commands:
+  validate [--watch] [<files>...]
+                     Check that files are valid JSON, printing the location of every error
+  fmt [--indent N] [--tabs] [--crlf] [--width N] [--ascii] [--html] [--sort-keys]
+      [--write] [--watch] [<files>...]
+                     Pretty-print files, or in place with `--write`, indented by N spaces
+                     (2 by default) or tabs with `--tabs`, with CRLF line endings with
+                     `--crlf`, keeping arrays and objects on one line where it stays
+                     within N characters with `--width`, escaping all non-ASCII characters
+                     with `--ascii` and characters unsafe in HTML or JavaScript with
+                     `--html`, and with the members of objects sorted by key with
+                     `--sort-keys`
+  minify [--write] [<files>...]
+                     Remove the whitespace of files, or in place with `--write`
+  get [--raw] <pointer> [<file>]
+                     Print the value a JSON Pointer such as `/users/0/name` refers to, with
+                     strings unquoted if `--raw` is given
+  query [--array] [--watch] <expression> [<file>]
+                     Print the values a JSONPath expression such as `$.items[?@.price > 10]`
+                     selects, one per line, or as a single array with `--array`
+  filter [--raw] <expression> [<file>]
+                     Print the outputs of a jq expression such as
+                     `.items[] | select(.active) | .name`, one per line, with strings
+                     unquoted if `--raw` is given
+  lines [--query <path>] [--filter <expression>] [<file>]
+                     Read one JSON value per line, as in NDJSON, in constant memory, printing
+                     those for which a filter such as `$.level == \"error\"` is true, or only
+                     what a JSONPath expression selects in them
+  stats [<files>...] Print the size, depth, number of values of each type, and largest
+                     arrays, objects and strings of files
+  schema infer [<files>...]
+                     Print a JSON Schema describing all the files, inferred from the types,
+                     properties and items found in them
+  diff [--patch] [--ignore-order] [--ignore POINTER]... <file> [<file>]
+                     Print the changes turning the first file into the second, one per line,
+                     or as a JSON Patch with `--patch`, ignoring the order of elements with
+                     `--ignore-order` and the values at every `--ignore` pointer, where `*`
+                     matches any member or element
+  patch [--merge] [--write] <file> [<patch>]
+                     Apply a JSON Patch, or a JSON Merge Patch with `--merge`, printing the
+                     result, or writing it in place with `--write`
+  merge [--write] <base> <ours> <theirs>
+                     Merge the changes both files made to their common ancestor, printing
+                     the result, or writing it in place of ours with `--write`, and every
+                     conflict, for which our side is kept
+  convert [--from FORMAT] [--to FORMAT] [<file>]
+                     Convert a file between JSON and another format. Formats are json (the
+                     default), yaml, toml, xml, csv, cbor and msgpack, some of which need the
+                     crate feature of the same name
+
+Exit status:
+  0                  Success, even if standard output is closed before everything is printed,
+                     as when piped into `head`
+  1                  Invalid input, such as a file which isn't valid JSON, a pointer which
+                     refers to nothing or a patch which doesn't apply, or for `diff`, files
+                     which differ
+  2                  Invalid arguments, or for `diff`, invalid input
+  3                  A file couldn't be read or written
+";
+
+/// The path standing for standard input.
+const STDIN: &str = "-";
+
+/// Whether JSON printed to standard output is colored, set once from `--color`.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Whether errors are printed as JSON, set once from `--output`.
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Why a command failed, which decides its exit status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Failure {
+    /// Input which isn't valid JSON, or which the command can't do what it's asked with.
+    Invalid = 1,
+    /// A file which couldn't be read or written.
+    Io = 3,
+}
+
+impl From<Failure> for ExitCode {
+    fn from(failure: Failure) -> ExitCode {
+        ExitCode::from(failure as u8)
+    }
+}
+
+/// Exit status for invalid arguments.
+const USAGE_ERROR: u8 = 2;
+
+/// Print to standard output like `print!`, through [`emit`].
+macro_rules! out {
+    ($($arg:tt)*) => {
+        emit(|stdout| write!(stdout, $($arg)*))
+    };
+}
+
+/// Print a line to standard output like `println!`, through [`emit`].
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        emit(|stdout| writeln!(stdout, $($arg)*))
+    };
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if let Err(message) = take_global_options(&mut args) {
+        eprintln!("error: {message}\n\n{USAGE}");
+        return ExitCode::from(USAGE_ERROR);
+    }
+
+    let mut args = args.into_iter();
+    let result = match args.next().as_deref() {
+        Some("validate") => validate(args.collect()),
+        Some("fmt") => fmt(args.collect()),
+        Some("minify") => minify(args.collect()),
+        Some("get") => get(args.collect()),
+        Some("query") => query(args.collect()),
+        Some("filter") => filter(args.collect()),
+        Some("lines") => lines(args.collect()),
+        Some("stats") => stats(args.collect()),
+        Some("schema") => match args.next().as_deref() {
+            Some("infer") => infer_schema(args.collect()),
+            Some(command) => Err(format!("unknown command `schema {command}`")),
+            None => Err("`schema` needs a command such as `infer`".to_string()),
+        },
+        Some("diff") => diff(args.collect()),
+        Some("patch") => patch(args.collect()),
+        Some("merge") => merge(args.collect()),
+        Some("convert") => convert(args.collect()),
+        Some("-h" | "--help") => {
+            out!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(command) => Err(format!("unknown command `{command}`")),
+        None => Err("missing command".to_string()),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            if JSON_OUTPUT.load(Ordering::Relaxed) {
+                report(None, None, "usage", &message);
+            } else {
+                eprintln!("error: {message}\n\n{USAGE}");
+            }
+            ExitCode::from(USAGE_ERROR)
+        }
+    }
+}
+
+/// Check the syntax of files, exiting with 1 if any of them isn't valid JSON.
+fn validate(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut watching = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--watch" => watching = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => paths.push(arg),
+        }
+    }
+    let paths = inputs(paths, false)?;
+
+    let run = || {
+        let mut summary = Summary::new("invalid");
+        for path in &paths {
+            summary.record(parse_file(path).map(drop));
+        }
+        summary.finish()
+    };
+    if watching {
+        return watch(&paths, run);
+    }
+
+    Ok(run())
+}
+
+/// Pretty-print files, exiting with 1 if any of them couldn't be formatted.
+fn fmt(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut options = WriterOptions {
+        indent: Some(2),
+        trailing_newline: true,
+        ..WriterOptions::default()
+    };
+    let mut write = false;
+    let mut watching = false;
+    let mut paths = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => {
+                let indent = args.next().ok_or("`--indent` needs a number of spaces")?;
+                options.indent = Some(
+                    indent
+                        .parse()
+                        .map_err(|_| format!("invalid indentation `{indent}`"))?,
+                );
+            }
+            "--width" => {
+                let width = args
+                    .next()
+                    .ok_or("`--width` needs a number of characters")?;
+                options.inline_width = Some(
+                    width
+                        .parse()
+                        .map_err(|_| format!("invalid width `{width}`"))?,
+                );
+            }
+            "--tabs" => options.indent_with_tabs = true,
+            "--crlf" => options.line_ending = LineEnding::CrLf,
+            "--ascii" => options.ascii_only = true,
+            "--html" => options.html_safe = true,
+            "--sort-keys" => options.sort_keys = true,
+            "--write" | "-w" => write = true,
+            "--watch" => watching = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => paths.push(arg),
+        }
+    }
+    let paths = inputs(paths, write)?;
+
+    let run = || {
+        let mut summary = Summary::new("failed");
+        for path in &paths {
+            let value = match parse_file(path) {
+                Ok(value) => value,
+                Err(failure) => {
+                    summary.record(Err(failure));
+                    continue;
+                }
+            };
+            let formatted = value.to_string_with_options(&options);
+
+            if !write {
+                out!("{}", paint(formatted));
+            } else if let Err(error) = fs::write(path, formatted) {
+                report(Some(path), None, "io", &error);
+                summary.record(Err(Failure::Io));
+                continue;
+            }
+            summary.record(Ok(()));
+        }
+        summary.finish()
+    };
+    if watching {
+        return watch(&paths, run);
+    }
+
+    Ok(run())
+}
+
+/// Remove the whitespace of files, exiting with 1 if any of them isn't valid JSON, in which
+/// case it's left as it is.
+fn minify(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut write = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--write" | "-w" => write = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => paths.push(arg),
+        }
+    }
+
+    let paths = inputs(paths, write)?;
+
+    let mut summary = Summary::new("failed");
+    for path in &paths {
+        let input = match read_file(path) {
+            Ok(input) => input,
+            Err(failure) => {
+                summary.record(Err(failure));
+                continue;
+            }
+        };
+        // The whole output is built before anything is written, so that invalid input is
+        // neither half printed nor written over its file.
+        let mut output = Vec::with_capacity(input.len());
+        if let Err(error) = ser::reformat(&input[..], &mut output, Layout::Compact) {
+            // Parse the input again to report every error at its line and column, like the
+            // other commands do.
+            let failure = parse(path, &input).map_or_else(
+                |failure| failure,
+                |_| {
+                    report(Some(path), None, "syntax", &error);
+                    Failure::Invalid
+                },
+            );
+            summary.record(Err(failure));
+            continue;
+        }
+
+        if !write {
+            emit(|stdout| {
+                stdout.write_all(&output)?;
+                writeln!(stdout)
+            });
+        } else if let Err(error) = fs::write(path, output) {
+            report(Some(path), None, "io", &error);
+            summary.record(Err(Failure::Io));
+            continue;
+        }
+        summary.record(Ok(()));
+    }
+
+    Ok(summary.finish())
+}
+
+/// Print the value a JSON Pointer refers to, exiting with 1 if there is none.
+fn get(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut raw = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--raw" | "-r" => raw = true,
+            // Pointers are empty or start with `/`, so they are never mistaken for options.
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => operands.push(arg),
+        }
+    }
+    let (pointer, path) = match &operands[..] {
+        [pointer] => (pointer, STDIN),
+        [pointer, path] => (pointer, path.as_str()),
+        _ => return Err("`get` takes a JSON Pointer and a file".to_string()),
+    };
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(format!(
+            "invalid JSON Pointer `{pointer}`, which must start with `/`"
+        ));
+    }
+
+    let value = match parse_file(path) {
+        Ok(value) => value,
+        Err(failure) => return Ok(failure.into()),
+    };
+    match value.pointer(pointer) {
+        Some(Value::String(string)) if raw => outln!("{string}"),
+        Some(found) => outln!("{}", paint(found.to_string_pretty())),
+        None => {
+            let message = format!("no value at `{pointer}`");
+            report(Some(path), None, "missing", &message);
+            return Ok(Failure::Invalid.into());
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the values a JSONPath expression selects.
+fn query(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut array = false;
+    let mut watching = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--array" | "-a" => array = true,
+            "--watch" => watching = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => operands.push(arg),
+        }
+    }
+    let (expression, path) = match &operands[..] {
+        [expression] => (expression, STDIN),
+        [expression, path] => (expression, path.as_str()),
+        _ => return Err("`query` takes a JSONPath expression and a file".to_string()),
+    };
+    let expression = JsonPath::parse(expression).map_err(|error| error.to_string())?;
+
+    let run = || {
+        let value = match parse_file(path) {
+            Ok(value) => value,
+            Err(failure) => return failure.into(),
+        };
+        let selected = expression.select(&value);
+        if array {
+            let selected = Value::Array(selected.into_iter().cloned().collect());
+            outln!("{}", paint(selected.to_string_pretty()));
+        } else {
+            for value in selected {
+                outln!("{}", paint(value.to_string_compact()));
+            }
+        }
+        ExitCode::SUCCESS
+    };
+    if watching {
+        return watch(&[path.to_string()], run);
+    }
+
+    Ok(run())
+}
+
+/// Print the outputs of a jq expression, exiting with 1 if it fails.
+fn filter(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut raw = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--raw" | "-r" => raw = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => operands.push(arg),
+        }
+    }
+    let (expression, path) = match &operands[..] {
+        [expression] => (expression, STDIN),
+        [expression, path] => (expression, path.as_str()),
+        _ => return Err("`filter` takes a jq expression and a file".to_string()),
+    };
+    let expression = Expression::parse(expression).map_err(|error| error.to_string())?;
+
+    let value = match parse_file(path) {
+        Ok(value) => value,
+        Err(failure) => return Ok(failure.into()),
+    };
+    let outputs = match expression.evaluate(&value) {
+        Ok(outputs) => outputs,
+        Err(error) => {
+            report(Some(path), None, "evaluation", &error);
+            return Ok(Failure::Invalid.into());
+        }
+    };
+    for output in outputs {
+        match output {
+            Value::String(string) if raw => outln!("{string}"),
+            output => outln!("{}", paint(output.to_string_compact())),
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Filter and transform a file of JSON values, one per line, exiting with 1 if any line isn't
+/// valid JSON.
+fn lines(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut query = None;
+    let mut filter = None;
+    let mut paths = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--query" | "-q" => {
+                let expression = args.next().ok_or("`--query` needs a JSONPath expression")?;
+                query = Some(JsonPath::parse(&expression).map_err(|error| error.to_string())?);
+            }
+            "--filter" | "-f" => {
+                let expression = args.next().ok_or("`--filter` needs an expression")?;
+                filter = Some(Filter::parse(&expression).map_err(|error| error.to_string())?);
+            }
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => paths.push(arg),
+        }
+    }
+    let path = match &paths[..] {
+        [] => STDIN,
+        [path] => path,
+        _ => return Err("`lines` takes a single file".to_string()),
+    };
+
+    let mut input = match open(path) {
+        Ok(input) => BufReader::new(input),
+        Err(error) => {
+            report(Some(path), None, "io", &error);
+            return Ok(Failure::Io.into());
+        }
+    };
+    let mut code = ExitCode::SUCCESS;
+    let mut line = String::new();
+    for number in 1.. {
+        line.clear();
+        match input.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(error) => {
+                report(Some(path), Some((number, 1)), "io", &error);
+                return Ok(Failure::Io.into());
+            }
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (record, errors) = JsonParser::parse_recovering(line.as_bytes());
+        if let Some(error) = errors.first() {
+            let location = (number, error.span.start.column);
+            report(Some(path), Some(location), "syntax", &error.message);
+            code = Failure::Invalid.into();
+            continue;
+        }
+        if filter
+            .as_ref()
+            .is_some_and(|filter| !filter.matches(&record))
+        {
+            continue;
+        }
+
+        let selected = match &query {
+            Some(query) => query.select(&record),
+            None => vec![&record],
+        };
+        for value in selected {
+            outln!("{}", paint(value.to_string_compact()));
+        }
+    }
+
+    Ok(code)
+}
+
+/// Describe the shape and size of files, exiting with 1 if any of them isn't valid JSON.
+fn stats(args: Vec<String>) -> Result<ExitCode, String> {
+    if let Some(flag) = args.iter().find(|arg| is_option(arg)) {
+        return Err(format!("unknown option `{flag}`"));
+    }
+    let paths = inputs(args, false)?;
+
+    let mut summary = Summary::new("invalid");
+    for (position, path) in paths.iter().enumerate() {
+        let parsed = read_file(path).and_then(|input| Ok((parse(path, &input)?, input.len())));
+        let (value, size) = match parsed {
+            Ok(parsed) => parsed,
+            Err(failure) => {
+                summary.record(Err(failure));
+                continue;
+            }
+        };
+        summary.record(Ok(()));
+
+        let stats = value.stats();
+        if paths.len() > 1 {
+            if position > 0 {
+                outln!();
+            }
+            outln!("{}:", name(path));
+        }
+        outln!("size:           {size} bytes");
+        outln!("depth:          {}", stats.max_depth);
+        outln!("values:         {}", stats.values());
+        outln!("  objects:      {}", stats.objects);
+        outln!("  arrays:       {}", stats.arrays);
+        outln!("  strings:      {}", stats.strings);
+        outln!("  integers:     {}", stats.integers);
+        outln!("  floats:       {}", stats.floats);
+        outln!("  booleans:     {}", stats.booleans);
+        outln!("  nulls:        {}", stats.nulls);
+        outln!("members:        {}", stats.members);
+        outln!("largest array:  {} elements", stats.largest_array);
+        outln!("largest object: {} members", stats.largest_object);
+        outln!("longest string: {} bytes", stats.longest_string);
+        outln!("string bytes:   {}", stats.string_bytes);
+        outln!("heap estimate:  {} bytes", stats.heap_bytes);
+    }
+
+    Ok(summary.finish())
+}
+
+/// Print a schema describing every file, exiting with 1 if any of them isn't valid JSON.
+fn infer_schema(args: Vec<String>) -> Result<ExitCode, String> {
+    if let Some(flag) = args.iter().find(|arg| is_option(arg)) {
+        return Err(format!("unknown option `{flag}`"));
+    }
+    let paths = inputs(args, false)?;
+
+    let mut samples = Vec::with_capacity(paths.len());
+    let mut summary = Summary::new("invalid");
+    for path in &paths {
+        let sample = parse_file(path);
+        summary.record(sample.as_ref().map(drop).map_err(|failure| *failure));
+        samples.extend(sample);
+    }
+    // A schema describing only some of the files would be misleading.
+    if samples.len() < paths.len() {
+        return Ok(summary.finish());
+    }
+    outln!(
+        "{}",
+        paint(schema::infer_schema(&samples).to_string_pretty())
+    );
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the changes between two files, exiting with 1 if there are any, and 2 if either
+/// file isn't valid JSON, like `diff` does.
+fn diff(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut patch = false;
+    let mut options = DiffOptions::default();
+    let mut operands = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--patch" | "-p" => patch = true,
+            "--ignore-order" => options.ignore_array_order = true,
+            "--ignore" => options
+                .ignore
+                .push(args.next().ok_or("`--ignore` needs a JSON Pointer")?),
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => operands.push(arg),
+        }
+    }
+    let (source, target) = match &operands[..] {
+        [source] => (source.as_str(), STDIN),
+        [source, target] => (source.as_str(), target.as_str()),
+        _ => return Err("`diff` takes two files".to_string()),
+    };
+    if source == STDIN && target == STDIN {
+        return Err("only one file can be read from standard input".to_string());
+    }
+    if patch && options != DiffOptions::default() {
+        return Err("`--patch` can't ignore any difference".to_string());
+    }
+
+    let (source, target) = match (parse_file(source), parse_file(target)) {
+        (Ok(source), Ok(target)) => (source, target),
+        (Err(Failure::Io), _) | (_, Err(Failure::Io)) => return Ok(Failure::Io.into()),
+        _ => return Ok(ExitCode::from(USAGE_ERROR)),
+    };
+    let identical = if patch {
+        let operations = source.diff(&target);
+        outln!("{}", paint(operations.to_json().to_string_pretty()));
+        operations.is_empty()
+    } else {
+        let changes = source.diff_report(&target, &options);
+        for change in &changes {
+            outln!("{change}");
+        }
+        changes.is_empty()
+    };
+
+    Ok(if identical {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Apply a patch to a file, exiting with 1 if it doesn't apply.
+fn patch(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut merge = false;
+    let mut write = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--merge" | "-m" => merge = true,
+            "--write" | "-w" => write = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => operands.push(arg),
+        }
+    }
+    let (path, patch_path) = match &operands[..] {
+        [path] => (path.as_str(), STDIN),
+        [path, patch_path] => (path.as_str(), patch_path.as_str()),
+        _ => return Err("`patch` takes a file and a patch".to_string()),
+    };
+    if path == STDIN && patch_path == STDIN {
+        return Err("only one file can be read from standard input".to_string());
+    }
+    if write && path == STDIN {
+        return Err("`--write` can't write to standard input".to_string());
+    }
+
+    let (mut value, patch) = match (parse_file(path), parse_file(patch_path)) {
+        (Ok(value), Ok(patch)) => (value, patch),
+        (Err(failure), Ok(_)) | (Ok(_), Err(failure)) => return Ok(failure.into()),
+        (Err(first), Err(second)) => return Ok(first.max(second).into()),
+    };
+    if merge {
+        value.merge_patch(&patch);
+    } else {
+        let result = Vec::<PatchOperation>::from_json(&patch)
+            .and_then(|operations| value.apply_patch(&operations));
+        if let Err(error) = result {
+            report(Some(patch_path), None, "patch", &error);
+            return Ok(Failure::Invalid.into());
+        }
+    }
+
+    let patched = value.to_string_pretty();
+    if !write {
+        outln!("{}", paint(patched));
+    } else if let Err(error) = fs::write(path, patched + "\n") {
+        report(Some(path), None, "io", &error);
+        return Ok(Failure::Io.into());
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Merge the changes two files made to a common ancestor, exiting with 1 if they conflict.
+fn merge(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut write = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--write" | "-w" => write = true,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => operands.push(arg),
+        }
+    }
+    let [base, ours, theirs] = &operands[..] else {
+        return Err("`merge` takes a base file and two changed files".to_string());
+    };
+    if operands.iter().filter(|path| *path == STDIN).count() > 1 {
+        return Err("only one file can be read from standard input".to_string());
+    }
+    if write && ours == STDIN {
+        return Err("`--write` can't write to standard input".to_string());
+    }
+
+    let mut values = Vec::new();
+    let mut failure = None;
+    for path in [base, ours, theirs] {
+        match parse_file(path) {
+            Ok(value) => values.push(value),
+            Err(error) => failure = failure.max(Some(error)),
+        }
+    }
+    if let Some(failure) = failure {
+        return Ok(failure.into());
+    }
+    let merge = merge3(&values[0], &values[1], &values[2]);
+
+    let merged = merge.value.to_string_pretty();
+    if !write {
+        outln!("{}", paint(merged));
+    } else if let Err(error) = fs::write(ours, merged + "\n") {
+        report(Some(ours), None, "io", &error);
+        return Ok(Failure::Io.into());
+    }
+    for conflict in &merge.conflicts {
+        report(Some(ours), None, "conflict", conflict);
+    }
+
+    Ok(if merge.is_clean() {
+        ExitCode::SUCCESS
+    } else {
+        Failure::Invalid.into()
+    })
+}
+
+/// A format `convert` reads or writes, only available if the crate feature it needs is
+/// enabled.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "xml")]
+    Xml,
+    Csv,
+    #[cfg(feature = "cbor")]
+    Cbor,
+    #[cfg(feature = "msgpack")]
+    Msgpack,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Format, String> {
+        Ok(match name {
+            "json" => Format::Json,
+            #[cfg(feature = "yaml")]
+            "yaml" => Format::Yaml,
+            #[cfg(feature = "toml")]
+            "toml" => Format::Toml,
+            #[cfg(feature = "xml")]
+            "xml" => Format::Xml,
+            "csv" => Format::Csv,
+            #[cfg(feature = "cbor")]
+            "cbor" => Format::Cbor,
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Format::Msgpack,
+            _ if ["yaml", "toml", "xml", "cbor", "msgpack"].contains(&name) => {
+                return Err(format!(
+                    "{name} support isn't built in, rebuild with `--features {name}`"
+                ))
+            }
+            _ => return Err(format!("unknown format `{name}`")),
+        })
+    }
+
+    /// Decode a value, from text for every format but the binary ones.
+    fn decode(self, input: &[u8]) -> Result<Value, String> {
+        let text = || std::str::from_utf8(input).map_err(|error| error.to_string());
+        let value = match self {
+            Format::Json => JsonParser::parse_from_bytes(input),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Value::from_yaml(text()?),
+            #[cfg(feature = "toml")]
+            Format::Toml => Value::from_toml(text()?),
+            #[cfg(feature = "xml")]
+            Format::Xml => Value::from_xml(text()?),
+            Format::Csv => Value::from_csv(text()?),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => Value::from_cbor(input),
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => Value::from_msgpack(input),
+        };
+
+        value.map_err(|error| error.to_string())
+    }
+
+    /// Encode a value, ending text with a line break.
+    fn encode(self, value: &Value) -> Result<Vec<u8>, String> {
+        let text = match self {
+            Format::Json => paint(value.to_string_pretty()),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => value.to_yaml(),
+            #[cfg(feature = "toml")]
+            Format::Toml => value.to_toml().map_err(|error| error.to_string())?,
+            #[cfg(feature = "xml")]
+            Format::Xml => value.to_xml().map_err(|error| error.to_string())?,
+            Format::Csv => value.to_csv().map_err(|error| error.to_string())?,
+            #[cfg(feature = "cbor")]
+            Format::Cbor => return Ok(value.to_cbor()),
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => return Ok(value.to_msgpack()),
+        };
+
+        let mut bytes = text.into_bytes();
+        if bytes.last() != Some(&b'\n') {
+            bytes.push(b'\n');
+        }
+        Ok(bytes)
+    }
+}
+
+/// Convert a file from one format to another, exiting with 1 if it couldn't be read or has no
+/// equivalent in the target format.
+fn convert(args: Vec<String>) -> Result<ExitCode, String> {
+    let mut from = Format::Json;
+    let mut to = Format::Json;
+    let mut paths = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" | "-f" => {
+                from = Format::parse(&args.next().ok_or("`--from` needs a format")?)?;
+            }
+            "--to" | "-t" => to = Format::parse(&args.next().ok_or("`--to` needs a format")?)?,
+            flag if is_option(flag) => return Err(format!("unknown option `{flag}`")),
+            _ => paths.push(arg),
+        }
+    }
+    let path = match &paths[..] {
+        [] => STDIN,
+        [path] => path,
+        _ => return Err("`convert` takes a single file".to_string()),
+    };
+
+    // JSON is checked strictly, with every error reported.
+    let value = if from == Format::Json {
+        parse_file(path)
+    } else {
+        read_file(path).and_then(|input| {
+            from.decode(&input).map_err(|error| {
+                report(Some(path), None, "conversion", &error);
+                Failure::Invalid
+            })
+        })
+    };
+    let value = match value {
+        Ok(value) => value,
+        Err(failure) => return Ok(failure.into()),
+    };
+
+    let output = match to.encode(&value) {
+        Ok(output) => output,
+        Err(error) => {
+            report(Some(path), None, "conversion", &error);
+            return Ok(Failure::Invalid.into());
+        }
+    };
+    emit(|stdout| stdout.write_all(&output));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Read and parse a file, reporting the location of every syntax error if it isn't valid
+/// JSON.
+fn parse_file(path: &str) -> Result<Value, Failure> {
+    parse(path, &read_file(path)?)
+}
+
+/// Read a whole file, reporting the error if it can't be read.
+fn read_file(path: &str) -> Result<Vec<u8>, Failure> {
+    read(path).map_err(|error| {
+        report(Some(path), None, "io", &error);
+        Failure::Io
+    })
+}
+
+/// Parse the contents of a file, reporting the location of every syntax error.
+fn parse(path: &str, input: &[u8]) -> Result<Value, Failure> {
+    // The strict checker decides whether the input is valid, while recovery finds the line
+    // and column of every error, so the strict error is only reported if recovery missed it.
+    let strict = JsonParser::validate(input);
+    let (value, errors) = JsonParser::parse_recovering(input);
+    for error in &errors {
+        let start = error.span.start;
+        report(
+            Some(path),
+            Some((start.line, start.column)),
+            "syntax",
+            &error.message,
+        );
+    }
+
+    match strict {
+        Ok(()) if errors.is_empty() => Ok(value),
+        Err(error) if errors.is_empty() => {
+            report(Some(path), None, "syntax", &error);
+            Err(Failure::Invalid)
+        }
+        _ => Err(Failure::Invalid),
+    }
+}
+
+/// Write to standard output, exiting quietly once it's closed, as when piped into `head`, and
+/// with the status of [`Failure::Io`] if it can't be written for another reason.
+fn emit(write: impl FnOnce(&mut io::StdoutLock<'static>) -> io::Result<()>) {
+    match write(&mut io::stdout().lock()) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => process::exit(0),
+        Err(error) => {
+            report(None, None, "io", &error);
+            process::exit(Failure::Io as i32);
+        }
+    }
+}
+
+/// Print an error to standard error, about a file or the command as a whole, and at a line
+/// and column of the file if known. `code` is a stable identifier of the kind of error, such
+/// as `syntax` or `io`, printed along with JSON output.
+fn report(path: Option<&str>, location: Option<(usize, usize)>, code: &str, message: &dyn Display) {
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        let optional = |value: Option<Value>| value.unwrap_or(Value::Null);
+        let mut error = Map::new();
+        error.insert(
+            "path".to_string(),
+            optional(path.map(|path| Value::from(name(path)))),
+        );
+        error.insert(
+            "line".to_string(),
+            optional(location.map(|(line, _)| line.to_json())),
+        );
+        error.insert(
+            "column".to_string(),
+            optional(location.map(|(_, column)| column.to_json())),
+        );
+        error.insert("code".to_string(), Value::from(code));
+        error.insert("message".to_string(), Value::from(message.to_string()));
+        eprintln!("{}", Value::Object(error).to_string_compact());
+        return;
+    }
+
+    match (path, location) {
+        (Some(path), Some((line, column))) => {
+            eprintln!("{}:{line}:{column}: {message}", name(path))
+        }
+        (Some(path), None) => eprintln!("{}: {message}", name(path)),
+        (None, _) => eprintln!("error: {message}"),
+    }
+}
+
+/// Whether an argument is an option rather than a path, `-` being standard input.
+fn is_option(arg: &str) -> bool {
+    arg.starts_with('-') && arg != STDIN
+}
+
+/// The name of a path in messages.
+fn name(path: &str) -> &str {
+    if path == STDIN {
+        "<stdin>"
+    } else {
+        path
+    }
+}
+
+/// The files a command reads, with glob patterns which aren't the path of a file expanded, or
+/// standard input if there are none. Standard input can't be read twice or written in place.
+fn inputs(arguments: Vec<String>, write: bool) -> Result<Vec<String>, String> {
+    let mut paths = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        // A file whose name looks like a pattern, such as `data[1].json`, is taken as is.
+        if !argument.contains(['*', '?', '[']) || Path::new(&argument).exists() {
+            paths.push(argument);
+            continue;
+        }
+
+        let matches = glob::glob(&argument)
+            .map_err(|error| format!("invalid pattern `{argument}`: {error}"))?;
+        let count = paths.len();
+        for path in matches {
+            let path = path.map_err(|error| error.to_string())?;
+            // Patterns like `**` also match directories.
+            if path.is_file() {
+                paths.push(path.to_string_lossy().into_owned());
+            }
+        }
+        if paths.len() == count {
+            return Err(format!("no files match `{argument}`"));
+        }
+    }
+    if paths.is_empty() {
+        paths.push(STDIN.to_string());
+    }
+    let from_stdin = paths.iter().filter(|path| *path == STDIN).count();
+    if from_stdin > 1 {
+        return Err("only one file can be read from standard input".to_string());
+    }
+    if write && from_stdin > 0 {
+        return Err("`--write` can't write to standard input".to_string());
+    }
+
+    Ok(paths)
+}
+
+/// Open a file, or standard input, for reading.
+fn open(path: &str) -> io::Result<Box<dyn Read>> {
+    Ok(if path == STDIN {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(path)?)
+    })
+}
+
+/// Read a whole file, or standard input.
+fn read(path: &str) -> io::Result<Vec<u8>> {
+    let mut input = Vec::new();
+    open(path)?.read_to_end(&mut input)?;
+
+    Ok(input)
+}
+
+/// Counts of the files a command processed, reported when there are several of them.
+struct Summary {
+    files: usize,
+    failed: usize,
+    /// What failing means for the command, such as `invalid`.
+    failure: &'static str,
+    /// The most serious failure so far.
+    worst: Option<Failure>,
+}
+
+impl Summary {
+    fn new(failure: &'static str) -> Summary {
+        Summary {
+            files: 0,
+            failed: 0,
+            failure,
+            worst: None,
+        }
+    }
+
+    fn record(&mut self, result: Result<(), Failure>) {
+        self.files += 1;
+        if let Err(failure) = result {
+            self.failed += 1;
+            self.worst = self.worst.max(Some(failure));
+        }
+    }
+
+    /// Print the counts if there were several files, and exit with the status of the most
+    /// serious failure.
+    fn finish(self) -> ExitCode {
+        if JSON_OUTPUT.load(Ordering::Relaxed) {
+            let mut counts = Map::new();
+            counts.insert("files".to_string(), self.files.to_json());
+            counts.insert("failed".to_string(), self.failed.to_json());
+            eprintln!("{}", Value::Object(counts).to_string_compact());
+        } else if self.files > 1 {
+            eprintln!("{} files, {} {}", self.files, self.failed, self.failure);
+        }
+
+        self.worst.map_or(ExitCode::SUCCESS, ExitCode::from)
+    }
+}
+
+/// Run a command, and again every time one of the files it reads is modified, until the
+/// program is interrupted.
+fn watch(paths: &[String], mut run: impl FnMut() -> ExitCode) -> Result<ExitCode, String> {
+    if paths.iter().any(|path| path == STDIN) {
+        return Err("`--watch` can't watch standard input".to_string());
+    }
+    let modified = || {
+        paths
+            .iter()
+            .map(|path| {
+                fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect::<Vec<Option<SystemTime>>>()
+    };
+
+    loop {
+        run();
+        // Taken after running, so that files written by the command don't trigger it again.
+        let seen = modified();
+        if !JSON_OUTPUT.load(Ordering::Relaxed) {
+            eprintln!("watching for changes, press Ctrl-C to stop");
+        }
+        while modified() == seen {
+            thread::sleep(Duration::from_millis(200));
+        }
+        if !JSON_OUTPUT.load(Ordering::Relaxed) {
+            eprintln!();
+        }
+    }
+}
+
+/// Remove the options which apply to every command from the arguments, wherever they are,
+/// and apply them.
+fn take_global_options(args: &mut Vec<String>) -> Result<(), String> {
+    let color = match take_option(args, "--color")?.as_deref().unwrap_or("auto") {
+        "always" => true,
+        "never" => false,
+        "auto" => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        when => return Err(format!("invalid color choice `{when}`")),
+    };
+    COLOR.store(color, Ordering::Relaxed);
+
+    let json = match take_option(args, "--output")?.as_deref().unwrap_or("text") {
+        "text" => false,
+        "json" => true,
+        format => return Err(format!("invalid output format `{format}`")),
+    };
+    JSON_OUTPUT.store(json, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// Remove every occurrence of an option taking a value, given as `--option value` or
+/// `--option=value`, returning the last value.
+fn take_option(args: &mut Vec<String>, option: &str) -> Result<Option<String>, String> {
+    let prefix = format!("{option}=");
+    let mut value = None;
+    while let Some(position) = args
+        .iter()
+        .position(|arg| arg == option || arg.starts_with(&prefix))
+    {
+        let arg = args.remove(position);
+        value = Some(match arg.strip_prefix(&prefix) {
+            Some(value) => value.to_string(),
+            None if position < args.len() => args.remove(position),
+            None => return Err(format!("`{option}` needs a value")),
+        });
+    }
+
+    Ok(value)
+}
+
+/// Color JSON text for a terminal if `--color` allows it, with keys, strings, numbers and
+/// literals in distinct colors.
+fn paint(json: String) -> String {
+    const KEY: &str = "\x1b[1;34m";
+    const STRING: &str = "\x1b[32m";
+    const NUMBER: &str = "\x1b[36m";
+    const BOOLEAN: &str = "\x1b[33m";
+    const NULL: &str = "\x1b[90m";
+    const RESET: &str = "\x1b[0m";
+
+    if !COLOR.load(Ordering::Relaxed) {
+        return json;
+    }
+
+    let mut output = String::with_capacity(json.len() * 2);
+    let mut characters = json.char_indices().peekable();
+    while let Some((start, character)) = characters.next() {
+        let color = match character {
+            '"' => {
+                let mut escaped = false;
+                for (_, character) in characters.by_ref() {
+                    match character {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+                // Keys are the strings followed by a `:`.
+                let end = characters.peek().map_or(json.len(), |&(end, _)| end);
+                if json[end..].trim_start().starts_with(':') {
+                    KEY
+                } else {
+                    STRING
+                }
+            }
+            '-' | '0'..='9' | 't' | 'f' | 'n' => {
+                // Numbers and literals run until the next structural character or whitespace.
+                while characters
+                    .next_if(|&(_, character)| {
+                        !matches!(character, ',' | ']' | '}' | ' ' | '\t' | '\n' | '\r')
+                    })
+                    .is_some()
+                {}
+                match character {
+                    't' | 'f' => BOOLEAN,
+                    'n' => NULL,
+                    _ => NUMBER,
+                }
+            }
+            _ => {
+                output.push(character);
+                continue;
+            }
+        };
+
+        let end = characters.peek().map_or(json.len(), |&(end, _)| end);
+        output.push_str(color);
+        output.push_str(&json[start..end]);
+        output.push_str(RESET);
+    }
+
+    output
 }
@@ -0,0 +1,541 @@
+use crate::value::Value;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+/// An insertion-ordered map of keys to JSON values, used to represent JSON objects.
+///
+/// Members are kept in the order they were inserted, which is the order in which they appear
+/// in the parsed document, so that object keys can be deterministically reordered and printed.
+/// Lookups by key go through a separate index and don't depend on the number of members.
+///
+/// Keys are reference counted, so the index shares them with the members, and maps parsed with
+/// [`ParserOptions::intern_keys`](crate::parser::ParserOptions::intern_keys) share them with
+/// each other.
+///
+/// Values are [`Value`]s unless another type is given, as for
+/// [`ArcObject`](crate::shared::ArcObject).
+///
+/// Two maps are equal if they contain the same key-value pairs, regardless of their order.
+#[derive(Clone)]
+pub struct Map<V = Value> {
+    /// Members of the object in insertion order.
+    entries: Vec<(Arc<str>, V)>,
+    /// Position of each key inside `entries`.
+    index: HashMap<Arc<str>, usize>,
+}
+
+impl<V> Default for Map<V> {
+    fn default() -> Self {
+        Map {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<V> Map<V> {
+    /// Create a new, empty [`Map`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new, empty [`Map`] with space for at least `capacity` members.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Map {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Number of members in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map has no members.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all members from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// Whether the map contains a member with the given key.
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Get a reference to the value stored under `key`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.index
+            .get(key)
+            .map(|&position| &self.entries[position].1)
+    }
+
+    /// Get a mutable reference to the value stored under `key`.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.index
+            .get(key)
+            .map(|&position| &mut self.entries[position].1)
+    }
+
+    /// Insert a value under `key`, returning the previous value if there was one.
+    ///
+    /// Replacing the value of an existing key keeps the key at its original position.
+    pub fn insert(&mut self, key: String, value: V) -> Option<V> {
+        self.insert_shared(key.into(), value)
+    }
+
+    /// Insert a value under a key which may be shared with other maps, returning the previous
+    /// value if there was one.
+    pub fn insert_shared(&mut self, key: Arc<str>, value: V) -> Option<V> {
+        if let Some(&position) = self.index.get(&key) {
+            return Some(std::mem::replace(&mut self.entries[position].1, value));
+        }
+
+        self.index.insert(Arc::clone(&key), self.entries.len());
+        self.entries.push((key, value));
+
+        None
+    }
+
+    /// Get the member stored under `key`, or the place to insert it, to update or insert it
+    /// with a single lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::map::Map;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let mut counts = Map::new();
+    /// for word in ["a", "b", "a"] {
+    ///     let count = counts.entry(word).or_insert(Value::from(0));
+    ///     if let Value::Number(Number::I64(count)) = count {
+    ///         *count += 1;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(counts["a"], Value::Number(Number::I64(2)));
+    /// assert_eq!(counts["b"], Value::Number(Number::I64(1)));
+    /// ```
+    pub fn entry(&mut self, key: &str) -> Entry<'_, V> {
+        match self.index.get(key) {
+            Some(&position) => Entry::Occupied(OccupiedEntry {
+                map: self,
+                position,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                key: key.into(),
+            }),
+        }
+    }
+
+    /// Remove the member stored under `key`, preserving the order of the remaining members.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let position = self.index.remove(key)?;
+        Some(self.remove_at(position))
+    }
+
+    /// Remove the member at `position`, whose key has been removed from the index already.
+    fn remove_at(&mut self, position: usize) -> V {
+        let (_, value) = self.entries.remove(position);
+
+        // Every member after the removed one moved back by one slot.
+        for (key, _) in &self.entries[position..] {
+            if let Some(slot) = self.index.get_mut(key) {
+                *slot -= 1;
+            }
+        }
+
+        value
+    }
+
+    /// Keep only the members for which `predicate` returns `true`, preserving their order.
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&str, &mut V) -> bool,
+    {
+        self.entries
+            .retain_mut(|(key, value)| predicate(key, value));
+        self.reindex();
+    }
+
+    /// Sort the members of the map by key.
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.reindex();
+    }
+
+    /// Sort the members of the map with a comparator over key-value pairs.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut((&str, &V), (&str, &V)) -> Ordering,
+    {
+        self.entries.sort_by(|(a_key, a_value), (b_key, b_value)| {
+            compare((a_key, a_value), (b_key, b_value))
+        });
+        self.reindex();
+    }
+
+    /// Iterate over the key-value pairs of the map in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, &V)> + ExactSizeIterator {
+        self.entries.iter().map(|(key, value)| (&**key, value))
+    }
+
+    /// Iterate over the key-value pairs of the map in order, with mutable values.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (&str, &mut V)> + ExactSizeIterator {
+        self.entries.iter_mut().map(|(key, value)| (&**key, value))
+    }
+
+    /// Iterate over the keys of the map in order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.entries.iter().map(|(key, _)| &**key)
+    }
+
+    /// Iterate over the values of the map in order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    /// Iterate over the values of the map in order, mutably.
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.entries.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Convert every value with `convert`, keeping the keys, their order and the index.
+    pub(crate) fn map_values<W>(self, mut convert: impl FnMut(V) -> W) -> Map<W> {
+        Map {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|(key, value)| (key, convert(value)))
+                .collect(),
+            index: self.index,
+        }
+    }
+
+    /// Rebuild the key index after the order of `entries` changed.
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (position, (key, _)) in self.entries.iter().enumerate() {
+            self.index.insert(Arc::clone(key), position);
+        }
+    }
+}
+
+impl<V: PartialEq> PartialEq for Map<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<V: Eq> Eq for Map<V> {}
+
+impl<V: Hash> Hash for Map<V> {
+    /// Hash the members independently of their order, consistently with [`PartialEq`].
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Combine the hashes of every member with a commutative operation, using a hasher
+        // with fixed keys so that all maps agree on the hash of a given member.
+        let members = self
+            .iter()
+            .map(|member| {
+                let mut hasher = DefaultHasher::new();
+                member.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0u64, u64::wrapping_add);
+
+        self.len().hash(state);
+        members.hash(state);
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Map<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V> Index<&str> for Map<V> {
+    type Output = V;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get(key).expect("key not present in map")
+    }
+}
+
+impl<V> IndexMut<&str> for Map<V> {
+    fn index_mut(&mut self, key: &str) -> &mut Self::Output {
+        self.get_mut(key).expect("key not present in map")
+    }
+}
+
+impl<V> FromIterator<(String, V)> for Map<V> {
+    fn from_iter<I: IntoIterator<Item = (String, V)>>(iterator: I) -> Self {
+        let mut map = Map::new();
+        map.extend(iterator);
+
+        map
+    }
+}
+
+impl<V> Extend<(String, V)> for Map<V> {
+    fn extend<I: IntoIterator<Item = (String, V)>>(&mut self, iterator: I) {
+        for (key, value) in iterator {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<V> IntoIterator for Map<V> {
+    type Item = (String, V);
+    type IntoIter =
+        std::iter::Map<std::vec::IntoIter<(Arc<str>, V)>, fn((Arc<str>, V)) -> (String, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Map<V> {
+    type Item = (&'a str, &'a V);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (Arc<str>, V)>,
+        fn(&'a (Arc<str>, V)) -> (&'a str, &'a V),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(key, value)| (&**key, value))
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut Map<V> {
+    type Item = (&'a str, &'a mut V);
+    type IntoIter = std::iter::Map<
+        std::slice::IterMut<'a, (Arc<str>, V)>,
+        fn(&'a mut (Arc<str>, V)) -> (&'a str, &'a mut V),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter_mut().map(|(key, value)| (&**key, value))
+    }
+}
+
+impl<V> From<HashMap<String, V>> for Map<V> {
+    fn from(map: HashMap<String, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// A member of a [`Map`], or the place to insert it, returned by [`Map::entry`].
+pub enum Entry<'a, V = Value> {
+    Occupied(OccupiedEntry<'a, V>),
+    Vacant(VacantEntry<'a, V>),
+}
+
+/// A member of a [`Map`], as an [`Entry`].
+pub struct OccupiedEntry<'a, V = Value> {
+    map: &'a mut Map<V>,
+    position: usize,
+}
+
+/// The place of a missing member of a [`Map`], as an [`Entry`].
+pub struct VacantEntry<'a, V = Value> {
+    map: &'a mut Map<V>,
+    key: Arc<str>,
+}
+
+impl<'a, V> Entry<'a, V> {
+    /// The key of the entry.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// The value of the member, after inserting `default` if it's missing.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// The value of the member, after inserting the result of `default` if it's missing.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Call `f` on the value of the member if it exists, before inserting anything.
+    #[must_use]
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+
+        self
+    }
+}
+
+impl<'a, V> OccupiedEntry<'a, V> {
+    /// The key of the member.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.map.entries[self.position].0
+    }
+
+    /// The value of the member.
+    #[must_use]
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.position].1
+    }
+
+    /// The value of the member, to modify it.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.position].1
+    }
+
+    /// The value of the member, borrowed for as long as the map was.
+    #[must_use]
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.position].1
+    }
+
+    /// Replace the value of the member, returning the previous one.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the member, preserving the order of the remaining members, and return its
+    /// value.
+    pub fn remove(self) -> V {
+        let key = Arc::clone(&self.map.entries[self.position].0);
+        self.map.index.remove(&key);
+        self.map.remove_at(self.position)
+    }
+}
+
+impl<'a, V> VacantEntry<'a, V> {
+    /// The key of the missing member.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Insert the member with `value` at the end of the map, and return its value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let position = self.map.entries.len();
+        self.map.index.insert(Arc::clone(&self.key), position);
+        self.map.entries.push((self.key, value));
+
+        &mut self.map.entries[position].1
+    }
+}
+
+impl Value {
+    /// Get the member of this object stored under `key`, or the place to insert it, like
+    /// [`Map::entry`]. `null` is first replaced with an empty object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is neither an object nor `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let records = JsonParser::parse_from_bytes(
+    ///     br#"[{"kind": "a", "id": 1}, {"kind": "b", "id": 2}, {"kind": "a", "id": 3}]"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut counts = Value::Null;
+    /// let mut groups = Value::Null;
+    /// for record in <&Vec<Value>>::try_from(&records).unwrap() {
+    ///     let Some(Value::String(kind)) = record.pointer("/kind") else {
+    ///         continue;
+    ///     };
+    ///     let count = counts.entry(kind).or_insert(Value::from(0));
+    ///     *count = Value::from(i64::try_from(&*count).unwrap() + 1);
+    ///
+    ///     let group = groups.entry(kind).or_insert_with(|| Value::Array(Vec::new()));
+    ///     if let Value::Array(ids) = group {
+    ///         ids.push(record.pointer("/id").unwrap().clone());
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(counts.to_string_compact(), r#"{"a":2,"b":1}"#);
+    /// assert_eq!(groups.to_string_compact(), r#"{"a":[1,3],"b":[2]}"#);
+    /// ```
+    pub fn entry(&mut self, key: &str) -> Entry<'_> {
+        if *self == Value::Null {
+            *self = Value::Object(Map::new());
+        }
+        match self {
+            Value::Object(members) => members.entry(key),
+            other => panic!("expected an object or null, found {}", other.type_name()),
+        }
+    }
+}
+
+/// Keys handed out for the objects of one parse, sharing one allocation between occurrences of
+/// the same key when interning is enabled.
+pub(crate) struct KeyPool {
+    keys: Option<HashSet<Arc<str>>>,
+}
+
+impl KeyPool {
+    pub(crate) fn new(intern: bool) -> Self {
+        KeyPool {
+            keys: intern.then(HashSet::new),
+        }
+    }
+
+    pub(crate) fn get(&mut self, key: &str) -> Arc<str> {
+        let Some(keys) = &mut self.keys else {
+            return key.into();
+        };
+        if let Some(shared) = keys.get(key) {
+            return Arc::clone(shared);
+        }
+
+        let shared: Arc<str> = key.into();
+        keys.insert(Arc::clone(&shared));
+        shared
+    }
+}
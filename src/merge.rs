@@ -0,0 +1,177 @@
+//! Three-way merging of concurrent edits to a document.
+
+use crate::map::Map;
+use crate::pointer::push_token;
+use crate::value::Value;
+use std::fmt::{self, Display, Formatter};
+
+/// Result of [`merge3`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    /// The merged document, holding our side of every conflict.
+    pub value: Value,
+    /// The places where both sides changed the document differently, in document order.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Whether the sides were merged without any conflict.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// A place where both sides of a [`merge3`] changed the document differently.
+///
+/// Each side is `None` where the value doesn't exist, such as a member added by only one
+/// side, or removed by one side and changed by the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// JSON Pointer of the conflicting value.
+    pub pointer: String,
+    /// The value in the common ancestor.
+    pub base: Option<Value>,
+    /// Our value, which is the one kept in the merged document.
+    pub ours: Option<Value>,
+    /// Their value.
+    pub theirs: Option<Value>,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let side = |value: &Option<Value>| {
+            value
+                .as_ref()
+                .map_or_else(|| "(absent)".to_string(), Value::to_string_compact)
+        };
+        let pointer = if self.pointer.is_empty() {
+            "(root)"
+        } else {
+            &self.pointer
+        };
+
+        write!(
+            f,
+            "conflict at {pointer}: ours {}, theirs {}, base {}",
+            side(&self.ours),
+            side(&self.theirs),
+            side(&self.base)
+        )
+    }
+}
+
+/// Merge the changes made by `ours` and `theirs` to their common ancestor `base`.
+///
+/// A value changed by only one side takes that side's value. Objects changed by both sides
+/// are merged member by member, keeping our order of members followed by the ones only they
+/// added, and arrays changed by both sides are merged element by element if all three have
+/// the same length. Anything else changed by both sides in different ways is a conflict,
+/// for which the merged document keeps our side.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::merge::merge3;
+/// use json_parser::parser::JsonParser;
+/// use json_parser::value::Value;
+///
+/// let base = JsonParser::parse_from_bytes(br#"{"port": 80, "host": "a", "debug": false}"#).unwrap();
+/// let ours = JsonParser::parse_from_bytes(br#"{"port": 8080, "host": "b", "debug": false}"#).unwrap();
+/// let theirs = JsonParser::parse_from_bytes(br#"{"port": 80, "host": "c", "log": "info"}"#).unwrap();
+///
+/// let merge = merge3(&base, &ours, &theirs);
+///
+/// assert_eq!(merge.value.to_string_compact(), r#"{"port":8080,"host":"b","log":"info"}"#);
+/// assert_eq!(merge.conflicts.len(), 1);
+/// assert_eq!(merge.conflicts[0].pointer, "/host");
+/// assert_eq!(merge.conflicts[0].theirs, Some(Value::from("c")));
+/// ```
+#[must_use]
+pub fn merge3(base: &Value, ours: &Value, theirs: &Value) -> Merge {
+    let mut conflicts = Vec::new();
+    let value = merge(
+        Some(base),
+        Some(ours),
+        Some(theirs),
+        &mut String::new(),
+        &mut conflicts,
+    )
+    .expect("our side exists");
+
+    Merge { value, conflicts }
+}
+
+/// Merge the values at `pointer`, each of which may be absent, returning the merged value or
+/// `None` if it's removed.
+fn merge(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    pointer: &mut String,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+
+    let length = pointer.len();
+    match (base, ours, theirs) {
+        // Members added by both sides are merged as if added to an empty object.
+        (None | Some(Value::Object(_)), Some(Value::Object(ours)), Some(Value::Object(theirs))) => {
+            let empty = Map::new();
+            let base = match base {
+                Some(Value::Object(base)) => base,
+                _ => &empty,
+            };
+            let keys = ours
+                .keys()
+                .chain(theirs.keys().filter(|key| !ours.contains_key(key)));
+
+            let mut merged = Map::with_capacity(ours.len());
+            for key in keys {
+                push_token(pointer, key);
+                let member = merge(
+                    base.get(key),
+                    ours.get(key),
+                    theirs.get(key),
+                    pointer,
+                    conflicts,
+                );
+                pointer.truncate(length);
+                if let Some(member) = member {
+                    merged.insert(key.to_string(), member);
+                }
+            }
+
+            Some(Value::Object(merged))
+        }
+        (Some(Value::Array(base)), Some(Value::Array(ours)), Some(Value::Array(theirs)))
+            if base.len() == ours.len() && base.len() == theirs.len() =>
+        {
+            let mut merged = Vec::with_capacity(ours.len());
+            for (index, ((base, ours), theirs)) in base.iter().zip(ours).zip(theirs).enumerate() {
+                push_token(pointer, &index.to_string());
+                let element = merge(Some(base), Some(ours), Some(theirs), pointer, conflicts);
+                pointer.truncate(length);
+                // Neither side removed the element, so the merge keeps it.
+                merged.push(element.expect("both sides have the element"));
+            }
+
+            Some(Value::Array(merged))
+        }
+        _ => {
+            conflicts.push(Conflict {
+                pointer: pointer.clone(),
+                base: base.cloned(),
+                ours: ours.cloned(),
+                theirs: theirs.cloned(),
+            });
+
+            ours.cloned()
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::error::JsonError;
+use crate::value::Value;
+
+impl Value {
+    /// Encode the value as [MessagePack](https://msgpack.org), a compact binary format with
+    /// the same data model as JSON.
+    ///
+    /// Integers are encoded as MessagePack integers and floats as 64-bit floats, so that
+    /// [`from_msgpack`] gives back an identical value.
+    ///
+    /// [`from_msgpack`]: Value::from_msgpack
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    ///
+    /// let value = Value::Array(vec![Value::from(7), Value::from(0.5)]);
+    /// let bytes = value.to_msgpack();
+    ///
+    /// // A two-element array holding a positive fixint, then a 64-bit float.
+    /// assert_eq!(bytes, [0x92, 0x07, 0xCB, 0x3F, 0xE0, 0, 0, 0, 0, 0, 0]);
+    /// assert_eq!(Value::from_msgpack(&bytes).unwrap(), value);
+    /// ```
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        rmp_serde::to_vec(self).expect("writing to a Vec can't fail")
+    }
+
+    /// Decode a value from MessagePack.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the bytes aren't valid MessagePack, or hold items that
+    /// have no JSON equivalent such as binary data or non-string map keys.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Value, JsonError> {
+        rmp_serde::from_slice(bytes).map_err(|error| JsonError::Format {
+            format: "MessagePack",
+            message: error.to_string(),
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use crate::value::Value;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+impl Value {
+    /// Read a string holding an IPv4 or IPv6 address, such as `"192.168.0.1"` or `"::1"`.
+    ///
+    /// Returns `None` for other values and other strings, including addresses with a port or
+    /// a prefix length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    /// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    ///
+    /// assert_eq!(
+    ///     Value::from("192.168.0.1").as_ip_addr(),
+    ///     Some(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)))
+    /// );
+    /// assert_eq!(Value::from("::1").as_ip_addr(), Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+    /// assert_eq!(Value::from("10.0.0.1:80").as_ip_addr(), None);
+    /// assert_eq!(Value::from(Ipv4Addr::LOCALHOST), Value::from("127.0.0.1"));
+    /// ```
+    #[must_use]
+    pub fn as_ip_addr(&self) -> Option<IpAddr> {
+        match self {
+            Value::String(string) => string.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<IpAddr> for Value {
+    fn from(address: IpAddr) -> Self {
+        Value::from(address.to_string())
+    }
+}
+
+impl From<Ipv4Addr> for Value {
+    fn from(address: Ipv4Addr) -> Self {
+        Value::from(address.to_string())
+    }
+}
+
+impl From<Ipv6Addr> for Value {
+    /// Write the address in its canonical form of RFC 5952, such as `"2001:db8::1"`.
+    fn from(address: Ipv6Addr) -> Self {
+        Value::from(address.to_string())
+    }
+}
@@ -1,180 +1,539 @@
-use crate::token::{JsonTokenizer, Token};
+use crate::error::JsonError;
+use crate::map::{KeyPool, Map};
+use crate::token::{JsonTokenizer, Scratch, Token};
 use crate::value::Value;
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, Cursor};
 use std::iter::Peekable;
 use std::slice::Iter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Main parser which is the entrypoint for parsing JSON.
+///
+/// Parsing never panics, whatever the input: malformed input, such as a misspelled literal,
+/// a missing or extra comma, unbalanced brackets, a number too large even for an `f64`, or
+/// arrays nested more than [`MAX_NESTING`](crate::token::MAX_NESTING) levels deep, gives
+/// [`JsonError::Syntax`]. This makes it safe to parse untrusted input without `catch_unwind`.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::error::JsonError;
+/// use json_parser::parser::JsonParser;
+///
+/// let deep = "[".repeat(100_000);
+/// for input in [
+///     "[tru]", "{\"a\": nul", "[1e5e5]", "[1e999]", "[1-]", "@", &deep,
+///     "[1,2", "[1,,2]", "{\"a\" 1}", "[}", "\"abc", "1 2", "[1,]", "{\"a\": 1,}", "",
+/// ] {
+///     let result = JsonParser::parse_from_bytes(input.as_bytes());
+///     assert!(matches!(result, Err(JsonError::Syntax(_))), "{input:.20}");
+/// }
+///
+/// // Integers too large for an `i64` are read as floats.
+/// let large = JsonParser::parse_from_bytes(b"[18446744073709551616]").unwrap();
+/// assert_eq!(large.to_string_compact(), "[1.8446744073709552e19]");
+/// ```
 pub struct JsonParser;
 
+/// Options controlling how [`JsonParser`] reads its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParserOptions {
+    /// What to do with bytes which aren't valid UTF-8.
+    pub invalid_utf8: InvalidUtf8,
+    /// What to do with `\u` escapes of surrogates which aren't part of a pair.
+    pub lone_surrogates: LoneSurrogate,
+    /// Whether object keys are interned, so that every occurrence of a key shares one
+    /// allocation. This saves memory and allocations for documents with many objects of the
+    /// same shape, such as arrays of records, at the cost of a lookup per key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::map::Map;
+    /// use json_parser::parser::{JsonParser, ParserOptions};
+    /// use json_parser::value::Value;
+    ///
+    /// let options = ParserOptions {
+    ///     intern_keys: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let input = br#"[{"id": 1}, {"id": 2}]"#;
+    /// let Value::Array(records) = JsonParser::parse_from_bytes_with_options(input, &options).unwrap()
+    /// else {
+    ///     unreachable!()
+    /// };
+    ///
+    /// let key = |record: &Value| <&Map>::try_from(record).unwrap().keys().next().unwrap().as_ptr();
+    /// assert_eq!(key(&records[0]), key(&records[1]));
+    /// ```
+    pub intern_keys: bool,
+}
+
+/// What to do with bytes which aren't valid UTF-8, such as text in a legacy encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidUtf8 {
+    /// Fail with [`JsonError::Syntax`], giving the byte offset of the invalid bytes.
+    #[default]
+    Error,
+    /// Replace each invalid sequence with `U+FFFD`, the replacement character.
+    Replace,
+    /// Leave invalid sequences out.
+    Skip,
+}
+
+/// What to do with a `\u` escape of a UTF-16 surrogate which isn't part of a pair, such as a
+/// lone `\uD800`.
+///
+/// JSON strings are sequences of UTF-16 code units, so they can hold such surrogates, which
+/// turn up in data produced from JavaScript or Windows strings. Rust strings can't.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::{JsonParser, LoneSurrogate, ParserOptions};
+/// use json_parser::value::Value;
+///
+/// let input = br#""a\ud800b""#;
+/// let parse = |lone_surrogates| {
+///     let options = ParserOptions {
+///         lone_surrogates,
+///         ..ParserOptions::default()
+///     };
+///     JsonParser::parse_from_bytes_with_options(input, &options)
+/// };
+///
+/// assert!(parse(LoneSurrogate::Error).is_err());
+/// assert_eq!(parse(LoneSurrogate::Replace).unwrap(), Value::from("a\u{FFFD}b"));
+/// assert_eq!(parse(LoneSurrogate::Escape).unwrap(), Value::from(r"a\ud800b"));
+///
+/// // Paired surrogates always decode to the character they encode.
+/// let pair = JsonParser::parse_from_bytes(br#""\ud83d\ude00""#).unwrap();
+/// assert_eq!(pair, Value::from("😀"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoneSurrogate {
+    /// Fail with [`JsonError::Syntax`].
+    Error,
+    /// Replace the surrogate with `U+FFFD`, the replacement character, like
+    /// [`Document`](crate::cst::Document) does.
+    #[default]
+    Replace,
+    /// Keep the escape sequence as text, such as the six characters `\ud800`, so that the
+    /// surrogate can still be told apart from the replacement character.
+    Escape,
+}
+
+/// A handle to stop parsing from another thread, or once a deadline has passed, for parsing
+/// huge or adversarial input without risking being stuck on it.
+///
+/// Clones share their state, so a clone can be handed to
+/// [`parse_from_bytes_cancellable`](JsonParser::parse_from_bytes_cancellable) while another is
+/// kept to [`cancel`](CancellationToken::cancel) it. The parser checks the token every few
+/// thousand tokens, and fails with [`JsonError::Cancelled`] once it's cancelled.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::error::JsonError;
+/// use json_parser::parser::{CancellationToken, JsonParser, ParserOptions};
+/// use std::time::{Duration, Instant};
+///
+/// let input = "[1, 2, 3]";
+/// let options = ParserOptions::default();
+///
+/// let token = CancellationToken::new();
+/// assert!(JsonParser::parse_from_bytes_cancellable(input.as_bytes(), &options, &token).is_ok());
+///
+/// token.clone().cancel();
+/// let result = JsonParser::parse_from_bytes_cancellable(input.as_bytes(), &options, &token);
+/// assert_eq!(result, Err(JsonError::Cancelled));
+///
+/// // A deadline which has passed cancels the parse as well.
+/// let expired = CancellationToken::with_deadline(Instant::now() - Duration::from_secs(1));
+/// assert!(expired.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Create a token which is only cancelled by [`cancel`](CancellationToken::cancel).
+    #[must_use]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Create a token which is cancelled once `deadline` has passed, if it isn't cancelled
+    /// before.
+    #[must_use]
+    pub fn with_deadline(deadline: Instant) -> CancellationToken {
+        CancellationToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Cancel the parses using this token or any of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token has been cancelled, or its deadline has passed.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// How far a parse has gone, as reported to the hook given to
+/// [`parse_from_file_with_progress`](JsonParser::parse_from_file_with_progress).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of bytes of the input read so far.
+    pub bytes_read: u64,
+    /// Size of the input in bytes, when it's known.
+    pub total_bytes: Option<u64>,
+}
+
+impl Progress {
+    /// The part of the input read so far, from 0 to 1, when the size of the input is known.
+    #[must_use]
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total_bytes? {
+            0 => Some(1.0),
+            total => Some((self.bytes_read as f64 / total as f64).min(1.0)),
+        }
+    }
+}
+
+/// A parser keeping its buffers from one input to the next, so that parsing many small
+/// documents, such as the messages a server receives, doesn't allocate them anew every time.
+///
+/// The buffers grow to fit the largest input parsed so far, and are kept until the parser is
+/// dropped.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::{JsonParser, Parser};
+///
+/// let mut parser = Parser::new();
+/// for message in [r#"{"id": 1, "name": "ada"}"#, r#"{"id": 2, "tags": []}"#, "[1e-3]"] {
+///     let value = parser.parse(message.as_bytes()).unwrap();
+///     assert_eq!(value, JsonParser::parse_from_bytes(message.as_bytes()).unwrap());
+/// }
+///
+/// assert!(parser.parse(b"[tru]").is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct Parser {
+    options: ParserOptions,
+    tokens: Vec<Token>,
+    scratch: Scratch,
+}
+
+impl Parser {
+    /// Create a parser with the default options.
+    #[must_use]
+    pub fn new() -> Parser {
+        Parser::default()
+    }
+
+    /// Create a parser reading its inputs according to `options`.
+    #[must_use]
+    pub fn with_options(options: &ParserOptions) -> Parser {
+        Parser {
+            options: *options,
+            ..Parser::default()
+        }
+    }
+
+    /// Parse JSON from bytes, as done by
+    /// [`parse_from_bytes_with_options`](JsonParser::parse_from_bytes_with_options) with the
+    /// options of this parser.
+    pub fn parse(&mut self, input: &[u8]) -> Result<Value, JsonError> {
+        let mut json_tokenizer = JsonTokenizer::<Cursor<&[u8]>>::from_bytes_with_buffers(
+            input,
+            std::mem::take(&mut self.tokens),
+            std::mem::take(&mut self.scratch),
+        )
+        .with_options(&self.options);
+        let value = json_tokenizer.tokenize_json().and_then(|tokens| {
+            JsonParser::tokens_to_value(tokens, &mut KeyPool::new(self.options.intern_keys))
+        });
+        (self.tokens, self.scratch) = json_tokenizer.into_buffers();
+
+        value
+    }
+}
+
 impl JsonParser {
     /// Create a new [`JsonParser`] that parses JSON from bytes.
-    pub fn parse_from_bytes(input: &[u8]) -> Result<Value, ()> {
-        let mut json_tokenizer = JsonTokenizer::<BufReader<Cursor<&[u8]>>>::from_bytes(input);
+    ///
+    /// A UTF-8 byte order mark at the start of the input is skipped. Use
+    /// [`parse_with_warnings`](JsonParser::parse_with_warnings) to find out whether there was
+    /// one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let with_bom = JsonParser::parse_from_bytes(b"\xEF\xBB\xBF[true]").unwrap();
+    /// assert_eq!(with_bom, JsonParser::parse_from_bytes(b"[true]").unwrap());
+    /// ```
+    pub fn parse_from_bytes(input: &[u8]) -> Result<Value, JsonError> {
+        Self::parse_from_bytes_with_options(input, &ParserOptions::default())
+    }
+
+    /// Parse JSON from bytes, as done by [`parse_from_bytes`](JsonParser::parse_from_bytes),
+    /// according to `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::{InvalidUtf8, JsonParser, ParserOptions};
+    /// use json_parser::value::Value;
+    ///
+    /// // "café" in Latin-1.
+    /// let input = b"\"caf\xE9\"";
+    /// assert!(JsonParser::parse_from_bytes(input).is_err());
+    ///
+    /// let options = ParserOptions {
+    ///     invalid_utf8: InvalidUtf8::Replace,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let value = JsonParser::parse_from_bytes_with_options(input, &options).unwrap();
+    /// assert_eq!(value, Value::from("caf\u{FFFD}"));
+    /// ```
+    pub fn parse_from_bytes_with_options(
+        input: &[u8],
+        options: &ParserOptions,
+    ) -> Result<Value, JsonError> {
+        let mut json_tokenizer =
+            JsonTokenizer::<BufReader<Cursor<&[u8]>>>::from_bytes(input).with_options(options);
         let tokens = json_tokenizer.tokenize_json()?;
 
-        Ok(Self::tokens_to_value(tokens))
+        Self::tokens_to_value(tokens, &mut KeyPool::new(options.intern_keys))
+    }
+
+    /// Parse JSON from bytes according to `options`, failing with [`JsonError::Cancelled`]
+    /// once `token` is cancelled.
+    pub fn parse_from_bytes_cancellable(
+        input: &[u8],
+        options: &ParserOptions,
+        token: &CancellationToken,
+    ) -> Result<Value, JsonError> {
+        let mut json_tokenizer = JsonTokenizer::<BufReader<Cursor<&[u8]>>>::from_bytes(input)
+            .with_options(options)
+            .with_cancellation(token);
+        let tokens = json_tokenizer.tokenize_json()?;
+
+        Self::tokens_to_value(tokens, &mut KeyPool::new(options.intern_keys))
     }
 
     /// Create a new [`JsonParser`] that parses JSON from a file.
-    pub fn parse_from_file(reader: File) -> Result<Value, ()> {
-        let mut json_tokenizer = JsonTokenizer::<BufReader<File>>::new(reader);
+    pub fn parse_from_file(reader: File) -> Result<Value, JsonError> {
+        Self::parse_from_file_with_options(reader, &ParserOptions::default())
+    }
+
+    /// Parse JSON from a file, as done by [`parse_from_file`](JsonParser::parse_from_file),
+    /// according to `options`.
+    pub fn parse_from_file_with_options(
+        reader: File,
+        options: &ParserOptions,
+    ) -> Result<Value, JsonError> {
+        let mut json_tokenizer =
+            JsonTokenizer::<BufReader<File>>::new(reader).with_options(options);
+        let tokens = json_tokenizer.tokenize_json()?;
+
+        Self::tokens_to_value(tokens, &mut KeyPool::new(options.intern_keys))
+    }
+
+    /// Parse JSON from a file according to `options`, failing with [`JsonError::Cancelled`]
+    /// once `token` is cancelled.
+    pub fn parse_from_file_cancellable(
+        reader: File,
+        options: &ParserOptions,
+        token: &CancellationToken,
+    ) -> Result<Value, JsonError> {
+        let mut json_tokenizer = JsonTokenizer::<BufReader<File>>::new(reader)
+            .with_options(options)
+            .with_cancellation(token);
         let tokens = json_tokenizer.tokenize_json()?;
 
-        Ok(Self::tokens_to_value(tokens))
+        Self::tokens_to_value(tokens, &mut KeyPool::new(options.intern_keys))
+    }
+
+    /// Parse JSON from a file according to `options`, calling `progress` with the number of
+    /// bytes read so far, out of the size of the file, to render a progress bar while parsing
+    /// large files.
+    ///
+    /// `progress` is called when parsing starts, after about every MiB read, and once the
+    /// whole file is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::{JsonParser, ParserOptions};
+    /// use std::fs::{self, File};
+    ///
+    /// let path = std::env::temp_dir().join("json-parser-progress.json");
+    /// fs::write(&path, format!("[{}0]", "0, ".repeat(1_000_000))).unwrap();
+    ///
+    /// let mut reports = Vec::new();
+    /// let file = File::open(&path).unwrap();
+    /// JsonParser::parse_from_file_with_progress(file, &ParserOptions::default(), |progress| {
+    ///     reports.push(progress.fraction().unwrap());
+    /// })
+    /// .unwrap();
+    /// # fs::remove_file(&path).unwrap();
+    ///
+    /// assert_eq!(reports.first(), Some(&0.0));
+    /// assert_eq!(reports.last(), Some(&1.0));
+    /// assert!(reports.len() > 3);
+    /// assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+    /// ```
+    pub fn parse_from_file_with_progress(
+        reader: File,
+        options: &ParserOptions,
+        mut progress: impl FnMut(Progress),
+    ) -> Result<Value, JsonError> {
+        let mut json_tokenizer =
+            JsonTokenizer::<BufReader<File>>::new(reader).with_options(options);
+        let tokens = json_tokenizer.tokenize_json_with_progress(&mut progress)?;
+
+        Self::tokens_to_value(tokens, &mut KeyPool::new(options.intern_keys))
     }
 
-    fn tokens_to_value(tokens: &[Token]) -> Value {
+    fn tokens_to_value(tokens: &[Token], keys: &mut KeyPool) -> Result<Value, JsonError> {
         // Create a peekable iterator over tokens
         let mut iterator = tokens.iter().peekable();
 
-        // Initialize final value to null.
-        let mut value = Value::Null;
-
-        // Loop while there are tokens in the iterator.
-        // Note that you do not need to manually handle advancing the iterator in this case which
-        // is why you can directly call `iterator.next()`.
-        while let Some(tokens) = iterator.next() {
-            match tokens {
-                Token::CurlyOpen => {
-                    value = Value::Object(Self::process_object(&mut iterator));
-                }
-                Token::String(string) => {
-                    value = Value::String(string.clone());
-                }
-                Token::Number(number) => {
-                    value = Value::Number(*number);
-                }
-                Token::ArrayOpen => {
-                    value = Value::Array(Self::process_array(&mut iterator));
-                }
-                Token::Boolean(boolean) => value = Value::Boolean(*boolean),
-                Token::Null => value = Value::Null,
-                // Ignore all delimiters as you don't need to explicitly do anything
-                // when you encounter them.
-                Token::Comma
-                | Token::CurlyClose
-                | Token::Quotes
-                | Token::Colon
-                | Token::ArrayClose => {}
-            }
+        // The input must hold exactly one value, so anything after it is an error.
+        let value = Self::process_value(&mut iterator, keys)?;
+        match Self::next_token(&mut iterator) {
+            None => Ok(value),
+            Some(token) => Err(unexpected("the end of input", Some(token))),
         }
+    }
 
-        value
+    /// The next token which isn't the [`Token::Quotes`] around a string, since the string
+    /// token itself is all that matters.
+    fn next_token<'t>(iterator: &mut Peekable<Iter<'t, Token>>) -> Option<&'t Token> {
+        iterator.find(|token| !matches!(token, Token::Quotes))
+    }
+
+    fn process_value(
+        iterator: &mut Peekable<Iter<Token>>,
+        keys: &mut KeyPool,
+    ) -> Result<Value, JsonError> {
+        Ok(match Self::next_token(iterator) {
+            Some(Token::CurlyOpen) => Value::Object(Self::process_object(iterator, keys)?),
+            Some(Token::ArrayOpen) => Value::Array(Self::process_array(iterator, keys)?),
+            Some(Token::String(string)) => Value::from(string.as_str()),
+            Some(Token::Number(number)) => Value::Number(*number),
+            Some(Token::Boolean(boolean)) => Value::Boolean(*boolean),
+            Some(Token::Null) => Value::Null,
+            token => return Err(unexpected("a value", token)),
+        })
     }
 
-    fn process_array(iterator: &mut Peekable<Iter<Token>>) -> Vec<Value> {
+    fn process_array(
+        iterator: &mut Peekable<Iter<Token>>,
+        keys: &mut KeyPool,
+    ) -> Result<Vec<Value>, JsonError> {
         // Initialise a vector of JSON Value type to hold the value of array that's currently being parsed.
         let mut internal_value = Vec::new();
 
-        // Iterate over all tokens provided.
-        while let Some(token) = iterator.next() {
-            match token {
-                Token::CurlyOpen => {
-                    internal_value.push(Value::Object(Self::process_object(iterator)));
-                }
-                Token::String(string) => internal_value.push(Value::String(string.clone())),
-                Token::Number(number) => internal_value.push(Value::Number(*number)),
-                Token::ArrayOpen => {
-                    internal_value.push(Value::Array(Self::process_array(iterator)));
-                }
-                Token::Boolean(boolean) => internal_value.push(Value::Boolean(*boolean)),
-                Token::Null => internal_value.push(Value::Null),
-                // Break loop if array is closed. Due to recursive nature of process_array,
-                // we don't need to explicitly check if the closing token matches the opening
-                // one.
-                Token::ArrayClose => {
-                    break;
-                }
-                // Ignore delimiters
-                Token::Comma | Token::CurlyClose | Token::Quotes | Token::Colon => {}
+        // An empty array is closed right away, and any other has a value before each comma.
+        if iterator
+            .next_if(|token| **token == Token::ArrayClose)
+            .is_some()
+        {
+            return Ok(internal_value);
+        }
+
+        loop {
+            internal_value.push(Self::process_value(iterator, keys)?);
+
+            // Each element is followed by a comma and another element, or by the end of the
+            // array.
+            match Self::next_token(iterator) {
+                Some(Token::Comma) => {}
+                Some(Token::ArrayClose) => break,
+                token => return Err(unexpected("`,` or `]`", token)),
             }
         }
 
-        internal_value
+        Ok(internal_value)
     }
 
-    fn process_object(iterator: &mut Peekable<Iter<Token>>) -> HashMap<String, Value> {
-        // Wether the item being parsed is a key or a value. The first element should always be a
-        // key so this is initialized to true.
-        let mut is_key = true;
+    fn process_object(
+        iterator: &mut Peekable<Iter<Token>>,
+        keys: &mut KeyPool,
+    ) -> Result<Map, JsonError> {
+        // The current state of parsed object.
+        let mut value = Map::new();
 
-        // The current key for which the value is being parsed.
-        let mut current_key: Option<&str> = None;
+        // An empty object is closed right away, and any other has a member before each comma.
+        if iterator
+            .next_if(|token| **token == Token::CurlyClose)
+            .is_some()
+        {
+            return Ok(value);
+        }
 
-        // The current state of parsed object.
-        let mut value = HashMap::<String, Value>::new();
-
-        while let Some(token) = iterator.next() {
-            match token {
-                // If it is a nested object, recursively parse it and store in the hashmap with
-                // current key.
-                Token::CurlyOpen => {
-                    if let Some(current_key) = current_key {
-                        value.insert(
-                            current_key.to_string(),
-                            Value::Object(Self::process_object(iterator)),
-                        );
-                    }
-                }
-                // If this token is encountered, break the loop since it indicates end of an object
-                // being parsed.
-                Token::CurlyClose => break,
-                Token::Quotes | Token::ArrayClose => {}
-                // If the token is a colon, it is the separator between key and value pair. So the
-                // item being parsed from this point ahead will not be a key.
-                Token::Colon => {
-                    is_key = false;
-                }
-                Token::String(string) => {
-                    if is_key {
-                        // If the process is presently parsing key, set the value as current key.
-                        current_key = Some(string);
-                    } else if let Some(key) = current_key {
-                        // If the process already has a key set for present item, parse string as
-                        // value instead, and set the current_key to none once done to prepare for
-                        // the next key-value pair.
-                        value.insert(key.to_string(), Value::String(string.clone()));
-                        // Set current_key to None to prepare for next key-value pair.
-                        current_key = None;
-                    }
-                }
-                Token::Number(number) => {
-                    if let Some(key) = current_key {
-                        value.insert(key.to_string(), Value::Number(*number));
-                        // Set current_key to None to prepare for next key-value pair.
-                        current_key = None;
-                    }
-                }
-                Token::ArrayOpen => {
-                    if let Some(key) = current_key {
-                        value.insert(key.to_string(), Value::Array(Self::process_array(iterator)));
-                        // Set current_key to None to prepare for next key-value pair.
-                        current_key = None;
-                    }
-                }
-                // If the token is a comma, it is the separator between multiple key-value pairs
-                // in JSON. So the item being parsed from this point ahead will be a key.
-                Token::Comma => is_key = true,
-                Token::Boolean(boolean) => {
-                    if let Some(key) = current_key {
-                        value.insert(key.to_string(), Value::Boolean(*boolean));
-                        // Set current_key to None to prepare for the next key-value pair.
-                        current_key = None;
-                    }
-                }
-                Token::Null => {
-                    if let Some(key) = current_key {
-                        value.insert(key.to_string(), Value::Null);
-                        // Set current_key to None to prepare for the next key-value pair.
-                        current_key = None;
-                    }
-                }
+        loop {
+            // Each member is a string key, a colon separating it from the value, and the value.
+            let key = match Self::next_token(iterator) {
+                Some(Token::String(key)) => key,
+                token => return Err(unexpected("a string key", token)),
+            };
+            match Self::next_token(iterator) {
+                Some(Token::Colon) => {}
+                token => return Err(unexpected("`:`", token)),
+            }
+            let member = Self::process_value(iterator, keys)?;
+            value.insert_shared(keys.get(key), member);
+
+            // Each member is followed by a comma and another member, or by the end of the
+            // object.
+            match Self::next_token(iterator) {
+                Some(Token::Comma) => {}
+                Some(Token::CurlyClose) => break,
+                token => return Err(unexpected("`,` or `}`", token)),
             }
         }
 
-        value
+        Ok(value)
     }
 }
+
+/// The error for finding `token`, or the end of input if it's `None`, where `expected` was.
+fn unexpected(expected: &str, token: Option<&Token>) -> JsonError {
+    let found = match token {
+        None => "the end of input",
+        Some(Token::CurlyOpen) => "`{`",
+        Some(Token::CurlyClose) => "`}`",
+        Some(Token::ArrayOpen) => "`[`",
+        Some(Token::ArrayClose) => "`]`",
+        Some(Token::Comma) => "`,`",
+        Some(Token::Colon) => "`:`",
+        Some(Token::Quotes | Token::String(_)) => "a string",
+        Some(Token::Number(_)) => "a number",
+        Some(Token::Boolean(true)) => "`true`",
+        Some(Token::Boolean(false)) => "`false`",
+        Some(Token::Null) => "`null`",
+    };
+
+    JsonError::Syntax(format!("expected {expected}, found {found}"))
+}
@@ -0,0 +1,492 @@
+//! JSON Patch documents, as described in RFC 6902, listing the changes that turn one value into
+//! another.
+
+use crate::convert::{FromJson, ToJson};
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::pointer::{parse_index, push_token};
+use crate::value::{Number, Value};
+use std::fmt::{self, Display};
+
+/// One change of a JSON Patch, locating the values it applies to with JSON Pointers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOperation {
+    /// Set the member at `path`, or insert an element before the one at `path`, with `-`
+    /// standing for the end of an array.
+    Add {
+        path: String,
+        value: Value,
+    },
+    Remove {
+        path: String,
+    },
+    Replace {
+        path: String,
+        value: Value,
+    },
+    /// Remove the value at `from` and add it at `path`.
+    Move {
+        from: String,
+        path: String,
+    },
+    /// Add a copy of the value at `from` at `path`.
+    Copy {
+        from: String,
+        path: String,
+    },
+    /// Check that the value at `path` is equal to `value`, failing the whole patch otherwise.
+    /// Numbers are compared by value, so `1` and `1.0` are equal.
+    Test {
+        path: String,
+        value: Value,
+    },
+}
+
+impl Value {
+    /// The JSON Patch turning this value into `target`, which is empty if they're equal.
+    ///
+    /// Members are compared by key and elements by position: elements are replaced in place,
+    /// then added or removed at the end of arrays whose length changed. Values of different
+    /// types are replaced as a whole.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::convert::ToJson;
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let source = JsonParser::parse_from_bytes(br#"{"name": "app", "tags": ["a", "b"], "debug": true}"#)
+    ///     .unwrap();
+    /// let target = JsonParser::parse_from_bytes(br#"{"name": "app", "tags": ["a"], "port": 80}"#)
+    ///     .unwrap();
+    /// let patch = source.diff(&target);
+    ///
+    /// let lines: Vec<_> = patch.iter().map(ToString::to_string).collect();
+    /// assert_eq!(lines, ["remove /debug", "remove /tags/1", "add /port: 80"]);
+    /// assert_eq!(
+    ///     patch.to_json().to_string_compact(),
+    ///     r#"[{"op":"remove","path":"/debug"},{"op":"remove","path":"/tags/1"},{"op":"add","path":"/port","value":80}]"#
+    /// );
+    /// assert!(source.diff(&source).is_empty());
+    /// ```
+    #[must_use]
+    pub fn diff(&self, target: &Value) -> Vec<PatchOperation> {
+        let mut operations = Vec::new();
+        diff(self, target, &mut String::new(), &mut operations);
+
+        operations
+    }
+
+    /// Apply a JSON Patch, such as one made by [`diff`](Value::diff).
+    ///
+    /// The operations are applied in order, and either all of them are or, if any fails, none
+    /// is.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Patch`] for the first operation which refers to a value that
+    /// doesn't exist, or whose `test` fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::convert::FromJson;
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::patch::PatchOperation;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"name": "app", "tags": ["a"]}"#).unwrap();
+    /// let patch = JsonParser::parse_from_bytes(
+    ///     br#"[
+    ///         {"op": "test", "path": "/name", "value": "app"},
+    ///         {"op": "add", "path": "/tags/-", "value": "b"},
+    ///         {"op": "move", "from": "/name", "path": "/title"}
+    ///     ]"#,
+    /// )
+    /// .unwrap();
+    /// let patch = Vec::<PatchOperation>::from_json(&patch).unwrap();
+    ///
+    /// value.apply_patch(&patch).unwrap();
+    /// assert_eq!(value.to_string_compact(), r#"{"tags":["a","b"],"title":"app"}"#);
+    ///
+    /// // The failing test leaves the value untouched.
+    /// let failing = [
+    ///     PatchOperation::Remove { path: "/tags".to_string() },
+    ///     PatchOperation::Test { path: "/title".to_string(), value: "other".into() },
+    /// ];
+    /// assert!(value.apply_patch(&failing).is_err());
+    /// assert_eq!(value.to_string_compact(), r#"{"tags":["a","b"],"title":"app"}"#);
+    ///
+    /// // Numbers are tested by value, however they're written.
+    /// let mut limits = JsonParser::parse_from_bytes(br#"{"sizes": [1, {"max": 200}]}"#).unwrap();
+    /// let expected = JsonParser::parse_from_bytes(br#"[1.0, {"max": 2e2}]"#).unwrap();
+    /// let test = [PatchOperation::Test { path: "/sizes".to_string(), value: expected }];
+    /// assert!(limits.apply_patch(&test).is_ok());
+    /// ```
+    pub fn apply_patch(&mut self, patch: &[PatchOperation]) -> Result<(), JsonError> {
+        let mut patched = self.clone();
+        for (position, operation) in patch.iter().enumerate() {
+            apply(&mut patched, operation).map_err(|message| JsonError::Patch {
+                operation: position,
+                message,
+            })?;
+        }
+        *self = patched;
+
+        Ok(())
+    }
+
+    /// Apply a JSON Merge Patch, as described in RFC 7396.
+    ///
+    /// A merge patch is a value shaped like the document it modifies: the members of objects
+    /// are merged recursively, members set to `null` are removed, and anything else replaces
+    /// the value it corresponds to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(
+    ///     br#"{"title": "Hello", "author": {"name": "Ada", "email": "ada@example.com"}, "tags": ["a"]}"#,
+    /// )
+    /// .unwrap();
+    /// let patch = JsonParser::parse_from_bytes(
+    ///     br#"{"title": "Goodbye", "author": {"email": null}, "tags": ["b"]}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// value.merge_patch(&patch);
+    /// assert_eq!(
+    ///     value.to_string_compact(),
+    ///     r#"{"title":"Goodbye","author":{"name":"Ada"},"tags":["b"]}"#
+    /// );
+    /// ```
+    pub fn merge_patch(&mut self, patch: &Value) {
+        let Value::Object(patch) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, Value::Object(_)) {
+            *self = Value::Object(Map::new());
+        }
+        let Value::Object(target) = self else {
+            unreachable!("the value was just made an object");
+        };
+
+        for (key, value) in patch {
+            if *value == Value::Null {
+                target.remove(key);
+            } else if let Some(existing) = target.get_mut(key) {
+                existing.merge_patch(value);
+            } else {
+                // Merging into `null` removes the `null` members of nested objects.
+                let mut added = Value::Null;
+                added.merge_patch(value);
+                target.insert(key.to_string(), added);
+            }
+        }
+    }
+}
+
+/// Append the operations turning `source` into `target` to `operations`, both being located
+/// at `pointer`.
+fn diff(
+    source: &Value,
+    target: &Value,
+    pointer: &mut String,
+    operations: &mut Vec<PatchOperation>,
+) {
+    if source == target {
+        return;
+    }
+
+    let length = pointer.len();
+    match (source, target) {
+        (Value::Object(source), Value::Object(target)) => {
+            for (key, _) in source {
+                if !target.contains_key(key) {
+                    push_token(pointer, key);
+                    operations.push(PatchOperation::Remove {
+                        path: pointer.clone(),
+                    });
+                    pointer.truncate(length);
+                }
+            }
+            for (key, value) in target {
+                push_token(pointer, key);
+                match source.get(key) {
+                    Some(old) => diff(old, value, pointer, operations),
+                    None => operations.push(PatchOperation::Add {
+                        path: pointer.clone(),
+                        value: value.clone(),
+                    }),
+                }
+                pointer.truncate(length);
+            }
+        }
+        (Value::Array(source), Value::Array(target)) => {
+            let common = source.len().min(target.len());
+            for (index, (old, new)) in source.iter().zip(target).enumerate() {
+                push_token(pointer, &index.to_string());
+                diff(old, new, pointer, operations);
+                pointer.truncate(length);
+            }
+            for (index, value) in target.iter().enumerate().skip(common) {
+                push_token(pointer, &index.to_string());
+                operations.push(PatchOperation::Add {
+                    path: pointer.clone(),
+                    value: value.clone(),
+                });
+                pointer.truncate(length);
+            }
+            // Remove from the end, so that indices stay valid.
+            for index in (common..source.len()).rev() {
+                push_token(pointer, &index.to_string());
+                operations.push(PatchOperation::Remove {
+                    path: pointer.clone(),
+                });
+                pointer.truncate(length);
+            }
+        }
+        _ => operations.push(PatchOperation::Replace {
+            path: pointer.clone(),
+            value: target.clone(),
+        }),
+    }
+}
+
+/// Apply one operation, describing the problem if it can't be.
+fn apply(document: &mut Value, operation: &PatchOperation) -> Result<(), String> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone()),
+        PatchOperation::Remove { path } => remove(document, path).map(drop),
+        PatchOperation::Replace { path, value } => {
+            *find_mut(document, path)? = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                return Err(format!("can't move {from} into one of its children"));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = document
+                .pointer(from)
+                .ok_or_else(|| format!("no value at {from}"))?
+                .clone();
+            add(document, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            let actual = document
+                .pointer(path)
+                .ok_or_else(|| format!("no value at {path}"))?;
+            if equal(actual, value) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "test of {path} failed: expected {}, found {}",
+                    value.to_string_compact(),
+                    actual.to_string_compact()
+                ))
+            }
+        }
+    }
+}
+
+/// Whether two values are equal as the `test` operation requires, comparing numbers by their
+/// value so that `1` and `1.0` are equal.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(Number::I64(integer)), Value::Number(Number::F64(float)))
+        | (Value::Number(Number::F64(float)), Value::Number(Number::I64(integer))) => {
+            // Every integer in range converts exactly, unlike `integer as f64`.
+            float.fract() == 0.0
+                && *float >= -(2f64.powi(63))
+                && *float < 2f64.powi(63)
+                && *float as i64 == *integer
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| equal(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+fn find_mut<'v>(document: &'v mut Value, path: &str) -> Result<&'v mut Value, String> {
+    document
+        .pointer_mut(path)
+        .ok_or_else(|| format!("no value at {path}"))
+}
+
+/// Split a pointer into the pointer of its parent and its last reference token, or return
+/// `None` for the empty pointer, which refers to the whole document.
+fn split_last(path: &str) -> Result<Option<(&str, String)>, String> {
+    if path.is_empty() {
+        return Ok(None);
+    }
+    let (Some(_), Some((parent, token))) = (path.strip_prefix('/'), path.rsplit_once('/')) else {
+        return Err(format!("invalid JSON Pointer `{path}`"));
+    };
+
+    Ok(Some((parent, token.replace("~1", "/").replace("~0", "~"))))
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let Some((parent, token)) = split_last(path)? else {
+        *document = value;
+        return Ok(());
+    };
+
+    match find_mut(document, parent)? {
+        Value::Object(members) => {
+            members.insert(token, value);
+        }
+        Value::Array(elements) if token == "-" => elements.push(value),
+        Value::Array(elements) => match parse_index(&token) {
+            Some(index) if index <= elements.len() => elements.insert(index, value),
+            _ => return Err(format!("no element at {path}")),
+        },
+        other => {
+            return Err(format!(
+                "can't add to {}, which is {}",
+                parent,
+                other.type_name()
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, String> {
+    let Some((parent, token)) = split_last(path)? else {
+        return Err("can't remove the whole document".to_string());
+    };
+
+    let removed = match find_mut(document, parent)? {
+        Value::Object(members) => members.remove(&token),
+        Value::Array(elements) => parse_index(&token)
+            .filter(|&index| index < elements.len())
+            .map(|index| elements.remove(index)),
+        _ => None,
+    };
+
+    removed.ok_or_else(|| format!("no value at {path}"))
+}
+
+impl PatchOperation {
+    /// The name of the operation, as written in the `op` member of its JSON form.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+            PatchOperation::Move { .. } => "move",
+            PatchOperation::Copy { .. } => "copy",
+            PatchOperation::Test { .. } => "test",
+        }
+    }
+}
+
+impl Display for PatchOperation {
+    /// Describe the operation on one line, such as `add /port: 80` or `move /a to /b`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name();
+        match self {
+            PatchOperation::Add { path, value }
+            | PatchOperation::Replace { path, value }
+            | PatchOperation::Test { path, value } => {
+                write!(f, "{name} {path}: {}", value.to_string_compact())
+            }
+            PatchOperation::Remove { path } => write!(f, "{name} {path}"),
+            PatchOperation::Move { from, path } | PatchOperation::Copy { from, path } => {
+                write!(f, "{name} {from} to {path}")
+            }
+        }
+    }
+}
+
+impl ToJson for PatchOperation {
+    fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        object.insert("op".to_string(), Value::from(self.name()));
+        match self {
+            PatchOperation::Add { path, value }
+            | PatchOperation::Replace { path, value }
+            | PatchOperation::Test { path, value } => {
+                object.insert("path".to_string(), Value::from(path.as_str()));
+                object.insert("value".to_string(), value.clone());
+            }
+            PatchOperation::Remove { path } => {
+                object.insert("path".to_string(), Value::from(path.as_str()));
+            }
+            PatchOperation::Move { from, path } | PatchOperation::Copy { from, path } => {
+                object.insert("from".to_string(), Value::from(from.as_str()));
+                object.insert("path".to_string(), Value::from(path.as_str()));
+            }
+        }
+
+        Value::Object(object)
+    }
+}
+
+impl FromJson for PatchOperation {
+    fn from_json(value: &Value) -> Result<Self, JsonError> {
+        let Value::Object(object) = value else {
+            return Err(JsonError::TypeMismatch {
+                pointer: String::new(),
+                expected: "PatchOperation".to_string(),
+                found: value.type_name(),
+            });
+        };
+        let member = |name: &str| {
+            object.get(name).ok_or_else(|| JsonError::MissingField {
+                pointer: String::new(),
+                field: name.to_string(),
+            })
+        };
+        let string =
+            |name: &str| String::from_json(member(name)?).map_err(|error| error.nested(name));
+
+        let operation = string("op")?;
+        Ok(match operation.as_str() {
+            "add" => PatchOperation::Add {
+                path: string("path")?,
+                value: member("value")?.clone(),
+            },
+            "remove" => PatchOperation::Remove {
+                path: string("path")?,
+            },
+            "replace" => PatchOperation::Replace {
+                path: string("path")?,
+                value: member("value")?.clone(),
+            },
+            "move" => PatchOperation::Move {
+                from: string("from")?,
+                path: string("path")?,
+            },
+            "copy" => PatchOperation::Copy {
+                from: string("from")?,
+                path: string("path")?,
+            },
+            "test" => PatchOperation::Test {
+                path: string("path")?,
+                value: member("value")?.clone(),
+            },
+            _ => {
+                return Err(JsonError::UnknownVariant {
+                    pointer: "/op".to_string(),
+                    variant: operation,
+                })
+            }
+        })
+    }
+}
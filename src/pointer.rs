@@ -0,0 +1,93 @@
+use crate::value::Value;
+
+/// Append `token` to a JSON Pointer as a new reference token, escaping `~` as `~0` and `/` as
+/// `~1` as described in RFC 6901.
+pub(crate) fn push_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+
+    for character in token.chars() {
+        match character {
+            '~' => pointer.push_str("~0"),
+            '/' => pointer.push_str("~1"),
+            other => pointer.push(other),
+        }
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens, or return `None` if it doesn't
+/// start with a `/`. The empty pointer refers to the whole document and has no tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let tokens = pointer.strip_prefix('/')?.split('/');
+    Some(
+        tokens
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Parse a reference token as an array index, which can't have leading zeros or a sign.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if (token.len() > 1 && token.starts_with('0')) || token.starts_with('+') {
+        return None;
+    }
+
+    token.parse().ok()
+}
+
+impl Value {
+    /// Find the value referenced by a JSON Pointer, such as `/servers/0/port`, as described in
+    /// RFC 6901.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"servers": [{"port": 80}], "a/b": 1}"#)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(value.pointer("/servers/0/port"), Some(&Value::Number(Number::I64(80))));
+    /// assert_eq!(value.pointer("/a~1b"), Some(&Value::Number(Number::I64(1))));
+    /// assert_eq!(value.pointer("/servers/1"), None);
+    /// assert_eq!(value.pointer(""), Some(&value));
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| match value {
+                Value::Object(members) => members.get(token),
+                Value::Array(elements) => elements.get(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Find the value referenced by a JSON Pointer, like [`pointer`](Value::pointer), to modify
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"servers": [{"port": 80}]}"#).unwrap();
+    /// *value.pointer_mut("/servers/0/port").unwrap() = Value::from("auto");
+    ///
+    /// assert_eq!(value.pointer("/servers/0/port"), Some(&Value::from("auto")));
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| match value {
+                Value::Object(members) => members.get_mut(token),
+                Value::Array(elements) => elements.get_mut(parse_index(token)?),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,121 @@
+//! [proptest](https://docs.rs/proptest) strategies generating [`Value`]s, to property-test code
+//! built on this crate.
+//!
+//! [`Value`] implements [`Arbitrary`], so `any::<Value>()` gives documents of every shape, and
+//! `any_with::<Value>(params)` bounds their depth and size with [`Params`]. Numbers are finite,
+//! as JSON has no way to write the others, and strings hold any characters, to exercise
+//! escaping. [`round_trip`] checks a value survives being written and parsed again.
+
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::value::{Number, Value};
+use ::proptest::prelude::*;
+use ::proptest::test_runner::TestCaseError;
+
+/// Bounds of the values generated by [`any_with::<Value>`](any_with).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// Deepest level of nesting of arrays and objects, where 0 only gives scalars.
+    pub max_depth: u32,
+    /// Number of values, nested ones included, a document is aimed to hold. Documents may
+    /// hold a few more, as only the depth is a hard limit.
+    pub size: u32,
+    /// Most items of an array or members of an object, and most characters of a string.
+    pub max_len: usize,
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            max_depth: 4,
+            size: 64,
+            max_len: 8,
+        }
+    }
+}
+
+impl Arbitrary for Value {
+    type Parameters = Params;
+    type Strategy = BoxedStrategy<Value>;
+
+    fn arbitrary_with(params: Params) -> BoxedStrategy<Value> {
+        let max_len = params.max_len;
+        let string = move || {
+            ::proptest::collection::vec(any::<char>(), 0..=max_len)
+                .prop_map(|characters| characters.into_iter().collect::<String>())
+        };
+        let scalar = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Boolean),
+            any::<i64>().prop_map(|integer| Value::Number(Number::I64(integer))),
+            (::proptest::num::f64::NORMAL
+                | ::proptest::num::f64::SUBNORMAL
+                | ::proptest::num::f64::ZERO)
+                .prop_map(|float| Value::Number(Number::F64(float))),
+            string().prop_map(Value::from),
+        ];
+
+        scalar
+            .prop_recursive(
+                params.max_depth,
+                params.size,
+                max_len as u32,
+                move |inner| {
+                    prop_oneof![
+                        ::proptest::collection::vec(inner.clone(), 0..=max_len)
+                            .prop_map(Value::Array),
+                        ::proptest::collection::vec((string(), inner), 0..=max_len).prop_map(
+                            |members| Value::Object(members.into_iter().collect::<Map>())
+                        ),
+                    ]
+                },
+            )
+            .boxed()
+    }
+}
+
+/// Check that `value` gives back an equal value once written, compact as well as pretty
+/// printed, and parsed again.
+///
+/// # Errors
+///
+/// Returns a [`TestCaseError`] failing the test case, along with the JSON that didn't parse
+/// back, so this can be used with `?` inside [`proptest!`].
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::proptest::{round_trip, Params};
+/// use json_parser::value::Value;
+/// use proptest::prelude::*;
+///
+/// proptest! {
+///     # /*
+///     #[test]
+///     # */
+///     fn values_round_trip(value in any::<Value>()) {
+///         round_trip(&value)?;
+///     }
+///
+///     # /*
+///     #[test]
+///     # */
+///     fn shallow_values_round_trip(
+///         value in any_with::<Value>(Params { max_depth: 1, size: 8, max_len: 4 }),
+///     ) {
+///         prop_assert!(value.stats().max_depth <= 2);
+///         round_trip(&value)?;
+///     }
+/// }
+/// # values_round_trip();
+/// # shallow_values_round_trip();
+/// ```
+pub fn round_trip(value: &Value) -> Result<(), TestCaseError> {
+    for json in [value.to_string_compact(), value.to_string_pretty()] {
+        let parsed = JsonParser::parse_from_bytes(json.as_bytes())
+            .map_err(|error| TestCaseError::fail(format!("{json} doesn't parse: {error}")))?;
+        prop_assert_eq!(&parsed, value, "{} parses back differently", json);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::value::Value;
+
+/// Options controlling which members [`Value::prune`] removes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneOptions {
+    /// Remove members whose value is `null`.
+    pub nulls: bool,
+    /// Remove members whose value is an empty object, including objects that only become empty
+    /// after their own members were pruned.
+    pub empty_objects: bool,
+    /// Remove members whose value is an empty array, including arrays that only become empty
+    /// after their own elements were pruned.
+    pub empty_arrays: bool,
+    /// Also remove matching elements from arrays, and not only members from objects.
+    ///
+    /// This is disabled by default since it shifts the position of the remaining elements.
+    pub array_elements: bool,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        PruneOptions {
+            nulls: true,
+            empty_objects: true,
+            empty_arrays: true,
+            array_elements: false,
+        }
+    }
+}
+
+impl PruneOptions {
+    /// Whether a value that has already had its children pruned should be removed.
+    fn matches(&self, value: &Value) -> bool {
+        match value {
+            Value::Null => self.nulls,
+            Value::Object(object) => self.empty_objects && object.is_empty(),
+            Value::Array(array) => self.empty_arrays && array.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    /// Recursively remove `null` members, empty objects and empty arrays.
+    ///
+    /// Pruning happens bottom-up, so a container which only holds values that get pruned is
+    /// removed as well. The root value itself is never removed, but can end up empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::prune::PruneOptions;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"a":null,"b":{"c":[]},"d":1}"#).unwrap();
+    /// value.prune(PruneOptions::default());
+    ///
+    /// assert_eq!(value, JsonParser::parse_from_bytes(br#"{"d":1}"#).unwrap());
+    /// ```
+    pub fn prune(&mut self, options: PruneOptions) {
+        match self {
+            Value::Object(object) => {
+                object.retain(|_, value| {
+                    value.prune(options);
+                    !options.matches(value)
+                });
+            }
+            Value::Array(array) => {
+                array.retain_mut(|value| {
+                    value.prune(options);
+                    !(options.array_elements && options.matches(value))
+                });
+            }
+            _ => {}
+        }
+    }
+}
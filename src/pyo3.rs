@@ -0,0 +1,243 @@
+//! A Python extension module, `json_parser`, parsing JSON into native Python objects.
+//!
+//! Built as a shared library, for instance with
+//! `cargo rustc --lib --release --features pyo3 --crate-type cdylib` and renamed `json_parser.so`,
+//! the module exports three functions:
+//!
+//! ```python
+//! import json_parser
+//!
+//! config = json_parser.loads(b'{"servers": [{"port": 80}]}', invalid_utf8="replace")
+//! records = json_parser.load("records.json")  # read in chunks, not all at once
+//! print(json_parser.dumps(config, pretty=True))
+//! ```
+//!
+//! Objects become `dict`s, arrays `list`s, and numbers `int`s or `float`s like they were
+//! written. Errors in the input raise `ValueError`, and failing to read a file `OSError`.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::{InvalidUtf8, JsonParser, LoneSurrogate, ParserOptions};
+use crate::ser::{self, Layout};
+use crate::value::{Number, Value};
+use ::pyo3::exceptions::{PyOSError, PyTypeError, PyValueError};
+use ::pyo3::prelude::*;
+use ::pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use std::fs::File;
+
+impl<'py> IntoPyObject<'py> for &Value {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    /// Convert the value into the Python object `json.loads` would return for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    /// use pyo3::prelude::*;
+    /// use pyo3::types::PyDict;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"ids": [1, 2.5, null]}"#).unwrap();
+    ///
+    /// Python::initialize();
+    /// Python::attach(|py| {
+    ///     let object = (&value).into_pyobject(py).unwrap();
+    ///     assert!(object.is_instance_of::<PyDict>());
+    ///     assert_eq!(object.repr().unwrap().to_string(), "{'ids': [1, 2.5, None]}");
+    ///     assert_eq!(object.extract::<Value>().unwrap(), value);
+    /// });
+    /// ```
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(match self {
+            Value::Null => py.None().into_bound(py),
+            Value::Boolean(boolean) => PyBool::new(py, *boolean).to_owned().into_any(),
+            Value::Number(Number::I64(integer)) => integer.into_pyobject(py)?.into_any(),
+            Value::Number(Number::F64(float)) => PyFloat::new(py, *float).into_any(),
+            Value::String(string) => PyString::new(py, string).into_any(),
+            Value::Array(elements) => PyList::new(py, elements)?.into_any(),
+            Value::Object(members) => {
+                let dict = PyDict::new(py);
+                for (key, value) in members {
+                    dict.set_item(key, value)?;
+                }
+                dict.into_any()
+            }
+        })
+    }
+}
+
+impl FromPyObject<'_, '_> for Value {
+    type Error = PyErr;
+
+    /// Convert a Python object made of what `json.loads` returns, as well as tuples.
+    ///
+    /// # Errors
+    ///
+    /// Raises `ValueError` for integers out of the range of `i64` and floats which aren't
+    /// finite, and `TypeError` for other types of
+    /// objects, or dictionaries with keys which aren't strings.
+    fn extract(object: Borrowed<'_, '_, PyAny>) -> PyResult<Self> {
+        if object.is_none() {
+            return Ok(Value::Null);
+        }
+        // Booleans are integers in Python, so they're checked first.
+        if let Ok(boolean) = object.cast::<PyBool>() {
+            return Ok(Value::Boolean(boolean.is_true()));
+        }
+        if let Ok(integer) = object.cast::<PyInt>() {
+            return integer
+                .extract()
+                .map(|integer| Value::Number(Number::I64(integer)))
+                .map_err(|_| PyValueError::new_err(format!("integer {} out of range", *integer)));
+        }
+        if let Ok(float) = object.cast::<PyFloat>() {
+            let float = float.value();
+            if !float.is_finite() {
+                return Err(PyValueError::new_err(format!(
+                    "{float} can't be represented in JSON"
+                )));
+            }
+            return Ok(Value::Number(Number::F64(float)));
+        }
+        if let Ok(string) = object.cast::<PyString>() {
+            return Ok(Value::from(string.to_str()?));
+        }
+        if let Ok(list) = object.cast::<PyList>() {
+            return list
+                .iter()
+                .map(|element| element.extract())
+                .collect::<PyResult<_>>()
+                .map(Value::Array);
+        }
+        if let Ok(tuple) = object.cast::<PyTuple>() {
+            return tuple
+                .iter()
+                .map(|element| element.extract())
+                .collect::<PyResult<_>>()
+                .map(Value::Array);
+        }
+        if let Ok(dict) = object.cast::<PyDict>() {
+            let mut members = Map::with_capacity(dict.len());
+            for (key, value) in dict.iter() {
+                let Ok(key) = key.cast::<PyString>() else {
+                    return Err(PyTypeError::new_err(format!(
+                        "keys must be str, not {}",
+                        key.get_type().name()?
+                    )));
+                };
+                members.insert(key.to_str()?.to_string(), value.extract()?);
+            }
+            return Ok(Value::Object(members));
+        }
+
+        Err(PyTypeError::new_err(format!(
+            "Object of type {} is not JSON serializable",
+            object.get_type().name()?
+        )))
+    }
+}
+
+fn to_py_err(error: JsonError) -> PyErr {
+    match error {
+        JsonError::Io { .. } => PyOSError::new_err(error.to_string()),
+        _ => PyValueError::new_err(error.to_string()),
+    }
+}
+
+fn options(
+    invalid_utf8: &str,
+    lone_surrogates: &str,
+    intern_keys: bool,
+) -> PyResult<ParserOptions> {
+    let invalid_utf8 = match invalid_utf8 {
+        "error" => InvalidUtf8::Error,
+        "replace" => InvalidUtf8::Replace,
+        "skip" => InvalidUtf8::Skip,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "invalid_utf8 must be 'error', 'replace' or 'skip', not {invalid_utf8:?}"
+            )))
+        }
+    };
+    let lone_surrogates = match lone_surrogates {
+        "error" => LoneSurrogate::Error,
+        "replace" => LoneSurrogate::Replace,
+        "escape" => LoneSurrogate::Escape,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "lone_surrogates must be 'error', 'replace' or 'escape', not {lone_surrogates:?}"
+            )))
+        }
+    };
+
+    Ok(ParserOptions {
+        invalid_utf8,
+        lone_surrogates,
+        intern_keys,
+    })
+}
+
+/// Parse JSON from a `str` or `bytes` object.
+#[pyfunction]
+#[pyo3(signature = (data, *, invalid_utf8 = "error", lone_surrogates = "replace", intern_keys = false))]
+fn loads<'py>(
+    py: Python<'py>,
+    data: &Bound<'py, PyAny>,
+    invalid_utf8: &str,
+    lone_surrogates: &str,
+    intern_keys: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let options = options(invalid_utf8, lone_surrogates, intern_keys)?;
+    let value = if let Ok(bytes) = data.cast::<PyBytes>() {
+        JsonParser::parse_from_bytes_with_options(bytes.as_bytes(), &options)
+    } else {
+        let text = data.cast::<PyString>()?.to_str()?;
+        JsonParser::parse_from_bytes_with_options(text.as_bytes(), &options)
+    };
+
+    value.map_err(to_py_err)?.into_pyobject(py)
+}
+
+/// Parse JSON from the file at `path`, which is read in chunks as it's parsed.
+#[pyfunction]
+#[pyo3(signature = (path, *, invalid_utf8 = "error", lone_surrogates = "replace", intern_keys = false))]
+fn load<'py>(
+    py: Python<'py>,
+    path: std::path::PathBuf,
+    invalid_utf8: &str,
+    lone_surrogates: &str,
+    intern_keys: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let options = options(invalid_utf8, lone_surrogates, intern_keys)?;
+    let file = File::open(path)?;
+    let value = JsonParser::parse_from_file_with_options(file, &options).map_err(to_py_err)?;
+
+    value.into_pyobject(py)
+}
+
+/// Write a Python object as JSON text, indented by two spaces per level if `pretty` is set.
+#[pyfunction]
+#[pyo3(signature = (object, *, pretty = false))]
+fn dumps(object: Value, pretty: bool) -> PyResult<String> {
+    let text = object.to_string_compact();
+    if !pretty {
+        return Ok(text);
+    }
+
+    let mut output = Vec::with_capacity(text.len() * 2);
+    ser::reformat(text.as_bytes(), &mut output, Layout::Pretty).map_err(to_py_err)?;
+
+    String::from_utf8(output).map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+#[pymodule]
+fn json_parser(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(loads, module)?)?;
+    module.add_function(wrap_pyfunction!(load, module)?)?;
+    module.add_function(wrap_pyfunction!(dumps, module)?)?;
+
+    Ok(())
+}
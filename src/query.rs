@@ -0,0 +1,237 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+
+/// A single bracketed step of a query string key.
+enum Segment {
+    /// `name` or `[name]`, a member of an object.
+    Key(String),
+    /// `[]`, a new element at the end of an array.
+    Push,
+}
+
+impl Value {
+    /// Decode a URL query string such as `a=1&b[]=2&b[]=3&c[d]=4` into an object.
+    ///
+    /// Keys and values are percent-decoded, with `+` standing for a space. A key followed by
+    /// `[name]` sets a member of a nested object, and a key ending in `[]` appends to an array.
+    /// Values are always strings, except for keys without any `=` which map to `null`. When a
+    /// key is repeated, the last value wins.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::InvalidPath`] if a key has unbalanced brackets or a `[]` which
+    /// isn't at its end, [`JsonError::PathConflict`] if two keys describe incompatible
+    /// structures, such as `a=1` and `a[b]=2`, and [`JsonError::Format`] if a percent-decoded
+    /// component isn't valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let value = Value::from_query_string("q=rust+json&tags[]=a&tags[]=b&page[size]=10").unwrap();
+    /// let expected = JsonParser::parse_from_bytes(
+    ///     br#"{"q":"rust json","tags":["a","b"],"page":{"size":"10"}}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(value, expected);
+    /// assert_eq!(value.to_query_string().unwrap(), "q=rust+json&tags[]=a&tags[]=b&page[size]=10");
+    /// ```
+    pub fn from_query_string(input: &str) -> Result<Value, JsonError> {
+        let mut root = Value::Object(Map::new());
+
+        for pair in input.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = match pair.split_once('=') {
+                Some((key, value)) => (key, Value::from(decode(value)?)),
+                None => (pair, Value::Null),
+            };
+
+            let segments = parse_key(key)?;
+            insert(&mut root, &segments, value)
+                .ok_or_else(|| JsonError::PathConflict(key.to_string()))?;
+        }
+
+        Ok(root)
+    }
+
+    /// Encode an object as a URL query string, following the conventions of
+    /// [`from_query_string`].
+    ///
+    /// Numbers and booleans are written as text, `null` is written as a key without a value,
+    /// and empty objects and arrays are left out since they have no representation.
+    ///
+    /// [`from_query_string`]: Value::from_query_string
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] if the value isn't an object, or if an array holds
+    /// arrays or objects.
+    pub fn to_query_string(&self) -> Result<String, JsonError> {
+        let Value::Object(object) = self else {
+            return Err(JsonError::TypeMismatch {
+                pointer: String::new(),
+                expected: "object".to_string(),
+                found: self.type_name(),
+            });
+        };
+
+        let mut pairs = Vec::new();
+        for (key, value) in object {
+            encode_member(&mut pairs, encode(key), value).map_err(|error| error.nested(key))?;
+        }
+
+        Ok(pairs.join("&"))
+    }
+}
+
+/// Split a raw key such as `a[b][]` into its segments, decoding each of them.
+fn parse_key(key: &str) -> Result<Vec<Segment>, JsonError> {
+    let invalid = || JsonError::InvalidPath(key.to_string());
+
+    let (name, mut rest) = key.split_at(key.find('[').unwrap_or(key.len()));
+    if name.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut segments = vec![Segment::Key(decode(name)?)];
+    while !rest.is_empty() {
+        let Some((inner, remaining)) = rest.strip_prefix('[').and_then(|rest| rest.split_once(']'))
+        else {
+            return Err(invalid());
+        };
+
+        if let Some(Segment::Push) = segments.last() {
+            return Err(invalid());
+        }
+        segments.push(if inner.is_empty() {
+            Segment::Push
+        } else {
+            Segment::Key(decode(inner)?)
+        });
+        rest = remaining;
+    }
+
+    Ok(segments)
+}
+
+/// Store `value` at the position described by `segments`, or return `None` if the position
+/// is already taken by a value of an incompatible shape.
+fn insert(target: &mut Value, segments: &[Segment], value: Value) -> Option<()> {
+    let Some((segment, rest)) = segments.split_first() else {
+        // Later values replace earlier ones, unless the earlier one has nested members.
+        if let Value::Array(_) | Value::Object(_) = target {
+            return None;
+        }
+        *target = value;
+        return Some(());
+    };
+
+    match (segment, target) {
+        (Segment::Key(key), Value::Object(object)) => {
+            if !object.contains_key(key) {
+                let slot = match rest.first() {
+                    None => Value::Null,
+                    Some(Segment::Key(_)) => Value::Object(Map::new()),
+                    Some(Segment::Push) => Value::Array(Vec::new()),
+                };
+                object.insert(key.clone(), slot);
+            }
+
+            insert(object.get_mut(key)?, rest, value)
+        }
+        (Segment::Push, Value::Array(array)) => {
+            array.push(value);
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+/// Append the pairs for a member whose (already encoded) key is `prefix`.
+fn encode_member(pairs: &mut Vec<String>, prefix: String, value: &Value) -> Result<(), JsonError> {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                encode_member(pairs, format!("{prefix}[{}]", encode(key)), value)
+                    .map_err(|error| error.nested(key))?;
+            }
+        }
+        Value::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                if let Value::Array(_) | Value::Object(_) = element {
+                    return Err(JsonError::TypeMismatch {
+                        pointer: String::new(),
+                        expected: "string, number, boolean or null".to_string(),
+                        found: element.type_name(),
+                    }
+                    .nested(&index.to_string()));
+                }
+                encode_member(pairs, format!("{prefix}[]"), element)?;
+            }
+        }
+        Value::Null => pairs.push(prefix),
+        Value::Boolean(boolean) => pairs.push(format!("{prefix}={boolean}")),
+        Value::Number(Number::I64(integer)) => pairs.push(format!("{prefix}={integer}")),
+        Value::Number(Number::F64(float)) => {
+            pairs.push(format!("{prefix}={}", encode(&float.to_string())))
+        }
+        Value::String(string) => pairs.push(format!("{prefix}={}", encode(string))),
+    }
+
+    Ok(())
+}
+
+/// Percent-encode everything but unreserved characters, writing spaces as `+`.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char);
+            }
+            b' ' => encoded.push('+'),
+            byte => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+/// Percent-decode a component, reading `+` as a space. Malformed escapes are kept as is.
+fn decode(text: &str) -> Result<String, JsonError> {
+    percent_decode(text, true).ok_or_else(|| JsonError::Format {
+        format: "query string",
+        message: format!("`{text}` doesn't decode to valid UTF-8"),
+    })
+}
+
+/// Percent-decode `text`, keeping malformed escapes as is, and reading `+` as a space if
+/// `plus_as_space` is set. Returns `None` if the decoded bytes aren't valid UTF-8.
+pub(crate) fn percent_decode(text: &str, plus_as_space: bool) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (b'+', _) if plus_as_space => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8(decoded).ok()
+}
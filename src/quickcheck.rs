@@ -0,0 +1,140 @@
+//! [quickcheck](https://docs.rs/quickcheck) support for [`Value`], to property-test code built
+//! on this crate.
+//!
+//! [`Value`] implements [`Arbitrary`], generating documents of every shape which shrink
+//! towards smaller ones, and [`value`] bounds their depth. The [size](Gen::size) of the
+//! generator bounds the number of values a document holds. Numbers are finite, as JSON has no
+//! way to write the others, and strings hold any characters, to exercise escaping.
+//! [`round_trip`] checks a value survives being written and parsed again.
+
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::value::{Number, Value};
+use ::quickcheck::{Arbitrary, Gen, TestResult};
+
+/// Deepest level of nesting of the values given by [`Value::arbitrary`].
+const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Generate a value with arrays and objects nested at most `max_depth` levels deep, where 0
+/// only gives scalars.
+///
+/// The value holds up to [`g.size()`](Gen::size) values, nested ones included, which the
+/// items of arrays and the members of objects share.
+pub fn value(g: &mut Gen, max_depth: usize) -> Value {
+    generate(g, max_depth, g.size())
+}
+
+fn generate(g: &mut Gen, depth: usize, size: usize) -> Value {
+    let kinds = if depth == 0 || size <= 1 { 5 } else { 7 };
+    match u8::arbitrary(g) % kinds {
+        0 => Value::Null,
+        1 => Value::Boolean(bool::arbitrary(g)),
+        2 => Value::Number(Number::I64(i64::arbitrary(g))),
+        3 => Value::Number(Number::F64(finite(g))),
+        4 => Value::from(String::arbitrary(g)),
+        kind => {
+            // Every item gets an even share of what's left once the items themselves are
+            // counted.
+            let len = usize::arbitrary(g) % size;
+            let share = (size - 1 - len) / len.max(1) + 1;
+            if kind == 5 {
+                Value::Array((0..len).map(|_| generate(g, depth - 1, share)).collect())
+            } else {
+                let members = (0..len)
+                    .map(|_| (String::arbitrary(g), generate(g, depth - 1, share)))
+                    .collect::<Map>();
+                Value::Object(members)
+            }
+        }
+    }
+}
+
+fn finite(g: &mut Gen) -> f64 {
+    loop {
+        let float = f64::arbitrary(g);
+        if float.is_finite() {
+            return float;
+        }
+    }
+}
+
+impl Arbitrary for Value {
+    fn arbitrary(g: &mut Gen) -> Value {
+        value(g, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Shrink arrays and objects to each of their items first, then to fewer or smaller
+    /// items, and scalars as their Rust equivalent.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Value>> {
+        match self {
+            Value::Null => ::quickcheck::empty_shrinker(),
+            Value::Boolean(boolean) => Box::new(boolean.shrink().map(Value::Boolean)),
+            Value::Number(Number::I64(integer)) => Box::new(
+                integer
+                    .shrink()
+                    .map(|integer| Value::Number(Number::I64(integer))),
+            ),
+            Value::Number(Number::F64(float)) => Box::new(
+                float
+                    .shrink()
+                    .filter(|float| float.is_finite())
+                    .map(|float| Value::Number(Number::F64(float))),
+            ),
+            Value::String(string) => Box::new(string.to_string().shrink().map(Value::from)),
+            Value::Array(items) => Box::new(
+                items
+                    .clone()
+                    .into_iter()
+                    .chain(items.shrink().map(Value::Array)),
+            ),
+            Value::Object(object) => {
+                let members = object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.clone()))
+                    .collect::<Vec<_>>();
+                Box::new(
+                    object
+                        .values()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .chain(
+                            members
+                                .shrink()
+                                .map(|members| Value::Object(members.into_iter().collect())),
+                        ),
+                )
+            }
+        }
+    }
+}
+
+/// Check that `value` gives back an equal value once written, compact as well as pretty
+/// printed, and parsed again, failing with the JSON that didn't parse back otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::quickcheck::round_trip;
+/// use json_parser::value::Value;
+/// use quickcheck::{QuickCheck, TestResult};
+///
+/// QuickCheck::new().quickcheck(round_trip as fn(Value) -> TestResult);
+///
+/// // Values can also be generated with a bound depth and size.
+/// let mut g = quickcheck::Gen::new(16);
+/// let value = json_parser::quickcheck::value(&mut g, 1);
+/// assert!(value.stats().max_depth <= 2);
+/// assert!(!round_trip(value).is_failure());
+/// ```
+pub fn round_trip(value: Value) -> TestResult {
+    for json in [value.to_string_compact(), value.to_string_pretty()] {
+        match JsonParser::parse_from_bytes(json.as_bytes()) {
+            Ok(parsed) if parsed == value => {}
+            Ok(_) => return TestResult::error(format!("{json} parses back differently")),
+            Err(error) => return TestResult::error(format!("{json} doesn't parse: {error}")),
+        }
+    }
+
+    TestResult::passed()
+}
@@ -0,0 +1,94 @@
+use crate::error::JsonError;
+use crate::event::EventParser;
+use crate::parser::JsonParser;
+use crate::value::Value;
+use ::rayon::prelude::*;
+use std::collections::VecDeque;
+
+/// Size in bytes of the chunks of lines handed to each thread.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Iterator over the values of newline-delimited JSON parsed in parallel, returned by
+/// [`JsonParser::parse_ndjson_parallel`].
+pub struct ParallelNdjson<'a> {
+    /// Input not parsed yet.
+    input: &'a [u8],
+    /// Values parsed but not yielded yet, in input order.
+    parsed: VecDeque<Result<Value, JsonError>>,
+}
+
+impl JsonParser {
+    /// Parse newline-delimited JSON on all the threads of the rayon pool, yielding one value per
+    /// non-blank line in input order.
+    ///
+    /// The input is split into chunks of whole lines, which are parsed in batches of a few
+    /// chunks per thread, so only part of the values are held in memory at a time.
+    ///
+    /// # Errors
+    ///
+    /// Yields [`JsonError::Syntax`] for every line which isn't valid UTF-8 or valid JSON,
+    /// without stopping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let logs: String = (0..1000).map(|id| format!("{{\"id\": {id}}}\n")).collect();
+    /// let values: Vec<_> = JsonParser::parse_ndjson_parallel(logs.as_bytes()).collect();
+    ///
+    /// assert_eq!(values.len(), 1000);
+    /// assert_eq!(values[999], JsonParser::parse_from_bytes(br#"{"id": 999}"#));
+    /// ```
+    #[must_use]
+    pub fn parse_ndjson_parallel(input: &[u8]) -> ParallelNdjson<'_> {
+        ParallelNdjson {
+            input,
+            parsed: VecDeque::new(),
+        }
+    }
+}
+
+impl ParallelNdjson<'_> {
+    /// Split off the next chunks of lines and parse them in parallel.
+    fn parse_batch(&mut self) {
+        let mut chunks = Vec::new();
+        while !self.input.is_empty() && chunks.len() < ::rayon::current_num_threads() * 4 {
+            let end = match self.input.get(CHUNK_SIZE..) {
+                Some(rest) => rest
+                    .iter()
+                    .position(|&byte| byte == b'\n')
+                    .map_or(self.input.len(), |newline| CHUNK_SIZE + newline + 1),
+                None => self.input.len(),
+            };
+            let (chunk, rest) = self.input.split_at(end);
+            chunks.push(chunk);
+            self.input = rest;
+        }
+
+        let batch: Vec<Vec<_>> = chunks
+            .into_par_iter()
+            .map(|chunk| {
+                chunk
+                    .split(|&byte| byte == b'\n')
+                    .filter(|line| !line.trim_ascii().is_empty())
+                    .map(EventParser::parse)
+                    .collect()
+            })
+            .collect();
+        self.parsed.extend(batch.into_iter().flatten());
+    }
+}
+
+impl Iterator for ParallelNdjson<'_> {
+    type Item = Result<Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Chunks may hold only blank lines, so keep going until a value or the end.
+        while self.parsed.is_empty() && !self.input.is_empty() {
+            self.parse_batch();
+        }
+
+        self.parsed.pop_front()
+    }
+}
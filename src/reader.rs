@@ -1,11 +1,61 @@
+use crate::parser::InvalidUtf8;
 use std::{
     collections::VecDeque,
-    io::{BufReader, Cursor, Read, Seek},
+    io::{BufRead, BufReader, Cursor, Read, Seek},
     str::from_utf8,
 };
 
+/// The Unicode encodings a [`JsonReader`] can decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding {
+    /// Detect the encoding of JSON text from its first bytes.
+    ///
+    /// A byte order mark is used if there is one. Otherwise, since JSON text starts with two
+    /// ASCII characters, the pattern of null bytes among the first four tells the encoding, as
+    /// described in RFC 4627. Anything else is assumed to be UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::reader::Encoding;
+    ///
+    /// assert_eq!(Encoding::detect(b"\xFF\xFE[\x00"), Encoding::Utf16Le);
+    /// assert_eq!(Encoding::detect(b"\x00\x00\x00["), Encoding::Utf32Be);
+    /// assert_eq!(Encoding::detect(b"[1]"), Encoding::Utf8);
+    /// ```
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        match bytes {
+            // Byte order marks, checking UTF-32 first since its little endian mark starts like
+            // the UTF-16 one.
+            [0x00, 0x00, 0xFE, 0xFF, ..] => Encoding::Utf32Be,
+            [0xFF, 0xFE, 0x00, 0x00, ..] => Encoding::Utf32Le,
+            [0xFE, 0xFF, ..] => Encoding::Utf16Be,
+            [0xFF, 0xFE, ..] => Encoding::Utf16Le,
+            [0xEF, 0xBB, 0xBF, ..] => Encoding::Utf8,
+            // Null byte patterns.
+            [0x00, 0x00, 0x00, _, ..] => Encoding::Utf32Be,
+            [_, 0x00, 0x00, 0x00, ..] => Encoding::Utf32Le,
+            [0x00, _, ..] => Encoding::Utf16Be,
+            [_, 0x00, ..] => Encoding::Utf16Le,
+            _ => Encoding::Utf8,
+        }
+    }
+}
+
 /// A struct that handles reading input data to be parsed and
 /// provides an iterator over said data character-by-character.
+///
+/// Besides UTF-8, input in UTF-16 and UTF-32 is decoded, its
+/// encoding being detected from its first bytes.
 pub struct JsonReader<T>
 where
     T: Read + Seek,
@@ -25,6 +75,14 @@ where
     /// because characters need to be read out from the start
     /// of the buffer.
     character_buffer: VecDeque<char>,
+    /// The encoding of the input, detected when the reader is created.
+    encoding: Encoding,
+    /// What to do with bytes which aren't valid UTF-8.
+    invalid_utf8: InvalidUtf8,
+    /// Number of bytes read so far.
+    offset: usize,
+    /// Offset of the invalid UTF-8 which stopped the reader, if any.
+    invalid_utf8_at: Option<usize>,
 }
 
 impl<T> JsonReader<T>
@@ -45,10 +103,18 @@ where
     ///
     /// let json_reader = JsonReader::new(reader);
     /// ```
-    pub fn new(reader: BufReader<T>) -> Self {
+    pub fn new(mut reader: BufReader<T>) -> Self {
+        // Peek at the first bytes without consuming them. A failed read is left to be reported
+        // when reading characters.
+        let encoding = reader.fill_buf().map_or(Encoding::Utf8, Encoding::detect);
+
         JsonReader {
             reader,
             character_buffer: VecDeque::with_capacity(4),
+            encoding,
+            invalid_utf8: InvalidUtf8::default(),
+            offset: 0,
+            invalid_utf8_at: None,
         }
     }
 
@@ -69,6 +135,273 @@ where
         JsonReader {
             reader: BufReader::new(Cursor::new(bytes)),
             character_buffer: VecDeque::with_capacity(4),
+            encoding: Encoding::detect(bytes),
+            invalid_utf8: InvalidUtf8::default(),
+            offset: 0,
+            invalid_utf8_at: None,
+        }
+    }
+
+    /// Set what to do with bytes which aren't valid UTF-8, which by default stop the reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use json_parser::parser::InvalidUtf8;
+    /// use json_parser::reader::JsonReader;
+    ///
+    /// let input = b"[\"a\xFFb\"]";
+    ///
+    /// let json_reader = JsonReader::<Cursor<&[u8]>>::from_bytes(input);
+    /// assert_eq!(json_reader.collect::<String>(), "[\"a");
+    ///
+    /// let json_reader =
+    ///     JsonReader::<Cursor<&[u8]>>::from_bytes(input).with_invalid_utf8(InvalidUtf8::Replace);
+    /// assert_eq!(json_reader.collect::<String>(), "[\"a\u{FFFD}b\"]");
+    ///
+    /// let json_reader =
+    ///     JsonReader::<Cursor<&[u8]>>::from_bytes(input).with_invalid_utf8(InvalidUtf8::Skip);
+    /// assert_eq!(json_reader.collect::<String>(), "[\"ab\"]");
+    /// ```
+    #[must_use]
+    pub fn with_invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Self {
+        self.invalid_utf8 = invalid_utf8;
+        self
+    }
+
+    /// The byte offset of the invalid UTF-8 which ended the characters read, when invalid
+    /// UTF-8 is treated as an error.
+    #[must_use]
+    pub fn invalid_utf8_at(&self) -> Option<usize> {
+        self.invalid_utf8_at
+    }
+
+    /// Unwrap the underlying input. Whatever was read from it but not returned as characters
+    /// yet is lost.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.reader.into_inner()
+    }
+
+    /// Number of bytes of the input read so far, including those of the characters buffered
+    /// by [`peek`](JsonReader::peek).
+    pub(crate) fn bytes_read(&self) -> usize {
+        self.offset
+    }
+
+    /// Return the next character without consuming it.
+    pub(crate) fn peek(&mut self) -> Option<&char> {
+        if self.character_buffer.is_empty() {
+            let character = self.next()?;
+            self.character_buffer.push_front(character);
+        }
+
+        self.character_buffer.front()
+    }
+
+    /// Consume the next character if it's equal to `expected`.
+    pub(crate) fn next_if_eq(&mut self, expected: &char) -> Option<char> {
+        if self.peek() == Some(expected) {
+            return self.next();
+        }
+
+        None
+    }
+
+    /// Whether the input can be scanned in bulk rather than decoded character by character,
+    /// which is the case for UTF-8 once the buffered characters are used up.
+    fn can_scan(&self) -> bool {
+        self.encoding == Encoding::Utf8
+            && self.character_buffer.is_empty()
+            && self.invalid_utf8_at.is_none()
+    }
+
+    /// Consume the whitespace at the current position.
+    pub(crate) fn skip_whitespace(&mut self) {
+        while self
+            .character_buffer
+            .front()
+            .is_some_and(char::is_ascii_whitespace)
+        {
+            self.character_buffer.pop_front();
+        }
+
+        while self.can_scan() {
+            let Ok(buffer) = self.reader.fill_buf() else {
+                return;
+            };
+            let length = buffer
+                .iter()
+                .position(|byte| !byte.is_ascii_whitespace())
+                .unwrap_or(buffer.len());
+            if length == 0 {
+                return;
+            }
+
+            self.reader.consume(length);
+            self.offset += length;
+        }
+    }
+
+    /// Append the characters of a string up to the next `"` or `\` to `string`, stopping
+    /// early at anything which needs decoding character by character, such as invalid UTF-8.
+    pub(crate) fn read_string_run(&mut self, string: &mut String) {
+        while self.can_scan() {
+            let Ok(buffer) = self.reader.fill_buf() else {
+                return;
+            };
+            let end = find_quote_or_backslash(buffer).unwrap_or(buffer.len());
+            // A character cut off by the end of the buffer is left for `next` to read.
+            let run = match from_utf8(&buffer[..end]) {
+                Ok(run) => run,
+                Err(error) => from_utf8(&buffer[..error.valid_up_to()]).unwrap_or_default(),
+            };
+            let length = run.len();
+            string.push_str(run);
+            let done = length == 0 || length < buffer.len();
+
+            self.reader.consume(length);
+            self.offset += length;
+            if done {
+                return;
+            }
+        }
+    }
+
+    /// The encoding the input is decoded from, as detected by [`Encoding::detect`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use json_parser::reader::{Encoding, JsonReader};
+    ///
+    /// let input: Vec<u8> = "[\"é\"]".encode_utf16().flat_map(u16::to_be_bytes).collect();
+    /// let json_reader = JsonReader::<Cursor<&[u8]>>::from_bytes(&input);
+    ///
+    /// assert_eq!(json_reader.encoding(), Encoding::Utf16Be);
+    /// assert_eq!(json_reader.collect::<String>(), "[\"é\"]");
+    /// ```
+    #[must_use]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Read a code unit of `N` bytes, returning how many bytes could be read before the end
+    /// of the input.
+    fn read_unit<const N: usize>(&mut self) -> (usize, [u8; N]) {
+        let mut unit = [0; N];
+        let mut length = 0;
+        while length < N {
+            match self.reader.read(&mut unit[length..]) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => length += read,
+            }
+        }
+
+        (length, unit)
+    }
+
+    fn read_utf16(&mut self) -> Option<u16> {
+        let unit = self.read_unit::<2>();
+        self.offset += unit.0;
+        match unit {
+            (0, _) => None,
+            // A truncated code unit can't be decoded.
+            (1, _) => Some(0xFFFD),
+            (_, unit) if self.encoding == Encoding::Utf16Be => Some(u16::from_be_bytes(unit)),
+            (_, unit) => Some(u16::from_le_bytes(unit)),
+        }
+    }
+
+    fn next_utf16(&mut self) -> Option<char> {
+        let high = self.read_utf16()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Some(char::from_u32(high.into()).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+
+        // A high surrogate must be followed by a low one to form a character.
+        match self.read_utf16() {
+            Some(low) if (0xDC00..0xE000).contains(&low) => {
+                let code = 0x10000 + ((u32::from(high) - 0xD800) << 10) + (u32::from(low) - 0xDC00);
+                char::from_u32(code)
+            }
+            Some(other) => {
+                // Keep the following code unit, which is read again as the next character.
+                let other = char::from_u32(other.into()).unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.character_buffer.push_back(other);
+                Some(char::REPLACEMENT_CHARACTER)
+            }
+            None => Some(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    fn next_utf32(&mut self) -> Option<char> {
+        let unit = self.read_unit::<4>();
+        self.offset += unit.0;
+        let code = match unit {
+            (0, _) => return None,
+            (4, unit) if self.encoding == Encoding::Utf32Be => u32::from_be_bytes(unit),
+            (4, unit) => u32::from_le_bytes(unit),
+            _ => return Some(char::REPLACEMENT_CHARACTER),
+        };
+
+        Some(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn next_utf8(&mut self) -> Option<char> {
+        loop {
+            let (length, utf8_buffer) = self.read_unit::<4>();
+            let bytes = &utf8_buffer[..length];
+
+            match from_utf8(bytes) {
+                Ok(string) => {
+                    self.offset += length;
+                    self.character_buffer = string.chars().collect();
+                    return self.character_buffer.pop_front();
+                }
+                Err(error) if error.valid_up_to() > 0 => {
+                    // Read valid bytes, and rewind the buffered reader for
+                    // the remaining bytes so that they can be read again in the
+                    // next iteration.
+
+                    let valid_bytes = error.valid_up_to();
+                    let string = from_utf8(&bytes[..valid_bytes]).unwrap();
+
+                    let remaining_bytes = length - valid_bytes;
+
+                    let _ = self.reader.seek_relative(-(remaining_bytes as i64));
+                    self.offset += valid_bytes;
+
+                    // Collect the valid characters into character_buffer
+                    self.character_buffer = string.chars().collect();
+
+                    // Return the first character from character_buffer
+                    return self.character_buffer.pop_front();
+                }
+                Err(error) => {
+                    // The bytes start with an invalid sequence, or one which
+                    // is cut off by the end of the input. Rewind for the bytes
+                    // after it.
+                    let invalid_bytes = error.error_len().unwrap_or(length);
+                    let _ = self
+                        .reader
+                        .seek_relative(-((length - invalid_bytes) as i64));
+                    let offset = self.offset;
+                    self.offset += invalid_bytes;
+
+                    match self.invalid_utf8 {
+                        InvalidUtf8::Error => {
+                            self.invalid_utf8_at = Some(offset);
+                            return None;
+                        }
+                        InvalidUtf8::Replace => return Some(char::REPLACEMENT_CHARACTER),
+                        InvalidUtf8::Skip => {}
+                    }
+                }
+            }
         }
     }
 }
@@ -79,38 +412,28 @@ where
 {
     type Item = char;
 
-    #[allow(clippy::cast_possible_wrap)]
     fn next(&mut self) -> Option<Self::Item> {
         if !self.character_buffer.is_empty() {
             return self.character_buffer.pop_front();
         }
+        if self.invalid_utf8_at.is_some() {
+            return None;
+        }
 
-        let mut utf8_buffer = [0, 0, 0, 0];
-        let _ = self.reader.read(&mut utf8_buffer);
-
-        match from_utf8(&utf8_buffer) {
-            Ok(string) => {
-                self.character_buffer = string.chars().collect();
-                self.character_buffer.pop_front()
-            }
-            Err(error) => {
-                // Read valid bytes, and rewind the buffered reader for
-                // the remaining bytes so that they can be read again in the
-                // next iteration.
-
-                let valid_bytes = error.valid_up_to();
-                let string = from_utf8(&utf8_buffer[..valid_bytes]).unwrap();
-
-                let remaining_bytes = 4 - valid_bytes;
-
-                let _ = self.reader.seek_relative(-(remaining_bytes as i64));
-
-                // Collect the valid characters into character_buffer
-                self.character_buffer = string.chars().collect();
-
-                // Return the first character from character_buffer
-                self.character_buffer.pop_front()
-            }
+        match self.encoding {
+            Encoding::Utf8 => self.next_utf8(),
+            Encoding::Utf16Le | Encoding::Utf16Be => self.next_utf16(),
+            Encoding::Utf32Le | Encoding::Utf32Be => self.next_utf32(),
         }
     }
 }
+
+#[cfg(feature = "simd")]
+pub(crate) fn find_quote_or_backslash(bytes: &[u8]) -> Option<usize> {
+    memchr::memchr2(b'"', b'\\', bytes)
+}
+
+#[cfg(not(feature = "simd"))]
+pub(crate) fn find_quote_or_backslash(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|&byte| byte == b'"' || byte == b'\\')
+}
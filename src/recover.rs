@@ -0,0 +1,698 @@
+//! A parse mode which carries on past syntax errors.
+//!
+//! Linters and editors need to report every problem of a document at once, and to keep
+//! working with the parts of it which are fine. [`JsonParser::parse_recovering`] never fails:
+//! it records each syntax error along with its location, skips or patches the offending text,
+//! and returns the best value it could make of the rest. [`JsonParser::repair`] applies the
+//! same recovery to salvage data from truncated or sloppily written JSON, but only for
+//! problems with an obvious fix.
+
+use crate::cst::parse_number;
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::span::{LineIndex, Span};
+use crate::token::MAX_NESTING;
+use crate::value::{Number, Value};
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// A syntax error found by [`JsonParser::parse_recovering`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The text the error is about, which is empty for missing text such as a `,`.
+    pub span: Span,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.span.start;
+        write!(f, "{}:{}: {}", start.line, start.column, self.message)
+    }
+}
+
+/// A change made by [`JsonParser::repair`] to turn its input into valid JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// The text the fix applies to, which is empty for inserted text.
+    pub span: Span,
+    pub kind: FixKind,
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.span.start;
+        write!(f, "{}:{}: {}", start.line, start.column, self.kind)
+    }
+}
+
+/// The kinds of problems [`JsonParser::repair`] knows how to fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixKind {
+    /// Closed a string left open at the end of a line or of the input.
+    CloseString,
+    /// Closed an array left open at the end of the input or of an enclosing value.
+    CloseArray,
+    /// Closed an object left open at the end of the input or of an enclosing value.
+    CloseObject,
+    RemoveTrailingComma,
+    /// Turned a bare word used as a key into a string.
+    QuoteKey,
+    /// Inserted a `,` missing between two elements or members.
+    InsertComma,
+    /// Inserted a `:` missing between a key and its value.
+    InsertColon,
+    /// Used `null` for a value missing at the end of the input.
+    InsertNull,
+}
+
+impl Display for FixKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FixKind::CloseString => "closed the string",
+            FixKind::CloseArray => "closed the array",
+            FixKind::CloseObject => "closed the object",
+            FixKind::RemoveTrailingComma => "removed the trailing comma",
+            FixKind::QuoteKey => "quoted the key",
+            FixKind::InsertComma => "inserted a missing `,`",
+            FixKind::InsertColon => "inserted a missing `:`",
+            FixKind::InsertNull => "inserted `null` for the missing value",
+        })
+    }
+}
+
+impl JsonParser {
+    /// Parse JSON from bytes, collecting every syntax error instead of stopping at the first.
+    ///
+    /// The returned value holds everything which could be recovered: missing commas and
+    /// colons are assumed, trailing commas, unclosed strings, arrays and objects are accepted,
+    /// unquoted keys are read as strings, and anything else which isn't valid is left out or,
+    /// for object members, replaced by `null`. The value is exactly the parsed document when
+    /// there are no errors.
+    ///
+    /// Invalid UTF-8 sequences are reported and replaced by `U+FFFD`, and the spans of the
+    /// errors refer to the text after this replacement.
+    ///
+    /// Arrays and objects nested more than [`MAX_NESTING`] levels deep are reported and left
+    /// out, rather than overflowing the stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let input = b"{\n  \"name\": \"app\",\n  \"port\": 80\n  \"debug\": tru,\n}";
+    /// let (value, errors) = JsonParser::parse_recovering(input);
+    ///
+    /// let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    /// assert_eq!(
+    ///     messages,
+    ///     [
+    ///         "4:3: expected `,` or `}`, found `\"`",
+    ///         "4:12: expected a value, found `tru`",
+    ///         "4:15: trailing comma",
+    ///     ]
+    /// );
+    ///
+    /// let expected =
+    ///     JsonParser::parse_from_bytes(br#"{"name": "app", "port": 80, "debug": null}"#).unwrap();
+    /// assert_eq!(value, expected);
+    ///
+    /// let deep = format!("[{}{}, 2]", "[".repeat(100_000), "]".repeat(100_000));
+    /// let (value, errors) = JsonParser::parse_recovering(deep.as_bytes());
+    ///
+    /// assert_eq!(
+    ///     errors[0].to_string(),
+    ///     "1:513: arrays and objects are nested more than 512 levels deep",
+    /// );
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(value.pointer("/1").unwrap().to_string_compact(), "2");
+    /// ```
+    pub fn parse_recovering(input: &[u8]) -> (Value, Vec<Diagnostic>) {
+        let (source, value, problems) = recover(input);
+        let lines = LineIndex::new(&source);
+        let errors = problems
+            .into_iter()
+            .map(|problem| Diagnostic {
+                span: lines.span(problem.span),
+                message: problem.message,
+            })
+            .collect();
+
+        (value, errors)
+    }
+
+    /// Parse JSON from bytes, fixing the common problems of truncated or sloppily written
+    /// documents, and return the fixes applied along with the value.
+    ///
+    /// Unclosed strings, arrays and objects are closed, trailing commas are removed, bare
+    /// words used as keys are quoted, missing `,` and `:` are inserted, and a value missing at
+    /// the end of the input is taken to be `null`. See [`FixKind`] for the details. Valid JSON
+    /// is returned as is, without any fixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] for the first problem which can't be fixed with
+    /// confidence, such as an unknown literal, an invalid number or text after the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let input = br#"{name: "app", "tags": ["a", "b",], "log": "started"#;
+    /// let (value, fixes) = JsonParser::repair(input).unwrap();
+    ///
+    /// let expected =
+    ///     JsonParser::parse_from_bytes(br#"{"name": "app", "tags": ["a", "b"], "log": "started"}"#)
+    ///         .unwrap();
+    /// assert_eq!(value, expected);
+    ///
+    /// let fixes: Vec<_> = fixes.iter().map(ToString::to_string).collect();
+    /// assert_eq!(
+    ///     fixes,
+    ///     [
+    ///         "1:1: closed the object",
+    ///         "1:2: quoted the key",
+    ///         "1:32: removed the trailing comma",
+    ///         "1:43: closed the string",
+    ///     ]
+    /// );
+    ///
+    /// assert!(JsonParser::repair(b"[1, 2, three]").is_err());
+    /// ```
+    pub fn repair(input: &[u8]) -> Result<(Value, Vec<Fix>), JsonError> {
+        let (source, value, problems) = recover(input);
+        let lines = LineIndex::new(&source);
+
+        let mut fixes = Vec::with_capacity(problems.len());
+        for problem in problems {
+            let span = lines.span(problem.span);
+            let Some(kind) = problem.fix else {
+                let start = span.start;
+                return Err(JsonError::Syntax(format!(
+                    "{}:{}: {}",
+                    start.line, start.column, problem.message
+                )));
+            };
+            fixes.push(Fix { span, kind });
+        }
+
+        Ok((value, fixes))
+    }
+}
+
+/// A syntax error, along with the fix applied by [`JsonParser::repair`] for it, if any.
+struct Problem {
+    span: Range<usize>,
+    message: String,
+    fix: Option<FixKind>,
+}
+
+/// Parse the input as well as possible, returning the text it was read as, the value and
+/// every problem found, in the order of their positions.
+fn recover(input: &[u8]) -> (String, Value, Vec<Problem>) {
+    let mut source = String::with_capacity(input.len());
+    let mut problems = Vec::new();
+    for chunk in input.utf8_chunks() {
+        source.push_str(chunk.valid());
+        if !chunk.invalid().is_empty() {
+            let start = source.len();
+            source.push(char::REPLACEMENT_CHARACTER);
+            problems.push(Problem {
+                span: start..source.len(),
+                message: "invalid UTF-8".to_string(),
+                fix: None,
+            });
+        }
+    }
+
+    let mut parser = Parser {
+        source: &source,
+        // Skip a leading byte order mark.
+        position: if source.starts_with('\u{FEFF}') {
+            '\u{FEFF}'.len_utf8()
+        } else {
+            0
+        },
+        problems,
+        depth: 0,
+    };
+    let value = parser.parse_value().unwrap_or(Value::Null);
+    parser.skip_whitespace();
+    if parser.position < source.len() {
+        let span = parser.position..source.len();
+        parser.report(span, "unexpected text after the value");
+    }
+
+    let mut problems = parser.problems;
+    problems.sort_by_key(|problem| problem.span.start);
+
+    (source, value, problems)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    /// Byte offset of the next character to read.
+    position: usize,
+    problems: Vec<Problem>,
+    /// Number of arrays and objects the current position is in.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn report(&mut self, span: Range<usize>, message: impl Into<String>) {
+        self.problems.push(Problem {
+            span,
+            message: message.into(),
+            fix: None,
+        });
+    }
+
+    /// Report a problem which [`JsonParser::repair`] fixes.
+    fn report_fixable(&mut self, span: Range<usize>, message: impl Into<String>, fix: FixKind) {
+        self.problems.push(Problem {
+            span,
+            message: message.into(),
+            fix: Some(fix),
+        });
+    }
+
+    /// Report that `expected` is missing before the next character, without consuming it.
+    fn expected(&mut self, expected: &str, fix: Option<FixKind>) {
+        let (found, length) = match self.rest().chars().next() {
+            Some(found) => (format!("`{found}`"), found.len_utf8()),
+            None => ("end of input".to_string(), 0),
+        };
+        self.problems.push(Problem {
+            span: self.position..self.position + length,
+            message: format!("expected {expected}, found {found}"),
+            fix,
+        });
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        let length = rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+        self.position += length;
+    }
+
+    /// Skip the next character, after reporting that `expected` should have been found
+    /// instead.
+    fn skip_unexpected(&mut self, expected: &str) {
+        self.expected(expected, None);
+        self.position += self.rest().chars().next().map_or(0, char::len_utf8);
+    }
+
+    /// Parse the value at the current position, or return `None` after reporting an error if
+    /// there's no valid value there.
+    ///
+    /// A `,`, `]` or `}` where a value is expected is left for the caller to deal with, while
+    /// any other unexpected character is skipped.
+    fn parse_value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{' | b'[') if self.depth >= MAX_NESTING => {
+                self.skip_nested();
+                None
+            }
+            Some(b'{') => Some(self.parse_object()),
+            Some(b'[') => Some(self.parse_array()),
+            Some(b'"') => Some(Value::from(self.parse_string())),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(byte) if is_word(byte) => self.parse_literal(),
+            Some(b',' | b']' | b'}') => {
+                self.expected("a value", None);
+                None
+            }
+            None => {
+                // Empty input isn't the cut off end of a document.
+                let fix = Some(FixKind::InsertNull).filter(|_| !self.source.trim().is_empty());
+                self.expected("a value", fix);
+                None
+            }
+            Some(_) => {
+                self.skip_unexpected("a value");
+                None
+            }
+        }
+    }
+
+    /// Report and skip an array or object nested too deeply to be parsed without overflowing
+    /// the stack, up to its matching closing character or the end of the input.
+    fn skip_nested(&mut self) {
+        let start = self.position;
+        let mut depth = 0_usize;
+        let mut bytes = self.source.as_bytes()[start..].iter().enumerate();
+        let mut end = self.source.len();
+        while let Some((offset, byte)) = bytes.next() {
+            match byte {
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = start + offset + 1;
+                        break;
+                    }
+                }
+                b'"' => {
+                    // Skip over the string, so that brackets inside it aren't counted.
+                    while let Some((_, byte)) = bytes.next() {
+                        match byte {
+                            b'\\' => {
+                                bytes.next();
+                            }
+                            b'"' | b'\n' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.position = end;
+
+        let message = format!("arrays and objects are nested more than {MAX_NESTING} levels deep");
+        self.report(start..start + 1, message);
+    }
+
+    /// Read a run of letters, digits and underscores.
+    fn word(&mut self) -> &str {
+        let start = self.position;
+        self.position += self
+            .rest()
+            .bytes()
+            .take_while(|&byte| is_word(byte))
+            .count();
+
+        &self.source[start..self.position]
+    }
+
+    fn parse_literal(&mut self) -> Option<Value> {
+        let start = self.position;
+        match self.word() {
+            "null" => Some(Value::Null),
+            "true" => Some(Value::Boolean(true)),
+            "false" => Some(Value::Boolean(false)),
+            word => {
+                let message = format!("expected a value, found `{word}`");
+                self.report(start..self.position, message);
+                None
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<Value> {
+        let start = self.position;
+        self.position += self
+            .rest()
+            .bytes()
+            .take_while(|byte| matches!(byte, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+            .count();
+        let text = &self.source[start..self.position];
+
+        if is_valid_number(text) {
+            return Some(Value::Number(parse_number(text)));
+        }
+        let message = format!("invalid number `{text}`");
+        self.report(start..self.position, message);
+
+        // Keep numbers which are only written in a non-standard way, such as `01` or `1.`.
+        if let Ok(integer) = text.parse() {
+            Some(Value::Number(Number::I64(integer)))
+        } else if let Ok(float) = text.parse() {
+            Some(Value::Number(Number::F64(float)))
+        } else {
+            None
+        }
+    }
+
+    /// Parse a string, ending it at the end of the line if it isn't closed.
+    fn parse_string(&mut self) -> String {
+        let start = self.position;
+        // Skip the opening quote.
+        self.position += 1;
+        let mut string = String::new();
+
+        loop {
+            let Some(character) = self.rest().chars().next() else {
+                self.report_fixable(
+                    start..self.position,
+                    "unclosed string",
+                    FixKind::CloseString,
+                );
+                return string;
+            };
+
+            match character {
+                '"' => {
+                    self.position += 1;
+                    return string;
+                }
+                '\n' | '\r' => {
+                    self.report_fixable(
+                        start..self.position,
+                        "unclosed string",
+                        FixKind::CloseString,
+                    );
+                    return string;
+                }
+                '\\' => string.push(self.parse_escape()),
+                control if control < ' ' => {
+                    let message = "unescaped control character in string";
+                    self.report(self.position..self.position + 1, message);
+                    self.position += 1;
+                    string.push(control);
+                }
+                character => {
+                    self.position += character.len_utf8();
+                    string.push(character);
+                }
+            }
+        }
+    }
+
+    /// Decode the escape sequence starting at the current `\`, keeping the escaped character
+    /// as is if the escape isn't valid.
+    fn parse_escape(&mut self) -> char {
+        let start = self.position;
+        // Skip the backslash.
+        self.position += 1;
+        let Some(escape) = self.rest().chars().next() else {
+            return '\\';
+        };
+        self.position += escape.len_utf8();
+
+        match escape {
+            '"' | '\\' | '/' => escape,
+            'b' => '\u{08}',
+            'f' => '\u{0C}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let Some(high) = self.parse_hex() else {
+                    self.report(start..self.position, "expected four hexadecimal digits");
+                    return char::REPLACEMENT_CHARACTER;
+                };
+                if (0xD800..0xDC00).contains(&high) && self.rest().starts_with("\\u") {
+                    let checkpoint = self.position;
+                    self.position += 2;
+                    match self.parse_hex() {
+                        Some(low) if (0xDC00..0xE000).contains(&low) => {
+                            let code = 0x10000
+                                + ((u32::from(high) - 0xD800) << 10)
+                                + (u32::from(low) - 0xDC00);
+                            return char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                        }
+                        // Leave the following escape to be decoded on its own.
+                        _ => self.position = checkpoint,
+                    }
+                }
+                char::from_u32(high.into()).unwrap_or(char::REPLACEMENT_CHARACTER)
+            }
+            escape => {
+                let message = format!("invalid escape sequence `\\{escape}`");
+                self.report(start..self.position, message);
+                escape
+            }
+        }
+    }
+
+    fn parse_hex(&mut self) -> Option<u16> {
+        let code = self
+            .rest()
+            .get(..4)
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())?;
+        self.position += 4;
+
+        Some(code)
+    }
+
+    /// After an element or member, consume the `,` separating it from the next one and
+    /// return its position. A missing `,` is reported but assumed when a value follows.
+    fn parse_separator(&mut self, expected: &str) -> Option<usize> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.position += 1;
+                Some(self.position - 1)
+            }
+            // Closing characters, including mismatched ones, are dealt with by the caller.
+            Some(b']' | b'}') | None => None,
+            Some(byte) if starts_value(byte) => {
+                self.expected(expected, Some(FixKind::InsertComma));
+                None
+            }
+            Some(_) => {
+                self.skip_unexpected(expected);
+                None
+            }
+        }
+    }
+
+    /// Handle the character at the start of an element or member, returning whether the
+    /// array or object ends there.
+    ///
+    /// A mismatched closing character, such as the `}` of `[1}`, is taken to close an
+    /// enclosing value instead, and is left for it to consume.
+    fn at_end(&mut self, start: usize, close: u8, comma: Option<usize>) -> bool {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(byte) if byte == close => {
+                self.report_trailing_comma(comma);
+                self.position += 1;
+                true
+            }
+            Some(b']' | b'}') | None => {
+                self.report_trailing_comma(comma);
+                let (message, fix) = if close == b']' {
+                    ("unclosed array", FixKind::CloseArray)
+                } else {
+                    ("unclosed object", FixKind::CloseObject)
+                };
+                self.report_fixable(start..start + 1, message, fix);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn report_trailing_comma(&mut self, comma: Option<usize>) {
+        if let Some(comma) = comma {
+            let fix = FixKind::RemoveTrailingComma;
+            self.report_fixable(comma..comma + 1, "trailing comma", fix);
+        }
+    }
+
+    fn parse_array(&mut self) -> Value {
+        let start = self.position;
+        // Skip the opening bracket.
+        self.position += 1;
+        self.depth += 1;
+        let mut elements = Vec::new();
+        let mut comma = None;
+
+        while !self.at_end(start, b']', comma) {
+            if self.peek() == Some(b',') {
+                self.skip_unexpected("a value");
+                comma = Some(self.position - 1);
+                continue;
+            }
+
+            elements.extend(self.parse_value());
+            comma = self.parse_separator("`,` or `]`");
+        }
+
+        self.depth -= 1;
+        Value::Array(elements)
+    }
+
+    fn parse_object(&mut self) -> Value {
+        let start = self.position;
+        // Skip the opening brace.
+        self.position += 1;
+        self.depth += 1;
+        let mut members = Map::new();
+        let mut comma = None;
+
+        while !self.at_end(start, b'}', comma) {
+            let key = match self.peek() {
+                Some(b'"') => self.parse_string(),
+                Some(byte) if is_word(byte) => {
+                    let key_start = self.position;
+                    let key = self.word().to_string();
+                    let message = format!("expected a string key, found `{key}`");
+                    self.report_fixable(key_start..self.position, message, FixKind::QuoteKey);
+                    key
+                }
+                _ => {
+                    self.skip_unexpected("a string key");
+                    comma = None;
+                    continue;
+                }
+            };
+
+            self.skip_whitespace();
+            if self.peek() == Some(b':') {
+                self.position += 1;
+            } else {
+                self.expected("`:`", Some(FixKind::InsertColon));
+            }
+            let value = self.parse_value().unwrap_or(Value::Null);
+            members.insert(key, value);
+
+            comma = self.parse_separator("`,` or `}`");
+        }
+
+        self.depth -= 1;
+        Value::Object(members)
+    }
+}
+
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+fn starts_value(byte: u8) -> bool {
+    matches!(byte, b'{' | b'[' | b'"' | b'-') || is_word(byte)
+}
+
+/// Check a number against the JSON grammar, which is stricter than Rust's.
+pub(crate) fn is_valid_number(text: &str) -> bool {
+    fn digits(bytes: &mut std::iter::Peekable<std::str::Bytes>) -> usize {
+        let mut count = 0;
+        while bytes.next_if(u8::is_ascii_digit).is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    let mut bytes = text.bytes().peekable();
+    bytes.next_if_eq(&b'-');
+    // The integer part can't have leading zeros.
+    if bytes.next_if_eq(&b'0').is_none() && digits(&mut bytes) == 0 {
+        return false;
+    }
+    if bytes.next_if_eq(&b'.').is_some() && digits(&mut bytes) == 0 {
+        return false;
+    }
+    if bytes.next_if(|byte| matches!(byte, b'e' | b'E')).is_some() {
+        bytes.next_if(|byte| matches!(byte, b'+' | b'-'));
+        if digits(&mut bytes) == 0 {
+            return false;
+        }
+    }
+
+    bytes.next().is_none()
+}
@@ -0,0 +1,95 @@
+use crate::value::Value;
+
+/// Replacement used by [`Value::redact`].
+const REDACTED: &str = "***";
+
+impl Value {
+    /// Replace the value of every object member whose key matches one of `patterns` with
+    /// `"***"`, anywhere in the tree.
+    ///
+    /// Patterns are globs where `*` matches any sequence of characters and `?` matches a single
+    /// character. Matching ignores ASCII case, so `password` also matches `Password`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut value =
+    ///     JsonParser::parse_from_bytes(br#"{"user":{"password":"hunter2","api_token":"abc"}}"#)
+    ///         .unwrap();
+    /// value.redact(&["password", "*_token"]);
+    ///
+    /// let expected =
+    ///     JsonParser::parse_from_bytes(br#"{"user":{"password":"***","api_token":"***"}}"#)
+    ///         .unwrap();
+    /// assert_eq!(value, expected);
+    /// ```
+    pub fn redact(&mut self, patterns: &[&str]) {
+        self.redact_with(patterns, &Value::from(REDACTED));
+    }
+
+    /// Replace the value of every object member whose key matches one of `patterns` with a
+    /// clone of `replacement`, anywhere in the tree.
+    ///
+    /// See [`Value::redact`] for the pattern syntax.
+    pub fn redact_with(&mut self, patterns: &[&str], replacement: &Value) {
+        match self {
+            Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    if patterns.iter().any(|pattern| glob_matches(pattern, key)) {
+                        *value = replacement.clone();
+                    } else {
+                        value.redact_with(patterns, replacement);
+                    }
+                }
+            }
+            Value::Array(array) => {
+                for value in array {
+                    value.redact_with(patterns, replacement);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Match `text` against a glob `pattern` supporting `*` and `?`, ignoring ASCII case.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut pattern_index, mut text_index) = (0, 0);
+
+    // Position of the last `*` seen in the pattern, and the text position it was matched at, so
+    // that the star can be backtracked to consume one more character on a mismatch.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some(&character)
+                if character == '?' || character.eq_ignore_ascii_case(&text[text_index]) =>
+            {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_text_index)) => {
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                    backtrack = Some((star_index, star_text_index + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    // Any remaining pattern characters must all be stars matching the empty string.
+    pattern[pattern_index..]
+        .iter()
+        .all(|&character| character == '*')
+}
@@ -0,0 +1,230 @@
+//! Resolution of JSON References, the `{"$ref": "..."}` objects of JSON Schema and OpenAPI
+//! documents.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::query::percent_decode;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+impl Value {
+    /// Copy this value with every reference inside it replaced by the value it refers to,
+    /// itself with its references resolved.
+    ///
+    /// A reference is an object with a string `$ref` member, such as
+    /// `{"$ref": "#/definitions/address"}`, whose other members are ignored. The part after
+    /// the `#` is a JSON Pointer into this value, percent-decoded as a URI fragment. Objects
+    /// whose `$ref` isn't a string, such as a property named `$ref` in a schema, are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Reference`] for a reference to another document, whose fragment
+    /// isn't a JSON Pointer, which refers to nothing, or which refers to a value containing
+    /// itself, as recursive schemas do.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let schema = JsonParser::parse_from_bytes(br##"{
+    ///     "properties": {"home": {"$ref": "#/definitions/address"}},
+    ///     "definitions": {"address": {"type": "string"}}
+    /// }"##)
+    /// .unwrap();
+    ///
+    /// let resolved = schema.resolve_refs().unwrap();
+    /// assert_eq!(
+    ///     resolved.pointer("/properties").unwrap().to_string_compact(),
+    ///     r#"{"home":{"type":"string"}}"#
+    /// );
+    ///
+    /// let recursive =
+    ///     JsonParser::parse_from_bytes(br##"{"node": {"next": {"$ref": "#/node"}}}"##).unwrap();
+    /// assert!(recursive.resolve_refs().is_err());
+    /// ```
+    pub fn resolve_refs(&self) -> Result<Value, JsonError> {
+        Resolver {
+            loader: None,
+            documents: HashMap::new(),
+            expanding: Vec::new(),
+        }
+        .resolve(self, "", self)
+    }
+
+    /// Resolve references like [`resolve_refs`](Value::resolve_refs), including references
+    /// to other documents, such as `"common.json#/definitions/id"`, which `loader` loads.
+    ///
+    /// `loader` is called once for each document, with its location relative to this one's.
+    /// A relative location in a loaded document is joined to the directory of that document,
+    /// so `"types.json"` in `"schemas/user.json"` is loaded as `"schemas/types.json"`, while
+    /// locations starting with `/` or containing `://` aren't joined to anything. The `.` and
+    /// `..` segments of locations are resolved, apart from those of URLs.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`resolve_refs`](Value::resolve_refs) other than for
+    /// references to other documents, as well as any error `loader` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use std::collections::HashMap;
+    ///
+    /// let files = HashMap::from([
+    ///     ("schemas/user.json", r##"{"properties": {"id": {"$ref": "types.json#/id"}}}"##),
+    ///     ("schemas/types.json", r#"{"id": {"type": "integer"}}"#),
+    /// ]);
+    /// let api = JsonParser::parse_from_bytes(br#"{"user": {"$ref": "schemas/user.json"}}"#)
+    ///     .unwrap();
+    ///
+    /// let resolved = api
+    ///     .resolve_refs_with(|location| JsonParser::parse_from_bytes(files[location].as_bytes()))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     resolved.to_string_compact(),
+    ///     r#"{"user":{"properties":{"id":{"type":"integer"}}}}"#
+    /// );
+    /// ```
+    pub fn resolve_refs_with<F>(&self, mut loader: F) -> Result<Value, JsonError>
+    where
+        F: FnMut(&str) -> Result<Value, JsonError>,
+    {
+        Resolver {
+            loader: Some(&mut loader),
+            documents: HashMap::new(),
+            expanding: Vec::new(),
+        }
+        .resolve(self, "", self)
+    }
+}
+
+/// Loads the document at a location, for [`Value::resolve_refs_with`].
+type Loader<'l> = &'l mut dyn FnMut(&str) -> Result<Value, JsonError>;
+
+/// Copies values while following their references.
+struct Resolver<'l> {
+    loader: Option<Loader<'l>>,
+    /// The documents loaded so far, by location.
+    documents: HashMap<String, Rc<Value>>,
+    /// The location and pointer of every reference being followed, to detect cycles.
+    expanding: Vec<(String, String)>,
+}
+
+impl Resolver<'_> {
+    /// Copy `value`, which is inside `document` loaded from `location`, resolving its
+    /// references.
+    fn resolve(
+        &mut self,
+        value: &Value,
+        location: &str,
+        document: &Value,
+    ) -> Result<Value, JsonError> {
+        match value {
+            Value::Object(members) => {
+                if let Some(Value::String(reference)) = members.get("$ref") {
+                    return self.follow(reference, location, document);
+                }
+
+                let mut resolved = Map::with_capacity(members.len());
+                for (key, member) in members {
+                    resolved.insert(key.to_string(), self.resolve(member, location, document)?);
+                }
+                Ok(Value::Object(resolved))
+            }
+            Value::Array(elements) => elements
+                .iter()
+                .map(|element| self.resolve(element, location, document))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// Resolve the value `reference` refers to, from inside `document` loaded from `location`.
+    fn follow(
+        &mut self,
+        reference: &str,
+        location: &str,
+        document: &Value,
+    ) -> Result<Value, JsonError> {
+        let error = |message: &str| JsonError::Reference {
+            reference: reference.to_string(),
+            message: message.to_string(),
+        };
+        let (path, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let pointer = percent_decode(fragment, false)
+            .filter(|pointer| pointer.is_empty() || pointer.starts_with('/'))
+            .ok_or_else(|| error("the fragment isn't a JSON Pointer"))?;
+
+        let (location, loaded) = if path.is_empty() {
+            (location.to_string(), None)
+        } else {
+            let location = join(location, path);
+            let loaded = match self.documents.get(&location) {
+                Some(loaded) => Rc::clone(loaded),
+                None => {
+                    let loader = self
+                        .loader
+                        .as_mut()
+                        .ok_or_else(|| error("other documents need a loader"))?;
+                    let loaded = Rc::new(loader(&location)?);
+                    self.documents.insert(location.clone(), Rc::clone(&loaded));
+                    loaded
+                }
+            };
+            (location, Some(loaded))
+        };
+        let document = loaded.as_deref().unwrap_or(document);
+        let target = document
+            .pointer(&pointer)
+            .ok_or_else(|| error("nothing exists at its pointer"))?;
+
+        let key = (location, pointer);
+        if self.expanding.contains(&key) {
+            return Err(error("it refers to a value containing itself"));
+        }
+        let location = key.0.clone();
+        self.expanding.push(key);
+        let resolved = self.resolve(target, &location, document);
+        self.expanding.pop();
+
+        resolved
+    }
+}
+
+/// The location of `path` referenced from the document at `base`, which is `path` itself if
+/// it's a URL or an absolute path, or `path` in the directory of `base` otherwise, with its
+/// `.` and `..` segments removed.
+pub(crate) fn join(base: &str, path: &str) -> String {
+    if path.contains("://") {
+        return path.to_string();
+    }
+
+    match base.rfind('/') {
+        Some(end) if !path.starts_with('/') => normalize(&format!("{}{path}", &base[..=end])),
+        _ => normalize(path),
+    }
+}
+
+/// Remove the `.` segments of a path, and the `..` segments along with the segments they
+/// cancel, so that a document reached by different paths has a single location.
+pub(crate) fn normalize(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." if segments
+                .last()
+                .is_some_and(|last| !last.is_empty() && *last != "..") =>
+            {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
@@ -0,0 +1,618 @@
+//! Inferring a [JSON Schema](https://json-schema.org) from sample documents.
+//!
+//! [`infer_schema`] describes the types seen at every location of the samples, the
+//! properties of objects along with those present in all of them, the items of arrays, and
+//! the values of strings which look like they come from a fixed set, to document data that
+//! came without a schema. The other way around, a [`Generator`] makes up random values
+//! conforming to a schema.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::pointer::push_token;
+use crate::value::{Number, Value};
+use std::collections::HashMap;
+
+/// The dialect of the schemas [`infer_schema`] writes.
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Most distinct strings a location can have to be described by an `enum`.
+const MAX_ENUM_VALUES: usize = 10;
+
+/// What was seen at one location of the samples.
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    float: bool,
+    /// Number of strings seen.
+    strings: usize,
+    /// The distinct strings seen, in the order they were first seen, unless there were more
+    /// than [`MAX_ENUM_VALUES`].
+    enumeration: Option<Vec<String>>,
+    /// The merged shape of the items of every array, if any array was seen.
+    array: Option<Box<Shape>>,
+    object: Option<ObjectShape>,
+}
+
+/// What was seen in the objects at one location of the samples.
+#[derive(Debug, Default)]
+struct ObjectShape {
+    /// Number of objects seen.
+    count: usize,
+    /// Properties in the order they were first seen, along with the number of objects having
+    /// them.
+    properties: Vec<(String, usize, Shape)>,
+    positions: HashMap<String, usize>,
+}
+
+impl Shape {
+    fn observe(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Boolean(_) => self.boolean = true,
+            Value::Number(Number::I64(_)) => self.integer = true,
+            Value::Number(Number::F64(_)) => self.float = true,
+            Value::String(string) => {
+                if self.strings == 0 {
+                    self.enumeration = Some(Vec::new());
+                }
+                self.strings += 1;
+                if let Some(values) = &mut self.enumeration {
+                    if !values.iter().any(|value| **value == **string) {
+                        values.push(string.to_string());
+                    }
+                    if values.len() > MAX_ENUM_VALUES {
+                        self.enumeration = None;
+                    }
+                }
+            }
+            Value::Array(elements) => {
+                let items = self.array.get_or_insert_with(Box::default);
+                for element in elements {
+                    items.observe(element);
+                }
+            }
+            Value::Object(members) => {
+                let object = self.object.get_or_insert_with(ObjectShape::default);
+                object.count += 1;
+                for (key, value) in members {
+                    let position = *object.positions.entry(key.to_string()).or_insert_with(|| {
+                        object
+                            .properties
+                            .push((key.to_string(), 0, Shape::default()));
+                        object.properties.len() - 1
+                    });
+                    let (_, count, shape) = &mut object.properties[position];
+                    *count += 1;
+                    shape.observe(value);
+                }
+            }
+        }
+    }
+
+    /// Add the keywords describing the shape to `schema`.
+    fn describe(&self, schema: &mut Map) {
+        let mut types = Vec::new();
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        // Integers are numbers too, so they're only told apart if there are no floats.
+        if self.float {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.strings > 0 {
+            types.push("string");
+        }
+        if self.array.is_some() {
+            types.push("array");
+        }
+        if self.object.is_some() {
+            types.push("object");
+        }
+
+        match types[..] {
+            [] => {}
+            [single] => {
+                schema.insert("type".to_string(), Value::from(single));
+            }
+            _ => {
+                let types = types.iter().copied().map(Value::from).collect();
+                schema.insert("type".to_string(), Value::Array(types));
+            }
+        }
+
+        // Strings are taken to come from a fixed set if each of them was seen twice on average,
+        // and nothing but strings was seen.
+        if let (["string"], Some(values)) = (&types[..], &self.enumeration) {
+            if self.strings >= 2 * values.len() {
+                let values = values.iter().map(|value| Value::from(value.as_str()));
+                schema.insert("enum".to_string(), Value::Array(values.collect()));
+            }
+        }
+
+        if let Some(object) = &self.object {
+            let mut properties = Map::with_capacity(object.properties.len());
+            let mut required = Vec::new();
+            for (key, count, shape) in &object.properties {
+                properties.insert(key.clone(), shape.to_schema());
+                if *count == object.count {
+                    required.push(Value::from(key.as_str()));
+                }
+            }
+            schema.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), Value::Array(required));
+            }
+        }
+        if let Some(shape) = &self.array {
+            let mut items = Map::new();
+            shape.describe(&mut items);
+            // Only empty arrays were seen, so nothing is known about their items.
+            if !items.is_empty() {
+                schema.insert("items".to_string(), Value::Object(items));
+            }
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut schema = Map::new();
+        self.describe(&mut schema);
+
+        Value::Object(schema)
+    }
+}
+
+/// Infer a JSON Schema describing every one of `samples`.
+///
+/// The schema gives the types seen at each location, `integer` only if no floats were seen
+/// there, the properties of objects in the order they were first seen, with the ones found in
+/// every object `required`, and the items of arrays, whose elements are all described by the
+/// same schema. Locations where nothing was seen, such as the items of arrays which were always
+/// empty, accept anything.
+///
+/// Locations holding only strings get an `enum` of the values seen there when these look like
+/// they come from a fixed set: there are at most ten of them, and they were seen twice each on
+/// average, so that a sample with names or identifiers doesn't make them an enumeration.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::JsonParser;
+/// use json_parser::schema::infer_schema;
+///
+/// let samples = [
+///     br#"{"id": 1, "tags": ["a"], "level": "info", "score": 2.5}"#.as_slice(),
+///     br#"{"id": 2, "tags": [], "level": "error", "score": null}"#,
+///     br#"{"id": 3, "tags": ["b", "c"], "level": "info"}"#,
+///     br#"{"id": 4, "tags": ["d"], "level": "info"}"#,
+/// ];
+/// let samples = samples.map(|sample| JsonParser::parse_from_bytes(sample).unwrap());
+/// let schema = infer_schema(&samples);
+///
+/// let expected = JsonParser::parse_from_bytes(
+///     br#"{
+///         "$schema": "https://json-schema.org/draft/2020-12/schema",
+///         "type": "object",
+///         "properties": {
+///             "id": {"type": "integer"},
+///             "tags": {"type": "array", "items": {"type": "string"}},
+///             "level": {"type": "string", "enum": ["info", "error"]},
+///             "score": {"type": ["null", "number"]}
+///         },
+///         "required": ["id", "tags", "level"]
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(schema, expected);
+/// ```
+pub fn infer_schema<'a, I>(samples: I) -> Value
+where
+    I: IntoIterator<Item = &'a Value>,
+{
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.observe(sample);
+    }
+
+    let mut schema = Map::new();
+    schema.insert("$schema".to_string(), Value::from(DIALECT));
+    shape.describe(&mut schema);
+
+    Value::Object(schema)
+}
+
+/// Maximum depth of the arrays and objects [`Generator`] makes up for schemas which don't
+/// describe them, such as `true`.
+const MAX_ANY_DEPTH: usize = 2;
+
+/// A generator of random values conforming to a JSON Schema, to fuzz services or seed tests
+/// with.
+///
+/// The generator understands the keywords describing the structure of values: `type`, `enum`
+/// and `const`, `anyOf` and `oneOf`, which it picks a branch of, `properties` and `required`,
+/// `items`, `minItems` and `maxItems`, `minimum`, `maximum`, `exclusiveMinimum` and
+/// `exclusiveMaximum`, and `minLength` and `maxLength`. Other keywords, such as `pattern` or
+/// `$ref`, are ignored, so values may not conform to schemas using them.
+///
+/// Values are drawn from a small pseudo-random generator rather than the operating system, so
+/// that the same seed always gives the same values.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::JsonParser;
+/// use json_parser::schema::Generator;
+/// use json_parser::value::{Number, Value};
+///
+/// let schema = JsonParser::parse_from_bytes(
+///     br#"{
+///         "type": "object",
+///         "properties": {
+///             "id": {"type": "integer", "minimum": 1, "maximum": 10},
+///             "level": {"enum": ["info", "error"]},
+///             "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 3}
+///         },
+///         "required": ["id", "level"]
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// let mut generator = Generator::new(42);
+/// for _ in 0..100 {
+///     let Value::Object(record) = generator.generate(&schema).unwrap() else {
+///         panic!("expected an object");
+///     };
+///     assert!(matches!(record.get("id"), Some(Value::Number(Number::I64(1..=10)))));
+///     assert!(matches!(record.get("level"), Some(Value::String(level)) if *level == "info" || *level == "error"));
+///     if let Some(Value::Array(tags)) = record.get("tags") {
+///         assert!(tags.len() <= 3);
+///     }
+/// }
+///
+/// // The same seed gives the same values.
+/// assert_eq!(Generator::new(7).generate(&schema), Generator::new(7).generate(&schema));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    /// Create a generator whose values are all determined by `seed`.
+    #[must_use]
+    pub fn new(seed: u64) -> Generator {
+        Generator { state: seed }
+    }
+
+    /// Generate a value conforming to `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if `schema` isn't a valid schema, or if no value can
+    /// conform to it, such as with the `false` schema or a `minimum` above the `maximum`.
+    pub fn generate(&mut self, schema: &Value) -> Result<Value, JsonError> {
+        self.generate_at(schema, "")
+    }
+
+    /// Generate a value for the schema at `pointer` inside the schema document, which errors
+    /// refer to.
+    fn generate_at(&mut self, schema: &Value, pointer: &str) -> Result<Value, JsonError> {
+        let schema = match schema {
+            Value::Boolean(true) => return Ok(self.any(0)),
+            Value::Boolean(false) => {
+                return Err(schema_error(pointer, "the `false` schema accepts no value"))
+            }
+            Value::Object(schema) => schema,
+            _ => {
+                return Err(schema_error(
+                    pointer,
+                    "a schema must be an object or a boolean",
+                ))
+            }
+        };
+
+        if let Some(value) = schema.get("const") {
+            return Ok(value.clone());
+        }
+        if let Some(values) = schema.get("enum") {
+            return match values {
+                Value::Array(values) if !values.is_empty() => Ok(self.pick(values).clone()),
+                _ => Err(schema_error(pointer, "`enum` must be a non-empty array")),
+            };
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(branches) = schema.get(keyword) {
+                let Value::Array(branches) = branches else {
+                    return Err(schema_error(
+                        pointer,
+                        &format!("`{keyword}` must be an array"),
+                    ));
+                };
+                if branches.is_empty() {
+                    return Err(schema_error(pointer, &format!("`{keyword}` is empty")));
+                }
+                let branch = self.below(branches.len() as u64) as usize;
+                return self
+                    .generate_at(&branches[branch], &format!("{pointer}/{keyword}/{branch}"));
+            }
+        }
+
+        let kind = match schema.get("type") {
+            Some(Value::String(kind)) => kind.to_string(),
+            Some(Value::Array(kinds)) if !kinds.is_empty() => match self.pick(kinds) {
+                Value::String(kind) => kind.to_string(),
+                _ => return Err(schema_error(pointer, "`type` must hold strings")),
+            },
+            Some(_) => return Err(schema_error(pointer, "`type` must be a string or an array")),
+            // Without a type, the keywords tell what kind of value is described, if any.
+            None if schema.contains_key("properties") || schema.contains_key("required") => {
+                "object".to_string()
+            }
+            None if schema.contains_key("items") => "array".to_string(),
+            None => return Ok(self.any(0)),
+        };
+
+        match kind.as_str() {
+            "null" => Ok(Value::Null),
+            "boolean" => Ok(Value::Boolean(self.below(2) == 1)),
+            "integer" => self.integer(schema, pointer),
+            "number" => self.number(schema, pointer),
+            "string" => self.string(schema, pointer),
+            "array" => self.array(schema, pointer),
+            "object" => self.object(schema, pointer),
+            _ => Err(schema_error(pointer, &format!("unknown type `{kind}`"))),
+        }
+    }
+
+    fn integer(&mut self, schema: &Map, pointer: &str) -> Result<Value, JsonError> {
+        let bound = |keyword| -> Result<Option<f64>, JsonError> {
+            match schema.get(keyword) {
+                None => Ok(None),
+                Some(Value::Number(number)) => Ok(Some(as_f64(*number))),
+                Some(_) => Err(schema_error(
+                    pointer,
+                    &format!("`{keyword}` must be a number"),
+                )),
+            }
+        };
+        let mut minimum = bound("minimum")?.map(f64::ceil);
+        let mut maximum = bound("maximum")?.map(f64::floor);
+        if let Some(exclusive) = bound("exclusiveMinimum")? {
+            minimum = Some(minimum.unwrap_or(f64::MIN).max(exclusive.floor() + 1.0));
+        }
+        if let Some(exclusive) = bound("exclusiveMaximum")? {
+            maximum = Some(maximum.unwrap_or(f64::MAX).min(exclusive.ceil() - 1.0));
+        }
+        // Bounds beyond the range of integers are clamped by the conversions.
+        let (minimum, maximum) = match (minimum, maximum) {
+            (Some(minimum), Some(maximum)) => (minimum as i64, maximum as i64),
+            (Some(minimum), None) => (minimum as i64, (minimum as i64).saturating_add(100)),
+            (None, Some(maximum)) => ((maximum as i64).saturating_sub(100), maximum as i64),
+            (None, None) => (-100, 100),
+        };
+        if minimum > maximum {
+            return Err(schema_error(pointer, "no integer is within the bounds"));
+        }
+
+        Ok(Value::Number(Number::I64(self.between(minimum, maximum))))
+    }
+
+    fn number(&mut self, schema: &Map, pointer: &str) -> Result<Value, JsonError> {
+        let bound = |keyword| -> Result<Option<f64>, JsonError> {
+            match schema.get(keyword) {
+                None => Ok(None),
+                Some(Value::Number(number)) => Ok(Some(as_f64(*number))),
+                Some(_) => Err(schema_error(
+                    pointer,
+                    &format!("`{keyword}` must be a number"),
+                )),
+            }
+        };
+        let exclusive_minimum = bound("exclusiveMinimum")?;
+        let exclusive_maximum = bound("exclusiveMaximum")?;
+        let minimum = [bound("minimum")?, exclusive_minimum]
+            .into_iter()
+            .flatten()
+            .reduce(f64::max);
+        let maximum = [bound("maximum")?, exclusive_maximum]
+            .into_iter()
+            .flatten()
+            .reduce(f64::min);
+        let (minimum, maximum) = match (minimum, maximum) {
+            (Some(minimum), Some(maximum)) => (minimum, maximum),
+            (Some(minimum), None) => (minimum, minimum + 100.0),
+            (None, Some(maximum)) => (maximum - 100.0, maximum),
+            (None, None) => (-100.0, 100.0),
+        };
+        if minimum > maximum {
+            return Err(schema_error(pointer, "no number is within the bounds"));
+        }
+
+        // Values landing on an exclusive bound are drawn again, giving up on bounds so close
+        // that nothing lies between them.
+        for _ in 0..100 {
+            let number = minimum + self.fraction() * (maximum - minimum);
+            if Some(number) != exclusive_minimum && Some(number) != exclusive_maximum {
+                return Ok(Value::Number(Number::F64(number)));
+            }
+        }
+        Err(schema_error(pointer, "no number is within the bounds"))
+    }
+
+    fn string(&mut self, schema: &Map, pointer: &str) -> Result<Value, JsonError> {
+        let (minimum, maximum) = self.length(schema, pointer, "minLength", "maxLength")?;
+        let length = self.between(minimum as i64, maximum as i64);
+        let string = (0..length)
+            .map(|_| char::from(b'a' + self.below(26) as u8))
+            .collect::<String>();
+
+        Ok(Value::from(string))
+    }
+
+    fn array(&mut self, schema: &Map, pointer: &str) -> Result<Value, JsonError> {
+        let (minimum, maximum) = self.length(schema, pointer, "minItems", "maxItems")?;
+        let length = self.between(minimum as i64, maximum as i64);
+        let items = schema.get("items").unwrap_or(&Value::Boolean(true));
+        let pointer = format!("{pointer}/items");
+
+        (0..length)
+            .map(|_| self.generate_at(items, &pointer))
+            .collect::<Result<_, _>>()
+            .map(Value::Array)
+    }
+
+    fn object(&mut self, schema: &Map, pointer: &str) -> Result<Value, JsonError> {
+        let empty = Map::new();
+        let properties = match schema.get("properties") {
+            None => &empty,
+            Some(Value::Object(properties)) => properties,
+            Some(_) => return Err(schema_error(pointer, "`properties` must be an object")),
+        };
+        let required = match schema.get("required") {
+            None => Vec::new(),
+            Some(Value::Array(required)) => required
+                .iter()
+                .map(|key| match key {
+                    Value::String(key) => Ok(key.as_ref()),
+                    _ => Err(schema_error(pointer, "`required` must hold strings")),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(schema_error(pointer, "`required` must be an array")),
+        };
+
+        let mut object = Map::with_capacity(properties.len());
+        for (key, property) in properties.iter() {
+            // Optional properties are left out half of the time.
+            if required.contains(&key) || self.below(2) == 1 {
+                let mut property_pointer = format!("{pointer}/properties/");
+                push_token(&mut property_pointer, key);
+                object.insert(
+                    key.to_string(),
+                    self.generate_at(property, &property_pointer)?,
+                );
+            }
+        }
+        // Required properties without a schema can hold anything.
+        for key in required {
+            if !object.contains_key(key) {
+                object.insert(key.to_string(), self.any(1));
+            }
+        }
+
+        Ok(Value::Object(object))
+    }
+
+    /// The bounds of the length of a string or array, which default to a few more than the
+    /// minimum.
+    fn length(
+        &mut self,
+        schema: &Map,
+        pointer: &str,
+        minimum: &str,
+        maximum: &str,
+    ) -> Result<(u32, u32), JsonError> {
+        let count = |keyword| match schema.get(keyword) {
+            None => Ok(None),
+            Some(&Value::Number(Number::I64(count))) if count >= 0 => {
+                Ok(Some(u32::try_from(count).unwrap_or(u32::MAX)))
+            }
+            Some(_) => Err(schema_error(
+                pointer,
+                &format!("`{keyword}` must be a non-negative integer"),
+            )),
+        };
+        let minimum_count = count(minimum)?.unwrap_or(0);
+        let maximum_count = count(maximum)?.unwrap_or(minimum_count.saturating_add(5));
+        if minimum_count > maximum_count {
+            return Err(schema_error(
+                pointer,
+                &format!("`{minimum}` is above `{maximum}`"),
+            ));
+        }
+
+        Ok((minimum_count, maximum_count))
+    }
+
+    /// A value of any type, with arrays and objects only up to [`MAX_ANY_DEPTH`].
+    fn any(&mut self, depth: usize) -> Value {
+        let kinds = if depth < MAX_ANY_DEPTH { 7 } else { 5 };
+        match self.below(kinds) {
+            0 => Value::Null,
+            1 => Value::Boolean(self.below(2) == 1),
+            2 => Value::Number(Number::I64(self.between(-100, 100))),
+            3 => Value::Number(Number::F64(self.fraction() * 200.0 - 100.0)),
+            4 => {
+                let length = self.below(6);
+                Value::from(
+                    (0..length)
+                        .map(|_| char::from(b'a' + self.below(26) as u8))
+                        .collect::<String>(),
+                )
+            }
+            5 => Value::Array((0..self.below(4)).map(|_| self.any(depth + 1)).collect()),
+            _ => {
+                let mut object = Map::new();
+                for index in 0..self.below(4) {
+                    object.insert(format!("key{index}"), self.any(depth + 1));
+                }
+                Value::Object(object)
+            }
+        }
+    }
+
+    fn pick<'a>(&mut self, values: &'a [Value]) -> &'a Value {
+        &values[self.below(values.len() as u64) as usize]
+    }
+
+    /// The next output of SplitMix64.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but excluding `bound`, which must not be 0.
+    fn below(&mut self, bound: u64) -> u64 {
+        // The modulo bias is negligible for the small bounds used here.
+        self.next() % bound
+    }
+
+    /// An integer from `minimum` to `maximum`, both included.
+    fn between(&mut self, minimum: i64, maximum: i64) -> i64 {
+        let span = (i128::from(maximum) - i128::from(minimum) + 1) as u128;
+        let offset = u128::from(self.next()) % span;
+        (i128::from(minimum) + offset as i128) as i64
+    }
+
+    /// A float from 0 up to but excluding 1.
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn as_f64(number: Number) -> f64 {
+    match number {
+        Number::I64(integer) => integer as f64,
+        Number::F64(float) => float,
+    }
+}
+
+fn schema_error(pointer: &str, message: &str) -> JsonError {
+    let location = if pointer.is_empty() {
+        "(root)"
+    } else {
+        pointer
+    };
+    JsonError::Format {
+        format: "JSON Schema",
+        message: format!("{message} at {location}"),
+    }
+}
@@ -0,0 +1,940 @@
+//! Writing values out as JSON text.
+
+use crate::error::JsonError;
+use crate::event::{Event, EventReader};
+use crate::pointer::push_token;
+use crate::value::{Number, Value};
+use std::convert::Infallible;
+use std::fmt;
+use std::io::{self, BufWriter, Read, Write};
+
+impl Value {
+    /// Serialize the value as JSON without any whitespace.
+    ///
+    /// Members are written in the order of the object, floats always keep a fraction or an
+    /// exponent so that they parse back as floats, and NaN or infinite floats, which JSON can't
+    /// represent, are written as `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{ "a": [1, 2.0, null], "b": "x" }"#).unwrap();
+    ///
+    /// assert_eq!(value.to_string_compact(), r#"{"a":[1,2.0,null],"b":"x"}"#);
+    /// ```
+    #[must_use]
+    pub fn to_string_compact(&self) -> String {
+        self.to_string_with_options(&WriterOptions::default())
+    }
+
+    /// Serialize the value as JSON with every element and member on its own line, indented by
+    /// two spaces per level of nesting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"a": [1, 2], "b": {}}"#).unwrap();
+    ///
+    /// assert_eq!(value.to_string_pretty(), "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}");
+    /// ```
+    #[must_use]
+    pub fn to_string_pretty(&self) -> String {
+        self.to_string_with_options(&WriterOptions {
+            indent: Some(2),
+            ..WriterOptions::default()
+        })
+    }
+
+    /// Serialize the value as JSON according to `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value holds a NaN or infinite float and `options.non_finite` is
+    /// [`NonFinite::Error`], in which case [`Value::try_to_string_with_options`] should be
+    /// used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::ser::WriterOptions;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"a": [1]}"#).unwrap();
+    /// let options = WriterOptions {
+    ///     indent: Some(4),
+    ///     ..WriterOptions::default()
+    /// };
+    ///
+    /// assert_eq!(value.to_string_with_options(&options), "{\n    \"a\": [\n        1\n    ]\n}");
+    /// ```
+    #[must_use]
+    pub fn to_string_with_options(&self, options: &WriterOptions) -> String {
+        match self.try_to_string_with_options(options) {
+            Ok(output) => output,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Serialize the value as JSON according to `options`, failing instead of panicking when
+    /// it holds a float that `options` don't allow.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::UnrepresentableNumber`] for the first NaN or infinite float if
+    /// `options.non_finite` is [`NonFinite::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::error::JsonError;
+    /// use json_parser::ser::{NonFinite, WriterOptions};
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let value = Value::Array(vec![Value::Number(Number::F64(f64::INFINITY))]);
+    /// let options = WriterOptions {
+    ///     non_finite: NonFinite::Error,
+    ///     ..WriterOptions::default()
+    /// };
+    ///
+    /// assert!(matches!(
+    ///     value.try_to_string_with_options(&options),
+    ///     Err(JsonError::UnrepresentableNumber { pointer, .. }) if pointer == "/0"
+    /// ));
+    /// ```
+    pub fn try_to_string_with_options(&self, options: &WriterOptions) -> Result<String, JsonError> {
+        check_finite(self, options)?;
+
+        let mut output = String::new();
+        Serializer::new(&mut output, options)
+            .document(self)
+            .expect("writing to a String can't fail");
+
+        Ok(output)
+    }
+}
+
+/// Options controlling how [`Value::to_string_with_options`] writes JSON text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriterOptions {
+    /// Number of spaces to indent each level of nesting by, with every element and member on
+    /// its own line, or `None` to write no whitespace at all. Empty arrays and objects always
+    /// stay on one line.
+    pub indent: Option<usize>,
+    /// Whether each level of nesting is indented by a tab instead of spaces when pretty
+    /// printing, with tabs counted as `indent` characters for [`WriterOptions::inline_width`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::ser::{LineEnding, WriterOptions};
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"a":[1]}"#).unwrap();
+    /// let options = WriterOptions {
+    ///     indent: Some(4),
+    ///     indent_with_tabs: true,
+    ///     line_ending: LineEnding::CrLf,
+    ///     space_after_colon: false,
+    ///     trailing_newline: true,
+    ///     ..WriterOptions::default()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     value.to_string_with_options(&options),
+    ///     "{\r\n\t\"a\":[\r\n\t\t1\r\n\t]\r\n}\r\n",
+    /// );
+    /// ```
+    pub indent_with_tabs: bool,
+    /// Line ending written between lines when pretty printing, and at the end with
+    /// [`WriterOptions::trailing_newline`].
+    pub line_ending: LineEnding,
+    /// Whether the text ends with a line ending, as most editors expect of files.
+    pub trailing_newline: bool,
+    /// Whether a space is written after each `:` when pretty printing, which is the default.
+    pub space_after_colon: bool,
+    /// Maximum width in characters up to which an array or object is kept on one line when
+    /// pretty printing, like `[1, 2, 3]`, counting from the start of its line, or `None` to
+    /// give every element and member its own line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::ser::WriterOptions;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"matrix":[[1,0],[0,1]],"name":"identity"}"#)
+    ///     .unwrap();
+    /// let options = WriterOptions {
+    ///     indent: Some(2),
+    ///     inline_width: Some(30),
+    ///     ..WriterOptions::default()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     value.to_string_with_options(&options),
+    ///     "{\n  \"matrix\": [[1, 0], [0, 1]],\n  \"name\": \"identity\"\n}",
+    /// );
+    /// ```
+    pub inline_width: Option<usize>,
+    /// Whether every character outside of ASCII is escaped as `\uXXXX`, with a surrogate pair
+    /// for those outside of the Basic Multilingual Plane, so that the text survives systems
+    /// which mangle UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::ser::WriterOptions;
+    /// use json_parser::value::Value;
+    ///
+    /// let options = WriterOptions {
+    ///     ascii_only: true,
+    ///     ..WriterOptions::default()
+    /// };
+    /// let json = Value::from("café ☕ 🎉").to_string_with_options(&options);
+    ///
+    /// assert_eq!(json, r#""caf\u00e9 \u2615 \ud83c\udf89""#);
+    /// assert_eq!(JsonParser::parse_from_bytes(json.as_bytes()).unwrap(), Value::from("café ☕ 🎉"));
+    /// ```
+    pub ascii_only: bool,
+    /// Whether `<`, `>`, `&`, U+2028 and U+2029 are escaped as `\uXXXX`, so that the text can
+    /// be inlined in a `<script>` tag or a JavaScript string literal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::ser::WriterOptions;
+    /// use json_parser::value::Value;
+    ///
+    /// let options = WriterOptions {
+    ///     html_safe: true,
+    ///     ..WriterOptions::default()
+    /// };
+    /// let json = Value::from("</script>&\u{2028}").to_string_with_options(&options);
+    ///
+    /// assert_eq!(json, r#""\u003c/script\u003e\u0026\u2028""#);
+    /// ```
+    pub html_safe: bool,
+    /// Whether the members of every object are written sorted by key, rather than in the
+    /// order of the map, so that the output is the same however the value was built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::ser::WriterOptions;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"b":1,"a":{"d":2,"c":3}}"#).unwrap();
+    /// let options = WriterOptions {
+    ///     sort_keys: true,
+    ///     ..WriterOptions::default()
+    /// };
+    ///
+    /// assert_eq!(value.to_string_with_options(&options), r#"{"a":{"c":3,"d":2},"b":1}"#);
+    /// ```
+    pub sort_keys: bool,
+    /// How finite floats are written.
+    pub floats: FloatFormat,
+    /// How NaN and infinite floats, which JSON can't represent, are written.
+    pub non_finite: NonFinite,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            indent: None,
+            indent_with_tabs: false,
+            line_ending: LineEnding::Lf,
+            trailing_newline: false,
+            space_after_colon: true,
+            inline_width: None,
+            ascii_only: false,
+            html_safe: false,
+            sort_keys: false,
+            floats: FloatFormat::Shortest,
+            non_finite: NonFinite::Null,
+        }
+    }
+}
+
+/// Line ending written by [`WriterOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, as on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// The characters of the line ending.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How [`WriterOptions`] write finite floats.
+///
+/// Whatever the format, floats are written with a fraction or an exponent so that they parse
+/// back as floats, except with [`FloatFormat::Fixed`] and no digits.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::ser::{FloatFormat, WriterOptions};
+/// use json_parser::value::{Number, Value};
+///
+/// let value = Value::Array(vec![
+///     Value::Number(Number::F64(0.1)),
+///     Value::Number(Number::F64(2.0 / 3.0)),
+///     Value::Number(Number::F64(1.5e-7)),
+///     Value::Number(Number::F64(1e21)),
+/// ]);
+/// let write = |floats| {
+///     value.to_string_with_options(&WriterOptions {
+///         floats,
+///         ..WriterOptions::default()
+///     })
+/// };
+///
+/// assert_eq!(write(FloatFormat::Shortest), "[0.1,0.6666666666666666,1.5e-7,1e21]");
+/// assert_eq!(write(FloatFormat::Fixed(3)), "[0.100,0.667,0.000,1000000000000000000000.000]");
+/// assert_eq!(
+///     write(FloatFormat::Scientific { threshold: 3 }),
+///     "[0.1,0.6666666666666666,1.5e-7,1e21]"
+/// );
+/// assert_eq!(
+///     write(FloatFormat::Scientific { threshold: 30 }),
+///     "[0.1,0.6666666666666666,0.00000015,1000000000000000000000.0]"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// The shortest digits which read back as the same float, in scientific notation when
+    /// the float is very large or very small.
+    #[default]
+    Shortest,
+    /// A fixed number of digits after the decimal point, rounding the float and never using
+    /// scientific notation.
+    Fixed(usize),
+    /// The shortest digits which read back as the same float, in scientific notation exactly
+    /// when the decimal exponent is at least `threshold` or at most `-threshold`.
+    Scientific {
+        /// Magnitude of the decimal exponent from which scientific notation is used.
+        threshold: u32,
+    },
+}
+
+/// How [`WriterOptions`] write NaN and infinite floats, which JSON can't represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /// Write them as `null`.
+    #[default]
+    Null,
+    /// Fail with [`JsonError::UnrepresentableNumber`], before anything is written.
+    Error,
+    /// Write them as the literals `NaN`, `Infinity` and `-Infinity`, as JavaScript and JSON5
+    /// do, even though the output isn't valid JSON.
+    Literal,
+}
+
+/// Check that `value` has no NaN or infinite floats if `options` forbid them.
+fn check_finite(value: &Value, options: &WriterOptions) -> Result<(), JsonError> {
+    fn find(value: &Value, pointer: &mut String) -> Option<f64> {
+        match value {
+            Value::Number(Number::F64(float)) if !float.is_finite() => Some(*float),
+            Value::Array(array) => array.iter().enumerate().find_map(|(index, element)| {
+                let length = pointer.len();
+                push_token(pointer, &index.to_string());
+                let found = find(element, pointer);
+                if found.is_none() {
+                    pointer.truncate(length);
+                }
+                found
+            }),
+            Value::Object(object) => object.iter().find_map(|(key, member)| {
+                let length = pointer.len();
+                push_token(pointer, key);
+                let found = find(member, pointer);
+                if found.is_none() {
+                    pointer.truncate(length);
+                }
+                found
+            }),
+            _ => None,
+        }
+    }
+
+    if options.non_finite != NonFinite::Error {
+        return Ok(());
+    }
+    let mut pointer = String::new();
+    match find(value, &mut pointer) {
+        Some(float) => Err(JsonError::UnrepresentableNumber {
+            pointer,
+            number: float.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Remove all whitespace between the tokens of a JSON document, leaving everything else,
+/// including the text of numbers and the escapes inside strings, untouched.
+///
+/// This works on the tokens of the text, read as by [`reformat`], without building a
+/// [`Value`].
+///
+/// # Errors
+///
+/// Returns [`JsonError::Syntax`] if the input isn't valid JSON.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::ser::minify;
+///
+/// let input = "{\n  \"name\": \"a b\",\n  \"sizes\": [ 1.50, 2e3 ]\n}\n";
+/// assert_eq!(minify(input).unwrap(), r#"{"name":"a b","sizes":[1.50,2e3]}"#);
+///
+/// // Whitespace between scalars isn't removed, which would join them, but rejected.
+/// assert!(minify("[1 2]").is_err());
+/// ```
+pub fn minify(input: &str) -> Result<String, JsonError> {
+    let mut output = Vec::with_capacity(input.len());
+    reformat(input.as_bytes(), &mut output, Layout::Compact)?;
+
+    Ok(String::from_utf8(output).expect("tokens are copied from valid UTF-8"))
+}
+
+/// How [`reformat`] lays out the JSON text it writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// No whitespace at all, as done by [`minify`].
+    Compact,
+    /// Every element and member on its own line, indented by two spaces per level of nesting,
+    /// with a space after each `:`. Empty arrays and objects stay on one line.
+    Pretty,
+}
+
+/// Copy the JSON document read from `reader` to `writer`, replacing its whitespace according
+/// to `layout`.
+///
+/// The document is read as a stream of events, like by [`EventReader`](crate::event::EventReader),
+/// without building a [`Value`], so arbitrarily large documents are reformatted in memory
+/// which only grows with their nesting depth. The text of numbers and strings is copied as
+/// is, including the escapes inside strings.
+///
+/// # Errors
+///
+/// Returns [`JsonError::Syntax`] if the input isn't valid JSON, in which case the text up to
+/// the error may have been written already, and [`JsonError::Io`] for any error raised while
+/// reading from `reader` or writing to `writer`.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::ser::{reformat, Layout};
+///
+/// let mut output = Vec::new();
+/// reformat(r#"{"a":[1,2],"b":{}}"#.as_bytes(), &mut output, Layout::Pretty).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}"
+/// );
+///
+/// for invalid in ["1 2", "[1, 2", "[1}", r#"{"a" 1}"#] {
+///     assert!(reformat(invalid.as_bytes(), &mut Vec::new(), Layout::Compact).is_err());
+/// }
+/// ```
+pub fn reformat<R: Read, W: Write>(reader: R, writer: W, layout: Layout) -> Result<(), JsonError> {
+    let mut writer = BufWriter::new(writer);
+    let mut events = EventReader::raw(reader);
+    let mut depth = 0usize;
+    // Whether the next value or key is the first of its array or object, in which case it
+    // isn't preceded by a comma, and whether it's the value of a key, in which case it isn't
+    // preceded by anything.
+    let mut first = true;
+    let mut after_key = false;
+
+    let newline = |writer: &mut BufWriter<W>, depth: usize| -> io::Result<()> {
+        writer.write_all(b"\n")?;
+        for _ in 0..depth {
+            writer.write_all(b"  ")?;
+        }
+        Ok(())
+    };
+
+    while let Some(event) = events.next() {
+        let event = event?;
+
+        if let Event::EndObject | Event::EndArray = event {
+            depth -= 1;
+            // Empty arrays and objects stay on one line.
+            if layout == Layout::Pretty && !first {
+                newline(&mut writer, depth)?;
+            }
+            writer.write_all(if event == Event::EndObject {
+                b"}"
+            } else {
+                b"]"
+            })?;
+            first = false;
+            continue;
+        }
+
+        if !std::mem::take(&mut after_key) {
+            if !first {
+                writer.write_all(b",")?;
+            }
+            if layout == Layout::Pretty && depth > 0 {
+                newline(&mut writer, depth)?;
+            }
+        }
+        first = false;
+
+        match event {
+            Event::StartObject | Event::StartArray => {
+                writer.write_all(if event == Event::StartObject {
+                    b"{"
+                } else {
+                    b"["
+                })?;
+                depth += 1;
+                first = true;
+            }
+            Event::Key(_) => {
+                writer.write_all(events.token())?;
+                writer.write_all(if layout == Layout::Pretty {
+                    b": "
+                } else {
+                    b":"
+                })?;
+                after_key = true;
+            }
+            _ => writer.write_all(events.token())?,
+        }
+    }
+
+    Ok(writer.flush()?)
+}
+
+/// Serialize `value` as JSON without any whitespace, like [`Value::to_string_compact`],
+/// directly to `writer`.
+///
+/// The text is written piece by piece as it's produced, with the characters of strings which
+/// need no escaping written in runs, so the whole text is never held in memory. Writes are
+/// small, so a [`BufWriter`] should be used for files and sockets.
+///
+/// # Errors
+///
+/// Returns any error raised while writing to `writer`.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::JsonParser;
+///
+/// let value = JsonParser::parse_from_bytes(br#"{"name": "a\"b", "sizes": [1, 2.5]}"#).unwrap();
+/// let mut output = Vec::new();
+/// json_parser::ser::to_writer(&mut output, &value).unwrap();
+///
+/// assert_eq!(output, br#"{"name":"a\"b","sizes":[1,2.5]}"#);
+/// ```
+pub fn to_writer<W: Write + ?Sized>(writer: &mut W, value: &Value) -> io::Result<()> {
+    to_writer_with_options(writer, value, &WriterOptions::default())
+}
+
+/// Serialize `value` as JSON according to `options`, like
+/// [`Value::to_string_with_options`], directly to `writer`.
+///
+/// # Errors
+///
+/// Returns any error raised while writing to `writer`, or an error of kind
+/// [`io::ErrorKind::InvalidData`] wrapping [`JsonError::UnrepresentableNumber`] if `value`
+/// holds a NaN or infinite float and `options.non_finite` is [`NonFinite::Error`], in which
+/// case nothing is written.
+pub fn to_writer_with_options<W: Write + ?Sized>(
+    writer: &mut W,
+    value: &Value,
+    options: &WriterOptions,
+) -> io::Result<()> {
+    check_finite(value, options)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Serializer::new(&mut IoOutput(writer), options).document(value)
+}
+
+/// Where serialized text goes, which is either a `String` or an [`io::Write`] wrapped in
+/// [`IoOutput`].
+trait Output {
+    fn push(&mut self, text: &str) -> io::Result<()>;
+
+    fn push_fmt(&mut self, arguments: fmt::Arguments<'_>) -> io::Result<()>;
+}
+
+impl Output for String {
+    fn push(&mut self, text: &str) -> io::Result<()> {
+        self.push_str(text);
+        Ok(())
+    }
+
+    fn push_fmt(&mut self, arguments: fmt::Arguments<'_>) -> io::Result<()> {
+        fmt::Write::write_fmt(self, arguments).map_err(io::Error::other)
+    }
+}
+
+struct IoOutput<'w, W: ?Sized>(&'w mut W);
+
+impl<W: Write + ?Sized> Output for IoOutput<'_, W> {
+    fn push(&mut self, text: &str) -> io::Result<()> {
+        self.0.write_all(text.as_bytes())
+    }
+
+    fn push_fmt(&mut self, arguments: fmt::Arguments<'_>) -> io::Result<()> {
+        self.0.write_fmt(arguments)
+    }
+}
+
+/// Collects the text of a value written on one line, failing once it's wider than the
+/// `remaining` characters.
+struct InlineOutput {
+    text: String,
+    remaining: usize,
+}
+
+impl Output for InlineOutput {
+    fn push(&mut self, text: &str) -> io::Result<()> {
+        let width = text.chars().count();
+        if width > self.remaining {
+            return Err(io::Error::other("line is too wide"));
+        }
+        self.remaining -= width;
+        self.text.push_str(text);
+
+        Ok(())
+    }
+
+    fn push_fmt(&mut self, arguments: fmt::Arguments<'_>) -> io::Result<()> {
+        self.push(&fmt::format(arguments))
+    }
+}
+
+/// Writes values to an [`Output`] according to the options.
+struct Serializer<'a, O: ?Sized> {
+    output: &'a mut O,
+    options: &'a WriterOptions,
+    /// Whether arrays and objects are being written on one line while pretty printing.
+    inline: bool,
+    /// Column at which the value being written starts, only tracked when pretty printing
+    /// with [`WriterOptions::inline_width`].
+    column: usize,
+}
+
+impl<'a, O: Output + ?Sized> Serializer<'a, O> {
+    fn new(output: &'a mut O, options: &'a WriterOptions) -> Self {
+        Serializer {
+            output,
+            options,
+            inline: false,
+            column: 0,
+        }
+    }
+
+    /// Write `value` as a whole document.
+    fn document(&mut self, value: &Value) -> io::Result<()> {
+        self.value(value, 0)?;
+        if self.options.trailing_newline {
+            self.output.push(self.options.line_ending.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    /// Write `value`, nested `depth` levels deep.
+    fn value(&mut self, value: &Value, depth: usize) -> io::Result<()> {
+        match value {
+            Value::Null => self.output.push("null"),
+            Value::Boolean(boolean) => self.output.push(if *boolean { "true" } else { "false" }),
+            Value::Number(number) => self.number(*number),
+            Value::String(string) => self.string(string),
+            Value::Array(_) | Value::Object(_) if self.fits_inline(value)? => Ok(()),
+            Value::Array(array) => self.array(array, depth),
+            Value::Object(object) if self.options.sort_keys => {
+                let mut members: Vec<_> = object.iter().collect();
+                members.sort_by_key(|&(key, _)| key);
+                self.object(members, depth)
+            }
+            Value::Object(object) => self.object(object.iter(), depth),
+        }
+    }
+
+    /// Write `value` on one line if pretty printing allows it and it fits within
+    /// [`WriterOptions::inline_width`], returning whether it was written.
+    fn fits_inline(&mut self, value: &Value) -> io::Result<bool> {
+        let Some(width) = self.options.inline_width else {
+            return Ok(false);
+        };
+        if self.inline || self.options.indent.is_none() {
+            return Ok(false);
+        }
+        let mut output = InlineOutput {
+            text: String::new(),
+            remaining: width.saturating_sub(self.column),
+        };
+        let mut serializer = Serializer::new(&mut output, self.options);
+        serializer.inline = true;
+        if serializer.value(value, 0).is_err() {
+            return Ok(false);
+        }
+        self.output.push(&output.text)?;
+
+        Ok(true)
+    }
+
+    fn array(&mut self, array: &[Value], depth: usize) -> io::Result<()> {
+        self.output.push("[")?;
+        for (index, element) in array.iter().enumerate() {
+            if index > 0 {
+                self.comma()?;
+            }
+            self.newline(depth + 1)?;
+            self.value(element, depth + 1)?;
+        }
+        if !array.is_empty() {
+            self.newline(depth)?;
+        }
+        self.output.push("]")
+    }
+
+    /// Write an object with `members` in the order given.
+    fn object<'v>(
+        &mut self,
+        members: impl IntoIterator<Item = (&'v str, &'v Value)>,
+        depth: usize,
+    ) -> io::Result<()> {
+        self.output.push("{")?;
+        let mut empty = true;
+        for (key, member) in members {
+            if !empty {
+                self.comma()?;
+            }
+            empty = false;
+            self.newline(depth + 1)?;
+            self.string(key)?;
+            let space = self.options.indent.is_some() && self.options.space_after_colon;
+            self.output.push(if space { ": " } else { ":" })?;
+            if self.options.inline_width.is_some() {
+                // The quotes and `:` around the key, plus the key as it's escaped.
+                self.column += 3 + usize::from(space);
+                let _ = escape(key, self.options, |piece| {
+                    self.column += piece.chars().count();
+                    Ok::<_, Infallible>(())
+                });
+            }
+            self.value(member, depth + 1)?;
+        }
+        if !empty {
+            self.newline(depth)?;
+        }
+        self.output.push("}")
+    }
+
+    /// Separate elements or members, with a space after the comma when writing on one line.
+    fn comma(&mut self) -> io::Result<()> {
+        self.output.push(if self.inline { ", " } else { "," })
+    }
+
+    /// Start a new line indented for `depth` levels of nesting, when pretty printing and not
+    /// writing on one line.
+    fn newline(&mut self, depth: usize) -> io::Result<()> {
+        let Some(indent) = self.options.indent else {
+            return Ok(());
+        };
+        if self.inline {
+            return Ok(());
+        }
+        self.output.push(self.options.line_ending.as_str())?;
+        if self.options.indent_with_tabs {
+            for _ in 0..depth {
+                self.output.push("\t")?;
+            }
+        } else {
+            for _ in 0..indent * depth {
+                self.output.push(" ")?;
+            }
+        }
+        self.column = indent * depth;
+
+        Ok(())
+    }
+
+    /// Write a number, with floats formatted according to the options.
+    fn number(&mut self, number: Number) -> io::Result<()> {
+        let float = match number {
+            Number::I64(integer) => return self.output.push_fmt(format_args!("{integer}")),
+            Number::F64(float) => float,
+        };
+        if !float.is_finite() {
+            return self.output.push(match self.options.non_finite {
+                // Values are checked for non-finite floats before anything is written when
+                // they are an error.
+                NonFinite::Null | NonFinite::Error => "null",
+                NonFinite::Literal if float.is_nan() => "NaN",
+                NonFinite::Literal if float > 0.0 => "Infinity",
+                NonFinite::Literal => "-Infinity",
+            });
+        }
+
+        match self.options.floats {
+            // The debug format is the shortest one that reads back as the same float, and
+            // always includes a `.` or an exponent.
+            FloatFormat::Shortest => self.output.push_fmt(format_args!("{float:?}")),
+            FloatFormat::Fixed(digits) => self.output.push_fmt(format_args!("{float:.digits$}")),
+            FloatFormat::Scientific { threshold } => {
+                // The exponent format also has the shortest digits, and gives the exponent
+                // needed to decide between the two notations.
+                let scientific = format!("{float:e}");
+                let (_, exponent) = scientific.split_once('e').expect("exponent is written");
+                let exponent: i32 = exponent.parse().expect("exponent is an integer");
+                if float != 0.0 && exponent.unsigned_abs() >= threshold {
+                    self.output.push(&scientific)
+                } else if float.fract() == 0.0 {
+                    self.output.push_fmt(format_args!("{float}.0"))
+                } else {
+                    self.output.push_fmt(format_args!("{float}"))
+                }
+            }
+        }
+    }
+
+    fn string(&mut self, string: &str) -> io::Result<()> {
+        self.output.push("\"")?;
+        escape(string, self.options, |piece| self.output.push(piece))?;
+        self.output.push("\"")
+    }
+}
+
+/// Pass the escaped contents of `string` to `push` in pieces, which are either runs of
+/// characters needing no escaping or single escape sequences.
+///
+/// Only the characters which must be escaped are, unless `options` say otherwise: quotes,
+/// backslashes and control characters, using the short forms such as `\n` where they exist
+/// and `\u00XX` otherwise.
+fn escape<E>(
+    string: &str,
+    options: &WriterOptions,
+    mut push: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut run = 0;
+    let mut buffer = [0; 12];
+    for (index, character) in string.char_indices() {
+        let escaped = match character {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\u{08}' => "\\b",
+            '\u{0C}' => "\\f",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            control if control < ' ' => unicode_escape(control, &mut buffer),
+            other if options.ascii_only && !other.is_ascii() => unicode_escape(other, &mut buffer),
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if options.html_safe => {
+                unicode_escape(character, &mut buffer)
+            }
+            _ => continue,
+        };
+        push(&string[run..index])?;
+        push(escaped)?;
+        run = index + character.len_utf8();
+    }
+
+    push(&string[run..])
+}
+
+/// Write the `\uXXXX` escape of `character` to `buffer`, as a surrogate pair for characters
+/// outside of the Basic Multilingual Plane.
+fn unicode_escape(character: char, buffer: &mut [u8; 12]) -> &str {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let mut length = 0;
+    for unit in character.encode_utf16(&mut [0; 2]) {
+        buffer[length..length + 2].copy_from_slice(b"\\u");
+        for (position, shift) in [12, 8, 4, 0].into_iter().enumerate() {
+            buffer[length + 2 + position] = HEX[usize::from(*unit >> shift) & 0xF];
+        }
+        length += 6;
+    }
+
+    std::str::from_utf8(&buffer[..length]).expect("escapes are ASCII")
+}
+
+/// Append `string` to `output` as a quoted JSON string, escaped like by [`to_writer`].
+pub(crate) fn write_string(output: &mut String, string: &str) {
+    output.push('"');
+    let _ = escape(string, &WriterOptions::default(), |piece| {
+        output.push_str(piece);
+        Ok::<_, ()>(())
+    });
+    output.push('"');
+}
+
+/// Write `string` to `writer` as a quoted JSON string, escaped like by [`to_writer`].
+pub(crate) fn write_string_to<W: Write + ?Sized>(writer: &mut W, string: &str) -> io::Result<()> {
+    writer.write_all(b"\"")?;
+    escape(string, &WriterOptions::default(), |piece| {
+        writer.write_all(piece.as_bytes())
+    })?;
+    writer.write_all(b"\"")
+}
+
+/// Format a finite float the way ECMAScript's `Number.prototype.toString` does, which is the
+/// shortest representation that reads back as the same float, switching to exponent notation
+/// for very large and very small magnitudes.
+pub(crate) fn format_float_ecmascript(float: f64) -> String {
+    if float == 0.0 {
+        // Includes negative zero, which is written without its sign.
+        return "0".to_string();
+    }
+    if float < 0.0 {
+        return format!("-{}", format_float_ecmascript(-float));
+    }
+
+    // The exponent form gives the shortest digits along with the position of the point.
+    let scientific = format!("{float:e}");
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("exponent notation always has an exponent");
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent.parse().expect("exponents are integers");
+
+    // With the digits written as `d1 d2 ... dk`, the value is `0.d1d2...dk × 10^point`.
+    let length = digits.len() as i32;
+    let point = exponent + 1;
+
+    if length <= point && point <= 21 {
+        format!("{digits}{}", "0".repeat((point - length) as usize))
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        format!("{integer}.{fraction}")
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{digits}", "0".repeat(-point as usize))
+    } else {
+        let sign = if point > 0 { '+' } else { '-' };
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        format!("{first}{fraction}e{sign}{}", (point - 1).abs())
+    }
+}
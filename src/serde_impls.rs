@@ -0,0 +1,193 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, Serializer};
+use std::fmt;
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Number::I64(integer) => serializer.serialize_i64(*integer),
+            Number::F64(float) => serializer.serialize_f64(*float),
+        }
+    }
+}
+
+impl Serialize for Map {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::String(string) => serializer.serialize_str(string),
+            Value::Number(number) => number.serialize(serializer),
+            Value::Boolean(boolean) => serializer.serialize_bool(*boolean),
+            Value::Array(array) => serializer.collect_seq(array),
+            Value::Object(object) => object.serialize(serializer),
+            Value::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
+/// Visitor building a [`Number`] out of any numeric type.
+struct NumberVisitor;
+
+impl Visitor<'_> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON number")
+    }
+
+    fn visit_i64<E: de::Error>(self, integer: i64) -> Result<Number, E> {
+        Ok(Number::I64(integer))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_u64<E: de::Error>(self, integer: u64) -> Result<Number, E> {
+        // Integers which don't fit in an `i64` fall back to a float.
+        Ok(i64::try_from(integer).map_or(Number::F64(integer as f64), Number::I64))
+    }
+
+    fn visit_f64<E: de::Error>(self, float: f64) -> Result<Number, E> {
+        Ok(Number::F64(float))
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+/// Visitor building a [`Map`] out of any map type.
+struct MapVisitor;
+
+impl<'de> Visitor<'de> for MapVisitor {
+    type Value = Map;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Map, A::Error> {
+        let mut map = Map::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+}
+
+impl<'de> Deserialize<'de> for Map {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(MapVisitor)
+    }
+}
+
+/// Visitor building a [`Value`] out of anything in the serde data model which maps onto JSON.
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, boolean: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(boolean))
+    }
+
+    fn visit_i64<E: de::Error>(self, integer: i64) -> Result<Value, E> {
+        NumberVisitor.visit_i64(integer).map(Value::Number)
+    }
+
+    fn visit_u64<E: de::Error>(self, integer: u64) -> Result<Value, E> {
+        NumberVisitor.visit_u64(integer).map(Value::Number)
+    }
+
+    fn visit_f64<E: de::Error>(self, float: f64) -> Result<Value, E> {
+        NumberVisitor.visit_f64(float).map(Value::Number)
+    }
+
+    fn visit_str<E: de::Error>(self, string: &str) -> Result<Value, E> {
+        Ok(Value::from(string))
+    }
+
+    fn visit_string<E: de::Error>(self, string: String) -> Result<Value, E> {
+        Ok(Value::from(string))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut array = Vec::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some(value) = access.next_element()? {
+            array.push(value);
+        }
+
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, access: A) -> Result<Value, A::Error> {
+        MapVisitor.visit_map(access).map(Value::Object)
+    }
+}
+
+/// Integers and floats stay apart, and object members keep their order, through any serde
+/// format.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::value::{Number, Value};
+///
+/// let json = r#"{"b":1,"a":[1.0,-2,2.5e3],"c":null}"#;
+/// let value: Value = serde_json::from_str(json).unwrap();
+///
+/// assert_eq!(value.pointer("/a/0"), Some(&Value::Number(Number::F64(1.0))));
+/// assert_eq!(value.pointer("/a/1"), Some(&Value::Number(Number::I64(-2))));
+///
+/// let serialized = serde_json::to_string(&value).unwrap();
+///
+/// assert_eq!(serialized, r#"{"b":1,"a":[1.0,-2,2500.0],"c":null}"#);
+/// assert_eq!(serde_json::from_str::<Value>(&serialized).unwrap(), value);
+/// ```
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl de::Error for JsonError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        JsonError::Custom(message.to_string())
+    }
+}
+
+impl ser::Error for JsonError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        JsonError::Custom(message.to_string())
+    }
+}
@@ -0,0 +1,110 @@
+use crate::error::JsonError;
+use crate::value::{Number, Value};
+
+impl TryFrom<serde_json::Value> for Value {
+    type Error = JsonError;
+
+    /// Convert a [`serde_json::Value`] into a [`Value`].
+    ///
+    /// Fails with [`JsonError::UnrepresentableNumber`] for unsigned integers larger than
+    /// [`i64::MAX`], which would lose precision as a float.
+    ///
+    /// Object members are converted in order both ways. `serde_json` sorts them by key unless
+    /// its `preserve_order` feature is enabled, such as by the `serde_json_preserve_order`
+    /// feature of this crate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"a":[2.5,{"y":true,"z":null}],"b":1}"#)
+    ///     .unwrap();
+    /// let converted = serde_json::Value::try_from(value.clone()).unwrap();
+    ///
+    /// assert_eq!(converted.to_string(), r#"{"a":[2.5,{"y":true,"z":null}],"b":1}"#);
+    /// assert_eq!(Value::try_from(converted).unwrap(), value);
+    /// ```
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(boolean) => Value::Boolean(boolean),
+            serde_json::Value::Number(number) => {
+                if let Some(integer) = number.as_i64() {
+                    Value::Number(Number::I64(integer))
+                } else if number.is_f64() {
+                    Value::Number(Number::F64(number.as_f64().unwrap_or_default()))
+                } else {
+                    return Err(JsonError::UnrepresentableNumber {
+                        pointer: String::new(),
+                        number: number.to_string(),
+                    });
+                }
+            }
+            serde_json::Value::String(string) => Value::from(string),
+            serde_json::Value::Array(array) => Value::Array(
+                array
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        Value::try_from(value).map_err(|error| error.nested(&index.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            serde_json::Value::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| match Value::try_from(value) {
+                        Ok(value) => Ok((key, value)),
+                        Err(error) => Err(error.nested(&key)),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+impl TryFrom<Value> for serde_json::Value {
+    type Error = JsonError;
+
+    /// Convert a [`Value`] into a [`serde_json::Value`].
+    ///
+    /// Fails with [`JsonError::UnrepresentableNumber`] for NaN and infinite floats, which
+    /// `serde_json` can't hold.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Boolean(boolean) => serde_json::Value::Bool(boolean),
+            Value::Number(Number::I64(integer)) => serde_json::Value::from(integer),
+            Value::Number(Number::F64(float)) => serde_json::Number::from_f64(float)
+                .map(serde_json::Value::Number)
+                .ok_or_else(|| JsonError::UnrepresentableNumber {
+                    pointer: String::new(),
+                    number: float.to_string(),
+                })?,
+            // Converting is only needed for the `compact_str` feature.
+            #[allow(clippy::useless_conversion)]
+            Value::String(string) => serde_json::Value::String(string.into()),
+            Value::Array(array) => serde_json::Value::Array(
+                array
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        serde_json::Value::try_from(value)
+                            .map_err(|error| error.nested(&index.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(object) => serde_json::Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| match serde_json::Value::try_from(value) {
+                        Ok(value) => Ok((key, value)),
+                        Err(error) => Err(error.nested(&key)),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
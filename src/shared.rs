@@ -0,0 +1,213 @@
+//! Values whose parts are reference counted, to be cloned in constant time and shared across
+//! threads, and modified by copying only what's on the path to the change.
+
+use crate::map::Map;
+use crate::pointer::{parse_index, parse_pointer};
+use crate::value::{Number, Value};
+use std::sync::Arc;
+
+/// A [`Value`] whose strings, arrays and objects are reference counted, so cloning it or any
+/// part of it doesn't copy anything.
+///
+/// Modifying a value copies the arrays and objects on the path to the change if they're
+/// shared, and nothing else, so that clones taken before are left as they were. This makes
+/// cheap snapshots of large documents, each version sharing what it didn't change with the
+/// others.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::JsonParser;
+/// use json_parser::shared::ArcValue;
+/// use std::thread;
+///
+/// let value = JsonParser::parse_from_bytes(br#"{"users": [{"name": "Ada"}]}"#).unwrap();
+/// let cached = ArcValue::from(value);
+///
+/// let users = cached.get("users").unwrap().clone();
+/// let name = thread::spawn(move || users.at(0)?.get("name")?.as_str().map(str::to_string));
+///
+/// assert_eq!(name.join().unwrap().as_deref(), Some("Ada"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArcValue {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(Arc<str>),
+    Array(Arc<Vec<ArcValue>>),
+    Object(Arc<ArcObject>),
+}
+
+/// The members of an [`ArcValue::Object`], in insertion order.
+pub type ArcObject = Map<ArcValue>;
+
+impl ArcValue {
+    /// The value of the member with the given key, if this is an object which has one.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&ArcValue> {
+        match self {
+            ArcValue::Object(object) => object.get(key),
+            _ => None,
+        }
+    }
+
+    /// The element at the given position, if this is an array long enough.
+    #[must_use]
+    pub fn at(&self, position: usize) -> Option<&ArcValue> {
+        match self {
+            ArcValue::Array(elements) => elements.get(position),
+            _ => None,
+        }
+    }
+
+    /// Find the value referenced by a JSON Pointer, like [`Value::pointer`].
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&ArcValue> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| match value {
+                ArcValue::Object(object) => object.get(token),
+                ArcValue::Array(elements) => elements.get(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// The value of the member with the given key, to modify it, if this is an object which
+    /// has one.
+    ///
+    /// The object is copied first if it's shared, but its members aren't, since they're
+    /// reference counted themselves.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut ArcValue> {
+        match self {
+            ArcValue::Object(object) if object.get(key).is_some() => {
+                Arc::make_mut(object).get_mut(key)
+            }
+            _ => None,
+        }
+    }
+
+    /// The element at the given position, to modify it, if this is an array long enough.
+    ///
+    /// The array is copied first if it's shared, but its elements aren't.
+    pub fn at_mut(&mut self, position: usize) -> Option<&mut ArcValue> {
+        match self {
+            ArcValue::Array(elements) if position < elements.len() => {
+                Arc::make_mut(elements).get_mut(position)
+            }
+            _ => None,
+        }
+    }
+
+    /// Find the value referenced by a JSON Pointer, to modify it, copying the arrays and
+    /// objects on the path to it which are shared.
+    ///
+    /// Nothing is copied if there's no such value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::shared::ArcValue;
+    /// use json_parser::value::Number;
+    /// use std::sync::Arc;
+    ///
+    /// let input = br#"{"config": {"port": 80, "hosts": ["a", "b"]}, "data": [1, 2, 3]}"#;
+    /// let v1 = ArcValue::from(JsonParser::parse_from_bytes(input).unwrap());
+    ///
+    /// let mut v2 = v1.clone();
+    /// *v2.pointer_mut("/config/port").unwrap() = ArcValue::Number(Number::I64(8080));
+    /// let config = v2.pointer_mut("/config").unwrap().as_object_mut().unwrap();
+    /// config.insert("tls".to_string(), ArcValue::Boolean(true));
+    ///
+    /// assert_eq!(v1.pointer("/config/port"), Some(&ArcValue::Number(Number::I64(80))));
+    /// assert_eq!(v2.pointer("/config/port"), Some(&ArcValue::Number(Number::I64(8080))));
+    /// assert_eq!(v1.pointer("/config/tls"), None);
+    ///
+    /// // What wasn't on the path to the changes is still shared by both versions.
+    /// for pointer in ["/data", "/config/hosts"] {
+    ///     let (Some(ArcValue::Array(old)), Some(ArcValue::Array(new))) =
+    ///         (v1.pointer(pointer), v2.pointer(pointer))
+    ///     else {
+    ///         unreachable!()
+    ///     };
+    ///     assert!(Arc::ptr_eq(old, new));
+    /// }
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut ArcValue> {
+        self.pointer(pointer)?;
+
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| match value {
+                ArcValue::Object(_) => value.get_mut(token),
+                ArcValue::Array(_) => value.at_mut(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// The members of an object, to modify them, copying the object first if it's shared.
+    pub fn as_object_mut(&mut self) -> Option<&mut ArcObject> {
+        match self {
+            ArcValue::Object(object) => Some(Arc::make_mut(object)),
+            _ => None,
+        }
+    }
+
+    /// The elements of an array, to modify them, copying the array first if it's shared.
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<ArcValue>> {
+        match self {
+            ArcValue::Array(elements) => Some(Arc::make_mut(elements)),
+            _ => None,
+        }
+    }
+
+    /// The contents of a string, or `None` for other values.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ArcValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Copy the value into a [`Value`], which can be modified.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self {
+            ArcValue::Null => Value::Null,
+            ArcValue::Boolean(boolean) => Value::Boolean(*boolean),
+            ArcValue::Number(number) => Value::Number(*number),
+            ArcValue::String(string) => Value::from(&**string),
+            ArcValue::Array(elements) => {
+                Value::Array(elements.iter().map(ArcValue::to_value).collect())
+            }
+            // Cloning the members only takes a reference to each of them.
+            ArcValue::Object(object) => {
+                Value::Object(Map::clone(object).map_values(|value| value.to_value()))
+            }
+        }
+    }
+}
+
+impl From<Value> for ArcValue {
+    /// Convert a value, sharing the keys of its objects rather than copying them.
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => ArcValue::Null,
+            Value::Boolean(boolean) => ArcValue::Boolean(boolean),
+            Value::Number(number) => ArcValue::Number(number),
+            Value::String(string) => ArcValue::String(Arc::from(&*string)),
+            Value::Array(elements) => {
+                ArcValue::Array(Arc::new(elements.into_iter().map(ArcValue::from).collect()))
+            }
+            Value::Object(map) => ArcValue::Object(Arc::new(map.map_values(ArcValue::from))),
+        }
+    }
+}
+
+impl From<&Value> for ArcValue {
+    fn from(value: &Value) -> Self {
+        ArcValue::from(value.clone())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::value::Value;
+use std::cmp::Ordering;
+
+impl Value {
+    /// Recursively sort the keys of every object in the value.
+    ///
+    /// Arrays keep their order, but objects nested inside them are sorted as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"b":1,"a":{"d":2,"c":3}}"#).unwrap();
+    /// value.sort_keys();
+    ///
+    /// let object: &json_parser::map::Map = (&value).try_into().unwrap();
+    /// assert_eq!(object.keys().collect::<Vec<_>>(), ["a", "b"]);
+    /// ```
+    pub fn sort_keys(&mut self) {
+        match self {
+            Value::Object(object) => {
+                object.sort_keys();
+                object.values_mut().for_each(Value::sort_keys);
+            }
+            Value::Array(array) => array.iter_mut().for_each(Value::sort_keys),
+            _ => {}
+        }
+    }
+
+    /// Recursively sort the keys of every object, and the elements of every array using
+    /// `compare`.
+    ///
+    /// Nested values are sorted before the array containing them, so `compare` always sees
+    /// normalized elements.
+    pub fn sort_keys_and_arrays_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&Value, &Value) -> Ordering,
+    {
+        self.sort_recursive(&mut compare);
+    }
+
+    fn sort_recursive<F>(&mut self, compare: &mut F)
+    where
+        F: FnMut(&Value, &Value) -> Ordering,
+    {
+        match self {
+            Value::Object(object) => {
+                object.sort_keys();
+                for value in object.values_mut() {
+                    value.sort_recursive(compare);
+                }
+            }
+            Value::Array(array) => {
+                for value in array.iter_mut() {
+                    value.sort_recursive(compare);
+                }
+                array.sort_by(|a, b| compare(a, b));
+            }
+            _ => {}
+        }
+    }
+}
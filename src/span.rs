@@ -0,0 +1,303 @@
+//! Locations of parsed values in their source text.
+
+use crate::cst::{Document, Node, NodeKind};
+use crate::error::JsonError;
+use crate::parser::JsonParser;
+use crate::pointer::push_token;
+use crate::tape::{unescape_at, Scalar, Visitor, Walker};
+use crate::value::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A location in source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Byte offset from the start of the text.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+}
+
+/// The range of source text covered by a value or key, from `start` up to but excluding `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    /// The span as a range of byte offsets, to slice the source text with.
+    #[must_use]
+    pub fn bytes(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+}
+
+/// Spans of every value and object key of a document, keyed by JSON Pointer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpanTable {
+    values: HashMap<String, Span>,
+    keys: HashMap<String, Span>,
+}
+
+impl SpanTable {
+    /// Span of the value referenced by a JSON Pointer, such as `/servers/0/port`.
+    #[must_use]
+    pub fn get(&self, pointer: &str) -> Option<Span> {
+        self.values.get(pointer).copied()
+    }
+
+    /// Span of the key, including its quotes, of the object member referenced by a JSON
+    /// Pointer.
+    #[must_use]
+    pub fn get_key(&self, pointer: &str) -> Option<Span> {
+        self.keys.get(pointer).copied()
+    }
+
+    /// Iterate over the pointers and spans of every value, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Span)> {
+        self.values
+            .iter()
+            .map(|(pointer, span)| (pointer.as_str(), *span))
+    }
+}
+
+/// An object member whose key was looked for by [`JsonParser::scan_keys`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyMatch {
+    /// JSON Pointer of the member, such as `/users/0/password`.
+    pub pointer: String,
+    /// Span of the key, including its quotes.
+    pub key: Span,
+    /// Span of the value of the member.
+    pub value: Span,
+}
+
+impl JsonParser {
+    /// Find every object member whose key is one of `keys`, without building any value, to
+    /// audit huge documents quickly, such as to check that no object has a `password`.
+    ///
+    /// Members are returned in the order they appear in the input, so members nested in the
+    /// value of another match come after it. The whole input is checked against the grammar,
+    /// but only keys are decoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON, or has arrays
+    /// and objects nested more than [`MAX_NESTING`](crate::token::MAX_NESTING) levels deep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let input = br#"{"users": [{"name": "ada", "password": "hunter2"}, {"name": "bob"}],
+    ///   "db": {"password": {"env": "DB_PASSWORD"}}}"#;
+    /// let matches = JsonParser::scan_keys(input, &["password"]).unwrap();
+    ///
+    /// let pointers: Vec<_> = matches.iter().map(|found| found.pointer.as_str()).collect();
+    /// assert_eq!(pointers, ["/users/0/password", "/db/password"]);
+    ///
+    /// let db = &matches[1];
+    /// assert_eq!((db.key.start.line, db.key.start.column), (2, 10));
+    /// assert_eq!(&input[db.value.bytes()], br#"{"env": "DB_PASSWORD"}"#);
+    ///
+    /// let deep = format!("{}{{\"password\": 1}}", "[".repeat(100_000));
+    /// assert!(JsonParser::scan_keys(deep.as_bytes(), &["password"]).is_err());
+    /// ```
+    pub fn scan_keys(input: &[u8], keys: &[&str]) -> Result<Vec<KeyMatch>, JsonError> {
+        let walker = Walker::new(input)?;
+        let mut scanner = KeyScanner {
+            keys,
+            lines: LineIndex::new(walker.source()),
+            pointer: String::new(),
+            members: Vec::new(),
+            matches: Vec::new(),
+        };
+        walker.walk(&mut scanner)?;
+
+        Ok(scanner.matches)
+    }
+
+    /// Parse JSON from bytes, also returning where every value is located in the input.
+    ///
+    /// This accepts more than JSON: like [`Document::parse`], it allows `//` and `/* */`
+    /// comments wherever whitespace is allowed. Use
+    /// [`parse_with_warnings`](JsonParser::parse_with_warnings) to find out whether there are
+    /// any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON, or has arrays
+    /// and objects nested more than [`MAX_NESTING`](crate::token::MAX_NESTING) levels deep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let input = b"{\n  \"name\": \"app\",\n  \"port\": \"eighty\"\n}";
+    /// let (_, spans) = JsonParser::parse_with_spans(input).unwrap();
+    ///
+    /// let port = spans.get("/port").unwrap();
+    /// assert_eq!((port.start.line, port.start.column), (3, 11));
+    /// assert_eq!(&input[port.bytes()], b"\"eighty\"");
+    ///
+    /// let (value, _) = JsonParser::parse_with_spans(b"[1, /* two */ 2]").unwrap();
+    /// assert_eq!(value, JsonParser::parse_from_bytes(b"[1, 2]").unwrap());
+    /// ```
+    pub fn parse_with_spans(input: &[u8]) -> Result<(Value, SpanTable), JsonError> {
+        let input = std::str::from_utf8(input).map_err(|error| {
+            JsonError::Syntax(format!("invalid UTF-8 at byte {}", error.valid_up_to()))
+        })?;
+        let document = Document::parse(input)?;
+
+        Ok((document.to_value(), document.spans()))
+    }
+}
+
+impl Document {
+    /// Spans of every value and object key of the document.
+    #[must_use]
+    pub fn spans(&self) -> SpanTable {
+        let lines = LineIndex::new(self.source());
+        let mut table = SpanTable::default();
+        collect_spans(self.root(), &mut String::new(), &lines, &mut table);
+
+        table
+    }
+}
+
+fn collect_spans(node: &Node, pointer: &mut String, lines: &LineIndex, table: &mut SpanTable) {
+    table
+        .values
+        .insert(pointer.clone(), lines.span(node.span()));
+
+    let length = pointer.len();
+    match node.kind() {
+        NodeKind::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                push_token(pointer, &index.to_string());
+                collect_spans(element, pointer, lines, table);
+                pointer.truncate(length);
+            }
+        }
+        NodeKind::Object(members) => {
+            for member in members {
+                push_token(pointer, member.key());
+                table
+                    .keys
+                    .insert(pointer.clone(), lines.span(member.key_span()));
+                collect_spans(member.value(), pointer, lines, table);
+                pointer.truncate(length);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keeps track of the pointer of the current value as the second stage of
+/// [`JsonParser::parse_tape`] walks a document, recording the members whose key is looked for.
+struct KeyScanner<'a, 'k> {
+    keys: &'k [&'k str],
+    lines: LineIndex<'a>,
+    pointer: String,
+    /// Length of `pointer` before the token of every member or element being walked, with the
+    /// position in `matches` of the member if its key is looked for.
+    members: Vec<(usize, Option<usize>)>,
+    matches: Vec<KeyMatch>,
+}
+
+impl<'a> Visitor<'a> for KeyScanner<'a, '_> {
+    fn open(&mut self) {}
+
+    fn close(&mut self, _object: bool, _length: usize) {}
+
+    fn key(&mut self, start: usize, text: &'a str) -> Result<(), JsonError> {
+        let key = if text.contains('\\') {
+            Cow::Owned(unescape_at(text, start)?)
+        } else {
+            Cow::Borrowed(text)
+        };
+        let length = self.pointer.len();
+        push_token(&mut self.pointer, &key);
+
+        let mut found = None;
+        if self.keys.contains(&key.as_ref()) {
+            // The match is recorded before the members nested in its value.
+            found = Some(self.matches.len());
+            self.matches.push(KeyMatch {
+                pointer: self.pointer.clone(),
+                key: self.lines.span(start - 1..start + text.len() + 1),
+                value: self.lines.span(0..0),
+            });
+        }
+        self.members.push((length, found));
+
+        Ok(())
+    }
+
+    fn element(&mut self, position: usize) {
+        self.members.push((self.pointer.len(), None));
+        push_token(&mut self.pointer, &position.to_string());
+    }
+
+    fn scalar(&mut self, start: usize, scalar: Scalar<'a>) -> Result<(), JsonError> {
+        if let Scalar::String(text) = scalar {
+            if text.contains('\\') {
+                unescape_at(text, start)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn value_end(&mut self, span: Range<usize>) {
+        // The root value isn't a member or element.
+        let Some((length, found)) = self.members.pop() else {
+            return;
+        };
+        if let Some(found) = found {
+            self.matches[found].value = self.lines.span(span);
+        }
+        self.pointer.truncate(length);
+    }
+}
+
+/// Offsets at which every line of a text starts, to turn byte offsets into positions.
+pub(crate) struct LineIndex<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        LineIndex { text, starts }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        // The line is the last one starting at or before the offset.
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let line_start = self.starts[line - 1];
+
+        Position {
+            offset,
+            line,
+            column: self.text[line_start..offset].chars().count() + 1,
+        }
+    }
+
+    pub(crate) fn span(&self, range: Range<usize>) -> Span {
+        Span {
+            start: self.position(range.start),
+            end: self.position(range.end),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::value::{Number, Value};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// Statistics about the shape and size of a [`Value`], as returned by [`Value::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Deepest level of nesting, where a scalar at the root has a depth of 1.
+    pub max_depth: usize,
+    /// Number of objects.
+    pub objects: usize,
+    /// Number of arrays.
+    pub arrays: usize,
+    /// Number of strings, not counting object keys.
+    pub strings: usize,
+    /// Number of integers.
+    pub integers: usize,
+    /// Number of floating point numbers.
+    pub floats: usize,
+    /// Number of booleans.
+    pub booleans: usize,
+    /// Number of nulls.
+    pub nulls: usize,
+    /// Number of object members.
+    pub members: usize,
+    /// Total length in bytes of all strings, including object keys.
+    pub string_bytes: usize,
+    /// Number of elements of the largest array.
+    pub largest_array: usize,
+    /// Number of members of the largest object.
+    pub largest_object: usize,
+    /// Length in bytes of the longest string, not counting object keys.
+    pub longest_string: usize,
+    /// Estimated number of bytes allocated on the heap to hold the value.
+    ///
+    /// This counts the capacity of every string, vector and map, and is only an estimate
+    /// since it can't account for allocator overhead, the internals of the key index of
+    /// objects, or keys shared between objects by interning.
+    pub heap_bytes: usize,
+}
+
+impl Stats {
+    /// Total number of values, including the root.
+    #[must_use]
+    pub fn values(&self) -> usize {
+        self.objects
+            + self.arrays
+            + self.strings
+            + self.integers
+            + self.floats
+            + self.booleans
+            + self.nulls
+    }
+
+    fn visit(&mut self, value: &Value, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+
+        match value {
+            Value::String(string) => {
+                self.strings += 1;
+                self.string_bytes += string.len();
+                self.longest_string = self.longest_string.max(string.len());
+                self.heap_bytes += string.capacity();
+            }
+            Value::Number(Number::I64(_)) => self.integers += 1,
+            Value::Number(Number::F64(_)) => self.floats += 1,
+            Value::Boolean(_) => self.booleans += 1,
+            Value::Null => self.nulls += 1,
+            Value::Array(array) => {
+                self.arrays += 1;
+                self.largest_array = self.largest_array.max(array.len());
+                self.heap_bytes += array.capacity() * size_of::<Value>();
+
+                for value in array {
+                    self.visit(value, depth + 1);
+                }
+            }
+            Value::Object(object) => {
+                self.objects += 1;
+                self.members += object.len();
+                self.largest_object = self.largest_object.max(object.len());
+                // Every member is stored as a key-value pair, plus the key and its position in
+                // the index, which share the key's allocation along with its reference counts.
+                self.heap_bytes += object.len()
+                    * (size_of::<(Arc<str>, Value)>() + size_of::<(Arc<str>, usize)>());
+
+                for (key, value) in object {
+                    self.string_bytes += key.len();
+                    self.heap_bytes += key.len() + 2 * size_of::<usize>();
+                    self.visit(value, depth + 1);
+                }
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Compute statistics about the shape and size of the value, such as its maximum depth
+    /// and the number of values of each type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"{"a":[1,2.5,"three"],"b":null}"#).unwrap();
+    /// let stats = value.stats();
+    ///
+    /// assert_eq!(stats.max_depth, 3);
+    /// assert_eq!(stats.integers, 1);
+    /// assert_eq!(stats.string_bytes, "a".len() + "three".len() + "b".len());
+    /// assert_eq!(stats.values(), 6);
+    /// assert_eq!(stats.largest_array, 3);
+    /// assert_eq!(stats.longest_string, "three".len());
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        stats.visit(self, 1);
+
+        stats
+    }
+}
@@ -0,0 +1,695 @@
+//! A two-stage parser which lays documents out on a flat tape, to be navigated without
+//! building the whole value.
+//!
+//! The first stage finds where every token starts, skipping over the contents of strings in
+//! bulk. The second stage checks the tokens against the grammar and records them on the tape,
+//! where every array and object knows where it ends so it can be skipped at once.
+
+use crate::borrowed::BorrowedValue;
+use crate::cst::parse_number;
+use crate::error::JsonError;
+use crate::event::{decode, unescape};
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::reader::find_quote_or_backslash;
+use crate::recover::is_valid_number;
+use crate::token::MAX_NESTING;
+use crate::value::{Number, Value};
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// A document parsed by [`JsonParser::parse_tape`] or [`JsonParser::parse_lazy`], borrowing
+/// its strings from the input.
+#[derive(Debug, Clone)]
+pub struct Tape<'a> {
+    source: &'a str,
+    nodes: Vec<Node>,
+    /// Decoded contents of the strings which have escape sequences, filled in when they're
+    /// first read if the tape was parsed lazily.
+    escaped: Vec<OnceLock<String>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    /// An object with `length` members, laid out as key and value nodes up to the node at
+    /// `end`.
+    Object {
+        length: usize,
+        end: usize,
+    },
+    /// An array with `length` elements, laid out up to the node at `end`.
+    Array {
+        length: usize,
+        end: usize,
+    },
+    /// A string without escape sequences, whose contents are at `start..end` in the input.
+    String {
+        start: usize,
+        end: usize,
+    },
+    /// A string with escape sequences, whose contents start at `start` in the input and are
+    /// decoded at the given index of `escaped`.
+    EscapedString {
+        start: usize,
+        index: usize,
+    },
+    Number(Number),
+    /// A number whose text is at `start..end` in the input, converted when it's read.
+    RawNumber {
+        start: usize,
+        end: usize,
+    },
+    Boolean(bool),
+    Null,
+}
+
+/// A value of a [`Tape`], read from the tape as needed.
+#[derive(Debug, Clone, Copy)]
+pub struct TapeValue<'t> {
+    tape: &'t Tape<'t>,
+    index: usize,
+}
+
+impl JsonParser {
+    /// Parse JSON from bytes onto a [`Tape`], which is faster than building a [`Value`] and
+    /// lets single values be looked up without materializing the rest.
+    ///
+    /// A leading byte order mark is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON, or has arrays
+    /// and objects nested more than [`MAX_NESTING`] levels deep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Number;
+    ///
+    /// let input = br#"{"servers": [{"host": "a", "port": 80}, {"host": "b\u00e9", "port": 81}]}"#;
+    /// let tape = JsonParser::parse_tape(input).unwrap();
+    ///
+    /// let server = tape.root().get("servers").and_then(|servers| servers.at(1)).unwrap();
+    /// assert_eq!(server.get("host").and_then(|host| host.as_str()), Some("bé"));
+    /// assert_eq!(server.get("port").and_then(|port| port.as_number()), Some(Number::I64(81)));
+    ///
+    /// assert_eq!(tape.to_value(), JsonParser::parse_from_bytes(input).unwrap());
+    ///
+    /// let deep = "[".repeat(100_000);
+    /// assert!(JsonParser::parse_tape(deep.as_bytes()).is_err());
+    /// ```
+    pub fn parse_tape(input: &[u8]) -> Result<Tape<'_>, JsonError> {
+        parse(input, false)
+    }
+}
+
+/// Parse JSON from bytes onto a [`Tape`], leaving numbers and escape sequences to be decoded
+/// when they're read if `lazy` is set.
+pub(crate) fn parse(input: &[u8], lazy: bool) -> Result<Tape<'_>, JsonError> {
+    let walker = Walker::new(input)?;
+    let mut builder = Builder {
+        lazy,
+        containers: Vec::new(),
+        tape: Tape {
+            source: walker.source(),
+            nodes: Vec::new(),
+            escaped: Vec::new(),
+        },
+    };
+    walker.walk(&mut builder)?;
+
+    Ok(builder.tape)
+}
+
+impl<'a> Tape<'a> {
+    /// The root value of the document.
+    #[must_use]
+    pub fn root(&self) -> TapeValue<'_> {
+        TapeValue {
+            tape: self,
+            index: 0,
+        }
+    }
+
+    /// Build the [`Value`] of the whole document.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        self.root().to_value()
+    }
+
+    /// Turn the tape into a [`BorrowedValue`], which takes the strings decoded from escape
+    /// sequences and borrows the others from the input.
+    pub(crate) fn into_borrowed(mut self) -> BorrowedValue<'a> {
+        let mut escaped = std::mem::take(&mut self.escaped);
+        self.borrowed(0, &mut escaped)
+    }
+
+    fn borrowed(&self, index: usize, escaped: &mut [OnceLock<String>]) -> BorrowedValue<'a> {
+        match self.nodes[index] {
+            Node::Object { length, .. } => {
+                let mut index = index + 1;
+                let members = (0..length)
+                    .map(|_| {
+                        let key = match self.borrowed(index, escaped) {
+                            BorrowedValue::String(key) => key,
+                            _ => Cow::Borrowed(""),
+                        };
+                        let value = self.borrowed(index + 1, escaped);
+                        index = self.skip(index + 1);
+                        (key, value)
+                    })
+                    .collect();
+                BorrowedValue::Object(members)
+            }
+            Node::Array { length, .. } => {
+                let mut index = index + 1;
+                let elements = (0..length)
+                    .map(|_| {
+                        let element = self.borrowed(index, escaped);
+                        index = self.skip(index);
+                        element
+                    })
+                    .collect();
+                BorrowedValue::Array(elements)
+            }
+            Node::String { start, end } => {
+                BorrowedValue::String(Cow::Borrowed(&self.source[start..end]))
+            }
+            // Every escaped string is on the tape once, so it can be moved out.
+            Node::EscapedString { start, index } => BorrowedValue::String(Cow::Owned(
+                escaped[index]
+                    .take()
+                    .unwrap_or_else(|| self.unescape(start)),
+            )),
+            Node::Number(number) => BorrowedValue::Number(number),
+            Node::RawNumber { start, end } => {
+                BorrowedValue::Number(parse_number(&self.source[start..end]))
+            }
+            Node::Boolean(boolean) => BorrowedValue::Boolean(boolean),
+            Node::Null => BorrowedValue::Null,
+        }
+    }
+
+    /// Decode the string whose contents start at `start`, whose escape sequences were checked
+    /// when parsing.
+    fn unescape(&self, start: usize) -> String {
+        let end = closing_quote(self.source.as_bytes(), start).unwrap_or(self.source.len());
+        unescape(&self.source[start..end]).unwrap_or_default()
+    }
+
+    /// Index of the node following the one at `index` and everything nested in it.
+    fn skip(&self, index: usize) -> usize {
+        match self.nodes[index] {
+            Node::Object { end, .. } | Node::Array { end, .. } => end,
+            _ => index + 1,
+        }
+    }
+}
+
+impl<'t> TapeValue<'t> {
+    fn node(&self) -> Node {
+        self.tape.nodes[self.index]
+    }
+
+    fn at_index(&self, index: usize) -> TapeValue<'t> {
+        TapeValue {
+            tape: self.tape,
+            index,
+        }
+    }
+
+    /// The value of the member with the given key, if this is an object which has one. Of
+    /// duplicate keys, the last one counts.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<TapeValue<'t>> {
+        self.members()?
+            .filter(|(member, _)| *member == key)
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    /// The element at the given position, if this is an array long enough.
+    #[must_use]
+    pub fn at(&self, position: usize) -> Option<TapeValue<'t>> {
+        self.elements()?.nth(position)
+    }
+
+    /// The number of elements of an array or members of an object, or `None` for other
+    /// values.
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        match self.node() {
+            Node::Object { length, .. } | Node::Array { length, .. } => Some(length),
+            _ => None,
+        }
+    }
+
+    /// Whether this is an empty array or object.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// The elements of an array, or `None` for other values.
+    pub fn elements(&self) -> Option<impl ExactSizeIterator<Item = TapeValue<'t>>> {
+        let Node::Array { length, .. } = self.node() else {
+            return None;
+        };
+
+        let array = *self;
+        let mut index = self.index + 1;
+        Some((0..length).map(move |_| {
+            let element = array.at_index(index);
+            index = array.tape.skip(index);
+            element
+        }))
+    }
+
+    /// The keys and values of the members of an object, or `None` for other values.
+    pub fn members(&self) -> Option<impl ExactSizeIterator<Item = (&'t str, TapeValue<'t>)>> {
+        let Node::Object { length, .. } = self.node() else {
+            return None;
+        };
+
+        let object = *self;
+        let mut index = self.index + 1;
+        Some((0..length).map(move |_| {
+            let key = object.at_index(index).as_str().unwrap_or_default();
+            let value = object.at_index(index + 1);
+            index = object.tape.skip(index + 1);
+            (key, value)
+        }))
+    }
+
+    /// The contents of a string, or `None` for other values.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'t str> {
+        match self.node() {
+            Node::String { start, end } => Some(&self.tape.source[start..end]),
+            Node::EscapedString { start, index } => {
+                Some(self.tape.escaped[index].get_or_init(|| self.tape.unescape(start)))
+            }
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        match self.node() {
+            Node::Number(number) => Some(number),
+            Node::RawNumber { start, end } => Some(parse_number(&self.tape.source[start..end])),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node() {
+            Node::Boolean(boolean) => Some(boolean),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self.node(), Node::Null)
+    }
+
+    /// Build the [`Value`] of this part of the document.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self.node() {
+            Node::Object { .. } => {
+                let mut map = Map::new();
+                for (key, value) in self.members().into_iter().flatten() {
+                    map.insert(key.to_string(), value.to_value());
+                }
+                Value::Object(map)
+            }
+            Node::Array { .. } => Value::Array(
+                self.elements()
+                    .into_iter()
+                    .flatten()
+                    .map(|element| element.to_value())
+                    .collect(),
+            ),
+            Node::String { .. } | Node::EscapedString { .. } => {
+                Value::from(self.as_str().unwrap_or_default())
+            }
+            Node::Number(number) => Value::Number(number),
+            Node::RawNumber { start, end } => {
+                Value::Number(parse_number(&self.tape.source[start..end]))
+            }
+            Node::Boolean(boolean) => Value::Boolean(boolean),
+            Node::Null => Value::Null,
+        }
+    }
+}
+
+/// Offsets at which every token of the source starts. The ends of tokens aren't needed, since
+/// strings end at their closing quote and other tokens are delimited by the next one.
+fn structural_index(source: &str) -> Result<Vec<usize>, JsonError> {
+    let bytes = source.as_bytes();
+    let mut index = Vec::new();
+    // Skip the byte order mark which Windows tools often write at the start of UTF-8 text.
+    let mut position = if source.starts_with('\u{FEFF}') {
+        '\u{FEFF}'.len_utf8()
+    } else {
+        0
+    };
+
+    while let Some(&byte) = bytes.get(position) {
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\r' => position += 1,
+            b'"' => {
+                index.push(position);
+                position = closing_quote(bytes, position + 1).ok_or_else(|| {
+                    JsonError::Syntax(format!("unterminated string at byte {position}"))
+                })? + 1;
+            }
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => {
+                index.push(position);
+                position += 1;
+            }
+            _ => {
+                index.push(position);
+                position += atom_length(&bytes[position..]);
+            }
+        }
+    }
+
+    Ok(index)
+}
+
+/// Offset of the quote closing the string whose contents start at `position`.
+fn closing_quote(bytes: &[u8], mut position: usize) -> Option<usize> {
+    loop {
+        position += find_quote_or_backslash(bytes.get(position..)?)?;
+        if bytes[position] == b'"' {
+            return Some(position);
+        }
+        // Skip the escaped character, which may be a quote.
+        position += 2;
+    }
+}
+
+/// Length of the number or literal at the start of `bytes`.
+fn atom_length(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|byte| {
+            matches!(
+                byte,
+                b' ' | b'\t' | b'\n' | b'\r' | b'"' | b'{' | b'}' | b'[' | b']' | b':' | b','
+            )
+        })
+        .unwrap_or(bytes.len())
+}
+
+/// Something of the input found by the second stage, once checked against the grammar.
+pub(crate) enum Scalar<'a> {
+    /// The contents of a string, between its quotes, with its control characters checked but
+    /// not its escape sequences.
+    String(&'a str),
+    /// A valid number.
+    Number(&'a str),
+    Boolean(bool),
+    Null,
+}
+
+/// What to do with the tokens of a document as [`Walker::walk`] checks them against the
+/// grammar.
+pub(crate) trait Visitor<'a> {
+    /// An array or object opens.
+    fn open(&mut self);
+
+    /// The array or object opened last closes after `length` elements or members.
+    fn close(&mut self, object: bool, length: usize);
+
+    /// The value of an object member follows the key whose contents start at `start`.
+    fn key(&mut self, start: usize, text: &'a str) -> Result<(), JsonError>;
+
+    /// The element at `position` of an array follows.
+    fn element(&mut self, _position: usize) {}
+
+    /// A value other than an array or object starts at `start`, after the quote of a string.
+    fn scalar(&mut self, start: usize, scalar: Scalar<'a>) -> Result<(), JsonError>;
+
+    /// The value covering `span` in bytes has been walked.
+    fn value_end(&mut self, _span: Range<usize>) {}
+}
+
+/// The second stage, checking the tokens found by the first one against the grammar and
+/// handing them to a [`Visitor`].
+pub(crate) struct Walker<'a> {
+    source: &'a str,
+    index: Vec<usize>,
+    /// Position in `index` of the next token.
+    next: usize,
+    /// Number of arrays and objects the next token is in.
+    depth: usize,
+}
+
+impl<'a> Walker<'a> {
+    /// Run the first stage over `input`.
+    pub(crate) fn new(input: &'a [u8]) -> Result<Self, JsonError> {
+        let source = std::str::from_utf8(input).map_err(|error| {
+            JsonError::Syntax(format!("invalid UTF-8 at byte {}", error.valid_up_to()))
+        })?;
+
+        Ok(Walker {
+            source,
+            index: structural_index(source)?,
+            next: 0,
+            depth: 0,
+        })
+    }
+
+    pub(crate) fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Walk the value making up the whole input.
+    pub(crate) fn walk(mut self, visitor: &mut impl Visitor<'a>) -> Result<(), JsonError> {
+        self.value(visitor)?;
+        if let Some(&offset) = self.index.get(self.next) {
+            return Err(self.unexpected(offset, "the end of input"));
+        }
+
+        Ok(())
+    }
+
+    fn unexpected(&self, offset: usize, expected: &str) -> JsonError {
+        let found = self.source[offset..].chars().next().unwrap_or_default();
+        JsonError::Syntax(format!(
+            "expected {expected} at byte {offset}, found `{found}`"
+        ))
+    }
+
+    /// Consume the next token, returning its offset and first byte.
+    fn next_token(&mut self, expected: &str) -> Result<(usize, u8), JsonError> {
+        let Some(&offset) = self.index.get(self.next) else {
+            return Err(JsonError::Syntax(format!(
+                "expected {expected} at byte {}, found the end of input",
+                self.source.len()
+            )));
+        };
+        self.next += 1;
+
+        Ok((offset, self.source.as_bytes()[offset]))
+    }
+
+    /// Consume the next token if it starts with `byte`, returning its offset.
+    fn next_if(&mut self, byte: u8) -> Option<usize> {
+        let offset = *self.index.get(self.next)?;
+        if self.source.as_bytes()[offset] != byte {
+            return None;
+        }
+        self.next += 1;
+
+        Some(offset)
+    }
+
+    fn value(&mut self, visitor: &mut impl Visitor<'a>) -> Result<(), JsonError> {
+        let (start, byte) = self.next_token("a value")?;
+        let end = match byte {
+            b'{' | b'[' => self.container(start, byte, visitor)?,
+            b'"' => {
+                let text = self.string(start)?;
+                visitor.scalar(start + 1, Scalar::String(text))?;
+                start + text.len() + 2
+            }
+            _ => {
+                let end = start + atom_length(&self.source.as_bytes()[start..]);
+                let scalar = match &self.source[start..end] {
+                    "true" => Scalar::Boolean(true),
+                    "false" => Scalar::Boolean(false),
+                    "null" => Scalar::Null,
+                    text if is_valid_number(text) => Scalar::Number(text),
+                    _ => return Err(self.unexpected(start, "a value")),
+                };
+                visitor.scalar(start, scalar)?;
+                end
+            }
+        };
+        visitor.value_end(start..end);
+
+        Ok(())
+    }
+
+    /// Walk the members of an object or the elements of an array, whose opening bracket is at
+    /// `offset`, returning the offset following the closing bracket.
+    fn container(
+        &mut self,
+        offset: usize,
+        open: u8,
+        visitor: &mut impl Visitor<'a>,
+    ) -> Result<usize, JsonError> {
+        // Visitors keep a frame per open container and values are walked recursively, so deep
+        // nesting would overflow the stack.
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(JsonError::Syntax(format!(
+                "arrays and objects are nested more than {MAX_NESTING} levels deep at byte {offset}"
+            )));
+        }
+        visitor.open();
+
+        let object = open == b'{';
+        let (close, expected) = match open {
+            b'{' => (b'}', "`,` or `}`"),
+            _ => (b']', "`,` or `]`"),
+        };
+        let mut length = 0;
+        let end = match self.next_if(close) {
+            Some(end) => end,
+            None => loop {
+                if object {
+                    match self.next_token("a string key")? {
+                        (offset, b'"') => visitor.key(offset + 1, self.string(offset)?)?,
+                        (offset, _) => return Err(self.unexpected(offset, "a string key")),
+                    }
+                    match self.next_token("`:`")? {
+                        (_, b':') => {}
+                        (offset, _) => return Err(self.unexpected(offset, "`:`")),
+                    }
+                } else {
+                    visitor.element(length);
+                }
+                self.value(visitor)?;
+                length += 1;
+
+                match self.next_token(expected)? {
+                    (_, b',') => {}
+                    (offset, byte) if byte == close => break offset,
+                    (offset, _) => return Err(self.unexpected(offset, expected)),
+                }
+            },
+        };
+
+        visitor.close(object, length);
+        self.depth -= 1;
+
+        Ok(end + 1)
+    }
+
+    /// The contents of the string whose opening quote is at `offset`, which the first stage
+    /// found the end of.
+    fn string(&self, offset: usize) -> Result<&'a str, JsonError> {
+        let start = offset + 1;
+        let end = closing_quote(self.source.as_bytes(), start).unwrap_or(self.source.len());
+        let text = &self.source[start..end];
+
+        if let Some(control) = text.bytes().position(|byte| byte < 0x20) {
+            return Err(JsonError::Syntax(format!(
+                "unescaped control character in string at byte {}",
+                start + control
+            )));
+        }
+
+        Ok(text)
+    }
+}
+
+/// Decode the escape sequences of the string contents `text`, which start at `start`.
+pub(crate) fn unescape_at(text: &str, start: usize) -> Result<String, JsonError> {
+    unescape(text).map_err(|index| invalid_escape(start + index))
+}
+
+fn invalid_escape(offset: usize) -> JsonError {
+    JsonError::Syntax(format!("invalid escape sequence at byte {offset}"))
+}
+
+/// Records what the second stage finds on a tape.
+struct Builder<'a> {
+    /// Whether to leave numbers and escape sequences to be decoded when they're read.
+    lazy: bool,
+    /// Positions on the tape of the arrays and objects which are open.
+    containers: Vec<usize>,
+    tape: Tape<'a>,
+}
+
+impl Builder<'_> {
+    fn push_string(&mut self, start: usize, text: &str) -> Result<(), JsonError> {
+        let node = if text.contains('\\') {
+            let string = if self.lazy {
+                decode(text, |_| {}).map_err(|index| invalid_escape(start + index))?;
+                OnceLock::new()
+            } else {
+                OnceLock::from(unescape_at(text, start)?)
+            };
+            self.tape.escaped.push(string);
+            Node::EscapedString {
+                start,
+                index: self.tape.escaped.len() - 1,
+            }
+        } else {
+            Node::String {
+                start,
+                end: start + text.len(),
+            }
+        };
+        self.tape.nodes.push(node);
+
+        Ok(())
+    }
+}
+
+impl<'a> Visitor<'a> for Builder<'a> {
+    fn open(&mut self) {
+        self.containers.push(self.tape.nodes.len());
+        self.tape.nodes.push(Node::Null);
+    }
+
+    fn close(&mut self, object: bool, length: usize) {
+        let node = self.containers.pop().expect("every close follows an open");
+        let end = self.tape.nodes.len();
+        self.tape.nodes[node] = if object {
+            Node::Object { length, end }
+        } else {
+            Node::Array { length, end }
+        };
+    }
+
+    fn key(&mut self, start: usize, text: &'a str) -> Result<(), JsonError> {
+        self.push_string(start, text)
+    }
+
+    fn scalar(&mut self, start: usize, scalar: Scalar<'a>) -> Result<(), JsonError> {
+        let node = match scalar {
+            Scalar::String(text) => return self.push_string(start, text),
+            Scalar::Number(text) if self.lazy => Node::RawNumber {
+                start,
+                end: start + text.len(),
+            },
+            Scalar::Number(text) => Node::Number(parse_number(text)),
+            Scalar::Boolean(boolean) => Node::Boolean(boolean),
+            Scalar::Null => Node::Null,
+        };
+        self.tape.nodes.push(node);
+
+        Ok(())
+    }
+}
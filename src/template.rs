@@ -0,0 +1,89 @@
+//! Substitution of `{{name}}` placeholders in strings, for simple JSON templates.
+
+use crate::value::Value;
+use std::collections::HashMap;
+
+impl Value {
+    /// Replace the `{{name}}` placeholders in the strings inside this value with the value of
+    /// `name` in `values`, ignoring spaces around the name.
+    ///
+    /// A string which is nothing but a placeholder is replaced by the value itself, whatever
+    /// its type. Inside a longer string, a string value is inserted as is, and other values as
+    /// compact JSON. Placeholders whose name isn't in `values` are kept, and keys aren't
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::{Number, Value};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut request = JsonParser::parse_from_bytes(
+    ///     br#"{"url": "/users/{{ id }}", "limit": "{{limit}}", "note": "{{unknown}}"}"#,
+    /// )
+    /// .unwrap();
+    /// let values = HashMap::from([
+    ///     ("id".to_string(), Value::from("ada")),
+    ///     ("limit".to_string(), Value::Number(Number::I64(10))),
+    /// ]);
+    /// request.substitute(&values);
+    ///
+    /// assert_eq!(
+    ///     request.to_string_compact(),
+    ///     r#"{"url":"/users/ada","limit":10,"note":"{{unknown}}"}"#
+    /// );
+    /// ```
+    pub fn substitute(&mut self, values: &HashMap<String, Value>) {
+        match self {
+            Value::String(text) => {
+                if let Some(value) = whole_placeholder(text).and_then(|name| values.get(name)) {
+                    *self = value.clone();
+                } else if text.contains("{{") {
+                    *self = Value::from(substitute(text, values));
+                }
+            }
+            Value::Object(members) => {
+                for member in members.values_mut() {
+                    member.substitute(values);
+                }
+            }
+            Value::Array(elements) => {
+                for element in elements {
+                    element.substitute(values);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The name of the placeholder `text` consists of, if it's a single one.
+fn whole_placeholder(text: &str) -> Option<&str> {
+    let name = text.strip_prefix("{{")?.strip_suffix("}}")?;
+
+    (!name.contains("{{") && !name.contains("}}")).then(|| name.trim())
+}
+
+/// Replace the placeholders in `text` whose name is in `values`.
+fn substitute(text: &str, values: &HashMap<String, Value>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+
+        output.push_str(&rest[..start]);
+        match values.get(after[..end].trim()) {
+            Some(Value::String(value)) => output.push_str(value),
+            Some(value) => output.push_str(&value.to_string_compact()),
+            None => output.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    output
+}
@@ -0,0 +1,27 @@
+use crate::datetime::{FromDate, FromDateTime};
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Month, OffsetDateTime};
+
+impl FromDateTime for OffsetDateTime {
+    fn from_rfc3339(text: &str) -> Option<Self> {
+        OffsetDateTime::parse(text, &Rfc3339).ok()
+    }
+
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()
+    }
+}
+
+impl FromDate for Date {
+    fn from_ymd(year: i32, month: u8, day: u8) -> Option<Self> {
+        Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()
+    }
+
+    fn from_rfc3339(text: &str) -> Option<Self> {
+        OffsetDateTime::from_rfc3339(text).map(OffsetDateTime::date)
+    }
+
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        OffsetDateTime::from_unix_nanos(nanos).map(OffsetDateTime::date)
+    }
+}
@@ -1,8 +1,19 @@
+use crate::error::JsonError;
+use crate::parser::{CancellationToken, LoneSurrogate, ParserOptions, Progress};
 use crate::reader::JsonReader;
 use crate::value::Number;
 use std::fs::File;
-use std::io::{BufReader, Cursor, Read, Seek};
-use std::iter::Peekable;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+
+/// Deepest nesting of arrays and objects accepted by [`JsonTokenizer`], as the parser builds
+/// values recursively.
+pub const MAX_NESTING: usize = 512;
+
+/// Number of tokens read between checks of the [`CancellationToken`], which may need the time.
+const CANCELLATION_INTERVAL: usize = 4096;
+
+/// Number of bytes read between reports of [`Progress`].
+const PROGRESS_INTERVAL: u64 = 1 << 20;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -24,7 +35,27 @@ where
     T: Read + Seek,
 {
     tokens: Vec<Token>,
-    iterator: Peekable<JsonReader<T>>,
+    iterator: JsonReader<T>,
+    options: ParserOptions,
+    /// Number of arrays and objects open at the current position.
+    depth: usize,
+    cancellation: Option<CancellationToken>,
+    /// Size of the input in bytes, when it's known.
+    total_bytes: Option<u64>,
+    scratch: Scratch,
+}
+
+/// Buffers reused while reading strings and numbers, which a [`Parser`] keeps between inputs.
+///
+/// [`Parser`]: crate::parser::Parser
+#[derive(Debug, Default)]
+pub(crate) struct Scratch {
+    /// The characters of the string being read.
+    string: String,
+    /// The characters of the number being read, apart from its exponent.
+    number: String,
+    /// The characters of the exponent of the number being read.
+    exponent: String,
 }
 
 impl<T> JsonTokenizer<T>
@@ -32,11 +63,17 @@ where
     T: Read + Seek,
 {
     pub fn new(reader: File) -> JsonTokenizer<File> {
+        let total_bytes = reader.metadata().ok().map(|metadata| metadata.len());
         let json_reader = JsonReader::<File>::new(BufReader::new(reader));
 
         JsonTokenizer {
             tokens: vec![],
-            iterator: json_reader.peekable(),
+            iterator: json_reader,
+            options: ParserOptions::default(),
+            depth: 0,
+            cancellation: None,
+            total_bytes,
+            scratch: Scratch::default(),
         }
     }
 
@@ -45,12 +82,139 @@ where
 
         JsonTokenizer {
             tokens: Vec::with_capacity(input.len()),
-            iterator: json_reader.peekable(),
+            iterator: json_reader,
+            options: ParserOptions::default(),
+            depth: 0,
+            cancellation: None,
+            total_bytes: Some(input.len() as u64),
+            scratch: Scratch::default(),
         }
     }
 
-    pub fn tokenize_json(&mut self) -> Result<&[Token], ()> {
-        while let Some(character) = self.iterator.peek() {
+    /// Create a tokenizer reading `input` into `tokens` and with `scratch`, the buffers of
+    /// a previous tokenizer, instead of allocating new ones.
+    pub(crate) fn from_bytes_with_buffers<'a>(
+        input: &'a [u8],
+        mut tokens: Vec<Token>,
+        scratch: Scratch,
+    ) -> JsonTokenizer<Cursor<&'a [u8]>> {
+        tokens.clear();
+
+        JsonTokenizer {
+            tokens,
+            iterator: JsonReader::<Cursor<&'a [u8]>>::from_bytes(input),
+            options: ParserOptions::default(),
+            depth: 0,
+            cancellation: None,
+            total_bytes: Some(input.len() as u64),
+            scratch,
+        }
+    }
+
+    /// Apply `options` to the reading of the input.
+    #[must_use]
+    pub fn with_options(mut self, options: &ParserOptions) -> Self {
+        self.iterator = self.iterator.with_invalid_utf8(options.invalid_utf8);
+        self.options = *options;
+        self
+    }
+
+    /// Stop reading the input with [`JsonError::Cancelled`] once `token` is cancelled.
+    #[must_use]
+    pub fn with_cancellation(mut self, token: &CancellationToken) -> Self {
+        self.cancellation = Some(token.clone());
+        self
+    }
+
+    /// Start over with `input`, keeping the options, the cancellation token, and the memory
+    /// of the tokens read so far, which are dropped. Reusing a tokenizer this way saves
+    /// growing a new token buffer for every document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::token::{JsonTokenizer, Token};
+    /// use std::io::Cursor;
+    ///
+    /// let mut tokenizer = JsonTokenizer::<Cursor<&[u8]>>::from_bytes(b"[1, 2, 3]");
+    /// assert_eq!(tokenizer.tokenize_json().unwrap().len(), 7);
+    ///
+    /// tokenizer.reset(Cursor::new(b"[true]".as_slice()));
+    /// assert_eq!(
+    ///     tokenizer.tokenize_json().unwrap(),
+    ///     [Token::ArrayOpen, Token::Boolean(true), Token::ArrayClose]
+    /// );
+    ///
+    /// let (tokens, _) = tokenizer.into_parts();
+    /// assert_eq!(tokens.len(), 3);
+    /// ```
+    pub fn reset(&mut self, mut input: T) {
+        self.total_bytes = remaining_bytes(&mut input);
+        self.iterator =
+            JsonReader::new(BufReader::new(input)).with_invalid_utf8(self.options.invalid_utf8);
+        self.tokens.clear();
+        self.depth = 0;
+    }
+
+    /// Take apart the tokenizer into the tokens read so far and the input, so that the tokens
+    /// can be kept without copying them. Whatever was read from the input but not tokenized
+    /// yet is lost.
+    #[must_use]
+    pub fn into_parts(self) -> (Vec<Token>, T) {
+        (self.tokens, self.iterator.into_inner())
+    }
+
+    /// Take apart the tokenizer into its buffers, to create another one with them.
+    pub(crate) fn into_buffers(self) -> (Vec<Token>, Scratch) {
+        (self.tokens, self.scratch)
+    }
+
+    pub fn tokenize_json(&mut self) -> Result<&[Token], JsonError> {
+        self.tokenize_json_with_progress(&mut |_| {})
+    }
+
+    /// Tokenize the input like [`tokenize_json`](JsonTokenizer::tokenize_json), calling
+    /// `progress` with the number of bytes read so far at the start, after about every MiB,
+    /// and at the end.
+    pub fn tokenize_json_with_progress(
+        &mut self,
+        progress: &mut dyn FnMut(Progress),
+    ) -> Result<&[Token], JsonError> {
+        progress(Progress {
+            bytes_read: self.iterator.bytes_read() as u64,
+            total_bytes: self.total_bytes,
+        });
+        let mut next_report = self.iterator.bytes_read() as u64 + PROGRESS_INTERVAL;
+
+        // Skip the byte order mark which Windows tools often write at the start of UTF-8 text.
+        // It isn't part of the JSON value, so it's only allowed before the first token.
+        if self.tokens.is_empty() {
+            let _ = self.iterator.next_if_eq(&'\u{FEFF}');
+        }
+
+        for step in 0.. {
+            if step % CANCELLATION_INTERVAL == 0
+                && self
+                    .cancellation
+                    .as_ref()
+                    .is_some_and(CancellationToken::is_cancelled)
+            {
+                return Err(JsonError::Cancelled);
+            }
+            let bytes_read = self.iterator.bytes_read() as u64;
+            if bytes_read >= next_report {
+                progress(Progress {
+                    bytes_read,
+                    total_bytes: self.total_bytes,
+                });
+                next_report = bytes_read + PROGRESS_INTERVAL;
+            }
+
+            self.iterator.skip_whitespace();
+            let Some(character) = self.iterator.peek() else {
+                break;
+            };
+
             match *character {
                 '"' => {
                     // Pushed opening quote to output tokens list.
@@ -61,7 +225,7 @@ where
 
                     // Delegate parsing string value to a separate function.
                     // The function should also take care of advancing the iterator properly
-                    let string = self.parse_string();
+                    let string = self.parse_string()?;
 
                     // Push parsed string to ouput tokens list.
                     self.tokens.push(Token::String(string));
@@ -75,65 +239,46 @@ where
                 }
                 // Match `t` character which indicates beginning of a boolean literal.
                 't' => {
-                    // Advance iterator by 1.
-                    let _ = self.iterator.next();
-
-                    // Assert next character is `r` while advancing the iterator by 1.
-                    assert_eq!(Some('r'), self.iterator.next());
-                    // Assert next character is `u` while advancing the iterator by 1.
-                    assert_eq!(Some('u'), self.iterator.next());
-                    // Assert next character is `e` while advancing the iterator by 1.
-                    assert_eq!(Some('e'), self.iterator.next());
+                    // Check the rest of the literal while advancing the iterator past it.
+                    self.parse_literal("true")?;
 
                     // Push the literal value to token list.
                     self.tokens.push(Token::Boolean(true))
                 }
                 // Match `f` character which indicates beginning of a boolean literal.
                 'f' => {
-                    // Advance iterator by 1.
-                    let _ = self.iterator.next();
-
-                    // Assert next character is `a` while advancing the iterator by 1.
-                    assert_eq!(Some('a'), self.iterator.next());
-                    // Assert next character is `l` while advancing the iterator by 1.
-                    assert_eq!(Some('l'), self.iterator.next());
-                    // Assert next character is `s` while advancing the iterator by 1.
-                    assert_eq!(Some('s'), self.iterator.next());
-                    // Assert next character is `e` while advancing the iterator by 1.
-                    assert_eq!(Some('e'), self.iterator.next());
+                    // Check the rest of the literal while advancing the iterator past it.
+                    self.parse_literal("false")?;
 
                     // Push the literal value to token list.
                     self.tokens.push(Token::Boolean(false));
                 }
                 // Match `n` character which indicates beginning of a null literal.
                 'n' => {
-                    // Advance iterator by 1.
-                    let _ = self.iterator.next();
-
-                    // Assert next character is `u` while advancing the iterator by 1.
-                    assert_eq!(Some('u'), self.iterator.next());
-                    // Assert next character is `l` while advancing the iterator by 1.
-                    assert_eq!(Some('l'), self.iterator.next());
-                    // Assert next character is `l` while advancing the iterator by 1.
-                    assert_eq!(Some('l'), self.iterator.next());
+                    // Check the rest of the literal while advancing the iterator past it.
+                    self.parse_literal("null")?;
 
                     // Push null literal value to output tokens list.
                     self.tokens.push(Token::Null);
                 }
                 // Delimeters
                 '{' => {
+                    self.open()?;
                     self.tokens.push(Token::CurlyOpen);
                     let _ = self.iterator.next();
                 }
                 '}' => {
+                    self.depth = self.depth.saturating_sub(1);
                     self.tokens.push(Token::CurlyClose);
                     let _ = self.iterator.next();
                 }
                 '[' => {
+                    self.open()?;
                     self.tokens.push(Token::ArrayOpen);
                     let _ = self.iterator.next();
                 }
                 ']' => {
+                    self.depth = self.depth.saturating_sub(1);
                     self.tokens.push(Token::ArrayClose);
                     let _ = self.iterator.next();
                 }
@@ -148,47 +293,216 @@ where
                 '\0' => break,
                 other => {
                     if !other.is_ascii_whitespace() {
-                        panic!("Unexpected token encountered: {other}")
+                        return Err(JsonError::Syntax(format!("unexpected character `{other}`")));
                     } else {
                         self.iterator.next();
                     }
                 }
             }
         }
+
+        // The reader stops at invalid UTF-8 unless told to replace or skip it.
+        if let Some(offset) = self.iterator.invalid_utf8_at() {
+            return Err(JsonError::Syntax(format!("invalid UTF-8 at byte {offset}")));
+        }
+
+        progress(Progress {
+            bytes_read: self.iterator.bytes_read() as u64,
+            total_bytes: self.total_bytes,
+        });
+
         Ok(&self.tokens)
     }
 
-    fn parse_string(&mut self) -> String {
-        // Create new vector to hold parsed characters.
-        let mut string_characters = Vec::new();
+    /// Count an array or object being opened, failing if it's nested too deeply to be built
+    /// into a value without overflowing the stack.
+    fn open(&mut self) -> Result<(), JsonError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(JsonError::Syntax(format!(
+                "arrays and objects are nested more than {MAX_NESTING} levels deep"
+            )));
+        }
 
-        // Take each character by reference so that they aren't moved out of the iterator, which
-        // will require you to move the iterator into this function.
-        for character in self.iterator.by_ref() {
-            // If it encounters a closing `"`, break out of the loop as the string has ended.
-            if character == '"' {
-                break;
+        Ok(())
+    }
+
+    /// Read the literal `literal`, whose first character has been peeked at.
+    fn parse_literal(&mut self, literal: &str) -> Result<(), JsonError> {
+        for expected in literal.chars() {
+            match self.iterator.next() {
+                Some(character) if character == expected => {}
+                Some(character) => {
+                    return Err(JsonError::Syntax(format!(
+                        "unexpected character `{character}` in `{literal}`"
+                    )))
+                }
+                None => {
+                    return Err(JsonError::Syntax(format!(
+                        "unfinished `{literal}` at end of input"
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        // Read into the scratch buffer, which has grown to fit long strings already, and only
+        // allocate the string once its length is known.
+        let mut string = std::mem::take(&mut self.scratch.string);
+        string.clear();
+        let result = self.read_string(&mut string);
+        let parsed = result.map(|()| string.as_str().to_owned());
+        self.scratch.string = string;
+
+        parsed
+    }
+
+    fn read_string(&mut self, string: &mut String) -> Result<(), JsonError> {
+        loop {
+            // Copy the characters needing no decoding at once.
+            self.iterator.read_string_run(string);
+            let Some(character) = self.iterator.next() else {
+                return Err(JsonError::Syntax(
+                    "unterminated string at end of input".to_string(),
+                ));
+            };
+
+            match character {
+                // If it encounters a closing `"`, break out of the loop as the string has ended.
+                '"' => break,
+                // Decode escape sequences, which includes escaped quotes.
+                '\\' => self.parse_escape(string)?,
+                // Continue pushing to the string to build it.
+                character => string.push(character),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decode the escape sequence following a `\`, appending it to `string`.
+    fn parse_escape(&mut self, string: &mut String) -> Result<(), JsonError> {
+        let character = match self.iterator.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{08}',
+            Some('f') => '\u{0C}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => return self.parse_unicode_escape(string),
+            Some(other) => {
+                return Err(JsonError::Syntax(format!(
+                    "invalid escape sequence `\\{other}`"
+                )))
+            }
+            None => {
+                return Err(JsonError::Syntax(
+                    "unfinished escape sequence at end of input".to_string(),
+                ))
+            }
+        };
+
+        string.push(character);
+        Ok(())
+    }
+
+    /// Decode a `\\uXXXX` escape, whose `\\u` has already been read, along with the escape of
+    /// a low surrogate following it if it's a high surrogate.
+    fn parse_unicode_escape(&mut self, string: &mut String) -> Result<(), JsonError> {
+        let mut code = self.parse_hex()?;
+
+        loop {
+            match code {
+                0xD800..=0xDBFF => {
+                    // A high surrogate must be followed by an escaped low surrogate to form a
+                    // character. Anything else following it is decoded on its own.
+                    if self.iterator.next_if_eq(&'\\').is_none() {
+                        return self.push_lone_surrogate(code, string);
+                    }
+                    if self.iterator.next_if_eq(&'u').is_none() {
+                        self.push_lone_surrogate(code, string)?;
+                        return self.parse_escape(string);
+                    }
+
+                    let next = self.parse_hex()?;
+                    if let 0xDC00..=0xDFFF = next {
+                        let code = 0x10000
+                            + ((u32::from(code) - 0xD800) << 10)
+                            + (u32::from(next) - 0xDC00);
+                        string
+                            .push(char::from_u32(code).expect("surrogate pairs encode characters"));
+                        return Ok(());
+                    }
+
+                    self.push_lone_surrogate(code, string)?;
+                    code = next;
+                }
+                0xDC00..=0xDFFF => return self.push_lone_surrogate(code, string),
+                code => {
+                    string
+                        .push(char::from_u32(code.into()).expect("non-surrogates are characters"));
+                    return Ok(());
+                }
             }
+        }
+    }
+
+    /// Read the four hexadecimal digits of a `\\u` escape.
+    fn parse_hex(&mut self) -> Result<u16, JsonError> {
+        let hex: String = self.iterator.by_ref().take(4).collect();
+
+        if hex.len() == 4 && hex.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            Ok(u16::from_str_radix(&hex, 16).expect("four hexadecimal digits fit in a u16"))
+        } else {
+            Err(JsonError::Syntax(format!(
+                "expected four hexadecimal digits after `\\u`, found `{hex}`"
+            )))
+        }
+    }
 
-            // Continue pushing to the vector to build the string.
-            string_characters.push(character);
+    /// Handle a surrogate which isn't part of a pair, according to the options.
+    fn push_lone_surrogate(&mut self, code: u16, string: &mut String) -> Result<(), JsonError> {
+        match self.options.lone_surrogates {
+            LoneSurrogate::Error => {
+                return Err(JsonError::Syntax(format!(
+                    "unpaired surrogate `\\u{code:04x}`"
+                )))
+            }
+            LoneSurrogate::Replace => string.push(char::REPLACEMENT_CHARACTER),
+            LoneSurrogate::Escape => string.push_str(&format!("\\u{code:04x}")),
         }
 
-        // Create a string out of the character iterator and return it.
-        String::from_iter(string_characters)
+        Ok(())
     }
 
-    fn parse_number(&mut self) -> Result<Number, ()> {
-        // Store parsed number characters.
-        let mut number_characters = Vec::new();
+    fn parse_number(&mut self) -> Result<Number, JsonError> {
+        // Read into the scratch buffers rather than allocating new ones for every number.
+        let mut number_characters = std::mem::take(&mut self.scratch.number);
+        let mut epsilon_characters = std::mem::take(&mut self.scratch.exponent);
+        number_characters.clear();
+        epsilon_characters.clear();
+        let number = self.read_number(&mut number_characters, &mut epsilon_characters);
+        self.scratch.number = number_characters;
+        self.scratch.exponent = epsilon_characters;
+
+        number
+    }
 
+    /// Read a number, storing its characters apart from its exponent in `number_characters`,
+    /// and those of its exponent in `epsilon_characters`.
+    fn read_number(
+        &mut self,
+        number_characters: &mut String,
+        epsilon_characters: &mut String,
+    ) -> Result<Number, JsonError> {
         // Stores wether the digit being parsed is a `.` character making it a decimal.
         let mut is_decimal = false;
 
-        // Stores the characters after an apsilon character `e` or `E` to indicate the exponential
-        // value.
-        let mut epsilon_characters = Vec::new();
-
         // Stores wether the digit being parsed is part of the epsilon characters.
         let mut is_epsilon_characters = false;
 
@@ -239,17 +553,20 @@ where
                 }
                 // Match any of the characters that can signify end of the number literal value.
                 // This can be a comma which separated key-value pair, closing object character,
-                // closing array character, or a `:` which separates a key from its value.
-                '}' | ',' | ']' | ':' => {
+                // closing array character, a `:` which separates a key from its value, or
+                // whitespace, which mustn't join the number to whatever follows it.
+                '}' | ',' | ']' | ':' | ' ' | '\t' | '\n' | '\r' => {
                     break;
                 }
                 // Match the epsilon character which indicates that the number is in scrientific
                 // notation.
                 'e' | 'E' => {
-                    // Panic if it's already parsing an exponential number since this would mean
+                    // Fail if it's already parsing an exponential number since this would mean
                     // there are 2 epsilon characters which is invalid.
                     if is_epsilon_characters {
-                        panic!("Unexpected character while parsing number: {character}. Double epsilon characters encountered");
+                        return Err(JsonError::Syntax(format!(
+                            "unexpected character `{character}` in number, which already has an exponent"
+                        )));
                     }
 
                     // Set the current state of number being in scientific notation to true.
@@ -258,38 +575,52 @@ where
                     // Advance the iterator by 1.
                     let _ = self.iterator.next();
                 }
-                // Panic if any other character is encountered.
-                other => {
-                    if !other.is_ascii_whitespace() {
-                        panic!("Unexpected character while parsing number: {character}")
-                    } else {
-                        self.iterator.next();
-                    }
+                // Fail if any other character is encountered.
+                _ => {
+                    return Err(JsonError::Syntax(format!(
+                        "unexpected character `{character}` in number"
+                    )));
                 }
             }
         }
         if is_epsilon_characters {
-            // if the number is an exponential, perform the calculations to convert it to a
-            // floating point number in Rust.
-
-            // Parse base as floating point number.
-            let base: f64 = String::from_iter(number_characters).parse().unwrap();
-
-            // Parse exponential as floating point number;
-            let exponential: f64 = String::from_iter(epsilon_characters).parse().unwrap();
+            // if the number is an exponential, parse the whole number at once rather than
+            // scaling the base by a power of 10, which loses precision.
+            number_characters.push('e');
+            number_characters.push_str(epsilon_characters);
 
             // Return the final computed decial number.
-            Ok(Number::F64(base * 10_f64.powf(exponential)))
+            parse_float(number_characters)
         } else if is_decimal {
             // if the number is a decimal, parse it as a floating point number in rust.
-            Ok(Number::F64(
-                String::from_iter(number_characters).parse::<f64>().unwrap(),
-            ))
+            parse_float(number_characters)
         } else {
-            // Parse the number as an integer in Rust.
-            Ok(Number::I64(
-                String::from_iter(number_characters).parse::<i64>().unwrap(),
-            ))
+            // Parse the number as an integer in Rust, or as a floating point number if it's too
+            // large for one.
+            number_characters
+                .parse()
+                .map(Number::I64)
+                .or_else(|_| parse_float(number_characters))
         }
     }
 }
+
+/// Number of bytes from the current position of `input` to its end, if it can seek there and
+/// back.
+fn remaining_bytes(input: &mut impl Seek) -> Option<u64> {
+    let position = input.stream_position().ok()?;
+    let end = input.seek(SeekFrom::End(0)).ok()?;
+    input.seek(SeekFrom::Start(position)).ok()?;
+
+    Some(end.saturating_sub(position))
+}
+
+fn parse_float(number: &str) -> Result<Number, JsonError> {
+    match number.parse::<f64>() {
+        Ok(float) if float.is_finite() => Ok(Number::F64(float)),
+        Ok(_) => Err(JsonError::Syntax(format!(
+            "number `{number}` is out of range"
+        ))),
+        Err(_) => Err(JsonError::Syntax(format!("invalid number `{number}`"))),
+    }
+}
@@ -0,0 +1,58 @@
+use crate::error::JsonError;
+use crate::parser::{JsonParser, ParserOptions};
+use crate::value::Value;
+use ::tokio::io::{AsyncRead, AsyncReadExt};
+
+impl JsonParser {
+    /// Parse JSON from an asynchronous reader, such as a socket or the body of a request.
+    ///
+    /// The input is read to its end without blocking the thread, then parsed like with
+    /// [`parse_from_bytes`](JsonParser::parse_from_bytes). Parsing itself runs on the calling
+    /// task, so very large documents are better handed over to a blocking thread once read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Io`] if reading fails, or any error of
+    /// [`parse_from_bytes`](JsonParser::parse_from_bytes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let value = runtime.block_on(async {
+    ///     let body: &[u8] = br#"{"status": "ok"}"#;
+    ///     JsonParser::parse_from_async_reader(body).await
+    /// });
+    ///
+    /// assert_eq!(value, JsonParser::parse_from_bytes(br#"{"status": "ok"}"#));
+    /// ```
+    pub async fn parse_from_async_reader<R>(reader: R) -> Result<Value, JsonError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        Self::parse_from_async_reader_with_options(reader, &ParserOptions::default()).await
+    }
+
+    /// Parse JSON from an asynchronous reader, as done by
+    /// [`parse_from_async_reader`](JsonParser::parse_from_async_reader), according to
+    /// `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Io`] if reading fails, or any error of
+    /// [`parse_from_bytes_with_options`](JsonParser::parse_from_bytes_with_options).
+    pub async fn parse_from_async_reader_with_options<R>(
+        mut reader: R,
+        options: &ParserOptions,
+    ) -> Result<Value, JsonError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut input = Vec::new();
+        reader.read_to_end(&mut input).await?;
+
+        Self::parse_from_bytes_with_options(&input, options)
+    }
+}
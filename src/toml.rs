@@ -0,0 +1,125 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+
+impl Value {
+    /// Parse a TOML document into a value.
+    ///
+    /// Tables become objects and arrays become arrays, while datetimes become strings in their
+    /// RFC 3339 form, such as `"1979-05-27T07:32:00Z"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the document isn't valid TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    ///
+    /// let value = Value::from_toml("[server]\nport = 8080\nstarted = 1979-05-27T07:32:00Z\n").unwrap();
+    ///
+    /// assert_eq!(value.pointer("/server/port"), Some(&Value::from(8080)));
+    /// assert_eq!(
+    ///     value.pointer("/server/started"),
+    ///     Some(&Value::from("1979-05-27T07:32:00Z"))
+    /// );
+    /// assert_eq!(Value::from_toml(&value.to_toml().unwrap()).unwrap(), value);
+    /// ```
+    pub fn from_toml(input: &str) -> Result<Value, JsonError> {
+        let table: toml::Table = toml::from_str(input).map_err(|error| {
+            // The full error also renders the offending line, keep only the location.
+            let rendered = error.to_string();
+            let location = rendered.lines().next().unwrap_or_default();
+            let message = match error.message().trim() {
+                "" => location.to_string(),
+                message => format!("{location}: {message}"),
+            };
+
+            JsonError::Format {
+                format: "TOML",
+                message,
+            }
+        })?;
+
+        Ok(from_toml_table(table))
+    }
+
+    /// Format the value as a TOML document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] if the value isn't an object, since a TOML document
+    /// is always a table, or if it contains `null`, which TOML has no equivalent for.
+    pub fn to_toml(&self) -> Result<String, JsonError> {
+        let Value::Object(object) = self else {
+            return Err(JsonError::TypeMismatch {
+                pointer: String::new(),
+                expected: "TOML table".to_string(),
+                found: self.type_name(),
+            });
+        };
+
+        toml::to_string(&to_toml_table(object)?).map_err(|error| JsonError::Format {
+            format: "TOML",
+            message: error.to_string(),
+        })
+    }
+}
+
+fn from_toml_table(table: toml::Table) -> Value {
+    Value::Object(
+        table
+            .into_iter()
+            .map(|(key, value)| (key, from_toml_value(value)))
+            .collect::<Map>(),
+    )
+}
+
+fn from_toml_value(toml: toml::Value) -> Value {
+    match toml {
+        toml::Value::String(string) => Value::from(string),
+        toml::Value::Integer(integer) => Value::Number(Number::I64(integer)),
+        toml::Value::Float(float) => Value::Number(Number::F64(float)),
+        toml::Value::Boolean(boolean) => Value::Boolean(boolean),
+        toml::Value::Datetime(datetime) => Value::from(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(from_toml_value).collect()),
+        toml::Value::Table(table) => from_toml_table(table),
+    }
+}
+
+fn to_toml_table(object: &Map) -> Result<toml::Table, JsonError> {
+    object
+        .iter()
+        .map(|(key, value)| match to_toml_value(value) {
+            Ok(value) => Ok((key.to_string(), value)),
+            Err(error) => Err(error.nested(key)),
+        })
+        .collect()
+}
+
+fn to_toml_value(value: &Value) -> Result<toml::Value, JsonError> {
+    Ok(match value {
+        Value::Null => {
+            return Err(JsonError::TypeMismatch {
+                pointer: String::new(),
+                expected: "TOML value".to_string(),
+                found: value.type_name(),
+            })
+        }
+        Value::Boolean(boolean) => toml::Value::Boolean(*boolean),
+        Value::Number(Number::I64(integer)) => toml::Value::Integer(*integer),
+        Value::Number(Number::F64(float)) => toml::Value::Float(*float),
+        Value::String(string) => toml::Value::String(string.to_string()),
+        Value::Array(array) => toml::Value::Array(
+            array
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    to_toml_value(value).map_err(|error| error.nested(&index.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(object) => toml::Value::Table(to_toml_table(object)?),
+    })
+}
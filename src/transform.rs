@@ -0,0 +1,145 @@
+use crate::value::Value;
+use std::mem;
+
+impl Value {
+    /// Recursively rename every object key with `transform`.
+    ///
+    /// If two keys of the same object are mapped to the same name, the member which comes last
+    /// wins, but keeps the position of the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"Name":{"First":"Ada"}}"#).unwrap();
+    /// value.map_keys(|key| key.to_lowercase());
+    ///
+    /// assert_eq!(value, JsonParser::parse_from_bytes(br#"{"name":{"first":"Ada"}}"#).unwrap());
+    /// ```
+    pub fn map_keys<F>(&mut self, mut transform: F)
+    where
+        F: FnMut(&str) -> String,
+    {
+        self.map_keys_recursive(&mut transform);
+    }
+
+    fn map_keys_recursive<F>(&mut self, transform: &mut F)
+    where
+        F: FnMut(&str) -> String,
+    {
+        match self {
+            Value::Object(object) => {
+                *object = mem::take(object)
+                    .into_iter()
+                    .map(|(key, mut value)| {
+                        value.map_keys_recursive(transform);
+                        (transform(&key), value)
+                    })
+                    .collect();
+            }
+            Value::Array(array) => {
+                for value in array {
+                    value.map_keys_recursive(transform);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Recursively replace every value with the result of `transform`.
+    ///
+    /// Values are transformed bottom-up, so `transform` is called on the children of an object
+    /// or array before it is called on the container itself, which then already holds the
+    /// transformed children. The root value is transformed last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::{Number, Value};
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"port":"8080","tags":["1","a"]}"#).unwrap();
+    /// value.map_values(|value| match &value {
+    ///     Value::String(string) => match string.parse() {
+    ///         Ok(number) => Value::Number(Number::I64(number)),
+    ///         Err(_) => value,
+    ///     },
+    ///     _ => value,
+    /// });
+    ///
+    /// assert_eq!(value, JsonParser::parse_from_bytes(br#"{"port":8080,"tags":[1,"a"]}"#).unwrap());
+    /// ```
+    pub fn map_values<F>(&mut self, mut transform: F)
+    where
+        F: FnMut(Value) -> Value,
+    {
+        self.map_values_recursive(&mut transform);
+    }
+
+    fn map_values_recursive<F>(&mut self, transform: &mut F)
+    where
+        F: FnMut(Value) -> Value,
+    {
+        match self {
+            Value::Object(object) => {
+                for value in object.values_mut() {
+                    value.map_values_recursive(transform);
+                }
+            }
+            Value::Array(array) => {
+                for value in array.iter_mut() {
+                    value.map_values_recursive(transform);
+                }
+            }
+            _ => {}
+        }
+
+        *self = transform(mem::replace(self, Value::Null));
+    }
+
+    /// Recursively remove every object member for which `predicate` returns `false`.
+    ///
+    /// Members are checked top-down, so the children of a removed member are never passed to
+    /// `predicate`. Arrays are searched for nested objects, but their elements are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let mut value = JsonParser::parse_from_bytes(br#"{"id":1,"_meta":{},"items":[{"_rev":2,"id":3}]}"#).unwrap();
+    /// value.retain(|key, _| !key.starts_with('_'));
+    ///
+    /// assert_eq!(value, JsonParser::parse_from_bytes(br#"{"id":1,"items":[{"id":3}]}"#).unwrap());
+    /// ```
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&str, &Value) -> bool,
+    {
+        self.retain_recursive(&mut predicate);
+    }
+
+    fn retain_recursive<F>(&mut self, predicate: &mut F)
+    where
+        F: FnMut(&str, &Value) -> bool,
+    {
+        match self {
+            Value::Object(object) => {
+                object.retain(|key, value| {
+                    let keep = predicate(key, value);
+                    if keep {
+                        value.retain_recursive(predicate);
+                    }
+                    keep
+                });
+            }
+            Value::Array(array) => {
+                for value in array {
+                    value.retain_recursive(predicate);
+                }
+            }
+            _ => {}
+        }
+    }
+}
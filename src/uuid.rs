@@ -0,0 +1,36 @@
+use crate::value::Value;
+use uuid::Uuid;
+
+impl Value {
+    /// Read a string holding a UUID, such as `"67e55044-10b1-426f-9247-bb680e5fe0c8"`.
+    ///
+    /// Any of the forms [`Uuid::parse_str`] accepts is read, including those without hyphens,
+    /// in braces or as a URN. Returns `None` for other values and other strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    /// use uuid::Uuid;
+    ///
+    /// let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+    ///
+    /// assert_eq!(Value::from("67E55044-10B1-426F-9247-BB680E5FE0C8").as_uuid(), Some(id));
+    /// assert_eq!(Value::from(id), Value::from("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+    /// assert_eq!(Value::from("67e55044").as_uuid(), None);
+    /// ```
+    #[must_use]
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        match self {
+            Value::String(string) => Uuid::parse_str(string).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<Uuid> for Value {
+    /// Write the UUID in its hyphenated lowercase form.
+    fn from(uuid: Uuid) -> Self {
+        Value::from(uuid.hyphenated().to_string())
+    }
+}
@@ -1,27 +1,184 @@
-use std::collections::HashMap;
+use crate::convert::FromJson;
+use crate::error::JsonError;
+use crate::map::Map;
+use std::any::type_name;
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+/// A JSON number, stored as an integer when it has no fraction or exponent.
+///
+/// Floats are compared by value, so `0.0` and `-0.0` are equal, except that NaN is equal to
+/// itself so that numbers can be used in sets and as map keys.
+///
+/// Integers and floats are never equal to each other, even when they hold the same number, so
+/// `1` and `1.0` are different values. Where RFCs compare numbers by value instead, they're
+/// treated the same: [`to_canonical_string`](Value::to_canonical_string) and `digest` write
+/// both as `1`, and the `test` operation of a JSON Patch finds them equal.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::JsonParser;
+/// use std::collections::HashSet;
+///
+/// let values = [
+///     JsonParser::parse_from_bytes(br#"{"a":1,"b":[0.5]}"#).unwrap(),
+///     JsonParser::parse_from_bytes(br#"{"b":[0.5],"a":1}"#).unwrap(),
+///     JsonParser::parse_from_bytes(br#"{"a":1.0,"b":[0.5]}"#).unwrap(),
+/// ];
+/// assert_eq!(values[0].to_canonical_string(), values[2].to_canonical_string());
+///
+/// let unique = values.into_iter().collect::<HashSet<_>>();
+/// assert_eq!(unique.len(), 2);
+/// ```
+#[derive(Debug, Copy, Clone)]
 pub enum Number {
     I64(i64),
     F64(f64),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Number::I64(a), Number::I64(b)) => a == b,
+            (Number::F64(a), Number::F64(b)) => a == b || (a.is_nan() && b.is_nan()),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Number {}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Number::I64(integer) => {
+                state.write_u8(0);
+                integer.hash(state);
+            }
+            Number::F64(float) => {
+                state.write_u8(1);
+                // Equal floats must hash the same, whatever their sign or NaN payload.
+                let float = if float == 0.0 {
+                    0.0
+                } else if float.is_nan() {
+                    f64::NAN
+                } else {
+                    float
+                };
+                float.to_bits().hash(state);
+            }
+        }
+    }
+}
+
+/// The type of the contents of [`Value::String`].
+///
+/// This is [`String`], unless the `compact_str` feature is enabled, in which case it's a
+/// [`CompactString`](compact_str::CompactString) which stores strings of up to 24 bytes inline
+/// instead of allocating them. Both convert from `&str` and `String` with [`Into`].
+#[cfg(not(feature = "compact_str"))]
+pub type JsonString = String;
+#[cfg(feature = "compact_str")]
+pub type JsonString = compact_str::CompactString;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum Value {
-    String(String),
+    String(JsonString),
     Number(Number),
     Boolean(bool),
     Array(Vec<Value>),
-    Object(HashMap<String, Value>),
+    Object(Map),
     Null,
 }
 
+impl Value {
+    /// Name of the JSON type of the value, such as `"string"` or `"object"`.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Number(_) => "number",
+            Value::Boolean(_) => "boolean",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+            Value::Null => "null",
+        }
+    }
+
+    /// Convert an array into a vector of `T`, such as `Vec<String>` or `Vec<i32>`.
+    ///
+    /// Elements are converted with [`FromJson`], so integers must fit in `T` and can't have a
+    /// fractional part.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] pointing at the first element which can't be
+    /// converted to `T`, or at the root if the value isn't an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let value = JsonParser::parse_from_bytes(br#"["a","b",3]"#).unwrap();
+    /// let error = value.to_vec_of::<String>().unwrap_err();
+    ///
+    /// assert_eq!(error.to_string(), "type mismatch at /2: expected String, found number");
+    ///
+    /// let value = JsonParser::parse_from_bytes(b"[1,2.0,2.5]").unwrap();
+    /// let error = value.to_vec_of::<i64>().unwrap_err();
+    ///
+    /// assert_eq!(error.to_string(), "type mismatch at /2: expected i64, found number");
+    /// assert_eq!(value.to_vec_of::<f64>().unwrap(), [1.0, 2.0, 2.5]);
+    /// ```
+    pub fn to_vec_of<T: FromJson>(&self) -> Result<Vec<T>, JsonError> {
+        Vec::<T>::from_json(self)
+    }
+}
+
+/// Name of a type without module paths, so `alloc::vec::Vec<alloc::string::String>` becomes
+/// `Vec<String>`.
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let mut name = String::new();
+    let mut identifier = String::new();
+
+    for character in type_name::<T>().chars() {
+        if character.is_alphanumeric() || character == '_' {
+            identifier.push(character);
+        } else if character == ':' {
+            // Drop every path segment before a `::`.
+            identifier.clear();
+        } else {
+            name.push_str(&identifier);
+            identifier.clear();
+            name.push(character);
+        }
+    }
+    name.push_str(&identifier);
+
+    name
+}
+
+impl From<String> for Value {
+    // Converting is only needed for the `compact_str` feature.
+    #[allow(clippy::useless_conversion)]
+    fn from(string: String) -> Self {
+        Value::String(string.into())
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::String(string.into())
+    }
+}
+
 impl TryFrom<&Value> for String {
     type Error = ();
 
     fn try_from(value: &Value) -> Result<Self, Self::Error> {
         match value {
-            Value::String(value) => Ok(value.clone()),
+            Value::String(value) => Ok(value.to_string()),
             _ => Err(()),
         }
     }
@@ -77,8 +234,7 @@ impl<'a> TryFrom<&'a Value> for &'a Vec<Value> {
     }
 }
 
-#[allow(clippy::implicit_hasher)]
-impl<'a> TryFrom<&'a Value> for &'a HashMap<String, Value> {
+impl<'a> TryFrom<&'a Value> for &'a Map {
     type Error = ();
 
     fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
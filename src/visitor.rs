@@ -0,0 +1,82 @@
+use crate::pointer::push_token;
+use crate::value::Value;
+
+/// A visitor which is called for every node of a [`Value`] tree by [`Value::accept`].
+///
+/// Nodes are visited depth-first, in document order. Each call receives the JSON Pointer
+/// (RFC 6901) of the node, where the root is the empty pointer `""`.
+///
+/// # Examples
+///
+/// ```
+/// use json_parser::parser::JsonParser;
+/// use json_parser::value::Value;
+/// use json_parser::visitor::Visitor;
+///
+/// /// Collect the pointers of every null value.
+/// struct NullFinder(Vec<String>);
+///
+/// impl Visitor for NullFinder {
+///     fn enter(&mut self, pointer: &str, value: &Value) -> bool {
+///         if let Value::Null = value {
+///             self.0.push(pointer.to_string());
+///         }
+///         true
+///     }
+/// }
+///
+/// let value = JsonParser::parse_from_bytes(br#"{"a":[1,null],"b/c":null}"#).unwrap();
+/// let mut finder = NullFinder(Vec::new());
+/// value.accept(&mut finder);
+///
+/// assert_eq!(finder.0, ["/a/1", "/b~1c"]);
+/// ```
+pub trait Visitor {
+    /// Called when a node is reached, before any of its children.
+    ///
+    /// Returning `false` skips the children of the node, although [`Visitor::leave`] is
+    /// still called for it.
+    fn enter(&mut self, pointer: &str, value: &Value) -> bool {
+        let _ = (pointer, value);
+        true
+    }
+
+    /// Called after a node and all of its children have been visited.
+    fn leave(&mut self, pointer: &str, value: &Value) {
+        let _ = (pointer, value);
+    }
+}
+
+impl Value {
+    /// Walk the value depth-first, calling `visitor` for every node along with its JSON
+    /// Pointer.
+    pub fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        walk(self, &mut String::new(), visitor);
+    }
+}
+
+fn walk<V: Visitor + ?Sized>(value: &Value, pointer: &mut String, visitor: &mut V) {
+    if visitor.enter(pointer, value) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    let length = pointer.len();
+                    push_token(pointer, key);
+                    walk(value, pointer, visitor);
+                    pointer.truncate(length);
+                }
+            }
+            Value::Array(array) => {
+                for (index, value) in array.iter().enumerate() {
+                    let length = pointer.len();
+                    push_token(pointer, &index.to_string());
+                    walk(value, pointer, visitor);
+                    pointer.truncate(length);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    visitor.leave(pointer, value);
+}
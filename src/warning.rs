@@ -0,0 +1,192 @@
+//! Quality issues of documents which are valid JSON nonetheless.
+
+use crate::cst::{parse_number, Document, Node, NodeKind};
+use crate::error::JsonError;
+use crate::parser::JsonParser;
+use crate::span::{LineIndex, Span};
+use crate::value::{Number, Value};
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// Something questionable found by [`JsonParser::parse_with_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub span: Span,
+    pub kind: WarningKind,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.span.start;
+        write!(f, "{}:{}: {}", start.line, start.column, self.kind)
+    }
+}
+
+/// The kinds of issues reported by [`JsonParser::parse_with_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// A key appearing more than once in the same object, whose earlier values are ignored.
+    DuplicateKey(String),
+    /// A number which doesn't parse to exactly the value written, because it's too precise or
+    /// too large.
+    PrecisionLoss,
+    /// An escape sequence for a printable ASCII character which doesn't need one, such as `\/`
+    /// or `\u0041`.
+    UnnecessaryEscape,
+    /// A UTF-8 byte order mark at the start of the input, which JSON text shouldn't have.
+    ByteOrderMark,
+    /// A `//` or `/* */` comment, which JSON doesn't allow but many configuration files have.
+    Comment,
+}
+
+impl Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningKind::DuplicateKey(key) => write!(f, "duplicate key `{key}`"),
+            WarningKind::PrecisionLoss => f.write_str("number can't be represented exactly"),
+            WarningKind::UnnecessaryEscape => f.write_str("unnecessary escape sequence"),
+            WarningKind::ByteOrderMark => f.write_str("byte order mark"),
+            WarningKind::Comment => f.write_str("comment"),
+        }
+    }
+}
+
+impl JsonParser {
+    /// Parse JSON from bytes, also returning the issues which don't prevent it from being
+    /// parsed. See [`WarningKind`] for the issues looked for.
+    ///
+    /// A leading byte order mark is skipped, and the input may contain comments, as accepted by
+    /// [`Document::parse`], both of which are reported. Warnings are sorted by position.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Syntax`] if the input isn't valid UTF-8 or valid JSON, or has arrays
+    /// and objects nested more than [`MAX_NESTING`](crate::token::MAX_NESTING) levels deep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    ///
+    /// let input = b"\xEF\xBB\xBF{\"id\": 1, \"url\": \"a\\/b\", \"id\": 12345678901234567890}\n// end";
+    /// let (value, warnings) = JsonParser::parse_with_warnings(input).unwrap();
+    ///
+    /// let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    /// assert_eq!(
+    ///     warnings,
+    ///     [
+    ///         "1:1: byte order mark",
+    ///         "1:21: unnecessary escape sequence",
+    ///         "1:27: duplicate key `id`",
+    ///         "1:33: number can't be represented exactly",
+    ///         "2:1: comment",
+    ///     ]
+    /// );
+    ///
+    /// let expected = JsonParser::parse_from_bytes(br#"{"id": 1.2345678901234567e19, "url": "a/b"}"#);
+    /// assert_eq!(value, expected.unwrap());
+    /// ```
+    pub fn parse_with_warnings(input: &[u8]) -> Result<(Value, Vec<Warning>), JsonError> {
+        let text = std::str::from_utf8(input).map_err(|error| {
+            JsonError::Syntax(format!("invalid UTF-8 at byte {}", error.valid_up_to()))
+        })?;
+
+        let document = Document::parse(text)?;
+        let mut found = Vec::new();
+        if text.starts_with('\u{FEFF}') {
+            found.push((0..'\u{FEFF}'.len_utf8(), WarningKind::ByteOrderMark));
+        }
+        for comment in document.comment_spans() {
+            found.push((comment.clone(), WarningKind::Comment));
+        }
+        collect_warnings(document.root(), text, &mut found);
+        found.sort_by_key(|(span, _)| span.start);
+
+        let lines = LineIndex::new(text);
+        let warnings = found
+            .into_iter()
+            .map(|(span, kind)| Warning {
+                span: lines.span(span),
+                kind,
+            })
+            .collect();
+
+        Ok((document.to_value(), warnings))
+    }
+}
+
+fn collect_warnings(node: &Node, source: &str, found: &mut Vec<(Range<usize>, WarningKind)>) {
+    match node.kind() {
+        NodeKind::Number if loses_precision(&source[node.span()]) => {
+            found.push((node.span(), WarningKind::PrecisionLoss));
+        }
+        NodeKind::String(_) => find_unnecessary_escapes(node.span(), source, found),
+        NodeKind::Array(elements) => {
+            for element in elements {
+                collect_warnings(element, source, found);
+            }
+        }
+        NodeKind::Object(members) => {
+            let mut keys = HashSet::new();
+            for member in members {
+                find_unnecessary_escapes(member.key_span(), source, found);
+                if !keys.insert(member.key()) {
+                    let kind = WarningKind::DuplicateKey(member.key().to_string());
+                    found.push((member.key_span(), kind));
+                }
+                collect_warnings(member.value(), source, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Look for escapes of printable ASCII characters other than `"` and `\` in the text of a
+/// string, which is known to be valid.
+fn find_unnecessary_escapes(
+    span: Range<usize>,
+    source: &str,
+    found: &mut Vec<(Range<usize>, WarningKind)>,
+) {
+    let text = &source[span.clone()];
+    let mut index = 0;
+
+    while let Some(backslash) = text[index..].find('\\') {
+        let start = index + backslash;
+        let length = match text.as_bytes()[start + 1] {
+            b'u' => 6,
+            _ => 2,
+        };
+        let escape = &text[start..start + length];
+
+        let unnecessary = match escape.strip_prefix("\\u") {
+            Some(hex) => u8::from_str_radix(hex, 16)
+                .is_ok_and(|code| (b' '..=b'~').contains(&code) && !matches!(code, b'"' | b'\\')),
+            None => escape == "\\/",
+        };
+        if unnecessary {
+            let start = span.start + start;
+            found.push((start..start + length, WarningKind::UnnecessaryEscape));
+        }
+        index = start + length;
+    }
+}
+
+/// Whether the text of a valid number can't be recovered from the number it parses to.
+fn loses_precision(text: &str) -> bool {
+    match parse_number(text) {
+        Number::I64(_) => false,
+        Number::F64(float) if !float.is_finite() => true,
+        // The exponent form has the fewest digits which read back as the same float.
+        Number::F64(float) => significant_digits(text) != significant_digits(&format!("{float:e}")),
+    }
+}
+
+/// The digits of a number, without its exponent nor leading and trailing zeros.
+fn significant_digits(text: &str) -> String {
+    let mantissa = text.split(['e', 'E']).next().unwrap_or(text);
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+
+    digits.trim_matches('0').to_string()
+}
@@ -0,0 +1,199 @@
+//! Bindings exposing the parser to JavaScript through `wasm-bindgen`, for browser tooling.
+//!
+//! Built with `wasm-pack build --features wasm`, the module exports four functions:
+//!
+//! ```js
+//! import { parse, stringify, pointer, validate } from "json-parser";
+//!
+//! const config = parse('{"servers": [{"port": 80}]}');
+//! stringify(config, true);                              // indented over several lines
+//! pointer('{"servers": [{"port": 80}]}', "/servers/0/port"); // 80
+//! validate("[1, 2,]");                                  // "syntax error: ..."
+//! ```
+//!
+//! Integers beyond what a JavaScript number represents exactly are converted to `BigInt`s, and
+//! `BigInt`s back to integers.
+
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::parser::JsonParser;
+use crate::ser::{self, Layout};
+use crate::value::{Number, Value};
+use ::js_sys::{Array, BigInt, Object};
+use ::wasm_bindgen::prelude::*;
+use ::wasm_bindgen::JsCast;
+
+/// Largest integer a JavaScript number represents exactly, `Number.MAX_SAFE_INTEGER`.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Parse JSON text into plain JavaScript objects, arrays and primitives.
+///
+/// # Errors
+///
+/// Throws an `Error` if the text isn't valid JSON.
+#[wasm_bindgen]
+pub fn parse(text: &str) -> Result<JsValue, JsError> {
+    let value = JsonParser::parse_from_bytes(text.as_bytes())?;
+
+    Ok(JsValue::from(&value))
+}
+
+/// Write a JavaScript value as JSON text, indented by two spaces per level if `pretty` is set.
+///
+/// # Errors
+///
+/// Throws an `Error` if the value contains something JSON can't represent, such as a function
+/// or a number which isn't finite.
+#[wasm_bindgen]
+pub fn stringify(value: &JsValue, pretty: Option<bool>) -> Result<String, JsError> {
+    let text = Value::try_from(value)?.to_string_compact();
+    if !pretty.unwrap_or(false) {
+        return Ok(text);
+    }
+
+    let mut output = Vec::with_capacity(text.len() * 2);
+    ser::reformat(text.as_bytes(), &mut output, Layout::Pretty)?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+/// Find the value referenced by a JSON Pointer inside JSON text, or `undefined` if there is
+/// none.
+///
+/// # Errors
+///
+/// Throws an `Error` if the text isn't valid JSON.
+#[wasm_bindgen]
+pub fn pointer(text: &str, pointer: &str) -> Result<JsValue, JsError> {
+    let value = JsonParser::parse_from_bytes(text.as_bytes())?;
+
+    Ok(value
+        .pointer(pointer)
+        .map_or(JsValue::UNDEFINED, JsValue::from))
+}
+
+/// Check whether text is valid JSON, returning the description of its first error, or
+/// `undefined` if it's valid.
+#[must_use]
+#[wasm_bindgen]
+pub fn validate(text: &str) -> Option<String> {
+    JsonParser::parse_from_bytes(text.as_bytes())
+        .err()
+        .map(|error| error.to_string())
+}
+
+impl From<&Value> for JsValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => JsValue::NULL,
+            Value::Boolean(boolean) => JsValue::from_bool(*boolean),
+            Value::Number(Number::I64(integer)) => {
+                if integer.unsigned_abs() <= MAX_SAFE_INTEGER.unsigned_abs() {
+                    #[allow(clippy::cast_precision_loss)]
+                    JsValue::from_f64(*integer as f64)
+                } else {
+                    JsValue::from(*integer)
+                }
+            }
+            Value::Number(Number::F64(float)) => JsValue::from_f64(*float),
+            Value::String(string) => JsValue::from_str(string),
+            Value::Array(elements) => elements.iter().map(JsValue::from).collect::<Array>().into(),
+            Value::Object(members) => {
+                let entries: Array = members
+                    .iter()
+                    .map(|(key, value)| Array::of2(&JsValue::from_str(key), &JsValue::from(value)))
+                    .collect();
+                // Unlike assigning properties one by one, this creates a member named
+                // `__proto__` instead of changing the prototype of the object.
+                Object::from_entries(&entries)
+                    .expect("entries are key-value pairs")
+                    .into()
+            }
+        }
+    }
+}
+
+impl TryFrom<&JsValue> for Value {
+    type Error = JsonError;
+
+    /// Convert a JavaScript value, as `JSON.stringify` would see it.
+    ///
+    /// `undefined` is converted to `null`, and numbers which are integers within
+    /// `Number.MAX_SAFE_INTEGER` to [`Number::I64`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::UnrepresentableNumber`] for numbers which aren't finite and
+    /// `BigInt`s out of the range of `i64`, and [`JsonError::Custom`] for values such as
+    /// functions and symbols.
+    fn try_from(value: &JsValue) -> Result<Self, Self::Error> {
+        if value.is_null_or_undefined() {
+            return Ok(Value::Null);
+        }
+        if let Some(boolean) = value.as_bool() {
+            return Ok(Value::Boolean(boolean));
+        }
+        if let Some(float) = value.as_f64() {
+            return number_from_f64(float);
+        }
+        if value.is_bigint() {
+            return i64::try_from(value.clone())
+                .map(|integer| Value::Number(Number::I64(integer)))
+                .map_err(|_| JsonError::UnrepresentableNumber {
+                    pointer: String::new(),
+                    number: value
+                        .unchecked_ref::<BigInt>()
+                        .to_string(10)
+                        .map(String::from)
+                        .unwrap_or_default(),
+                });
+        }
+        if let Some(string) = value.as_string() {
+            return Ok(Value::from(string));
+        }
+        if Array::is_array(value) {
+            return value
+                .unchecked_ref::<Array>()
+                .iter()
+                .enumerate()
+                .map(|(position, element)| {
+                    Value::try_from(&element).map_err(|error| error.nested(&position.to_string()))
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Array);
+        }
+        if value.is_object() {
+            let entries = Object::entries(value.unchecked_ref::<Object>());
+            let mut members = Map::with_capacity(entries.length() as usize);
+            for entry in entries.iter() {
+                let entry = entry.unchecked_into::<Array>();
+                let key = entry.get(0).as_string().unwrap_or_default();
+                let member = Value::try_from(&entry.get(1)).map_err(|error| error.nested(&key))?;
+                members.insert(key, member);
+            }
+            return Ok(Value::Object(members));
+        }
+
+        Err(JsonError::Custom(format!(
+            "a JavaScript {} can't be converted to JSON",
+            value.js_typeof().as_string().unwrap_or_default()
+        )))
+    }
+}
+
+fn number_from_f64(float: f64) -> Result<Value, JsonError> {
+    if !float.is_finite() {
+        return Err(JsonError::UnrepresentableNumber {
+            pointer: String::new(),
+            number: float.to_string(),
+        });
+    }
+
+    if float.fract() == 0.0 && float.abs() <= MAX_SAFE_INTEGER as f64 {
+        // The float is an integer within range, so the conversion is exact.
+        #[allow(clippy::cast_possible_truncation)]
+        return Ok(Value::Number(Number::I64(float as i64)));
+    }
+
+    Ok(Value::Number(Number::F64(float)))
+}
@@ -0,0 +1,297 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Build the error for XML input which can't be parsed.
+fn xml_error(message: impl ToString) -> JsonError {
+    JsonError::Format {
+        format: "XML",
+        message: message.to_string(),
+    }
+}
+
+/// An element whose content is still being read.
+struct Element {
+    name: String,
+    /// Attributes, prefixed with `@`, followed by child elements.
+    members: Map,
+    text: String,
+}
+
+impl Element {
+    fn start(start: &BytesStart) -> Result<Self, JsonError> {
+        let mut members = Map::new();
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(xml_error)?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref());
+            let value = attribute.unescape_value().map_err(xml_error)?;
+            members.insert(format!("@{key}"), Value::from(value.into_owned()));
+        }
+
+        Ok(Element {
+            name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+            members,
+            text: String::new(),
+        })
+    }
+
+    /// Convert the element into its value: `null` or a string if it only holds text, or an
+    /// object otherwise.
+    fn finish(self) -> (String, Value) {
+        let Element {
+            name,
+            mut members,
+            text,
+        } = self;
+
+        let value = match (members.is_empty(), text.is_empty()) {
+            (true, true) => Value::Null,
+            (true, false) => Value::from(text),
+            (false, _) => {
+                if !text.is_empty() {
+                    members.insert("#text".to_string(), Value::from(text));
+                }
+                Value::Object(members)
+            }
+        };
+
+        (name, value)
+    }
+
+    /// Add a child element, turning repeated elements into an array.
+    fn push_child(&mut self, name: String, value: Value) {
+        match self.members.get_mut(&name) {
+            // Element values are never arrays, so an array means the element was repeated.
+            Some(Value::Array(array)) => array.push(value),
+            Some(existing) => {
+                let first = std::mem::replace(existing, Value::Null);
+                *existing = Value::Array(vec![first, value]);
+            }
+            None => {
+                self.members.insert(name, value);
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Convert an XML document into a value.
+    ///
+    /// The result is an object with a single member named after the root element. Elements
+    /// holding only text become strings, empty elements become `null`, and other elements
+    /// become objects holding their attributes prefixed with `@`, their child elements by name,
+    /// and their text under `#text`. Repeated child elements are collected into an array.
+    /// Comments, processing instructions and whitespace between elements are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the document isn't well-formed or doesn't have exactly
+    /// one root element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::parser::JsonParser;
+    /// use json_parser::value::Value;
+    ///
+    /// let xml = r#"<order id="7"><item>tea</item><item>milk</item><note/></order>"#;
+    /// let value = Value::from_xml(xml).unwrap();
+    /// let expected = JsonParser::parse_from_bytes(
+    ///     br#"{"order":{"@id":"7","item":["tea","milk"],"note":null}}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(value, expected);
+    /// assert_eq!(value.to_xml().unwrap(), xml);
+    /// ```
+    pub fn from_xml(input: &str) -> Result<Value, JsonError> {
+        let mut reader = Reader::from_str(input);
+        reader.config_mut().trim_text(true);
+
+        let mut stack: Vec<Element> = Vec::new();
+        let mut root = None;
+
+        loop {
+            let finished = match reader.read_event().map_err(xml_error)? {
+                Event::Start(start) => {
+                    stack.push(Element::start(&start)?);
+                    None
+                }
+                Event::Empty(start) => Some(Element::start(&start)?.finish()),
+                Event::End(_) => stack.pop().map(Element::finish),
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(xml_error)?;
+                    match stack.last_mut() {
+                        Some(element) => element.text.push_str(&text),
+                        None => return Err(xml_error("text outside of the root element")),
+                    }
+                    None
+                }
+                Event::CData(data) => {
+                    let data = String::from_utf8_lossy(&data);
+                    match stack.last_mut() {
+                        Some(element) => element.text.push_str(&data),
+                        None => return Err(xml_error("text outside of the root element")),
+                    }
+                    None
+                }
+                Event::Eof => break,
+                _ => None,
+            };
+
+            if let Some((name, value)) = finished {
+                match stack.last_mut() {
+                    Some(parent) => parent.push_child(name, value),
+                    None if root.is_none() => root = Some((name, value)),
+                    None => return Err(xml_error("more than one root element")),
+                }
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(xml_error("unclosed element"));
+        }
+        let (name, value) = root.ok_or_else(|| xml_error("missing root element"))?;
+
+        let mut document = Map::new();
+        document.insert(name, value);
+
+        Ok(Value::Object(document))
+    }
+
+    /// Convert the value into an XML document, following the mapping of [`from_xml`].
+    ///
+    /// Numbers and booleans are written as text, so they come back as strings when the
+    /// document is converted back.
+    ///
+    /// [`from_xml`]: Value::from_xml
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::TypeMismatch`] if the value isn't an object with a single member,
+    /// or if attributes or text hold arrays or objects, and [`JsonError::Format`] if a key
+    /// isn't a valid element or attribute name.
+    pub fn to_xml(&self) -> Result<String, JsonError> {
+        let root = match self {
+            Value::Object(object) if object.len() == 1 => object.iter().next(),
+            _ => None,
+        };
+        let Some((name, value)) = root else {
+            return Err(JsonError::TypeMismatch {
+                pointer: String::new(),
+                expected: "object with a single root element".to_string(),
+                found: self.type_name(),
+            });
+        };
+
+        let mut output = String::new();
+        match value {
+            Value::Array(_) => {
+                return Err(JsonError::TypeMismatch {
+                    pointer: String::new(),
+                    expected: "single root element".to_string(),
+                    found: value.type_name(),
+                }
+                .nested(name))
+            }
+            value => write_element(&mut output, name, value).map_err(|error| error.nested(name))?,
+        }
+
+        Ok(output)
+    }
+}
+
+/// Whether `name` can be used as the name of an element or attribute.
+fn is_valid_name(name: &str) -> bool {
+    let mut characters = name.chars();
+    characters
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == ':')
+        && characters.all(|character| {
+            character.is_alphanumeric() || matches!(character, '_' | ':' | '-' | '.')
+        })
+}
+
+/// Text of a scalar, or `None` for arrays and objects.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Boolean(boolean) => Some(boolean.to_string()),
+        Value::Number(Number::I64(integer)) => Some(integer.to_string()),
+        Value::Number(Number::F64(float)) => Some(float.to_string()),
+        Value::String(string) => Some(string.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Write the elements for the member `name` of an object, relative to which errors point.
+fn write_element(output: &mut String, name: &str, value: &Value) -> Result<(), JsonError> {
+    if !is_valid_name(name) {
+        return Err(xml_error(format!("invalid element name `{name}`")));
+    }
+
+    let scalar_mismatch = |value: &Value| JsonError::TypeMismatch {
+        pointer: String::new(),
+        expected: "string, number, boolean or null".to_string(),
+        found: value.type_name(),
+    };
+
+    match value {
+        Value::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                if let Value::Array(_) = element {
+                    return Err(scalar_mismatch(element).nested(&index.to_string()));
+                }
+                write_element(output, name, element)
+                    .map_err(|error| error.nested(&index.to_string()))?;
+            }
+        }
+        Value::Object(object) => {
+            output.push('<');
+            output.push_str(name);
+
+            let mut text = None;
+            let mut children = Vec::new();
+            for (key, member) in object {
+                if let Some(attribute) = key.strip_prefix('@') {
+                    if !is_valid_name(attribute) {
+                        return Err(xml_error(format!("invalid attribute name `{attribute}`")));
+                    }
+                    let value =
+                        scalar_text(member).ok_or_else(|| scalar_mismatch(member).nested(key))?;
+                    output.push_str(&format!(" {attribute}=\"{}\"", escape(value.as_str())));
+                } else if key == "#text" {
+                    text = Some(
+                        scalar_text(member).ok_or_else(|| scalar_mismatch(member).nested(key))?,
+                    );
+                } else {
+                    children.push((key, member));
+                }
+            }
+
+            if text.is_none() && children.is_empty() {
+                output.push_str("/>");
+                return Ok(());
+            }
+
+            output.push('>');
+            if let Some(text) = text {
+                output.push_str(&escape(text.as_str()));
+            }
+            for (key, member) in children {
+                write_element(output, key, member).map_err(|error| error.nested(key))?;
+            }
+            output.push_str(&format!("</{name}>"));
+        }
+        Value::Null => output.push_str(&format!("<{name}/>")),
+        scalar => {
+            let text = scalar_text(scalar).unwrap_or_default();
+            output.push_str(&format!("<{name}>{}</{name}>", escape(text.as_str())));
+        }
+    }
+
+    Ok(())
+}
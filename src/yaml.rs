@@ -0,0 +1,119 @@
+use crate::error::JsonError;
+use crate::map::Map;
+use crate::value::{Number, Value};
+
+/// Build the error for YAML input which can't be parsed or represented.
+fn yaml_error(message: impl ToString) -> JsonError {
+    JsonError::Format {
+        format: "YAML",
+        message: message.to_string(),
+    }
+}
+
+impl Value {
+    /// Parse a YAML document into a value.
+    ///
+    /// Mappings become objects, sequences become arrays, and scalars become strings, numbers,
+    /// booleans or `null`. Merge keys (`<<`) are applied, tags such as `!Custom` are dropped in
+    /// favor of the value they annotate, and scalar mapping keys are converted to their text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Format`] if the document isn't valid YAML or uses sequences or
+    /// mappings as keys, and [`JsonError::UnrepresentableNumber`] for unsigned integers larger
+    /// than [`i64::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use json_parser::value::Value;
+    ///
+    /// let yaml = "defaults: &defaults\n  retries: 3\nservice:\n  <<: *defaults\n  name: !Name api\n";
+    /// let value = Value::from_yaml(yaml).unwrap();
+    ///
+    /// assert_eq!(value.pointer("/service/retries"), Some(&Value::from(3)));
+    /// assert_eq!(value.pointer("/service/name"), Some(&Value::from("api")));
+    /// assert_eq!(Value::from_yaml(&value.to_yaml()).unwrap(), value);
+    /// ```
+    pub fn from_yaml(input: &str) -> Result<Value, JsonError> {
+        let mut yaml: serde_yaml::Value = serde_yaml::from_str(input).map_err(yaml_error)?;
+        yaml.apply_merge().map_err(yaml_error)?;
+
+        from_yaml_value(yaml)
+    }
+
+    /// Format the value as a YAML document.
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(&to_yaml_value(self)).expect("YAML values can always be formatted")
+    }
+}
+
+fn from_yaml_value(yaml: serde_yaml::Value) -> Result<Value, JsonError> {
+    Ok(match yaml {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(boolean) => Value::Boolean(boolean),
+        serde_yaml::Value::Number(number) => {
+            if let Some(integer) = number.as_i64() {
+                Value::Number(Number::I64(integer))
+            } else if number.is_f64() {
+                Value::Number(Number::F64(number.as_f64().unwrap_or_default()))
+            } else {
+                return Err(JsonError::UnrepresentableNumber {
+                    pointer: String::new(),
+                    number: number.to_string(),
+                });
+            }
+        }
+        serde_yaml::Value::String(string) => Value::from(string),
+        serde_yaml::Value::Sequence(sequence) => Value::Array(
+            sequence
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    from_yaml_value(value).map_err(|error| error.nested(&index.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut object = Map::with_capacity(mapping.len());
+            for (key, value) in mapping {
+                let key = match key {
+                    serde_yaml::Value::String(key) => key,
+                    serde_yaml::Value::Null => "null".to_string(),
+                    serde_yaml::Value::Bool(boolean) => boolean.to_string(),
+                    serde_yaml::Value::Number(number) => number.to_string(),
+                    _ => return Err(yaml_error("mapping keys must be scalars")),
+                };
+                let value = from_yaml_value(value).map_err(|error| error.nested(&key))?;
+                object.insert(key, value);
+            }
+
+            Value::Object(object)
+        }
+        serde_yaml::Value::Tagged(tagged) => from_yaml_value(tagged.value)?,
+    })
+}
+
+fn to_yaml_value(value: &Value) -> serde_yaml::Value {
+    match value {
+        Value::Null => serde_yaml::Value::Null,
+        Value::Boolean(boolean) => serde_yaml::Value::Bool(*boolean),
+        Value::Number(Number::I64(integer)) => serde_yaml::Value::Number((*integer).into()),
+        Value::Number(Number::F64(float)) => serde_yaml::Value::Number((*float).into()),
+        Value::String(string) => serde_yaml::Value::String(string.to_string()),
+        Value::Array(array) => {
+            serde_yaml::Value::Sequence(array.iter().map(to_yaml_value).collect())
+        }
+        Value::Object(object) => serde_yaml::Value::Mapping(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        serde_yaml::Value::String(key.to_string()),
+                        to_yaml_value(value),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
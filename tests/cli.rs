@@ -0,0 +1,832 @@
+//! Tests of the `json-parser` command line tool, running the built binary.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// A directory of its own for each test, in which the command is run, so that tests running
+/// in parallel don't see each other's files.
+struct Directory {
+    path: PathBuf,
+}
+
+/// What a run of the command did.
+struct Run {
+    status: i32,
+    stdout: String,
+    stderr: String,
+}
+
+impl Directory {
+    /// Create an empty directory for the test named `test`.
+    fn new(test: &str) -> Directory {
+        let path = std::env::temp_dir().join(format!("json-parser-cli-{test}"));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        Directory { path }
+    }
+
+    /// Write a file inside the directory.
+    fn write(&self, name: &str, contents: &str) -> &Directory {
+        fs::write(self.path.join(name), contents).unwrap();
+        self
+    }
+
+    /// Read a file inside the directory.
+    fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.path.join(name)).unwrap()
+    }
+
+    /// Run the command with `args` inside the directory, with `stdin` as its standard input.
+    fn run(&self, args: &[&str], stdin: &str) -> Run {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser"))
+            .args(args)
+            .current_dir(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // The command may exit without reading its input, such as for invalid arguments.
+        let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+        let output = child.wait_with_output().unwrap();
+
+        Run {
+            status: output.status.code().unwrap(),
+            stdout: String::from_utf8(output.stdout).unwrap(),
+            stderr: String::from_utf8(output.stderr).unwrap(),
+        }
+    }
+}
+
+const GOOD: &str = r#"{"name": "ada", "tags": ["x", "y"], "n": 1.5}"#;
+const BAD: &str = "{\n  \"a\" 1\n}\n";
+
+#[test]
+fn validate_accepts_valid_files() {
+    let directory = Directory::new("validate-valid");
+    directory.write("good.json", GOOD).write("other.json", "[]");
+
+    let run = directory.run(&["validate", "good.json", "other.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "2 files, 0 invalid\n");
+}
+
+#[test]
+fn validate_reports_errors_at_their_line_and_column() {
+    let directory = Directory::new("validate-invalid");
+    directory.write("good.json", GOOD).write("bad.json", BAD);
+
+    let run = directory.run(&["validate", "good.json", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "bad.json:2:7: expected `:`, found `1`\n2 files, 1 invalid\n"
+    );
+}
+
+#[test]
+fn validate_rejects_what_the_grammar_does_not_allow() {
+    let directory = Directory::new("validate-strict");
+    let deep = "[".repeat(1000) + &"]".repeat(1000);
+    directory
+        .write("form-feed.json", "[1]\u{C}")
+        .write("no-break-space.json", "[1]\u{A0}")
+        .write("deep.json", &deep);
+
+    let run = directory.run(
+        &[
+            "validate",
+            "form-feed.json",
+            "no-break-space.json",
+            "deep.json",
+        ],
+        "",
+    );
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "form-feed.json:1:4: unexpected text after the value\n\
+         no-break-space.json:1:4: unexpected text after the value\n\
+         deep.json:1:513: arrays and objects are nested more than 512 levels deep\n\
+         3 files, 3 invalid\n"
+    );
+}
+
+#[test]
+fn validate_fails_on_missing_files() {
+    let directory = Directory::new("validate-missing");
+
+    let run = directory.run(&["validate", "missing.json"], "");
+    assert_eq!(run.status, 3);
+    assert!(run.stderr.starts_with("missing.json: "), "{}", run.stderr);
+}
+
+#[test]
+fn invalid_arguments_exit_with_usage() {
+    let directory = Directory::new("usage");
+
+    for args in [&["frob"][..], &[], &["validate", "--frob"]] {
+        let run = directory.run(args, "");
+        assert_eq!(run.status, 2, "{args:?}");
+        assert!(run.stderr.contains("Usage: json-parser"), "{args:?}");
+    }
+
+    let run = directory.run(&["--help"], "");
+    assert_eq!(run.status, 0);
+    assert!(run.stdout.starts_with("Usage: json-parser"));
+}
+
+#[test]
+fn fmt_pretty_prints_files() {
+    let directory = Directory::new("fmt");
+    directory.write("good.json", GOOD);
+
+    let run = directory.run(&["fmt", "good.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "{\n  \"name\": \"ada\",\n  \"tags\": [\n    \"x\",\n    \"y\"\n  ],\n  \"n\": 1.5\n}\n"
+    );
+
+    let run = directory.run(&["fmt", "--indent", "1", "--sort-keys", "good.json"], "");
+    assert_eq!(
+        run.stdout,
+        "{\n \"n\": 1.5,\n \"name\": \"ada\",\n \"tags\": [\n  \"x\",\n  \"y\"\n ]\n}\n"
+    );
+
+    let run = directory.run(&["fmt", "--tabs", "--crlf", "good.json"], "");
+    assert!(run
+        .stdout
+        .starts_with("{\r\n\t\"name\": \"ada\",\r\n\t\"tags\": [\r\n\t\t\"x\""));
+}
+
+#[test]
+fn fmt_colors_values_without_their_whitespace() {
+    let directory = Directory::new("fmt-color");
+    directory.write("values.json", "[1, true, null]");
+
+    let run = directory.run(
+        &[
+            "fmt",
+            "--color",
+            "always",
+            "--tabs",
+            "--crlf",
+            "values.json",
+        ],
+        "",
+    );
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "[\r\n\t\x1b[36m1\x1b[0m,\r\n\t\x1b[33mtrue\x1b[0m,\r\n\t\x1b[90mnull\x1b[0m\r\n]\r\n"
+    );
+}
+
+#[test]
+fn fmt_writes_files_in_place() {
+    let directory = Directory::new("fmt-write");
+    directory.write("good.json", GOOD).write("bad.json", BAD);
+
+    let run = directory.run(&["fmt", "--write", "good.json", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stdout, "");
+    assert!(directory
+        .read("good.json")
+        .starts_with("{\n  \"name\": \"ada\",\n"));
+    // Invalid files are left as they are.
+    assert_eq!(directory.read("bad.json"), BAD);
+}
+
+#[test]
+fn fmt_reports_invalid_files() {
+    let directory = Directory::new("fmt-invalid");
+    directory.write("bad.json", BAD);
+
+    let run = directory.run(&["fmt", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "bad.json:2:7: expected `:`, found `1`\n");
+
+    let run = directory.run(&["fmt", "--indent", "two", "bad.json"], "");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn minify_removes_whitespace() {
+    let directory = Directory::new("minify");
+    directory.write(
+        "good.json",
+        "{\n  \"a\": [1.50, \"b c\"],\n  \"d\": {}\n}\n",
+    );
+
+    let run = directory.run(&["minify", "good.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "{\"a\":[1.50,\"b c\"],\"d\":{}}\n");
+
+    let run = directory.run(&["minify", "--write", "good.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(
+        directory.read("good.json"),
+        "{\"a\":[1.50,\"b c\"],\"d\":{}}"
+    );
+}
+
+#[test]
+fn minify_rejects_invalid_files() {
+    let directory = Directory::new("minify-invalid");
+    directory
+        .write("unclosed.json", "[1,2")
+        .write("bad.json", BAD)
+        .write("scalars.json", "[1 2]");
+
+    for (file, error) in [
+        ("unclosed.json", "unclosed.json:1:1: unclosed array\n"),
+        ("bad.json", "bad.json:2:7: expected `:`, found `1`\n"),
+        (
+            "scalars.json",
+            "scalars.json:1:4: expected `,` or `]`, found `2`\n",
+        ),
+    ] {
+        let run = directory.run(&["minify", file], "");
+        assert_eq!(run.status, 1, "{file}");
+        assert_eq!(run.stdout, "", "{file}");
+        assert_eq!(run.stderr, error);
+
+        // Invalid files aren't written over.
+        let before = directory.read(file);
+        let run = directory.run(&["minify", "--write", file], "");
+        assert_eq!(run.status, 1, "{file}");
+        assert_eq!(directory.read(file), before);
+    }
+}
+
+#[test]
+fn get_prints_the_value_at_a_pointer() {
+    let directory = Directory::new("get");
+    directory.write("good.json", GOOD);
+
+    let run = directory.run(&["get", "/tags/1", "good.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "\"y\"\n");
+
+    let run = directory.run(&["get", "--raw", "/name", "good.json"], "");
+    assert_eq!(run.stdout, "ada\n");
+
+    let run = directory.run(&["get", "", "good.json"], "");
+    assert!(run.stdout.starts_with("{\n  \"name\": \"ada\","));
+}
+
+#[test]
+fn get_fails_without_a_value() {
+    let directory = Directory::new("get-invalid");
+    directory.write("good.json", GOOD).write("bad.json", BAD);
+
+    let run = directory.run(&["get", "/missing", "good.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stderr, "good.json: no value at `/missing`\n");
+
+    let run = directory.run(&["get", "/a", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stderr, "bad.json:2:7: expected `:`, found `1`\n");
+
+    let run = directory.run(&["get", "name", "good.json"], "");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn query_prints_the_selected_values() {
+    let directory = Directory::new("query");
+    directory.write("good.json", GOOD);
+
+    let run = directory.run(&["query", "$.tags[*]", "good.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "\"x\"\n\"y\"\n");
+
+    let run = directory.run(&["query", "--array", "$.tags[*]", "good.json"], "");
+    assert_eq!(run.stdout, "[\n  \"x\",\n  \"y\"\n]\n");
+
+    let run = directory.run(&["query", "$.missing", "good.json"], "");
+    assert_eq!(run.status, 0);
+    assert_eq!(run.stdout, "");
+}
+
+#[test]
+fn query_rejects_invalid_input() {
+    let directory = Directory::new("query-invalid");
+    directory.write("bad.json", BAD);
+
+    let run = directory.run(&["query", "$.a", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stderr, "bad.json:2:7: expected `:`, found `1`\n");
+
+    let run = directory.run(&["query", "$.[", "bad.json"], "");
+    assert_eq!(run.status, 2);
+    assert!(
+        run.stderr.starts_with("error: invalid JSONPath"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn diff_prints_the_changes() {
+    let directory = Directory::new("diff");
+    directory
+        .write("old.json", r#"{"a": 1, "b": [1, 2]}"#)
+        .write("new.json", r#"{"a": 2, "b": [2, 1], "c": true}"#);
+
+    let run = directory.run(&["diff", "old.json", "new.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stdout,
+        "changed /a: 1 -> 2\nchanged /b/0: 1 -> 2\nchanged /b/1: 2 -> 1\nadded /c = true\n"
+    );
+
+    let run = directory.run(
+        &[
+            "diff",
+            "--ignore-order",
+            "--ignore",
+            "/a",
+            "old.json",
+            "new.json",
+        ],
+        "",
+    );
+    assert_eq!(run.stdout, "added /c = true\n");
+
+    let run = directory.run(&["diff", "--patch", "old.json", "old.json"], "");
+    assert_eq!(run.status, 0);
+    assert_eq!(run.stdout, "[]\n");
+}
+
+#[test]
+fn diff_rejects_invalid_input() {
+    let directory = Directory::new("diff-invalid");
+    directory.write("old.json", "{}").write("bad.json", BAD);
+
+    let run = directory.run(&["diff", "old.json", "bad.json"], "");
+    assert_eq!(run.status, 2);
+    assert_eq!(run.stderr, "bad.json:2:7: expected `:`, found `1`\n");
+
+    let run = directory.run(&["diff", "old.json", "missing.json"], "");
+    assert_eq!(run.status, 3);
+}
+
+#[test]
+fn patch_applies_patches() {
+    let directory = Directory::new("patch");
+    directory
+        .write("value.json", r#"{"a": 1, "b": [1, 2]}"#)
+        .write(
+            "patch.json",
+            r#"[{"op": "test", "path": "/a", "value": 1.0}, {"op": "replace", "path": "/a", "value": 5}]"#,
+        )
+        .write("merge.json", r#"{"b": null, "d": 4}"#);
+
+    let run = directory.run(&["patch", "value.json", "patch.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "{\n  \"a\": 5,\n  \"b\": [\n    1,\n    2\n  ]\n}\n"
+    );
+
+    let run = directory.run(
+        &["patch", "--merge", "--write", "value.json", "merge.json"],
+        "",
+    );
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "");
+    assert_eq!(
+        directory.read("value.json"),
+        "{\n  \"a\": 1,\n  \"d\": 4\n}\n"
+    );
+}
+
+#[test]
+fn patch_fails_when_it_does_not_apply() {
+    let directory = Directory::new("patch-invalid");
+    directory
+        .write("value.json", r#"{"a": 1}"#)
+        .write("patch.json", r#"[{"op": "remove", "path": "/missing"}]"#)
+        .write("bad.json", BAD);
+
+    let run = directory.run(&["patch", "--write", "value.json", "patch.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "patch.json: patch operation 0 failed: no value at /missing\n"
+    );
+    assert_eq!(directory.read("value.json"), r#"{"a": 1}"#);
+
+    let run = directory.run(&["patch", "value.json", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stderr, "bad.json:2:7: expected `:`, found `1`\n");
+}
+
+#[test]
+fn convert_converts_between_formats() {
+    let directory = Directory::new("convert");
+    directory.write(
+        "rows.json",
+        r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b,c"}]"#,
+    );
+
+    let run = directory.run(&["convert", "--to", "csv", "rows.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "id,name\n1,a\n2,\"b,c\"\n");
+
+    let run = directory.run(&["convert", "--from", "csv"], &run.stdout);
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert!(run
+        .stdout
+        .starts_with("[\n  {\n    \"id\": \"1\",\n    \"name\": \"a\"\n  },"));
+}
+
+#[test]
+fn convert_rejects_invalid_input() {
+    let directory = Directory::new("convert-invalid");
+    directory.write("bad.json", BAD);
+
+    let run = directory.run(&["convert", "--to", "csv", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stderr, "bad.json:2:7: expected `:`, found `1`\n");
+
+    let run = directory.run(&["convert", "--from", "csv"], "a,b\n\"1,2\n");
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "<stdin>: invalid CSV: unterminated quoted field\n"
+    );
+
+    let run = directory.run(&["convert", "--to", "csv"], r#"{"a": 1}"#);
+    assert_eq!(run.status, 1);
+
+    let run = directory.run(&["convert", "--to", "frob"], "{}");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn standard_input_is_read_without_files_or_for_dash() {
+    let directory = Directory::new("stdin");
+
+    let run = directory.run(&["fmt"], "[1,2]");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "[\n  1,\n  2\n]\n");
+
+    let run = directory.run(&["get", "/1", "-"], "[1,2]");
+    assert_eq!(run.stdout, "2\n");
+
+    let run = directory.run(&["validate", "-"], BAD);
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stderr, "<stdin>:2:7: expected `:`, found `1`\n");
+}
+
+#[test]
+fn standard_input_is_read_once_and_never_written() {
+    let directory = Directory::new("stdin-invalid");
+
+    let run = directory.run(&["validate", "-", "-"], "[]");
+    assert_eq!(run.status, 2);
+    assert!(run
+        .stderr
+        .starts_with("error: only one file can be read from standard input"));
+
+    let run = directory.run(&["fmt", "--write"], "[]");
+    assert_eq!(run.status, 2);
+    assert!(run
+        .stderr
+        .starts_with("error: `--write` can't write to standard input"));
+}
+
+#[test]
+fn glob_patterns_are_expanded() {
+    let directory = Directory::new("glob");
+    fs::create_dir_all(directory.path.join("nested")).unwrap();
+    directory
+        .write("a.json", "[]")
+        .write("nested/b.json", BAD)
+        .write("nested/c.txt", BAD);
+
+    let run = directory.run(&["validate", "**/*.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "nested/b.json:2:7: expected `:`, found `1`\n2 files, 1 invalid\n"
+    );
+
+    let run = directory.run(&["validate", "*.yaml"], "");
+    assert_eq!(run.status, 2);
+    assert!(run.stderr.starts_with("error: no files match `*.yaml`"));
+}
+
+#[test]
+fn files_named_like_patterns_are_read_as_is() {
+    let directory = Directory::new("glob-literal");
+    directory.write("data[1].json", "[1]");
+
+    let run = directory.run(&["fmt", "data[1].json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "[\n  1\n]\n");
+}
+
+#[test]
+fn lines_filters_and_queries_each_line() {
+    let directory = Directory::new("lines");
+    let log = "{\"id\":1,\"level\":\"info\"}\n{\"id\":2,\"level\":\"error\"}\n\n{\"id\":3,\"level\":\"error\"}\n";
+    directory.write("log.ndjson", log);
+
+    let run = directory.run(
+        &["lines", "--filter", "$.level == \"error\"", "log.ndjson"],
+        "",
+    );
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "{\"id\":2,\"level\":\"error\"}\n{\"id\":3,\"level\":\"error\"}\n"
+    );
+
+    let run = directory.run(
+        &[
+            "lines",
+            "--query",
+            "$.id",
+            "--filter",
+            "$.level == \"error\"",
+        ],
+        log,
+    );
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "2\n3\n");
+}
+
+#[test]
+fn lines_reports_invalid_lines_and_goes_on() {
+    let directory = Directory::new("lines-invalid");
+    directory.write("log.ndjson", "{\"id\":1}\n[\n{\"id\":2}\nnope\n");
+
+    let run = directory.run(&["lines", "--query", "$.id", "log.ndjson"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(run.stdout, "1\n2\n");
+    assert!(run.stderr.contains("log.ndjson:2:"), "{}", run.stderr);
+    assert!(run
+        .stderr
+        .contains("log.ndjson:4:1: expected a value, found `nope`"));
+
+    let run = directory.run(&["lines", "--filter", "$.id =="], "");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn stats_describes_files() {
+    let directory = Directory::new("stats");
+    directory.write("good.json", GOOD).write("bad.json", BAD);
+
+    let run = directory.run(&["stats", "good.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    for line in [
+        "size:           45 bytes",
+        "depth:          3",
+        "values:         6",
+        "  strings:      3",
+        "  floats:       1",
+        "largest array:  2 elements",
+        "largest object: 3 members",
+        "longest string: 3 bytes",
+    ] {
+        assert!(run.stdout.lines().any(|output| output == line), "{line}");
+    }
+
+    let run = directory.run(&["stats", "good.json", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert!(run.stdout.starts_with("good.json:\nsize:"));
+    assert!(run.stderr.contains("bad.json:2:7: expected `:`, found `1`"));
+    assert!(run.stderr.ends_with("2 files, 1 invalid\n"));
+}
+
+#[test]
+fn schema_infer_describes_all_files() {
+    let directory = Directory::new("schema");
+    directory
+        .write("one.json", r#"{"a": 1, "b": "x"}"#)
+        .write("two.json", r#"{"a": 2.5}"#)
+        .write("bad.json", BAD);
+
+    let run = directory.run(&["schema", "infer", "one.json", "two.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    let schema = run.stdout.split_whitespace().collect::<String>();
+    assert_eq!(
+        schema,
+        concat!(
+            r#"{"$schema":"https://json-schema.org/draft/2020-12/schema","type":"object","#,
+            r#""properties":{"a":{"type":"number"},"b":{"type":"string"}},"required":["a"]}"#
+        )
+    );
+
+    let run = directory.run(&["schema", "infer", "one.json", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert!(run.stderr.contains("bad.json:2:7: expected `:`, found `1`"));
+
+    let run = directory.run(&["schema"], "");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn watch_runs_again_when_a_file_changes() {
+    let directory = Directory::new("watch");
+    directory.write("config.json", GOOD);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser"))
+        .args(["validate", "--watch", "config.json"])
+        .current_dir(&directory.path)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Read standard error on another thread, so that a watch which never runs again fails the
+    // test instead of hanging it.
+    let (sender, receiver) = mpsc::channel();
+    let stderr = child.stderr.take().unwrap();
+    thread::spawn(move || {
+        for line in BufReader::new(stderr).lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next_line = || receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    assert_eq!(next_line(), "watching for changes, press Ctrl-C to stop");
+    // Leave enough time for the modification time to differ on coarse file systems.
+    thread::sleep(Duration::from_millis(50));
+    directory.write("config.json", BAD);
+    assert_eq!(next_line(), "");
+    assert_eq!(next_line(), "config.json:2:7: expected `:`, found `1`");
+    assert_eq!(next_line(), "watching for changes, press Ctrl-C to stop");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn watch_refuses_standard_input() {
+    let directory = Directory::new("watch-stdin");
+
+    let run = directory.run(&["validate", "--watch"], GOOD);
+    assert_eq!(run.status, 2);
+    assert!(run
+        .stderr
+        .starts_with("error: `--watch` can't watch standard input"));
+
+    let run = directory.run(&["get", "--watch", "/a"], GOOD);
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn output_json_prints_one_object_per_error() {
+    let directory = Directory::new("output-json");
+    directory.write("good.json", GOOD).write("bad.json", BAD);
+
+    let run = directory.run(
+        &["--output", "json", "validate", "good.json", "bad.json"],
+        "",
+    );
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        concat!(
+            r#"{"path":"bad.json","line":2,"column":7,"code":"syntax","message":"expected `:`, found `1`"}"#,
+            "\n",
+            r#"{"files":2,"failed":1}"#,
+            "\n"
+        )
+    );
+
+    let run = directory.run(&["validate", "--output", "json", "missing.json"], "");
+    assert_eq!(run.status, 3);
+    assert!(run
+        .stderr
+        .starts_with(r#"{"path":"missing.json","line":null,"column":null,"code":"io","message":"#));
+    assert!(run.stderr.ends_with("{\"files\":1,\"failed\":1}\n"));
+
+    let run = directory.run(&["--output", "xml", "validate", "good.json"], "");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn closed_standard_output_is_not_an_error() {
+    let directory = Directory::new("broken-pipe");
+    let numbers = (0..100_000).map(|n| n.to_string()).collect::<Vec<_>>();
+    directory.write("big.json", &format!("[{}]", numbers.join(",")));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser"))
+        .args(["fmt", "big.json"])
+        .current_dir(&directory.path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Close the reading end before the command is done writing, as `head` does.
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+}
+
+#[test]
+fn merge_combines_both_sides() {
+    let directory = Directory::new("merge");
+    directory
+        .write("base.json", r#"{"a": 1, "b": 1}"#)
+        .write("ours.json", r#"{"a": 5, "b": 1}"#)
+        .write("theirs.json", r#"{"a": 1, "b": 2}"#)
+        .write("conflicting.json", r#"{"a": 7, "b": 1}"#);
+
+    let run = directory.run(&["merge", "base.json", "ours.json", "theirs.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "{\n  \"a\": 5,\n  \"b\": 2\n}\n");
+
+    let run = directory.run(
+        &[
+            "merge",
+            "--write",
+            "base.json",
+            "ours.json",
+            "conflicting.json",
+        ],
+        "",
+    );
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "ours.json: conflict at /a: ours 5, theirs 7, base 1\n"
+    );
+    assert_eq!(
+        directory.read("ours.json"),
+        "{\n  \"a\": 5,\n  \"b\": 1\n}\n"
+    );
+}
+
+#[test]
+fn merge_rejects_invalid_input() {
+    let directory = Directory::new("merge-invalid");
+    directory.write("good.json", GOOD).write("bad.json", BAD);
+
+    let run = directory.run(&["merge", "good.json", "good.json", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert!(run.stderr.contains("bad.json:2:7: expected `:`, found `1`"));
+
+    let run = directory.run(&["merge", "good.json", "good.json"], "");
+    assert_eq!(run.status, 2);
+}
+
+#[test]
+fn filter_prints_the_outputs() {
+    let directory = Directory::new("filter");
+    directory.write(
+        "items.json",
+        r#"{"items": [{"name": "a", "active": true}, {"name": "b", "active": false}]}"#,
+    );
+
+    let run = directory.run(
+        &["filter", ".items[] | select(.active) | .name", "items.json"],
+        "",
+    );
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "\"a\"\n");
+
+    let run = directory.run(&["filter", "--raw", ".items[].name", "items.json"], "");
+    assert_eq!(run.status, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "a\nb\n");
+}
+
+#[test]
+fn filter_fails_on_errors() {
+    let directory = Directory::new("filter-invalid");
+    directory
+        .write("items.json", r#"{"items": []}"#)
+        .write("bad.json", BAD);
+
+    let run = directory.run(&["filter", ".items.name", "items.json"], "");
+    assert_eq!(run.status, 1);
+    assert_eq!(
+        run.stderr,
+        "items.json: evaluation failed: can't index array with string\n"
+    );
+
+    let run = directory.run(&["filter", ".", "bad.json"], "");
+    assert_eq!(run.status, 1);
+    assert!(run.stderr.contains("bad.json:2:7: expected `:`, found `1`"));
+
+    let run = directory.run(&["filter", ".items[", "items.json"], "");
+    assert_eq!(run.status, 2);
+    assert!(run.stderr.starts_with("error: invalid jq:"));
+}